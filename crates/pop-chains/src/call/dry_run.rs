@@ -0,0 +1,229 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use super::format_value;
+use crate::errors::Error;
+use pop_common::create_signer;
+use scale_value::{At, Value, ValueDef};
+use subxt::{
+	backend::{
+		legacy::{rpc_methods::DryRunResult as RpcDryRunResult, LegacyRpcMethods},
+		rpc::RpcClient,
+	},
+	dynamic,
+	error::DispatchError,
+	tx::{Payload, ValidationResult},
+	Metadata, OnlineClient, SubstrateConfig,
+};
+
+const DRY_RUN_API: &str = "DryRunApi";
+const DRY_RUN_CALL: &str = "dry_run_call";
+// The XCM version used for any forwarded messages, when required by the runtime API.
+const XCM_VERSION: u32 = 4;
+
+/// The result of dispatching a call during a dry run.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DryRunResult {
+	/// The call would be dispatched successfully.
+	Success,
+	/// The call would fail to dispatch, with the decoded error.
+	DispatchError(String),
+	/// The extrinsic would not be included in a block.
+	TransactionValidityError(String),
+}
+
+/// The outcome of dry running an extrinsic.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DryRunOutcome {
+	/// The result of dispatching the call.
+	pub result: DryRunResult,
+	/// The events that would be emitted, if the runtime reports them.
+	pub events: Option<Vec<String>>,
+}
+
+/// Signs and dry runs an extrinsic without broadcasting it.
+///
+/// The `DryRunApi` runtime API is used when provided by the runtime, which also reports the events
+/// that would be emitted. Otherwise, the extrinsic is executed using the `system_dryRun` RPC.
+///
+/// # Arguments
+/// * `client` - The client used to interact with the chain.
+/// * `url` - Endpoint of the node.
+/// * `xt` - The extrinsic to be dry run.
+/// * `suri` - The secret URI (e.g., mnemonic or private key) for signing the extrinsic.
+pub async fn dry_run_extrinsic<Xt: Payload>(
+	client: &OnlineClient<SubstrateConfig>,
+	url: &url::Url,
+	xt: Xt,
+	suri: &str,
+) -> Result<DryRunOutcome, Error> {
+	let signer = create_signer(suri)?;
+	let metadata = client.metadata();
+	let signed = client
+		.tx()
+		.create_signed(&xt, &signer, Default::default())
+		.await
+		.map_err(|e| Error::DryRunError(format!("{:?}", e)))?;
+	// Check the extrinsic would be accepted before executing it.
+	match signed.validate().await.map_err(|e| Error::DryRunError(format!("{:?}", e)))? {
+		ValidationResult::Valid(_) => {},
+		ValidationResult::Invalid(e) =>
+			return Ok(DryRunOutcome {
+				result: DryRunResult::TransactionValidityError(format!("{:?}", e)),
+				events: None,
+			}),
+		ValidationResult::Unknown(e) =>
+			return Ok(DryRunOutcome {
+				result: DryRunResult::TransactionValidityError(format!("{:?}", e)),
+				events: None,
+			}),
+	}
+
+	if metadata
+		.runtime_api_trait_by_name(DRY_RUN_API)
+		.and_then(|api| api.method_by_name(DRY_RUN_CALL))
+		.is_some()
+	{
+		let call_data = xt
+			.encode_call_data(&metadata)
+			.map_err(|e| Error::CallDataEncodingError(e.to_string()))?;
+		return dry_run_call(client, signer.account_id().0, &call_data).await;
+	}
+
+	let rpc_client = RpcClient::from_url(url.as_str())
+		.await
+		.map_err(|e| Error::ConnectionFailure(e.to_string()))?;
+	let result = LegacyRpcMethods::<SubstrateConfig>::new(rpc_client)
+		.dry_run(signed.encoded(), None)
+		.await
+		.map_err(|e| Error::DryRunError(e.to_string()))?
+		.into_dry_run_result(&metadata)
+		.map_err(|e| Error::DryRunError(e.to_string()))?;
+	let result = match result {
+		RpcDryRunResult::Success => DryRunResult::Success,
+		RpcDryRunResult::DispatchError(e) => DryRunResult::DispatchError(e.to_string()),
+		RpcDryRunResult::TransactionValidityError =>
+			DryRunResult::TransactionValidityError("Invalid transaction".to_string()),
+	};
+	Ok(DryRunOutcome { result, events: None })
+}
+
+// Executes the call using the `DryRunApi` runtime API, dispatched from a signed origin.
+async fn dry_run_call(
+	client: &OnlineClient<SubstrateConfig>,
+	account: [u8; 32],
+	call_data: &[u8],
+) -> Result<DryRunOutcome, Error> {
+	let metadata = client.metadata();
+	let call = scale_value::scale::decode_as_type(
+		&mut &call_data[..],
+		metadata.outer_enums().call_enum_ty(),
+		metadata.types(),
+	)
+	.map_err(|e| Error::CallDataDecodingError(e.to_string()))?
+	.remove_context();
+	let origin = Value::unnamed_variant(
+		"system",
+		[Value::unnamed_variant("Signed", [Value::from_bytes(account)])],
+	);
+	let mut args = vec![origin, call];
+	// Newer versions of the runtime API also expect the XCM version of any forwarded messages.
+	if metadata
+		.runtime_api_trait_by_name(DRY_RUN_API)
+		.and_then(|api| api.method_by_name(DRY_RUN_CALL))
+		.is_some_and(|method| method.inputs().len() > 2)
+	{
+		args.push(Value::u128(XCM_VERSION as u128));
+	}
+	let effects = client
+		.runtime_api()
+		.at_latest()
+		.await
+		.map_err(|e| Error::DryRunError(e.to_string()))?
+		.call(dynamic::runtime_api_call(DRY_RUN_API, DRY_RUN_CALL, args))
+		.await
+		.map_err(|e| Error::DryRunError(e.to_string()))?
+		.to_value()
+		.map_err(|e| Error::DryRunError(e.to_string()))?;
+	parse_dry_run_effects(&effects, &metadata)
+}
+
+// Parses the `CallDryRunEffects` returned by the `DryRunApi` runtime API.
+fn parse_dry_run_effects(
+	effects: &Value<u32>,
+	metadata: &Metadata,
+) -> Result<DryRunOutcome, Error> {
+	let ValueDef::Variant(outcome) = &effects.value else {
+		return Err(Error::DryRunError("Unexpected dry run result".to_string()));
+	};
+	if outcome.name == "Err" {
+		let error = outcome.values.at(0).map(format_value).unwrap_or_default();
+		return Err(Error::DryRunError(error));
+	}
+	let result = match effects.at(0).at("execution_result").map(|r| &r.value) {
+		Some(ValueDef::Variant(result)) if result.name == "Ok" => DryRunResult::Success,
+		Some(ValueDef::Variant(result)) => {
+			let error = result
+				.values
+				.at(0)
+				.and_then(|r| r.at("error"))
+				.ok_or_else(|| Error::DryRunError("Missing dispatch error".to_string()))?;
+			DryRunResult::DispatchError(decode_dispatch_error(error, metadata)?)
+		},
+		_ => return Err(Error::DryRunError("Missing execution result".to_string())),
+	};
+	let events = match effects.at(0).at("emitted_events").map(|e| &e.value) {
+		Some(ValueDef::Composite(events)) => events.values().map(format_event).collect(),
+		_ => vec![],
+	};
+	Ok(DryRunOutcome { result, events: Some(events) })
+}
+
+// Decodes a `DispatchError` value, resolving module errors against the metadata.
+fn decode_dispatch_error(error: &Value<u32>, metadata: &Metadata) -> Result<String, Error> {
+	let mut bytes = Vec::new();
+	scale_value::scale::encode_as_type(error, error.context, metadata.types(), &mut bytes)
+		.map_err(|e| Error::DryRunError(e.to_string()))?;
+	Ok(DispatchError::decode_from(bytes, metadata.clone())
+		.map(|e| e.to_string())
+		.unwrap_or_else(|_| format_value(error)))
+}
+
+// Formats a runtime event as `Pallet::Event { fields }`.
+pub(crate) fn format_event<T: Clone>(event: &Value<T>) -> String {
+	let ValueDef::Variant(pallet) = &event.value else {
+		return format_value(event);
+	};
+	match pallet.values.at(0).map(|e| &e.value) {
+		Some(ValueDef::Variant(variant)) if variant.values.is_empty() =>
+			format!("{}::{}", pallet.name, variant.name),
+		Some(ValueDef::Variant(variant)) => format!(
+			"{}::{} {}",
+			pallet.name,
+			variant.name,
+			format_value(&Value {
+				value: ValueDef::Composite(variant.values.clone()),
+				context: event.context.clone(),
+			})
+		),
+		_ => format_value(event),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn format_event_works() {
+		let event = Value::unnamed_variant(
+			"System",
+			[Value::named_variant(
+				"Remarked",
+				[("sender", Value::from_bytes([1u8; 4])), ("hash", Value::u128(1))],
+			)],
+		);
+		assert_eq!(format_event(&event), "System::Remarked { sender: 0x01010101, hash: 1 }");
+		let event = Value::unnamed_variant("Sudo", [Value::unnamed_variant("KeyRemoved", [])]);
+		assert_eq!(format_event(&event), "Sudo::KeyRemoved");
+	}
+}
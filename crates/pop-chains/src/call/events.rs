@@ -0,0 +1,291 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use super::format_value;
+use crate::errors::Error;
+use scale_value::{Composite, Primitive, Value, ValueDef};
+use serde::{Serialize, Serializer};
+use serde_json::{Map, Value as JsonValue};
+use sp_core::crypto::AccountId32;
+use std::fmt::{Display, Formatter};
+use strum_macros::{AsRefStr, EnumString};
+use subxt::{
+	backend::StreamOfResults, blocks::Block, events::Phase, utils::to_hex, OnlineClient,
+	SubstrateConfig,
+};
+
+/// The blocks from which events are streamed.
+#[derive(AsRefStr, Clone, Copy, Debug, Default, EnumString, Eq, PartialEq, clap::ValueEnum)]
+#[clap(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum BlockSubscription {
+	/// Finalized blocks only.
+	#[default]
+	Finalized,
+	/// The best block, which may be subject to re-organisation.
+	Best,
+}
+
+/// Criteria used to filter chain events.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EventFilter {
+	/// The name of the pallet emitting the event.
+	pub pallet: Option<String>,
+	/// The name of the event.
+	pub event: Option<String>,
+	/// An account involved in the event.
+	pub account: Option<AccountId32>,
+}
+
+impl EventFilter {
+	// Whether the decoded event matches the filter.
+	fn matches<T>(&self, pallet: &str, event: &str, fields: &Value<T>) -> bool {
+		self.pallet.as_ref().is_none_or(|p| p.eq_ignore_ascii_case(pallet)) &&
+			self.event.as_ref().is_none_or(|e| e.eq_ignore_ascii_case(event)) &&
+			self.account.as_ref().is_none_or(|a| contains_account(fields, a.as_ref()))
+	}
+}
+
+/// An event emitted by a chain, decoded against its metadata.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ChainEvent {
+	/// The number of the block containing the event.
+	pub block_number: u32,
+	/// The hash of the block containing the event.
+	pub block_hash: String,
+	/// The index of the extrinsic which emitted the event, if any.
+	pub extrinsic_index: Option<u32>,
+	/// The name of the pallet emitting the event.
+	pub pallet: String,
+	/// The name of the event.
+	pub event: String,
+	/// The decoded fields of the event, serialized as structured JSON with byte sequences
+	/// rendered as hex.
+	#[serde(serialize_with = "serialize_value")]
+	pub fields: Value,
+}
+
+impl Display for ChainEvent {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(f, "#{}", self.block_number)?;
+		if let Some(index) = self.extrinsic_index {
+			write!(f, "-{index}")?;
+		}
+		write!(f, " {}::{}", self.pallet, self.event)?;
+		let fields = format_value(&self.fields);
+		if !fields.is_empty() && fields != "()" {
+			write!(f, " {fields}")?;
+		}
+		Ok(())
+	}
+}
+
+/// A subscription to the events of a chain, decoding the events of each new block.
+pub struct EventSubscription {
+	blocks: StreamOfResults<Block<SubstrateConfig, OnlineClient<SubstrateConfig>>>,
+	filter: EventFilter,
+}
+
+impl EventSubscription {
+	/// Subscribes to the events of new blocks.
+	///
+	/// # Arguments
+	/// * `client` - The client used to interact with the chain.
+	/// * `subscription` - The blocks from which events are streamed.
+	/// * `filter` - Criteria used to filter the events.
+	pub async fn new(
+		client: &OnlineClient<SubstrateConfig>,
+		subscription: BlockSubscription,
+		filter: EventFilter,
+	) -> Result<Self, Error> {
+		let blocks = match subscription {
+			BlockSubscription::Finalized => client.blocks().subscribe_finalized().await,
+			BlockSubscription::Best => client.blocks().subscribe_best().await,
+		}
+		.map_err(|e| Error::EventSubscriptionError(e.to_string()))?;
+		Ok(Self { blocks, filter })
+	}
+
+	/// Waits for the next block, returning its events which match the filter. Returns `None` once
+	/// the subscription has ended.
+	pub async fn next(&mut self) -> Option<Result<Vec<ChainEvent>, Error>> {
+		let block = match self.blocks.next().await? {
+			Ok(block) => block,
+			Err(e) => return Some(Err(Error::EventSubscriptionError(e.to_string()))),
+		};
+		Some(self.decode_events(&block).await)
+	}
+
+	// Decodes the events of a block which match the filter.
+	async fn decode_events(
+		&self,
+		block: &Block<SubstrateConfig, OnlineClient<SubstrateConfig>>,
+	) -> Result<Vec<ChainEvent>, Error> {
+		let events =
+			block.events().await.map_err(|e| Error::EventSubscriptionError(e.to_string()))?;
+		let mut decoded = Vec::new();
+		for event in events.iter() {
+			let event = event.map_err(|e| Error::EventSubscriptionError(e.to_string()))?;
+			let fields =
+				event.field_values().map_err(|e| Error::EventSubscriptionError(e.to_string()))?;
+			let fields = Value { value: ValueDef::Composite(fields), context: 0 };
+			if !self.filter.matches(event.pallet_name(), event.variant_name(), &fields) {
+				continue;
+			}
+			decoded.push(ChainEvent {
+				block_number: block.number(),
+				block_hash: format!("{:?}", block.hash()),
+				extrinsic_index: match event.phase() {
+					Phase::ApplyExtrinsic(index) => Some(index),
+					_ => None,
+				},
+				pallet: event.pallet_name().to_string(),
+				event: event.variant_name().to_string(),
+				fields: fields.remove_context(),
+			});
+		}
+		Ok(decoded)
+	}
+}
+
+// Whether a decoded value contains the given account identifier.
+fn contains_account<T>(value: &Value<T>, account: &[u8]) -> bool {
+	let ValueDef::Composite(values) = &value.value else {
+		return false;
+	};
+	bytes(values).is_some_and(|bytes| bytes == account) ||
+		values.values().any(|v| contains_account(v, account))
+}
+
+// The bytes of a composite value, if it is a sequence of bytes.
+fn bytes<T>(values: &Composite<T>) -> Option<Vec<u8>> {
+	values
+		.values()
+		.map(|v| match v.value {
+			ValueDef::Primitive(Primitive::U128(byte)) => u8::try_from(byte).ok(),
+			_ => None,
+		})
+		.collect()
+}
+
+fn serialize_value<S: Serializer>(value: &Value, serializer: S) -> Result<S::Ok, S::Error> {
+	to_json(value).serialize(serializer)
+}
+
+// Converts a decoded value to JSON, rendering byte sequences as hex. Numbers too large for JSON are
+// rendered as strings.
+fn to_json<T>(value: &Value<T>) -> JsonValue {
+	match &value.value {
+		ValueDef::Composite(values) => composite_to_json(values),
+		ValueDef::Variant(variant) if variant.values.is_empty() =>
+			JsonValue::String(variant.name.clone()),
+		ValueDef::Variant(variant) => JsonValue::Object(Map::from_iter([(
+			variant.name.clone(),
+			composite_to_json(&variant.values),
+		)])),
+		ValueDef::Primitive(Primitive::Bool(b)) => JsonValue::Bool(*b),
+		ValueDef::Primitive(Primitive::Char(c)) => JsonValue::String(c.to_string()),
+		ValueDef::Primitive(Primitive::String(s)) => JsonValue::String(s.clone()),
+		ValueDef::Primitive(Primitive::U128(n)) => u64::try_from(*n)
+			.map(JsonValue::from)
+			.unwrap_or_else(|_| JsonValue::String(n.to_string())),
+		ValueDef::Primitive(Primitive::I128(n)) => i64::try_from(*n)
+			.map(JsonValue::from)
+			.unwrap_or_else(|_| JsonValue::String(n.to_string())),
+		_ => JsonValue::String(format_value(value)),
+	}
+}
+
+fn composite_to_json<T>(values: &Composite<T>) -> JsonValue {
+	match values {
+		Composite::Named(fields) => JsonValue::Object(
+			fields.iter().map(|(name, value)| (name.clone(), to_json(value))).collect(),
+		),
+		Composite::Unnamed(_) => match bytes(values) {
+			Some(bytes) if !bytes.is_empty() => JsonValue::String(to_hex(bytes)),
+			_ => JsonValue::Array(values.values().map(to_json).collect()),
+		},
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn event_filter_works() {
+		let alice = AccountId32::from([1u8; 32]);
+		let fields = Value::named_composite([
+			("who", Value::unnamed_composite([Value::from_bytes([1u8; 32])])),
+			("amount", Value::u128(10)),
+		]);
+		assert!(EventFilter::default().matches("Balances", "Transfer", &fields));
+		let filter = EventFilter {
+			pallet: Some("balances".into()),
+			event: Some("transfer".into()),
+			account: Some(alice),
+		};
+		assert!(filter.matches("Balances", "Transfer", &fields));
+		assert!(!filter.matches("System", "Transfer", &fields));
+		assert!(!filter.matches("Balances", "Deposit", &fields));
+		let filter =
+			EventFilter { account: Some(AccountId32::from([2u8; 32])), ..Default::default() };
+		assert!(!filter.matches("Balances", "Transfer", &fields));
+	}
+
+	#[test]
+	fn display_chain_event_works() {
+		let mut event = ChainEvent {
+			block_number: 10,
+			block_hash: "0x01".into(),
+			extrinsic_index: Some(2),
+			pallet: "System".into(),
+			event: "Remarked".into(),
+			fields: Value::named_composite([
+				("sender", Value::from_bytes([1u8])),
+				("hash", Value::from_bytes([2u8])),
+			]),
+		};
+		assert_eq!(event.to_string(), "#10-2 System::Remarked { sender: 0x01, hash: 0x02 }");
+		event.extrinsic_index = None;
+		event.fields = Value::unnamed_composite([]);
+		assert_eq!(event.to_string(), "#10 System::Remarked");
+	}
+
+	#[test]
+	fn serialize_chain_event_works() -> Result<(), serde_json::Error> {
+		let event = ChainEvent {
+			block_number: 10,
+			block_hash: "0x01".into(),
+			extrinsic_index: Some(2),
+			pallet: "Balances".into(),
+			event: "Transfer".into(),
+			fields: Value::named_composite([
+				("from", Value::unnamed_composite([Value::from_bytes([1u8, 2])])),
+				("amount", Value::u128(u128::MAX)),
+				("fee", Value::u128(10)),
+				("status", Value::unnamed_variant("Free", [])),
+				("memo", Value::unnamed_variant("Some", [Value::string("hi"), Value::bool(true)])),
+				("calls", Value::unnamed_composite([])),
+			]),
+		};
+		assert_eq!(
+			serde_json::to_value(&event)?,
+			serde_json::json!({
+				"block_number": 10,
+				"block_hash": "0x01",
+				"extrinsic_index": 2,
+				"pallet": "Balances",
+				"event": "Transfer",
+				"fields": {
+					"from": ["0x0102"],
+					"amount": u128::MAX.to_string(),
+					"fee": 10,
+					"status": "Free",
+					"memo": { "Some": ["hi", true] },
+					"calls": [],
+				},
+			})
+		);
+		Ok(())
+	}
+}
@@ -2,9 +2,15 @@
 
 use crate::errors::Error;
 use params::Param;
+use pop_common::format_type;
 use scale_value::stringify::custom_parsers;
 use std::fmt::{Display, Formatter};
-use subxt::{dynamic::Value, utils::to_hex, Metadata, OnlineClient, SubstrateConfig};
+use subxt::{
+	dynamic::Value,
+	metadata::types::{ConstantMetadata, StorageEntryMetadata, StorageEntryType},
+	utils::to_hex,
+	Metadata, OnlineClient, SubstrateConfig,
+};
 
 pub mod action;
 pub mod params;
@@ -20,6 +26,10 @@ pub struct Pallet {
 	pub docs: String,
 	/// The dispatchable functions of the pallet.
 	pub functions: Vec<Function>,
+	/// The storage items of the pallet.
+	pub storage: Vec<Storage>,
+	/// The constants of the pallet.
+	pub constants: Vec<Constant>,
 }
 
 impl Display for Pallet {
@@ -51,6 +61,50 @@ impl Display for Function {
 	}
 }
 
+/// Represents a storage item of a pallet.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Storage {
+	/// The pallet containing the storage item.
+	pub pallet: String,
+	/// The name of the storage item.
+	pub name: String,
+	/// The documentation of the storage item.
+	pub docs: String,
+	/// The type of the stored value.
+	pub type_name: String,
+	/// The keys of the storage item, if it is a map.
+	pub keys: Vec<Param>,
+	/// Whether this storage item is supported (keys of unsupported types cannot be provided).
+	pub is_supported: bool,
+}
+
+impl Display for Storage {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.name)
+	}
+}
+
+/// Represents a constant of a pallet.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Constant {
+	/// The pallet containing the constant.
+	pub pallet: String,
+	/// The name of the constant.
+	pub name: String,
+	/// The documentation of the constant.
+	pub docs: String,
+	/// The type of the constant.
+	pub type_name: String,
+	/// The decoded value of the constant, or `None` if it could not be decoded.
+	pub value: Option<String>,
+}
+
+impl Display for Constant {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.name)
+	}
+}
+
 /// Parses the chain metadata to extract information about pallets and their dispatchable functions.
 ///
 /// # Arguments
@@ -114,11 +168,28 @@ pub fn parse_chain_metadata(client: &OnlineClient<SubstrateConfig>) -> Result<Ve
 				})
 				.unwrap_or_else(|| Ok(vec![]))?;
 
+			let storage = pallet
+				.storage()
+				.map(|storage| {
+					storage
+						.entries()
+						.iter()
+						.map(|entry| parse_storage_entry(&metadata, pallet.name(), entry))
+						.collect()
+				})
+				.unwrap_or_default();
+			let constants = pallet
+				.constants()
+				.map(|constant| parse_constant(&metadata, pallet.name(), constant))
+				.collect();
+
 			Ok(Pallet {
 				name: pallet.name().to_string(),
 				index: pallet.index(),
 				docs: pallet.docs().join(" "),
 				functions,
+				storage,
+				constants,
 			})
 		})
 		.collect::<Result<Vec<Pallet>, Error>>()?;
@@ -126,6 +197,71 @@ pub fn parse_chain_metadata(client: &OnlineClient<SubstrateConfig>) -> Result<Ve
 	Ok(pallets)
 }
 
+// Parses a storage entry, including the parameters of its keys.
+fn parse_storage_entry(metadata: &Metadata, pallet: &str, entry: &StorageEntryMetadata) -> Storage {
+	let registry = metadata.types();
+	let keys = match entry.entry_type() {
+		StorageEntryType::Plain(_) => Ok(vec![]),
+		StorageEntryType::Map { hashers, key_ty, .. } => match registry.resolve(*key_ty) {
+			// Maps with multiple hashers use a tuple of keys, one for each hasher.
+			Some(key) if hashers.len() > 1 => match &key.type_def {
+				scale_info::TypeDef::Tuple(tuple) => tuple
+					.fields
+					.iter()
+					.enumerate()
+					.map(|(i, field)| {
+						params::type_to_param(&format!("key{}", i + 1), registry, field.id)
+					})
+					.collect(),
+				_ => Err(Error::MetadataParsingError(entry.name().to_string())),
+			},
+			_ => params::type_to_param("key", registry, *key_ty).map(|key| vec![key]),
+		},
+	};
+	Storage {
+		pallet: pallet.to_string(),
+		name: entry.name().to_string(),
+		docs: entry
+			.docs()
+			.iter()
+			.filter(|l| !l.is_empty())
+			.cloned()
+			.collect::<Vec<_>>()
+			.join(" "),
+		type_name: registry
+			.resolve(entry.entry_type().value_ty())
+			.map(|ty| format_type(ty, registry))
+			.unwrap_or_default(),
+		is_supported: keys.is_ok(),
+		keys: keys.unwrap_or_default(),
+	}
+}
+
+// Parses a constant, decoding its value. A value which cannot be decoded is left out rather than
+// failing the parsing of the whole metadata.
+fn parse_constant(metadata: &Metadata, pallet: &str, constant: &ConstantMetadata) -> Constant {
+	let registry = metadata.types();
+	let value = scale_value::scale::decode_as_type(&mut constant.value(), constant.ty(), registry)
+		.ok()
+		.map(|value| super::format_value(&value));
+	Constant {
+		pallet: pallet.to_string(),
+		name: constant.name().to_string(),
+		docs: constant
+			.docs()
+			.iter()
+			.filter(|l| !l.is_empty())
+			.cloned()
+			.collect::<Vec<_>>()
+			.join(" "),
+		type_name: registry
+			.resolve(constant.ty())
+			.map(|ty| format_type(ty, registry))
+			.unwrap_or_default(),
+		value,
+	}
+}
+
 /// Finds a specific pallet by name and retrieves its details from metadata.
 ///
 /// # Arguments
@@ -161,6 +297,44 @@ pub fn find_dispatchable_by_name<'a>(
 	}
 }
 
+/// Finds a specific storage item by name and retrieves its details from metadata.
+///
+/// # Arguments
+/// * `pallets`: List of pallets available within the chain's runtime.
+/// * `pallet_name`: The name of the pallet.
+/// * `storage_name`: Name of the storage item to locate.
+pub fn find_storage_by_name<'a>(
+	pallets: &'a [Pallet],
+	pallet_name: &str,
+	storage_name: &str,
+) -> Result<&'a Storage, Error> {
+	let pallet = find_pallet_by_name(pallets, pallet_name)?;
+	pallet
+		.storage
+		.iter()
+		.find(|s| s.name == storage_name)
+		.ok_or_else(|| Error::StorageNotFound(format!("{pallet_name}::{storage_name}")))
+}
+
+/// Finds a specific constant by name and retrieves its details from metadata.
+///
+/// # Arguments
+/// * `pallets`: List of pallets available within the chain's runtime.
+/// * `pallet_name`: The name of the pallet.
+/// * `constant_name`: Name of the constant to locate.
+pub fn find_constant_by_name<'a>(
+	pallets: &'a [Pallet],
+	pallet_name: &str,
+	constant_name: &str,
+) -> Result<&'a Constant, Error> {
+	let pallet = find_pallet_by_name(pallets, pallet_name)?;
+	pallet
+		.constants
+		.iter()
+		.find(|c| c.name == constant_name)
+		.ok_or_else(|| Error::ConstantNotFound(format!("{pallet_name}::{constant_name}")))
+}
+
 /// Parses and processes raw string parameter values for a dispatchable function, mapping them to
 /// `Value` types.
 ///
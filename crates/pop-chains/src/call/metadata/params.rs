@@ -46,7 +46,11 @@ pub fn field_to_param(metadata: &Metadata, field: &Field<PortableForm>) -> Resul
 /// * `name`: The name of the parameter.
 /// * `registry`: Type registry containing all types used in the metadata.
 /// * `type_id`: The ID of the type to be converted.
-fn type_to_param(name: &str, registry: &PortableRegistry, type_id: u32) -> Result<Param, Error> {
+pub(crate) fn type_to_param(
+	name: &str,
+	registry: &PortableRegistry,
+	type_id: u32,
+) -> Result<Param, Error> {
 	let type_info = registry
 		.resolve(type_id)
 		.ok_or_else(|| Error::MetadataParsingError(name.to_string()))?;
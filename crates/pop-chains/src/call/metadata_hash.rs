@@ -0,0 +1,311 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use crate::errors::Error;
+use frame_metadata::RuntimeMetadataPrefixed;
+use merkleized_metadata::{generate_metadata_digest, ExtraInfo};
+use pop_common::{KeyType, Signer};
+use scale::{Decode, Encode};
+use scale_info::PortableRegistry;
+use scale_value::At;
+use serde_json::Value;
+use subxt::{
+	backend::{legacy::LegacyRpcMethods, rpc::RpcClient},
+	client::ClientState,
+	config::{
+		signed_extensions::{self, CheckMetadataHashMode},
+		substrate::{BlakeTwo256, SubstrateHeader},
+		Config, DefaultExtrinsicParamsBuilder, ExtrinsicParams, ExtrinsicParamsEncoder,
+		ExtrinsicParamsError, RefineParams,
+	},
+	tx::{Payload, SubmittableExtrinsic, TransactionInvalid, ValidationResult},
+	utils::{AccountId32, MultiAddress, MultiSignature, H256},
+	OfflineClient, OnlineClient, SubstrateConfig,
+};
+
+const CHECK_METADATA_HASH: &str = "CheckMetadataHash";
+// The secret URI of the throwaway account used to check whether the runtime verifies the hash.
+const PROBE_SURI: &str = "//MetadataHashProbe";
+
+/// A configuration for Polkadot SDK based chains, equivalent to [`SubstrateConfig`] except that
+/// it can provide the metadata hash verified by the `CheckMetadataHash` signed extension.
+pub(crate) enum MetadataHashConfig {}
+
+impl Config for MetadataHashConfig {
+	type Hash = H256;
+	type AccountId = AccountId32;
+	type Address = MultiAddress<Self::AccountId, u32>;
+	type Signature = MultiSignature;
+	type Hasher = BlakeTwo256;
+	type Header = SubstrateHeader<u32, BlakeTwo256>;
+	type ExtrinsicParams = signed_extensions::AnyOf<
+		Self,
+		(
+			signed_extensions::CheckSpecVersion,
+			signed_extensions::CheckTxVersion,
+			signed_extensions::CheckNonce,
+			signed_extensions::CheckGenesis<Self>,
+			signed_extensions::CheckMortality<Self>,
+			signed_extensions::ChargeAssetTxPayment<Self>,
+			signed_extensions::ChargeTransactionPayment,
+			CheckMetadataHash,
+		),
+	>;
+	type AssetId = u32;
+}
+
+/// The `CheckMetadataHash` signed extension, which includes the hash of the chain metadata
+/// (RFC-0078) in the signed data when provided.
+pub(crate) struct CheckMetadataHash {
+	hash: Option<[u8; 32]>,
+}
+
+/// The parameters of the [`CheckMetadataHash`] signed extension: the metadata hash to include, if
+/// any.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct CheckMetadataHashParams(pub Option<[u8; 32]>);
+
+impl<T: Config> RefineParams<T> for CheckMetadataHashParams {}
+
+impl<T: Config> ExtrinsicParams<T> for CheckMetadataHash {
+	type Params = CheckMetadataHashParams;
+
+	fn new(_client: &ClientState<T>, params: Self::Params) -> Result<Self, ExtrinsicParamsError> {
+		Ok(CheckMetadataHash { hash: params.0 })
+	}
+}
+
+impl ExtrinsicParamsEncoder for CheckMetadataHash {
+	fn encode_extra_to(&self, v: &mut Vec<u8>) {
+		// The mode: whether the metadata hash is included in the signed data.
+		(self.hash.is_some() as u8).encode_to(v);
+	}
+
+	fn encode_additional_to(&self, v: &mut Vec<u8>) {
+		self.hash.encode_to(v);
+	}
+}
+
+impl<T: Config> signed_extensions::SignedExtension<T> for CheckMetadataHash {
+	type Decoded = CheckMetadataHashMode;
+
+	fn matches(identifier: &str, _type_id: u32, _types: &PortableRegistry) -> bool {
+		identifier == CHECK_METADATA_HASH
+	}
+}
+
+/// Whether the chain includes the `CheckMetadataHash` signed extension in its transactions.
+///
+/// # Arguments
+/// * `client` - The client used to interact with the chain.
+pub fn supports_metadata_hash(client: &OnlineClient<SubstrateConfig>) -> bool {
+	client
+		.metadata()
+		.extrinsic()
+		.signed_extensions()
+		.iter()
+		.any(|extension| extension.identifier() == CHECK_METADATA_HASH)
+}
+
+/// Generates the hash of the chain metadata, as described by RFC-0078, from the runtime metadata
+/// of the client and the chain properties.
+///
+/// # Arguments
+/// * `client` - The client used to interact with the chain.
+/// * `url` - Endpoint of the node.
+pub async fn generate_metadata_hash(
+	client: &OnlineClient<SubstrateConfig>,
+	url: &url::Url,
+) -> Result<[u8; 32], Error> {
+	// The metadata of the client is encoded as V15, from which the hash is generated.
+	let metadata = RuntimeMetadataPrefixed::decode(&mut client.metadata().encode().as_slice())
+		.map_err(|e| Error::MetadataHashError(e.to_string()))?
+		.1;
+	let version = client
+		.constants()
+		.at(&subxt::dynamic::constant("System", "Version"))
+		.and_then(|version| version.to_value().map_err(Into::into))
+		.map_err(|e| Error::MetadataHashError(e.to_string()))?;
+	// The token of the chain is only available from the chain properties.
+	let rpc_client = RpcClient::from_url(url.as_str())
+		.await
+		.map_err(|e| Error::ConnectionFailure(e.to_string()))?;
+	let properties = LegacyRpcMethods::<SubstrateConfig>::new(rpc_client)
+		.system_properties()
+		.await
+		.map_err(|e| Error::MetadataHashError(e.to_string()))?;
+	let extra_info = ExtraInfo {
+		spec_version: client.runtime_version().spec_version,
+		spec_name: version
+			.at("spec_name")
+			.and_then(|name| name.as_str())
+			.ok_or_else(|| Error::MetadataHashError("the runtime name is unknown".into()))?
+			.to_string(),
+		base58_prefix: properties
+			.get("ss58Format")
+			.and_then(Value::as_u64)
+			.and_then(|prefix| u16::try_from(prefix).ok())
+			.unwrap_or(42),
+		decimals: first_property(properties.get("tokenDecimals"))
+			.and_then(Value::as_u64)
+			.and_then(|decimals| u8::try_from(decimals).ok())
+			.ok_or_else(|| Error::MetadataHashError("the token decimals are unknown".into()))?,
+		token_symbol: first_property(properties.get("tokenSymbol"))
+			.and_then(Value::as_str)
+			.ok_or_else(|| Error::MetadataHashError("the token symbol is unknown".into()))?
+			.to_string(),
+	};
+	let digest =
+		generate_metadata_digest(&metadata, extra_info).map_err(Error::MetadataHashError)?;
+	Ok(digest.hash())
+}
+
+/// Returns the metadata hash to be included in transactions, if the chain verifies it.
+///
+/// The hash is only verified by runtimes built with it, so transactions including it are
+/// otherwise rejected, despite the chain including the `CheckMetadataHash` signed extension.
+///
+/// # Arguments
+/// * `client` - The client used to interact with the chain.
+/// * `url` - Endpoint of the node.
+pub async fn metadata_hash(
+	client: &OnlineClient<SubstrateConfig>,
+	url: &url::Url,
+) -> Result<Option<[u8; 32]>, Error> {
+	if !supports_metadata_hash(client) {
+		return Ok(None);
+	}
+	// Chains which do not specify their token cannot verify a metadata hash.
+	let Ok(hash) = generate_metadata_hash(client, url).await else {
+		return Ok(None);
+	};
+	// Validate a remark signed by a throwaway account: a mismatch of the signed data, such as an
+	// unexpected metadata hash, is reported as a bad proof.
+	let signer = Signer::from_uri(PROBE_SURI, KeyType::Sr25519)?;
+	let remark =
+		subxt::dynamic::tx("System", "remark", vec![subxt::dynamic::Value::from_bytes([])]);
+	let probe = sign(client, &remark, &signer, 0, Some(hash))?;
+	let validation = probe.validate().await.map_err(|e| Error::MetadataHashError(e.to_string()))?;
+	Ok(match validation {
+		ValidationResult::Invalid(TransactionInvalid::BadProof) => None,
+		_ => Some(hash),
+	})
+}
+
+/// Signs an extrinsic, including the metadata hash verified by the `CheckMetadataHash` signed
+/// extension when supported by the chain.
+///
+/// # Arguments
+/// * `client` - The client used to interact with the chain.
+/// * `url` - Endpoint of the node.
+/// * `xt` - The extrinsic to be signed.
+/// * `signer` - The signer of the extrinsic.
+pub async fn create_signed_extrinsic<Xt: Payload>(
+	client: &OnlineClient<SubstrateConfig>,
+	url: &url::Url,
+	xt: &Xt,
+	signer: &Signer,
+) -> Result<SubmittableExtrinsic<SubstrateConfig, OnlineClient<SubstrateConfig>>, Error> {
+	let hash = metadata_hash(client, url).await?;
+	let nonce = client
+		.tx()
+		.account_nonce(&signer.account_id())
+		.await
+		.map_err(|e| Error::ExtrinsicSubmissionError(e.to_string()))?;
+	sign(client, xt, signer, nonce, hash)
+}
+
+/// Creates an offline client for the chain, able to provide the metadata hash.
+///
+/// # Arguments
+/// * `client` - The client used to interact with the chain.
+pub(crate) fn offline_client(
+	client: &OnlineClient<SubstrateConfig>,
+) -> OfflineClient<MetadataHashConfig> {
+	OfflineClient::new(client.genesis_hash(), client.runtime_version(), client.metadata())
+}
+
+/// The parameters of an extrinsic.
+///
+/// # Arguments
+/// * `nonce` - The nonce of the signing account.
+/// * `mortality` - The block number and hash from which the extrinsic is valid, and the number of
+///   blocks for which it is valid. The extrinsic is immortal if not specified.
+/// * `hash` - The metadata hash to be included, if any.
+pub(crate) fn extrinsic_params(
+	nonce: u64,
+	mortality: Option<(u64, H256, u64)>,
+	hash: Option<[u8; 32]>,
+) -> <<MetadataHashConfig as Config>::ExtrinsicParams as ExtrinsicParams<MetadataHashConfig>>::Params
+{
+	let mut builder = DefaultExtrinsicParamsBuilder::<MetadataHashConfig>::new().nonce(nonce);
+	if let Some((block_number, block_hash, period)) = mortality {
+		builder = builder.mortal_unchecked(block_number, block_hash, period);
+	}
+	let (spec_version, tx_version, nonce, genesis, mortality, asset_payment, payment, _) =
+		builder.build();
+	(
+		spec_version,
+		tx_version,
+		nonce,
+		genesis,
+		mortality,
+		asset_payment,
+		payment,
+		CheckMetadataHashParams(hash),
+	)
+}
+
+// Signs an extrinsic with the given nonce, returning it ready to be submitted to the chain.
+fn sign<Xt: Payload>(
+	client: &OnlineClient<SubstrateConfig>,
+	xt: &Xt,
+	signer: &Signer,
+	nonce: u64,
+	hash: Option<[u8; 32]>,
+) -> Result<SubmittableExtrinsic<SubstrateConfig, OnlineClient<SubstrateConfig>>, Error> {
+	let signed = offline_client(client)
+		.tx()
+		.create_signed_offline(xt, signer, extrinsic_params(nonce, None, hash))
+		.map_err(|e| Error::ExtrinsicSubmissionError(format!("{:?}", e)))?;
+	Ok(SubmittableExtrinsic::from_bytes(client.clone(), signed.into_encoded()))
+}
+
+// Returns the first value of a chain property, which may be specified for multiple tokens.
+fn first_property(value: Option<&Value>) -> Option<&Value> {
+	match value? {
+		Value::Array(values) => values.first(),
+		value => Some(value),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::json;
+
+	#[test]
+	fn check_metadata_hash_encoding_works() {
+		let mut extra = Vec::new();
+		let mut additional = Vec::new();
+		let extension = CheckMetadataHash { hash: None };
+		extension.encode_extra_to(&mut extra);
+		extension.encode_additional_to(&mut additional);
+		assert_eq!((extra, additional), (vec![0], vec![0]));
+
+		let mut extra = Vec::new();
+		let mut additional = Vec::new();
+		let extension = CheckMetadataHash { hash: Some([1u8; 32]) };
+		extension.encode_extra_to(&mut extra);
+		extension.encode_additional_to(&mut additional);
+		assert_eq!(extra, vec![1]);
+		assert_eq!(additional, [vec![1], vec![1u8; 32]].concat());
+	}
+
+	#[test]
+	fn first_property_works() {
+		assert_eq!(first_property(Some(&json!(10))), Some(&json!(10)));
+		assert_eq!(first_property(Some(&json!(["DOT", "USDT"]))), Some(&json!("DOT")));
+		assert_eq!(first_property(Some(&json!([]))), None);
+		assert_eq!(first_property(None), None);
+	}
+}
@@ -5,14 +5,27 @@ use pop_common::{
 	call::{DefaultEnvironment, DisplayEvents, TokenMetadata, Verbosity},
 	create_signer,
 };
-use sp_core::bytes::{from_hex, to_hex};
+use scale_value::{At, Composite, ValueDef};
+use serde::Serialize;
+use sp_core::{
+	bytes::{from_hex, to_hex},
+	crypto::{AccountId32, Ss58Codec},
+};
+use std::fmt::{Display, Formatter};
+use strum_macros::{AsRefStr, EnumString};
 use subxt::{
 	blocks::ExtrinsicEvents,
 	dynamic::Value,
 	tx::{DynamicPayload, Payload, SubmittableExtrinsic},
-	OnlineClient, SubstrateConfig,
+	Metadata, OnlineClient, SubstrateConfig,
 };
+pub mod dry_run;
+pub mod events;
 pub mod metadata;
+pub mod metadata_hash;
+pub mod multisig;
+pub mod offline;
+pub mod storage;
 
 /// Sets up an [OnlineClient] instance for connecting to a blockchain.
 ///
@@ -73,7 +86,41 @@ pub fn construct_proxy_extrinsic(
 	Ok(subxt::dynamic::tx("Proxy", "proxy", [real, proxy_type, xt.into_value()].to_vec()))
 }
 
-/// Signs and submits a given extrinsic.
+/// The dispatchable function of the `Utility` pallet used to batch calls.
+#[derive(AsRefStr, Clone, Copy, Debug, Default, EnumString, Eq, PartialEq, clap::ValueEnum)]
+#[clap(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum BatchMode {
+	/// Dispatch the calls until the first failure, without reverting earlier calls.
+	#[default]
+	Batch,
+	/// Dispatch the calls atomically, reverting all of them if any fails.
+	BatchAll,
+	/// Dispatch all the calls, continuing past any failures.
+	ForceBatch,
+}
+
+/// Constructs a batch extrinsic, wrapping the calls in a dispatchable function of the `Utility`
+/// pallet.
+///
+/// # Arguments
+/// * `pallets`: List of pallets available within the chain's runtime.
+/// * `mode` - The dispatchable function used to batch the calls.
+/// * `calls` - The extrinsics representing the dispatchable function calls to be batched.
+pub fn construct_batch_extrinsic(
+	pallets: &[Pallet],
+	mode: BatchMode,
+	calls: Vec<DynamicPayload>,
+) -> Result<DynamicPayload, Error> {
+	find_dispatchable_by_name(pallets, "Utility", mode.as_ref())?;
+	Ok(subxt::dynamic::tx(
+		"Utility",
+		mode.as_ref(),
+		[Value::unnamed_composite(calls.into_iter().map(|xt| xt.into_value()))].to_vec(),
+	))
+}
+
+/// Signs and submits a given extrinsic, including the metadata hash when verified by the chain.
 ///
 /// # Arguments
 /// * `client` - The client used to interact with the chain.
@@ -87,9 +134,9 @@ pub async fn sign_and_submit_extrinsic<Xt: Payload>(
 	suri: &str,
 ) -> Result<String, Error> {
 	let signer = create_signer(suri)?;
-	let result = client
-		.tx()
-		.sign_and_submit_then_watch_default(&xt, &signer)
+	let result = metadata_hash::create_signed_extrinsic(client, url, &xt, &signer)
+		.await?
+		.submit_and_watch()
 		.await
 		.map_err(|e| Error::ExtrinsicSubmissionError(format!("{:?}", e)))?
 		.wait_for_finalized_success()
@@ -101,6 +148,72 @@ pub async fn sign_and_submit_extrinsic<Xt: Payload>(
 	Ok(format!("Extrinsic Submitted with hash: {:?}\n\n{}", result.extrinsic_hash(), events))
 }
 
+/// A submitted extrinsic, included in a finalized block.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SubmittedExtrinsic {
+	/// The hash of the extrinsic.
+	pub extrinsic_hash: String,
+	/// The events emitted by the extrinsic, formatted as `Pallet::Event`.
+	pub events: Vec<String>,
+}
+
+/// Signs and submits a given extrinsic, returning the names of the events it emitted once
+/// finalized.
+///
+/// # Arguments
+/// * `client` - The client used to interact with the chain.
+/// * `url` - Endpoint of the node.
+/// * `xt` - The extrinsic to be signed and submitted.
+/// * `suri` - The secret URI (e.g., mnemonic or private key) for signing the extrinsic.
+pub async fn submit_extrinsic_with_events<Xt: Payload>(
+	client: &OnlineClient<SubstrateConfig>,
+	url: &url::Url,
+	xt: Xt,
+	suri: &str,
+) -> Result<SubmittedExtrinsic, Error> {
+	let signer = create_signer(suri)?;
+	let result = metadata_hash::create_signed_extrinsic(client, url, &xt, &signer)
+		.await?
+		.submit_and_watch()
+		.await
+		.map_err(|e| Error::ExtrinsicSubmissionError(format!("{:?}", e)))?
+		.wait_for_finalized_success()
+		.await
+		.map_err(|e| Error::ExtrinsicSubmissionError(e.to_string()))?;
+	let mut events = Vec::new();
+	for event in result.iter() {
+		let event = event.map_err(|e| Error::ExtrinsicSubmissionError(e.to_string()))?;
+		// `Sudo::sudo` succeeds regardless of the result of the dispatched call, which is only
+		// reported by the `Sudid` event.
+		if event.pallet_name() == "Sudo" && event.variant_name() == "Sudid" {
+			let fields = event
+				.field_values()
+				.map_err(|e| Error::ExtrinsicSubmissionError(e.to_string()))?;
+			if let Some(error) = sudo_error(&fields) {
+				return Err(Error::ExtrinsicSubmissionError(format!(
+					"the sudo call failed: {error}"
+				)));
+			}
+		}
+		events.push(format!("{}::{}", event.pallet_name(), event.variant_name()));
+	}
+	Ok(SubmittedExtrinsic { extrinsic_hash: format!("{:?}", result.extrinsic_hash()), events })
+}
+
+// The error of the call dispatched by `Sudo::sudo`, as reported by the fields of a `Sudid` event.
+pub(crate) fn sudo_error(fields: &Composite<u32>) -> Option<String> {
+	let result = match fields {
+		Composite::Named(fields) =>
+			fields.iter().find(|(name, _)| name == "sudo_result").map(|(_, value)| value),
+		Composite::Unnamed(fields) => fields.first(),
+	}?;
+	match &result.value {
+		ValueDef::Variant(variant) if variant.name == "Err" =>
+			Some(variant.values.values().next().map(format_value).unwrap_or_default()),
+		_ => None,
+	}
+}
+
 /// Parses and formats the events from the extrinsic result.
 ///
 /// # Arguments
@@ -169,6 +282,228 @@ pub fn decode_call_data(call_data: &str) -> Result<Vec<u8>, Error> {
 	from_hex(call_data).map_err(|e| Error::CallDataDecodingError(e.to_string()))
 }
 
+/// A dispatchable function call decoded against the chain's metadata.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct DecodedCall {
+	/// The pallet containing the dispatchable function.
+	pub pallet: String,
+	/// The name of the dispatchable function.
+	pub function: String,
+	/// The arguments of the dispatchable function.
+	pub args: Vec<DecodedArg>,
+}
+
+impl DecodedCall {
+	// Writes the call as a tree, with nested calls indented beneath their parent.
+	fn write_tree(&self, f: &mut Formatter<'_>, indent: usize) -> std::fmt::Result {
+		write!(f, "{}::{}", self.pallet, self.function)?;
+		for arg in &self.args {
+			write!(f, "\n{:indent$}{}:", "", arg.name, indent = indent + 2)?;
+			arg.value.write_tree(f, indent + 2)?;
+		}
+		Ok(())
+	}
+}
+
+impl Display for DecodedCall {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		self.write_tree(f, 0)
+	}
+}
+
+/// A named value decoded against the chain's metadata.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct DecodedArg {
+	/// The name of the value.
+	pub name: String,
+	/// The decoded value.
+	pub value: DecodedValue,
+}
+
+/// A decoded value, which may contain nested calls.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum DecodedValue {
+	/// A nested call, such as the call dispatched by `Sudo::sudo`.
+	Call(Box<DecodedCall>),
+	/// A sequence of nested calls, such as the calls dispatched by `Utility::batch`.
+	Calls(Vec<DecodedCall>),
+	/// Any other value, formatted as a string.
+	Value(String),
+}
+
+impl DecodedValue {
+	fn write_tree(&self, f: &mut Formatter<'_>, indent: usize) -> std::fmt::Result {
+		match self {
+			DecodedValue::Call(call) => {
+				write!(f, " ")?;
+				call.write_tree(f, indent)
+			},
+			DecodedValue::Calls(calls) => {
+				for call in calls {
+					write!(f, "\n{:indent$}- ", "", indent = indent + 2)?;
+					call.write_tree(f, indent + 4)?;
+				}
+				Ok(())
+			},
+			DecodedValue::Value(value) => write!(f, " {value}"),
+		}
+	}
+}
+
+/// An extrinsic decoded against the chain's metadata.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct DecodedExtrinsic {
+	/// The account which signed the extrinsic, if signed.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub signer: Option<String>,
+	/// The signed extensions of the extrinsic (e.g. nonce or tip), if signed.
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub extensions: Vec<DecodedArg>,
+	/// The call of the extrinsic.
+	pub call: DecodedCall,
+}
+
+impl Display for DecodedExtrinsic {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		if let Some(signer) = &self.signer {
+			writeln!(f, "signer: {signer}")?;
+		}
+		for extension in &self.extensions {
+			write!(f, "{}:", extension.name)?;
+			extension.value.write_tree(f, 0)?;
+			writeln!(f)?;
+		}
+		write!(f, "call: ")?;
+		self.call.write_tree(f, 0)
+	}
+}
+
+/// Decodes SCALE-encoded call data or a full extrinsic, resolving the pallet, dispatchable
+/// function and arguments against the chain's metadata.
+///
+/// # Arguments
+/// * `client` - The client used to interact with the chain.
+/// * `data` - The hex-encoded call data or extrinsic.
+pub fn decode_extrinsic(
+	client: &OnlineClient<SubstrateConfig>,
+	data: &str,
+) -> Result<DecodedExtrinsic, Error> {
+	let bytes = decode_call_data(data)?;
+	let metadata = client.metadata();
+	// Try decoding as call data first, falling back to a full extrinsic.
+	if let Ok(call) = decode_call(&metadata, &bytes) {
+		return Ok(DecodedExtrinsic { signer: None, extensions: vec![], call });
+	}
+	let extrinsics = subxt::ext::subxt_core::blocks::decode_from::<SubstrateConfig>(
+		vec![bytes],
+		metadata.clone(),
+	)
+	.map_err(|e| Error::CallDataDecodingError(e.to_string()))?;
+	let extrinsic = extrinsics
+		.iter()
+		.next()
+		.ok_or_else(|| Error::CallDataDecodingError("No extrinsic found".to_string()))?;
+	let extensions = match extrinsic.signed_extensions() {
+		Some(extensions) => extensions
+			.iter()
+			// Skip extensions without any data included in the extrinsic.
+			.filter(|e| !e.bytes().is_empty())
+			.map(|e| {
+				Ok(DecodedArg {
+					name: e.name().to_string(),
+					value: DecodedValue::Value(format_value(&e.value()?)),
+				})
+			})
+			.collect::<Result<Vec<_>, subxt::ext::subxt_core::Error>>()
+			.map_err(|e| Error::CallDataDecodingError(e.to_string()))?,
+		None => vec![],
+	};
+	Ok(DecodedExtrinsic {
+		signer: extrinsic.address_bytes().map(format_address),
+		extensions,
+		call: decode_call(&metadata, extrinsic.call_bytes())?,
+	})
+}
+
+// Decodes call data into a call, ensuring all bytes are consumed.
+fn decode_call(metadata: &Metadata, call_data: &[u8]) -> Result<DecodedCall, Error> {
+	let call_ty = metadata.outer_enums().call_enum_ty();
+	let cursor = &mut &call_data[..];
+	let call = scale_value::scale::decode_as_type(cursor, call_ty, metadata.types())
+		.map_err(|e| Error::CallDataDecodingError(e.to_string()))?;
+	if !cursor.is_empty() {
+		return Err(Error::CallDataDecodingError(format!(
+			"{} bytes were not decoded",
+			cursor.len()
+		)));
+	}
+	to_decoded_call(&call, call_ty)
+}
+
+// Converts a value of the runtime's call type into a call.
+fn to_decoded_call(call: &Value<u32>, call_ty: u32) -> Result<DecodedCall, Error> {
+	let ValueDef::Variant(pallet) = &call.value else {
+		return Err(Error::CallDataDecodingError("Expected a pallet variant".to_string()));
+	};
+	let Some(ValueDef::Variant(function)) = pallet.values.at(0).map(|f| &f.value) else {
+		return Err(Error::CallDataDecodingError("Expected a function variant".to_string()));
+	};
+	let args = match &function.values {
+		Composite::Named(fields) => fields
+			.iter()
+			.map(|(name, value)| {
+				Ok(DecodedArg { name: name.clone(), value: to_decoded_value(value, call_ty)? })
+			})
+			.collect::<Result<Vec<_>, Error>>()?,
+		Composite::Unnamed(values) => values
+			.iter()
+			.enumerate()
+			.map(|(index, value)| {
+				Ok(DecodedArg { name: index.to_string(), value: to_decoded_value(value, call_ty)? })
+			})
+			.collect::<Result<Vec<_>, Error>>()?,
+	};
+	Ok(DecodedCall { pallet: pallet.name.clone(), function: function.name.clone(), args })
+}
+
+// Converts a value into a decoded value, resolving any nested calls.
+fn to_decoded_value(value: &Value<u32>, call_ty: u32) -> Result<DecodedValue, Error> {
+	if value.context == call_ty {
+		return Ok(DecodedValue::Call(Box::new(to_decoded_call(value, call_ty)?)));
+	}
+	if let ValueDef::Composite(values) = &value.value {
+		if !values.is_empty() && values.values().all(|v| v.context == call_ty) {
+			return Ok(DecodedValue::Calls(
+				values.values().map(|v| to_decoded_call(v, call_ty)).collect::<Result<_, _>>()?,
+			));
+		}
+	}
+	Ok(DecodedValue::Value(format_value(value)))
+}
+
+// Formats the address of a signer, using the SS58 format for account identifiers.
+fn format_address(address: &[u8]) -> String {
+	// A `MultiAddress::Id` is prefixed by its variant index.
+	let account = match address.len() {
+		32 => address,
+		33 if address[0] == 0 => &address[1..],
+		_ => return to_hex(address, false),
+	};
+	let mut bytes = [0u8; 32];
+	bytes.copy_from_slice(account);
+	AccountId32::from(bytes).to_ss58check()
+}
+
+// Formats a decoded value, rendering byte sequences as hex.
+pub(crate) fn format_value<T>(value: &Value<T>) -> String {
+	let mut output = String::new();
+	let _ = scale_value::stringify::to_writer_custom()
+		.add_custom_formatter(|v, w| scale_value::stringify::custom_formatters::format_hex(v, w))
+		.write(value, &mut output);
+	output
+}
+
 /// This struct implements the [`Payload`] trait and is used to submit
 /// pre-encoded SCALE call data directly, without the dynamic construction of transactions.
 pub struct CallData(Vec<u8>);
@@ -208,6 +543,67 @@ mod tests {
 		Ok(())
 	}
 
+	#[test]
+	fn display_decoded_call_works() {
+		let remark = DecodedCall {
+			pallet: "System".to_string(),
+			function: "remark".to_string(),
+			args: vec![DecodedArg {
+				name: "remark".to_string(),
+				value: DecodedValue::Value("0x11".to_string()),
+			}],
+		};
+		let batch = DecodedCall {
+			pallet: "Utility".to_string(),
+			function: "batch".to_string(),
+			args: vec![DecodedArg {
+				name: "calls".to_string(),
+				value: DecodedValue::Calls(vec![remark.clone(), remark.clone()]),
+			}],
+		};
+		let extrinsic = DecodedExtrinsic {
+			signer: Some("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY".to_string()),
+			extensions: vec![DecodedArg {
+				name: "CheckNonce".to_string(),
+				value: DecodedValue::Value("0".to_string()),
+			}],
+			call: DecodedCall {
+				pallet: "Sudo".to_string(),
+				function: "sudo".to_string(),
+				args: vec![DecodedArg {
+					name: "call".to_string(),
+					value: DecodedValue::Call(Box::new(batch)),
+				}],
+			},
+		};
+		assert_eq!(
+			extrinsic.to_string(),
+			"signer: 5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY
+CheckNonce: 0
+call: Sudo::sudo
+  call: Utility::batch
+    calls:
+      - System::remark
+          remark: 0x11
+      - System::remark
+          remark: 0x11"
+		);
+		assert_eq!(
+			serde_json::to_string(&remark).unwrap(),
+			r#"{"pallet":"System","function":"remark","args":[{"name":"remark","value":"0x11"}]}"#
+		);
+	}
+
+	#[test]
+	fn format_address_works() {
+		let alice =
+			from_hex("d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d").unwrap();
+		let expected = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+		assert_eq!(format_address(&alice), expected);
+		assert_eq!(format_address(&[vec![0u8], alice.clone()].concat()), expected);
+		assert_eq!(format_address(&[1u8, 2, 3]), "0x010203");
+	}
+
 	#[tokio::test]
 	async fn construct_extrinsic_works() -> Result<()> {
 		let transfer_allow_death = Function {
@@ -272,6 +668,59 @@ mod tests {
 		Ok(())
 	}
 
+	#[test]
+	fn construct_batch_extrinsic_works() -> Result<()> {
+		let remark = subxt::dynamic::tx("System", "remark", vec![Value::from_bytes("pop")]);
+		let utility = |name: &str| Function {
+			pallet: "Utility".into(),
+			name: name.into(),
+			..Default::default()
+		};
+		let pallets = vec![Pallet {
+			name: "Utility".into(),
+			functions: vec![utility("batch"), utility("batch_all"), utility("force_batch")],
+			..Default::default()
+		}];
+		for (mode, name) in [
+			(BatchMode::Batch, "batch"),
+			(BatchMode::BatchAll, "batch_all"),
+			(BatchMode::ForceBatch, "force_batch"),
+		] {
+			let xt =
+				construct_batch_extrinsic(&pallets, mode, vec![remark.clone(), remark.clone()])?;
+			assert_eq!(xt.call_name(), name);
+			assert_eq!(
+				xt,
+				subxt::dynamic::tx(
+					"Utility",
+					name,
+					vec![Value::unnamed_composite([
+						remark.clone().into_value(),
+						remark.clone().into_value()
+					])]
+				)
+			);
+		}
+		// The `Utility` pallet is required.
+		assert!(matches!(
+			construct_batch_extrinsic(&[], BatchMode::Batch, vec![remark]),
+			Err(Error::PalletNotFound(pallet)) if pallet == "Utility"
+		));
+		Ok(())
+	}
+
+	#[test]
+	fn sudo_error_works() {
+		let sudid = |result: Value| {
+			Composite::Named(vec![("sudo_result".to_string(), result.map_context(|_| 0))])
+		};
+		let ok = Value::unnamed_variant("Ok", [Value::unnamed_composite([])]);
+		assert_eq!(sudo_error(&sudid(ok)), None);
+		let err = Value::unnamed_variant("Err", [Value::unnamed_variant("BadOrigin", [])]);
+		assert_eq!(sudo_error(&sudid(err)), Some("BadOrigin ()".to_string()));
+		assert_eq!(sudo_error(&Composite::Named(vec![])), None);
+	}
+
 	#[tokio::test]
 	async fn construct_sudo_extrinsic_works() -> Result<()> {
 		let xt = construct_extrinsic(
@@ -0,0 +1,371 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use crate::errors::Error;
+use scale::Encode;
+use scale_value::{At, Primitive, ValueDef};
+use sp_core::{
+	blake2_256,
+	crypto::{AccountId32, Ss58Codec},
+};
+use subxt::{
+	dynamic::{self, Value},
+	tx::{DynamicPayload, Payload},
+	OnlineClient, SubstrateConfig,
+};
+
+const MULTISIG_PALLET: &str = "Multisig";
+// The prefix used by `pallet_multisig` when deriving the account of a multisig.
+const MULTISIG_PREFIX: &[u8; 16] = b"modlpy/utilisuba";
+
+/// A multisig account, composed of its signatories and the number of approvals required to
+/// dispatch a call.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Multisig {
+	/// The number of approvals required to dispatch a call.
+	pub threshold: u16,
+	/// The accounts which can approve calls, sorted as required by `pallet_multisig`.
+	pub signatories: Vec<AccountId32>,
+}
+
+impl Multisig {
+	/// Creates a new multisig from its threshold and signatories.
+	///
+	/// # Arguments
+	/// * `threshold` - The number of approvals required to dispatch a call.
+	/// * `signatories` - The addresses of the accounts which can approve calls.
+	pub fn new(threshold: u16, signatories: &[String]) -> Result<Self, Error> {
+		let mut accounts = signatories
+			.iter()
+			.map(|s| {
+				AccountId32::from_ss58check(s.trim())
+					.map_err(|_| Error::MultisigError(format!("Invalid signatory address: {s}")))
+			})
+			.collect::<Result<Vec<_>, _>>()?;
+		accounts.sort();
+		accounts.dedup();
+		if accounts.len() < 2 {
+			return Err(Error::MultisigError("At least two signatories are required".into()));
+		}
+		if threshold == 0 || threshold as usize > accounts.len() {
+			return Err(Error::MultisigError(format!(
+				"The threshold must be between 1 and the number of signatories ({})",
+				accounts.len()
+			)));
+		}
+		Ok(Self { threshold, signatories: accounts })
+	}
+
+	/// The account of the multisig, derived from its signatories and threshold.
+	pub fn account(&self) -> AccountId32 {
+		(MULTISIG_PREFIX, &self.signatories, self.threshold)
+			.using_encoded(blake2_256)
+			.into()
+	}
+
+	/// The signatories other than the signer, sorted as required by `pallet_multisig`.
+	///
+	/// # Arguments
+	/// * `signer` - The signatory approving the call.
+	pub fn other_signatories(&self, signer: &AccountId32) -> Result<Vec<AccountId32>, Error> {
+		if !self.signatories.contains(signer) {
+			return Err(Error::MultisigError(format!(
+				"{} is not a signatory of the multisig",
+				signer.to_ss58check()
+			)));
+		}
+		Ok(self.signatories.iter().filter(|s| *s != signer).cloned().collect())
+	}
+}
+
+/// The point in the chain at which a multisig operation was started.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Timepoint {
+	/// The height of the block.
+	pub height: u32,
+	/// The index of the extrinsic within the block.
+	pub index: u32,
+}
+
+/// A pending multisig operation, awaiting further approvals.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PendingMultisig {
+	/// The point in the chain at which the operation was started.
+	pub when: Timepoint,
+	/// The account which placed the deposit for the operation.
+	pub depositor: AccountId32,
+	/// The signatories which have approved the operation.
+	pub approvals: Vec<AccountId32>,
+}
+
+/// The weight of a call.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Weight {
+	/// The computational time of the call.
+	pub ref_time: u64,
+	/// The size of the proof required to validate the call.
+	pub proof_size: u64,
+}
+
+/// The action performed on a multisig operation by a signatory.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MultisigAction {
+	/// Approve the call, dispatching it once the threshold is reached (`as_multi`).
+	AsMulti {
+		/// The timepoint of the pending operation, unless this is the first approval.
+		timepoint: Option<Timepoint>,
+		/// The maximum weight of the call to be dispatched.
+		max_weight: Weight,
+	},
+	/// Approve the call by its hash, without dispatching it (`approve_as_multi`).
+	ApproveAsMulti {
+		/// The timepoint of the pending operation, unless this is the first approval.
+		timepoint: Option<Timepoint>,
+	},
+	/// Cancel a pending operation, refunding the deposit (`cancel_as_multi`).
+	CancelAsMulti {
+		/// The timepoint of the pending operation.
+		timepoint: Timepoint,
+	},
+}
+
+/// Computes the hash of a call, used to identify a multisig operation.
+///
+/// # Arguments
+/// * `client` - The client used to interact with the chain.
+/// * `xt` - The call.
+pub fn call_hash(
+	client: &OnlineClient<SubstrateConfig>,
+	xt: &DynamicPayload,
+) -> Result<[u8; 32], Error> {
+	let call_data = xt
+		.encode_call_data(&client.metadata())
+		.map_err(|e| Error::CallDataEncodingError(e.to_string()))?;
+	Ok(blake2_256(&call_data))
+}
+
+/// Constructs an extrinsic which performs the given action on a multisig operation, from a
+/// signatory.
+///
+/// A call to `as_multi_threshold_1` is constructed when a single approval is required.
+///
+/// # Arguments
+/// * `client` - The client used to interact with the chain.
+/// * `multisig` - The multisig dispatching the call.
+/// * `signer` - The signatory performing the action.
+/// * `action` - The action to perform.
+/// * `xt` - The call to be dispatched by the multisig.
+pub fn construct_multisig_extrinsic(
+	client: &OnlineClient<SubstrateConfig>,
+	multisig: &Multisig,
+	signer: &AccountId32,
+	action: MultisigAction,
+	xt: DynamicPayload,
+) -> Result<DynamicPayload, Error> {
+	let threshold = Value::u128(multisig.threshold as u128);
+	let others = Value::unnamed_composite(
+		multisig
+			.other_signatories(signer)?
+			.into_iter()
+			.map(|s| Value::from_bytes(<[u8; 32]>::from(s))),
+	);
+	if multisig.threshold == 1 {
+		if matches!(action, MultisigAction::CancelAsMulti { .. }) {
+			return Err(Error::MultisigError(
+				"Calls of a multisig with a threshold of one are dispatched immediately".into(),
+			));
+		}
+		return Ok(dynamic::tx(
+			MULTISIG_PALLET,
+			"as_multi_threshold_1",
+			vec![others, xt.into_value()],
+		));
+	}
+	let call_hash = Value::from_bytes(call_hash(client, &xt)?);
+	Ok(match action {
+		MultisigAction::AsMulti { timepoint, max_weight } => dynamic::tx(
+			MULTISIG_PALLET,
+			"as_multi",
+			vec![threshold, others, option(timepoint), xt.into_value(), weight(max_weight)],
+		),
+		MultisigAction::ApproveAsMulti { timepoint } => dynamic::tx(
+			MULTISIG_PALLET,
+			"approve_as_multi",
+			vec![threshold, others, option(timepoint), call_hash, weight(Weight::default())],
+		),
+		MultisigAction::CancelAsMulti { timepoint } => dynamic::tx(
+			MULTISIG_PALLET,
+			"cancel_as_multi",
+			vec![threshold, others, self::timepoint(timepoint), call_hash],
+		),
+	})
+}
+
+/// Queries the pending operation of a multisig for a call, if any.
+///
+/// # Arguments
+/// * `client` - The client used to interact with the chain.
+/// * `multisig` - The multisig dispatching the call.
+/// * `call_hash` - The hash of the call.
+pub async fn query_pending_multisig(
+	client: &OnlineClient<SubstrateConfig>,
+	multisig: &Multisig,
+	call_hash: [u8; 32],
+) -> Result<Option<PendingMultisig>, Error> {
+	let account: [u8; 32] = multisig.account().into();
+	let address = dynamic::storage(
+		MULTISIG_PALLET,
+		"Multisigs",
+		vec![Value::from_bytes(account), Value::from_bytes(call_hash)],
+	);
+	let Some(pending) = client
+		.storage()
+		.at_latest()
+		.await
+		.map_err(|e| Error::StorageQueryError(e.to_string()))?
+		.fetch(&address)
+		.await
+		.map_err(|e| Error::StorageQueryError(e.to_string()))?
+	else {
+		return Ok(None);
+	};
+	let pending = pending.to_value().map_err(|e| Error::StorageQueryError(e.to_string()))?;
+	let invalid = || Error::MultisigError("Unexpected multisig operation format".into());
+	let number = |v: Option<&Value<u32>>| v.and_then(|v| v.as_u128()).map(|n| n as u32);
+	let when = Timepoint {
+		height: number(pending.at("when").at("height")).ok_or_else(invalid)?,
+		index: number(pending.at("when").at("index")).ok_or_else(invalid)?,
+	};
+	let depositor = pending.at("depositor").and_then(to_account).ok_or_else(invalid)?;
+	let approvals = match pending.at("approvals").map(|a| &a.value) {
+		Some(ValueDef::Composite(approvals)) => approvals.values().filter_map(to_account).collect(),
+		_ => return Err(invalid()),
+	};
+	Ok(Some(PendingMultisig { when, depositor, approvals }))
+}
+
+/// Estimates the weight of a call, using the `TransactionPaymentCallApi` runtime API.
+///
+/// # Arguments
+/// * `client` - The client used to interact with the chain.
+/// * `xt` - The call.
+pub async fn estimate_call_weight(
+	client: &OnlineClient<SubstrateConfig>,
+	xt: &DynamicPayload,
+) -> Result<Weight, Error> {
+	let call_data = xt
+		.encode_call_data(&client.metadata())
+		.map_err(|e| Error::CallDataEncodingError(e.to_string()))?;
+	let info = client
+		.runtime_api()
+		.at_latest()
+		.await
+		.map_err(|e| Error::MultisigError(e.to_string()))?
+		.call(dynamic::runtime_api_call(
+			"TransactionPaymentCallApi",
+			"query_call_info",
+			vec![xt.clone().into_value(), Value::u128(call_data.len() as u128)],
+		))
+		.await
+		.map_err(|e| Error::MultisigError(format!("Failed to estimate the call weight: {e}")))?
+		.to_value()
+		.map_err(|e| Error::MultisigError(e.to_string()))?;
+	let field = |name: &str| info.at("weight").at(name).and_then(|v| v.as_u128()).map(|n| n as u64);
+	match (field("ref_time"), field("proof_size")) {
+		(Some(ref_time), Some(proof_size)) => Ok(Weight { ref_time, proof_size }),
+		_ => Err(Error::MultisigError("Unexpected call info format".into())),
+	}
+}
+
+// Encodes an optional timepoint.
+fn option(timepoint: Option<Timepoint>) -> Value {
+	match timepoint {
+		Some(timepoint) => Value::unnamed_variant("Some", [self::timepoint(timepoint)]),
+		None => Value::unnamed_variant("None", []),
+	}
+}
+
+// Encodes a timepoint.
+fn timepoint(timepoint: Timepoint) -> Value {
+	Value::named_composite([
+		("height", Value::u128(timepoint.height as u128)),
+		("index", Value::u128(timepoint.index as u128)),
+	])
+}
+
+// Encodes a weight.
+fn weight(weight: Weight) -> Value {
+	Value::named_composite([
+		("ref_time", Value::u128(weight.ref_time as u128)),
+		("proof_size", Value::u128(weight.proof_size as u128)),
+	])
+}
+
+// Extracts an account identifier from a decoded value, such as `AccountId32([u8; 32])`.
+fn to_account<T>(value: &scale_value::Value<T>) -> Option<AccountId32> {
+	fn collect_bytes<T>(value: &scale_value::Value<T>, bytes: &mut Vec<u8>) {
+		match &value.value {
+			ValueDef::Primitive(Primitive::U128(byte)) => bytes.push(*byte as u8),
+			ValueDef::Composite(values) => values.values().for_each(|v| collect_bytes(v, bytes)),
+			_ => {},
+		}
+	}
+	let mut bytes = Vec::new();
+	collect_bytes(value, &mut bytes);
+	<[u8; 32]>::try_from(bytes).ok().map(AccountId32::from)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::Result;
+
+	const ALICE: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+	const BOB: &str = "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty";
+	const CHARLIE: &str = "5FLSigC9HGRKVhB9FiEo4Y3koPsNmBmLJbpXg2mp1hXcS59Y";
+
+	#[test]
+	fn multisig_account_works() -> Result<()> {
+		let multisig = Multisig::new(2, &[ALICE.into(), BOB.into(), CHARLIE.into()])?;
+		assert_eq!(
+			multisig.account().to_ss58check(),
+			"5DjYJStmdZ2rcqXbXGX7TW85JsrW6uG4y9MUcLq2BoPMpRA7"
+		);
+		// The order of the signatories does not affect the account.
+		let reordered = Multisig::new(2, &[CHARLIE.into(), ALICE.into(), BOB.into(), BOB.into()])?;
+		assert_eq!(reordered, multisig);
+		Ok(())
+	}
+
+	#[test]
+	fn new_multisig_fails_with_invalid_config() {
+		assert!(matches!(
+			Multisig::new(1, &[ALICE.into()]),
+			Err(Error::MultisigError(e)) if e == "At least two signatories are required"
+		));
+		assert!(matches!(
+			Multisig::new(0, &[ALICE.into(), BOB.into()]),
+			Err(Error::MultisigError(_))
+		));
+		assert!(matches!(
+			Multisig::new(3, &[ALICE.into(), BOB.into()]),
+			Err(Error::MultisigError(_))
+		));
+		assert!(matches!(
+			Multisig::new(2, &[ALICE.into(), "invalid".into()]),
+			Err(Error::MultisigError(e)) if e == "Invalid signatory address: invalid"
+		));
+	}
+
+	#[test]
+	fn other_signatories_works() -> Result<()> {
+		let multisig = Multisig::new(2, &[ALICE.into(), BOB.into(), CHARLIE.into()])?;
+		let alice = AccountId32::from_ss58check(ALICE)?;
+		let others = multisig.other_signatories(&alice)?;
+		assert_eq!(others.len(), 2);
+		assert!(!others.contains(&alice));
+		assert!(others.windows(2).all(|w| w[0] < w[1]));
+		let eve = AccountId32::from_ss58check("5HGjWAeFDfFCWPsjFQdVV2Msvz2XtMktvgocEZcCj68kUMaw")?;
+		assert!(matches!(multisig.other_signatories(&eve), Err(Error::MultisigError(_))));
+		Ok(())
+	}
+}
@@ -0,0 +1,267 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use super::metadata_hash::{self, MetadataHashConfig};
+use crate::errors::Error;
+use scale::Decode;
+use serde::{Deserialize, Serialize};
+use sp_core::{
+	bytes::{from_hex, to_hex},
+	crypto::{AccountId32, Ss58Codec},
+};
+use std::{fs, path::Path};
+use subxt::{
+	tx::{PartialExtrinsic, Payload},
+	utils::{MultiAddress, MultiSignature},
+	OfflineClient, OnlineClient, SubstrateConfig,
+};
+
+/// The period during which a transaction is valid, starting from a given block.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Mortality {
+	/// The number of blocks for which the transaction is valid, rounded to a power of two.
+	pub period: u64,
+	/// The number of the block from which the transaction is valid.
+	pub block_number: u64,
+	/// The hash of the block from which the transaction is valid.
+	pub block_hash: String,
+}
+
+/// Everything required to sign an extrinsic on an offline (air-gapped) device, and to assemble the
+/// signed extrinsic once the signature is returned.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SigningPayload {
+	/// The SCALE encoded call data of the extrinsic.
+	pub call_data: String,
+	/// The address of the account signing the extrinsic.
+	pub signer: String,
+	/// The nonce of the signing account.
+	pub nonce: u64,
+	/// The mortality of the extrinsic, which is immortal if not specified.
+	pub mortality: Option<Mortality>,
+	/// The hash of the genesis block of the chain.
+	pub genesis_hash: String,
+	/// The runtime specification version of the chain.
+	pub spec_version: u32,
+	/// The transaction version of the chain.
+	pub transaction_version: u32,
+	/// The hash of the chain metadata, if verified by the `CheckMetadataHash` signed extension.
+	pub metadata_hash: Option<String>,
+	/// The bytes to be signed by the signing account.
+	pub payload: String,
+}
+
+impl SigningPayload {
+	/// Loads a signing payload from a JSON file.
+	///
+	/// # Arguments
+	/// * `path` - The path of the file.
+	pub fn load(path: &Path) -> Result<Self, Error> {
+		let contents = fs::read_to_string(path)?;
+		Ok(serde_json::from_str(&contents)?)
+	}
+
+	/// Saves the signing payload to a JSON file.
+	///
+	/// # Arguments
+	/// * `path` - The path of the file.
+	pub fn save(&self, path: &Path) -> Result<(), Error> {
+		fs::write(path, serde_json::to_string_pretty(self)?)?;
+		Ok(())
+	}
+}
+
+/// Creates the payload to be signed offline for an extrinsic, using the current nonce of the
+/// signer and the latest finalized block of the chain. The metadata hash is included when verified
+/// by the chain, allowing hardware wallets to decode the extrinsic.
+///
+/// # Arguments
+/// * `client` - The client used to interact with the chain.
+/// * `url` - Endpoint of the node.
+/// * `xt` - The extrinsic to be signed.
+/// * `signer` - The address of the account signing the extrinsic.
+/// * `mortality` - The number of blocks for which the extrinsic is valid, otherwise immortal.
+pub async fn create_signing_payload<Xt: Payload>(
+	client: &OnlineClient<SubstrateConfig>,
+	url: &url::Url,
+	xt: &Xt,
+	signer: &str,
+	mortality: Option<u64>,
+) -> Result<SigningPayload, Error> {
+	let account = parse_account(signer)?;
+	let nonce = client
+		.tx()
+		.account_nonce(&account)
+		.await
+		.map_err(|e| Error::OfflineSigningError(e.to_string()))?;
+	let mortality = match mortality {
+		Some(period) => {
+			let block = client
+				.blocks()
+				.at_latest()
+				.await
+				.map_err(|e| Error::OfflineSigningError(e.to_string()))?;
+			Some(Mortality {
+				period,
+				block_number: block.number().into(),
+				block_hash: format!("{:?}", block.hash()),
+			})
+		},
+		None => None,
+	};
+	let call_data = xt
+		.encode_call_data(&client.metadata())
+		.map_err(|e| Error::CallDataEncodingError(e.to_string()))?;
+	let metadata_hash = metadata_hash::metadata_hash(client, url).await?;
+	let runtime_version = client.runtime_version();
+	let mut payload = SigningPayload {
+		call_data: to_hex(&call_data, false),
+		signer: signer.to_string(),
+		nonce,
+		mortality,
+		genesis_hash: format!("{:?}", client.genesis_hash()),
+		spec_version: runtime_version.spec_version,
+		transaction_version: runtime_version.transaction_version,
+		metadata_hash: metadata_hash.map(|hash| to_hex(&hash, false)),
+		payload: String::new(),
+	};
+	payload.payload = to_hex(&partial_extrinsic(client, &payload)?.signer_payload(), false);
+	Ok(payload)
+}
+
+/// Assembles a signed extrinsic from a payload signed offline, returning the encoded extrinsic
+/// ready to be submitted.
+///
+/// # Arguments
+/// * `client` - The client used to interact with the chain.
+/// * `payload` - The payload which was signed.
+/// * `signature` - The signature of the payload, either as raw sr25519 signature bytes or a SCALE
+///   encoded `MultiSignature`.
+pub fn assemble_signed_extrinsic(
+	client: &OnlineClient<SubstrateConfig>,
+	payload: &SigningPayload,
+	signature: &str,
+) -> Result<String, Error> {
+	let genesis_hash = format!("{:?}", client.genesis_hash());
+	if payload.genesis_hash != genesis_hash {
+		return Err(Error::OfflineSigningError(format!(
+			"the payload was created for the chain with genesis hash {}, not {genesis_hash}",
+			payload.genesis_hash
+		)));
+	}
+	let runtime_version = client.runtime_version();
+	if payload.spec_version != runtime_version.spec_version ||
+		payload.transaction_version != runtime_version.transaction_version
+	{
+		return Err(Error::OfflineSigningError(format!(
+			"the runtime has been upgraded since the payload was created (spec version {}, transaction version {})",
+			runtime_version.spec_version, runtime_version.transaction_version
+		)));
+	}
+	let partial = partial_extrinsic(client, payload)?;
+	// Ensure the signed bytes are those which will be verified by the chain.
+	if to_hex(&partial.signer_payload(), false) != payload.payload {
+		return Err(Error::OfflineSigningError(
+			"the payload does not match its call data and parameters".into(),
+		));
+	}
+	let address = MultiAddress::Id(parse_account(&payload.signer)?);
+	let signature = parse_signature(signature)?;
+	let extrinsic = partial.sign_with_address_and_signature(&address, &signature);
+	Ok(to_hex(extrinsic.encoded(), false))
+}
+
+// Reconstructs the unsigned extrinsic described by the payload.
+fn partial_extrinsic(
+	client: &OnlineClient<SubstrateConfig>,
+	payload: &SigningPayload,
+) -> Result<PartialExtrinsic<MetadataHashConfig, OfflineClient<MetadataHashConfig>>, Error> {
+	let call_data =
+		from_hex(&payload.call_data).map_err(|e| Error::CallDataDecodingError(e.to_string()))?;
+	let mortality = match &payload.mortality {
+		Some(mortality) => {
+			let block_hash = mortality
+				.block_hash
+				.parse()
+				.map_err(|_| Error::OfflineSigningError("invalid block hash".into()))?;
+			Some((mortality.block_number, block_hash, mortality.period))
+		},
+		None => None,
+	};
+	let metadata_hash = match &payload.metadata_hash {
+		Some(hash) => Some(
+			from_hex(hash)
+				.ok()
+				.and_then(|hash| <[u8; 32]>::try_from(hash).ok())
+				.ok_or_else(|| Error::OfflineSigningError("invalid metadata hash".into()))?,
+		),
+		None => None,
+	};
+	let params = metadata_hash::extrinsic_params(payload.nonce, mortality, metadata_hash);
+	metadata_hash::offline_client(client)
+		.tx()
+		.create_partial_signed_offline(&super::CallData::new(call_data), params)
+		.map_err(|e| Error::OfflineSigningError(e.to_string()))
+}
+
+// Parses an account from its SS58 address.
+fn parse_account(address: &str) -> Result<subxt::utils::AccountId32, Error> {
+	let account = AccountId32::from_ss58check(address)
+		.map_err(|e| Error::OfflineSigningError(format!("invalid signer address: {e:?}")))?;
+	Ok(subxt::utils::AccountId32(account.into()))
+}
+
+// Parses a signature, provided either as raw sr25519 signature bytes or a SCALE encoded
+// `MultiSignature`.
+fn parse_signature(signature: &str) -> Result<MultiSignature, Error> {
+	let bytes = from_hex(signature.trim())
+		.map_err(|e| Error::OfflineSigningError(format!("invalid signature: {e}")))?;
+	match <[u8; 64]>::try_from(bytes.as_slice()) {
+		Ok(signature) => Ok(MultiSignature::Sr25519(signature)),
+		Err(_) => MultiSignature::decode(&mut bytes.as_slice())
+			.map_err(|e| Error::OfflineSigningError(format!("invalid signature: {e}"))),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use scale::Encode;
+	use tempfile::tempdir;
+
+	#[test]
+	fn signing_payload_save_and_load_works() -> Result<(), Error> {
+		let temp_dir = tempdir()?;
+		let path = temp_dir.path().join("payload.json");
+		let payload = SigningPayload {
+			call_data: "0x00000411".into(),
+			signer: "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY".into(),
+			nonce: 1,
+			mortality: Some(Mortality { period: 64, block_number: 10, block_hash: "0x01".into() }),
+			genesis_hash: "0x02".into(),
+			spec_version: 1,
+			transaction_version: 1,
+			metadata_hash: None,
+			payload: "0x03".into(),
+		};
+		payload.save(&path)?;
+		assert_eq!(SigningPayload::load(&path)?, payload);
+		Ok(())
+	}
+
+	#[test]
+	fn parse_signature_works() -> Result<(), Error> {
+		let raw = [1u8; 64];
+		assert_eq!(parse_signature(&to_hex(&raw, false))?, MultiSignature::Sr25519(raw));
+		let encoded = MultiSignature::Ed25519(raw).encode();
+		assert_eq!(parse_signature(&to_hex(&encoded, false))?, MultiSignature::Ed25519(raw));
+		assert!(matches!(parse_signature("0x0102"), Err(Error::OfflineSigningError(..))));
+		assert!(matches!(parse_signature("invalid"), Err(Error::OfflineSigningError(..))));
+		Ok(())
+	}
+
+	#[test]
+	fn parse_account_works() {
+		assert!(parse_account("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY").is_ok());
+		assert!(matches!(parse_account("invalid"), Err(Error::OfflineSigningError(..))));
+	}
+}
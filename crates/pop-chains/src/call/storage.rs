@@ -0,0 +1,68 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use super::{
+	format_value,
+	metadata::{parse_dispatchable_arguments, Storage},
+};
+use crate::errors::Error;
+use subxt::{
+	dynamic, metadata::types::StorageEntryModifier, utils::H256, OnlineClient, SubstrateConfig,
+};
+
+/// Queries the value of a storage item, decoded against the chain metadata.
+///
+/// Returns `None` when no value is stored under the given keys and the storage item has no default
+/// value.
+///
+/// # Arguments
+/// * `client` - The client used to interact with the chain.
+/// * `storage` - The storage item to query.
+/// * `keys` - The keys of the storage item, encoded as strings. Must be provided for each key of a
+///   storage map.
+/// * `at` - The hash of the block to query the storage at, otherwise the latest block is used.
+pub async fn query_storage(
+	client: &OnlineClient<SubstrateConfig>,
+	storage: &Storage,
+	keys: Vec<String>,
+	at: Option<H256>,
+) -> Result<Option<String>, Error> {
+	if keys.len() != storage.keys.len() {
+		return Err(Error::StorageQueryError(format!(
+			"{}::{} requires {} key(s), but {} provided",
+			storage.pallet,
+			storage.name,
+			storage.keys.len(),
+			keys.len()
+		)));
+	}
+	let metadata = client.metadata();
+	let entry = metadata
+		.pallet_by_name(&storage.pallet)
+		.and_then(|pallet| pallet.storage())
+		.and_then(|s| s.entry_by_name(&storage.name))
+		.ok_or_else(|| Error::StorageNotFound(format!("{}::{}", storage.pallet, storage.name)))?;
+	let keys = parse_dispatchable_arguments(&storage.keys, keys)?;
+	let address = dynamic::storage(&storage.pallet, &storage.name, keys);
+	let storage_client = match at {
+		Some(hash) => client.storage().at(hash),
+		None => client
+			.storage()
+			.at_latest()
+			.await
+			.map_err(|e| Error::StorageQueryError(e.to_string()))?,
+	};
+	// Optional storage items have no default value to fall back to.
+	let value = match entry.modifier() {
+		StorageEntryModifier::Optional => storage_client.fetch(&address).await,
+		StorageEntryModifier::Default => storage_client.fetch_or_default(&address).await.map(Some),
+	}
+	.map_err(|e| Error::StorageQueryError(e.to_string()))?;
+	value
+		.map(|value| {
+			value
+				.to_value()
+				.map(|value| format_value(&value))
+				.map_err(|e| Error::StorageQueryError(e.to_string()))
+		})
+		.transpose()
+}
@@ -33,33 +33,54 @@ pub enum Error {
 	/// A configuration error occurred.
 	#[error("Configuration error: {0}")]
 	Config(String),
+	/// The specified constant could not be found.
+	#[error("Failed to find the constant {0}")]
+	ConstantNotFound(String),
 	/// The current directory could not be accessed.
 	#[error("Failed to access the current directory")]
 	CurrentDirAccess,
+	/// An error occurred while dry running an extrinsic.
+	#[error("Failed to dry run the extrinsic: {0}")]
+	DryRunError(String),
 	/// The endowment value could not be parsed.
 	#[error("Failed to parse the endowment value")]
 	EndowmentError,
 	/// The specified event was not found.
 	#[error("Event {0} not found.")]
 	EventNotFound(String),
+	/// An error occurred while subscribing to or decoding chain events.
+	#[error("Failed to stream events: {0}")]
+	EventSubscriptionError(String),
 	/// An error occurred during the submission of an extrinsic.
 	#[error("Extrinsic submission error: {0}")]
 	ExtrinsicSubmissionError(String),
+	/// An error occurred while forking a chain or serving the fork.
+	#[error("Fork error: {0}")]
+	ForkError(String),
 	/// The dispatchable function is not supported.
 	#[error("The dispatchable function is not supported")]
 	FunctionNotSupported,
 	/// An error occurred while working with the genesis builder.
 	#[error("Genesis builder error: {0}")]
 	GenesisBuilderError(String),
+	/// The HRMP channel topology is invalid.
+	#[error("Invalid HRMP topology: {0}")]
+	HrmpTopologyError(String),
 	/// Failed to retrieve the image tag.
 	#[error("Failed to retrieve image tag.")]
 	ImageTagRetrievalFailed,
+	/// The name, such as that of a network or snapshot, is invalid.
+	#[error("Invalid name `{0}`: only letters, digits, `-` and `_` are permitted")]
+	InvalidName(String),
 	/// An IO error occurred.
 	#[error("IO error: {0}")]
 	IO(#[from] std::io::Error),
 	/// A JSON error occurred.
 	#[error("JSON error: {0}")]
 	JsonError(#[from] serde_json::Error),
+	/// An error occurred while generating the metadata hash of a chain.
+	#[error("Failed to generate the metadata hash: {0}")]
+	MetadataHashError(String),
 	/// An error occurred while parsing metadata of a parameter.
 	#[error("Error parsing metadata for parameter {0}")]
 	MetadataParsingError(String),
@@ -77,9 +98,18 @@ pub enum Error {
 		/// The binary used.
 		binary: String,
 	},
+	/// An error occurred while constructing or querying a multisig operation.
+	#[error("Multisig error: {0}")]
+	MultisigError(String),
+	/// An error occurred while signing an extrinsic offline.
+	#[error("Offline signing error: {0}")]
+	OfflineSigningError(String),
 	/// A network configuration error occurred.
 	#[error("Configuration error: {0:?}")]
 	NetworkConfigurationError(Vec<anyhow::Error>),
+	/// The network did not become ready.
+	#[error("The network is not ready: {0}")]
+	NetworkNotReady(String),
 	/// An orchestrator error occurred.
 	#[error("Orchestrator error: {0}")]
 	OrchestratorError(#[from] OrchestratorError),
@@ -104,9 +134,24 @@ pub enum Error {
 	/// The specified runtime could not be found.
 	#[error("Failed to find the runtime {0}")]
 	RuntimeNotFound(String),
+	/// An error occurred while upgrading the runtime of a chain.
+	#[error("Failed to upgrade the runtime: {0}")]
+	RuntimeUpgradeError(String),
+	/// An error occurred while sealing blocks on a development chain.
+	#[error("Failed to seal blocks: {0}")]
+	SealError(String),
+	/// An error occurred while creating or restoring a snapshot of a network.
+	#[error("Snapshot error: {0}")]
+	SnapshotError(String),
 	/// An error occurred sourcing a binary.
 	#[error("Template error: {0}")]
 	SourcingError(#[from] pop_common::sourcing::Error),
+	/// The specified storage item could not be found.
+	#[error("Failed to find the storage item {0}")]
+	StorageNotFound(String),
+	/// An error occurred while querying storage.
+	#[error("Failed to query storage: {0}")]
+	StorageQueryError(String),
 	/// An error occurred whilst interacting with a chain using `subxt`.
 	#[error("Subxt error: {0}")]
 	SubXtError(#[from] Box<subxt::Error>),
@@ -122,6 +167,9 @@ pub enum Error {
 	/// The workspace could not be located.
 	#[error("Failed to locate the workspace")]
 	WorkspaceLocate,
+	/// An error occurred while sending or tracing a cross-consensus message.
+	#[error("XCM error: {0}")]
+	XcmError(String),
 }
 
 // Handles command execution errors by extracting and returning the stderr message using the
@@ -0,0 +1,183 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use super::Header;
+use crate::errors::Error;
+use cumulus_primitives_core::PersistedValidationData;
+use cumulus_primitives_parachain_inherent::{ParachainInherentData, INHERENT_IDENTIFIER};
+use cumulus_test_relay_sproof_builder::RelayStateSproofBuilder;
+use scale::{Decode, Encode};
+use sp_core::twox_128;
+use sp_inherents::InherentData;
+use std::collections::BTreeMap;
+use subxt::{
+	config::substrate::{Digest, DigestItem},
+	Metadata,
+};
+
+/// The slot duration of the relay chain, in milliseconds.
+const RELAY_SLOT_DURATION: u64 = 6_000;
+/// The slot duration assumed when a runtime does not use slot-based consensus, in milliseconds.
+const DEFAULT_SLOT_DURATION: u64 = 6_000;
+/// The maximum size of a proof of validity, provided to the runtime of a rollup.
+const MAX_POV_SIZE: u32 = 5 * 1024 * 1024;
+/// The identifier of the timestamp inherent.
+const TIMESTAMP: [u8; 8] = *b"timstap0";
+/// The identifier of the inherent used by relay chains to include rollup candidates.
+const PARAS: [u8; 8] = *b"parachn0";
+
+/// The consensus engine of a runtime, which determines the pre-runtime digest of a block.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Consensus {
+	/// Authority round, as used by rollups and solochains.
+	Aura,
+	/// Blind assignment for blockchain extension, as used by relay chains.
+	Babe,
+	/// No slot-based consensus.
+	None,
+}
+
+/// Determines how blocks of a fork are authored, based on the pallets of its runtime.
+#[derive(Clone, Debug)]
+pub(crate) struct Authoring {
+	consensus: Consensus,
+	// The slot duration, in milliseconds.
+	slot_duration: u64,
+	// Whether the runtime is the runtime of a rollup, requiring validation data from the relay
+	// chain.
+	rollup: bool,
+	// Whether the runtime is the runtime of a relay chain, requiring rollup inclusion data.
+	relay: bool,
+}
+
+impl Authoring {
+	/// Determines how blocks are authored from the metadata of a runtime.
+	///
+	/// # Arguments
+	/// * `metadata` - The metadata of the runtime.
+	/// * `call` - Calls a runtime API of the runtime.
+	pub(crate) fn new(
+		metadata: &Metadata,
+		call: impl Fn(&str) -> Result<Vec<u8>, Error>,
+	) -> Result<Self, Error> {
+		let (consensus, slot_duration) = if metadata.pallet_by_name("Aura").is_some() {
+			(Consensus::Aura, decode::<u64>(&call("AuraApi_slot_duration")?)?)
+		} else if metadata.pallet_by_name("Babe").is_some() {
+			// The slot duration is the first field of the configuration.
+			(Consensus::Babe, decode::<u64>(&call("BabeApi_configuration")?)?)
+		} else {
+			(Consensus::None, DEFAULT_SLOT_DURATION)
+		};
+		Ok(Self {
+			consensus,
+			slot_duration,
+			rollup: metadata.pallet_by_name("ParachainSystem").is_some(),
+			relay: metadata.pallet_by_name("ParaInherent").is_some(),
+		})
+	}
+
+	/// Creates the digest and inherent data of the block following the parent block.
+	///
+	/// # Arguments
+	/// * `parent` - The header of the parent block.
+	/// * `storage` - Reads the storage of the parent block.
+	pub(crate) fn inherents(
+		&self,
+		parent: &Header,
+		storage: impl Fn(&[u8]) -> Result<Option<Vec<u8>>, Error>,
+	) -> Result<(Digest, InherentData), Error> {
+		let read = |pallet: &str, item: &str| -> Result<Option<Vec<u8>>, Error> {
+			storage(&[twox_128(pallet.as_bytes()), twox_128(item.as_bytes())].concat())
+		};
+		let now: u64 =
+			read("Timestamp", "Now")?.map(|v| decode(&v)).transpose()?.unwrap_or_default();
+		// The timestamp of a rollup block must also correspond to a relay chain slot.
+		let step = match self.rollup {
+			true => lcm(self.slot_duration, RELAY_SLOT_DURATION),
+			false => self.slot_duration,
+		};
+		let timestamp = (now / step + 1) * step;
+		let slot = timestamp / self.slot_duration;
+
+		let mut digest = Digest::default();
+		match self.consensus {
+			Consensus::Aura => digest.logs.push(DigestItem::PreRuntime(*b"aura", slot.encode())),
+			// A secondary plain pre-digest, authored by the first authority.
+			Consensus::Babe =>
+				digest.logs.push(DigestItem::PreRuntime(*b"BABE", (2u8, 0u32, slot).encode())),
+			Consensus::None => {},
+		}
+
+		let mut data = InherentData::new();
+		put(&mut data, TIMESTAMP, &timestamp)?;
+		if self.rollup {
+			let para_id: u32 = read("ParachainInfo", "ParachainId")?
+				.map(|v| decode(&v))
+				.transpose()?
+				.ok_or_else(|| Error::ForkError("the rollup identifier is unknown".into()))?;
+			let relay_parent_number = read("ParachainSystem", "LastRelayChainBlockNumber")?
+				.map(|v| decode::<u32>(&v))
+				.transpose()?
+				.unwrap_or_default() +
+				1;
+			let sproof = RelayStateSproofBuilder {
+				para_id: para_id.into(),
+				current_slot: (timestamp / RELAY_SLOT_DURATION).into(),
+				dmq_mqc_head: read("ParachainSystem", "LastDmqMqcHead")?
+					.map(|v| decode(&v))
+					.transpose()?,
+				included_para_head: Some(parent.encode().into()),
+				..Default::default()
+			};
+			let (relay_parent_storage_root, relay_chain_state) = sproof.into_state_root_and_proof();
+			let inherent = ParachainInherentData {
+				validation_data: PersistedValidationData {
+					parent_head: parent.encode().into(),
+					relay_parent_number,
+					relay_parent_storage_root,
+					max_pov_size: MAX_POV_SIZE,
+				},
+				relay_chain_state,
+				downward_messages: vec![],
+				horizontal_messages: BTreeMap::new(),
+			};
+			put(&mut data, INHERENT_IDENTIFIER, &inherent)?;
+		}
+		if self.relay {
+			// No bitfields, backed candidates or disputes.
+			let inherent = (Vec::<()>::new(), Vec::<()>::new(), Vec::<()>::new(), parent);
+			put(&mut data, PARAS, &inherent)?;
+		}
+		Ok((digest, data))
+	}
+}
+
+fn put(data: &mut InherentData, identifier: [u8; 8], value: &impl Encode) -> Result<(), Error> {
+	data.put_data(identifier, value)
+		.map_err(|e| Error::ForkError(format!("could not create inherent data: {e}")))
+}
+
+fn decode<T: Decode>(mut data: &[u8]) -> Result<T, Error> {
+	T::decode(&mut data).map_err(|e| Error::ForkError(format!("could not decode value: {e}")))
+}
+
+// Returns the least common multiple of two numbers.
+fn lcm(a: u64, b: u64) -> u64 {
+	let (mut x, mut y) = (a, b);
+	while y != 0 {
+		(x, y) = (y, x % y);
+	}
+	a / x * b
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn lcm_works() {
+		assert_eq!(lcm(6_000, 6_000), 6_000);
+		assert_eq!(lcm(12_000, 6_000), 12_000);
+		assert_eq!(lcm(2_000, 6_000), 6_000);
+		assert_eq!(lcm(4_000, 6_000), 12_000);
+	}
+}
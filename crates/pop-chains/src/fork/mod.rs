@@ -0,0 +1,794 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use crate::errors::Error;
+use inherents::Authoring;
+use sc_executor::WasmExecutor;
+use scale::{Decode, Encode};
+use serde_json::{json, Value};
+use sp_core::{blake2_128, blake2_256, twox_128, twox_64, Bytes};
+use sp_core_inkv6::{
+	storage::{well_known_keys::CODE, ChildInfo, ChildType, PrefixedStorageKey},
+	traits::{CallContext, ReadRuntimeVersionExt},
+};
+use sp_externalities::Extensions;
+use sp_runtime::{
+	traits::BlakeTwo256, transaction_validity::TransactionValidityError, StateVersion,
+};
+use sp_state_machine::{
+	backend::BackendRuntimeCode, Backend, IterArgs, OverlayedChanges, StateMachine,
+};
+use sp_version::RuntimeVersion;
+use std::{
+	path::{Path, PathBuf},
+	sync::Arc,
+};
+use storage::{load_snapshot, Changes, ForkBackend, Remote, Source};
+use subxt::{
+	backend::rpc::{rpc_params, RpcClient},
+	utils::H256,
+	Metadata, SubstrateConfig,
+};
+use url::Url;
+
+mod inherents;
+mod rpc;
+mod storage;
+
+pub use rpc::{serve, ForkServer, Seal};
+
+/// The header of a block of a fork.
+pub type Header = <SubstrateConfig as subxt::Config>::Header;
+
+/// Storage keys along with their new values, where `None` removes the value.
+pub type StorageOverrides = Vec<(Vec<u8>, Option<Vec<u8>>)>;
+
+// The host functions available to the runtime of a fork.
+pub(crate) type HostFunctions = (
+	sp_io::SubstrateHostFunctions,
+	cumulus_primitives_proof_size_hostfunction::storage_proof_size::HostFunctions,
+);
+
+/// The size of an encoded `AccountInfo` with a `u32` nonce and `u128` balances.
+const ACCOUNT_INFO_SIZE: usize = 80;
+/// The source of an extrinsic submitted to a fork, as seen by the transaction queue of its runtime.
+const EXTERNAL_SOURCE: u8 = 2;
+
+/// The state from which a chain is forked.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ForkSource {
+	/// A live chain, whose state is fetched lazily from its RPC endpoint.
+	Live {
+		/// The RPC endpoint of the chain.
+		url: Url,
+		/// The block to fork from, defaulting to the latest finalized block.
+		at: Option<H256>,
+	},
+	/// A snapshot created by `try-runtime create-snapshot`.
+	Snapshot(PathBuf),
+}
+
+/// A block of a fork.
+#[derive(Clone, Debug)]
+pub struct Block {
+	/// The hash of the block.
+	pub hash: H256,
+	/// The header of the block.
+	pub header: Header,
+	/// The encoded extrinsics of the block.
+	pub extrinsics: Vec<Vec<u8>>,
+	// The storage changes made on top of the source state, as of this block.
+	changes: Arc<Changes>,
+	runtime: Arc<Runtime>,
+}
+
+/// A block built on top of a fork, along with the pending extrinsics which it could not include.
+#[derive(Clone, Debug)]
+pub struct BlockOutcome {
+	/// The block built.
+	pub block: Block,
+	/// The hashes of the pending extrinsics which were dropped, as they could not be applied.
+	pub dropped: Vec<H256>,
+}
+
+// The runtime of a block.
+#[derive(Debug)]
+struct Runtime {
+	version: RuntimeVersion,
+	// The encoded metadata.
+	metadata: Vec<u8>,
+	pallets: Vec<String>,
+	authoring: Authoring,
+}
+
+impl Runtime {
+	// Loads the runtime stored within the state.
+	fn load(executor: &WasmExecutor<HostFunctions>, backend: &ForkBackend) -> Result<Self, Error> {
+		let call = |method: &str| {
+			execute(executor, backend, &mut Default::default(), method, &[], CallContext::Offchain)
+		};
+		let version = decode::<RuntimeVersion>(&call("Core_version")?)?;
+		let metadata = decode::<Vec<u8>>(&call("Metadata_metadata")?)?;
+		let decoded = decode::<Metadata>(&metadata)?;
+		let authoring = Authoring::new(&decoded, call)?;
+		let pallets = decoded.pallets().map(|p| p.name().to_string()).collect();
+		Ok(Self { version, metadata, pallets, authoring })
+	}
+}
+
+/// A local chain forked from the state of another chain, on top of which blocks are built on
+/// demand by executing its runtime.
+pub struct Fork {
+	source: Source,
+	executor: WasmExecutor<HostFunctions>,
+	// The blocks of the fork, starting with the block forked from.
+	blocks: Vec<Block>,
+	// The extrinsics pending inclusion, along with their hashes.
+	pool: Vec<(H256, Vec<u8>)>,
+	genesis_hash: H256,
+	chain: String,
+	properties: Value,
+}
+
+impl Fork {
+	/// Forks a chain. The state of a live chain is fetched lazily, caching it within the cache
+	/// directory.
+	///
+	/// # Arguments
+	/// * `source` - The state from which the chain is forked.
+	/// * `cache` - The directory used to cache the state of live chains.
+	pub async fn new(source: &ForkSource, cache: &Path) -> Result<Self, Error> {
+		let (source, header, extrinsics, chain, properties) = match source {
+			ForkSource::Live { url, at } => {
+				let client = RpcClient::from_url(url.as_str())
+					.await
+					.map_err(|e| Error::ConnectionFailure(e.to_string()))?;
+				let request_error = |e: subxt::Error| Error::ForkError(e.to_string());
+				let at = match at {
+					Some(at) => *at,
+					None => client
+						.request("chain_getFinalizedHead", rpc_params![])
+						.await
+						.map_err(request_error)?,
+				};
+				let block: Value = client
+					.request("chain_getBlock", rpc_params![at])
+					.await
+					.map_err(request_error)?;
+				let block = block
+					.get("block")
+					.ok_or_else(|| Error::ForkError(format!("block {at:?} not found")))?;
+				let header: Header = serde_json::from_value(block["header"].clone())?;
+				let extrinsics: Vec<Bytes> = serde_json::from_value(block["extrinsics"].clone())?;
+				let chain: String =
+					client.request("system_chain", rpc_params![]).await.map_err(request_error)?;
+				let properties: Value = client
+					.request("system_properties", rpc_params![])
+					.await
+					.map_err(request_error)?;
+				let remote = Remote::new(client, at, cache)?;
+				let extrinsics = extrinsics.into_iter().map(|e| e.0).collect();
+				(Source::Remote(remote), header, extrinsics, Some(chain), properties)
+			},
+			ForkSource::Snapshot(path) => {
+				let (backend, header) = load_snapshot(path)?;
+				(Source::Snapshot(backend), header, vec![], None, json!({}))
+			},
+		};
+		// Executing the runtime may fetch state, which blocks.
+		tokio::task::spawn_blocking(move || {
+			Self::init(source, header, extrinsics, chain, properties)
+		})
+		.await
+		.map_err(|e| Error::ForkError(e.to_string()))?
+	}
+
+	// Initializes the fork once the state and the block forked from are available.
+	fn init(
+		source: Source,
+		header: Header,
+		extrinsics: Vec<Vec<u8>>,
+		chain: Option<String>,
+		properties: Value,
+	) -> Result<Self, Error> {
+		let executor = WasmExecutor::<HostFunctions>::builder().build();
+		let changes = Arc::new(Changes::default());
+		let backend = ForkBackend::new(&source, &changes, header.state_root);
+		let runtime = Runtime::load(&executor, &backend)?;
+		let genesis_hash = backend
+			.storage(&block_hash_key(0))
+			.map_err(Error::ForkError)?
+			.map(|v| decode::<H256>(&v))
+			.transpose()?
+			.filter(|hash| !hash.is_zero())
+			.unwrap_or_else(|| hash(&header));
+		let chain = chain.unwrap_or_else(|| runtime.version.spec_name.to_string());
+		let head =
+			Block { hash: hash(&header), header, extrinsics, changes, runtime: Arc::new(runtime) };
+		Ok(Self {
+			source,
+			executor,
+			blocks: vec![head],
+			pool: vec![],
+			genesis_hash,
+			chain,
+			properties,
+		})
+	}
+
+	/// The hash of the genesis block of the forked chain.
+	pub fn genesis_hash(&self) -> H256 {
+		self.genesis_hash
+	}
+
+	/// The name of the forked chain.
+	pub fn chain(&self) -> &str {
+		&self.chain
+	}
+
+	/// The properties of the forked chain, such as its token symbol and decimals.
+	pub fn properties(&self) -> &Value {
+		&self.properties
+	}
+
+	/// The latest block of the fork.
+	pub fn head(&self) -> &Block {
+		self.blocks.last().expect("a fork always contains the block forked from; qed")
+	}
+
+	/// Returns the block with the provided hash, defaulting to the latest block.
+	///
+	/// # Arguments
+	/// * `hash` - The hash of the block.
+	pub fn block(&self, hash: Option<H256>) -> Result<&Block, Error> {
+		match hash {
+			Some(hash) => self
+				.blocks
+				.iter()
+				.rev()
+				.find(|b| b.hash == hash)
+				.ok_or_else(|| Error::ForkError(format!("unknown block {hash:?}"))),
+			None => Ok(self.head()),
+		}
+	}
+
+	/// Returns the hash of the block with the provided number, including blocks preceding the
+	/// block forked from which are still known to the forked chain.
+	///
+	/// # Arguments
+	/// * `number` - The number of the block.
+	pub fn block_hash(&self, number: u32) -> Result<Option<H256>, Error> {
+		let first = self.blocks[0].header.number;
+		if number >= first {
+			return Ok(self.blocks.get((number - first) as usize).map(|b| b.hash));
+		}
+		self.storage(None, None, &block_hash_key(number))?
+			.map(|v| decode::<H256>(&v))
+			.transpose()
+	}
+
+	/// The version of the runtime at the provided block.
+	///
+	/// # Arguments
+	/// * `at` - The hash of the block, defaulting to the latest block.
+	pub fn runtime_version(&self, at: Option<H256>) -> Result<&RuntimeVersion, Error> {
+		Ok(&self.block(at)?.runtime.version)
+	}
+
+	/// The encoded metadata of the runtime at the provided block.
+	///
+	/// # Arguments
+	/// * `at` - The hash of the block, defaulting to the latest block.
+	pub fn metadata(&self, at: Option<H256>) -> Result<&[u8], Error> {
+		Ok(&self.block(at)?.runtime.metadata)
+	}
+
+	/// Calls a runtime API at the provided block, discarding any changes made to storage.
+	///
+	/// # Arguments
+	/// * `at` - The hash of the block, defaulting to the latest block.
+	/// * `method` - The name of the runtime API function, e.g. `Core_version`.
+	/// * `data` - The encoded arguments.
+	pub fn call(&self, at: Option<H256>, method: &str, data: &[u8]) -> Result<Vec<u8>, Error> {
+		let backend = self.backend(self.block(at)?);
+		execute(
+			&self.executor,
+			&backend,
+			&mut Default::default(),
+			method,
+			data,
+			CallContext::Offchain,
+		)
+	}
+
+	/// Returns the value of a storage key at the provided block.
+	///
+	/// # Arguments
+	/// * `at` - The hash of the block, defaulting to the latest block.
+	/// * `child` - The prefixed storage key of a child trie, if the key is within a child trie.
+	/// * `key` - The storage key.
+	pub fn storage(
+		&self,
+		at: Option<H256>,
+		child: Option<&[u8]>,
+		key: &[u8],
+	) -> Result<Option<Vec<u8>>, Error> {
+		let backend = self.backend(self.block(at)?);
+		match child {
+			Some(child) => backend.child_storage(&child_info(child)?, key),
+			None => backend.storage(key),
+		}
+		.map_err(Error::ForkError)
+	}
+
+	/// Returns the storage keys with the provided prefix at the provided block, in lexicographic
+	/// order.
+	///
+	/// # Arguments
+	/// * `at` - The hash of the block, defaulting to the latest block.
+	/// * `child` - The prefixed storage key of a child trie, if the keys are within a child trie.
+	/// * `prefix` - The prefix of the keys.
+	/// * `count` - The maximum number of keys returned.
+	/// * `start` - The key after which keys are returned, if any.
+	pub fn keys(
+		&self,
+		at: Option<H256>,
+		child: Option<&[u8]>,
+		prefix: &[u8],
+		count: usize,
+		start: Option<&[u8]>,
+	) -> Result<Vec<Vec<u8>>, Error> {
+		let backend = self.backend(self.block(at)?);
+		let mut args = IterArgs::default();
+		args.prefix = Some(prefix);
+		args.start_at = start.filter(|start| *start > prefix);
+		args.start_at_exclusive = true;
+		args.child_info = child.map(child_info).transpose()?;
+		backend
+			.keys(args)
+			.map_err(Error::ForkError)?
+			.take(count)
+			.collect::<Result<_, _>>()
+			.map_err(Error::ForkError)
+	}
+
+	/// Overrides the storage of the latest block, without building a new block.
+	///
+	/// # Arguments
+	/// * `changes` - The storage keys and their new values, where `None` removes the value.
+	pub fn set_storage(
+		&mut self,
+		changes: impl IntoIterator<Item = (Vec<u8>, Option<Vec<u8>>)>,
+	) -> Result<(), Error> {
+		let head = self.blocks.last_mut().expect("a fork always contains a block; qed");
+		let state = Arc::make_mut(&mut head.changes);
+		let mut upgraded = false;
+		for (key, value) in changes {
+			upgraded |= key == CODE;
+			state.set(key, value);
+		}
+		if upgraded {
+			let head = self.head();
+			let runtime = Runtime::load(&self.executor, &self.backend(head))?;
+			self.blocks.last_mut().expect("a fork always contains a block; qed").runtime =
+				Arc::new(runtime);
+		}
+		Ok(())
+	}
+
+	/// Sets the account with sudo privileges.
+	///
+	/// # Arguments
+	/// * `account` - The account.
+	pub fn set_sudo(&mut self, account: [u8; 32]) -> Result<(), Error> {
+		if !self.head().runtime.pallets.iter().any(|p| p == "Sudo") {
+			return Err(Error::ForkError("the runtime does not include the sudo pallet".into()));
+		}
+		self.set_storage([(storage_key("Sudo", "Key"), Some(account.to_vec()))])
+	}
+
+	/// Sets the free balance of an account, updating the total issuance accordingly.
+	///
+	/// # Arguments
+	/// * `account` - The account.
+	/// * `amount` - The free balance of the account.
+	pub fn fund(&mut self, account: [u8; 32], amount: u128) -> Result<(), Error> {
+		let key =
+			[storage_key("System", "Account"), blake2_128(&account).to_vec(), account.to_vec()]
+				.concat();
+		// A new account, with a provider reference and the flags of the current balances logic.
+		let new = || (0u32, 0u32, 1u32, 0u32, 0u128, 0u128, 0u128, 1u128 << 127).encode();
+		let mut info = self.storage(None, None, &key)?.unwrap_or_else(new);
+		if info.len() != ACCOUNT_INFO_SIZE {
+			return Err(Error::ForkError(
+				"the account layout of the runtime is not supported".into(),
+			));
+		}
+		let previous = decode::<u128>(&info[16..32])?;
+		info[16..32].copy_from_slice(&amount.to_le_bytes());
+		let issuance_key = storage_key("Balances", "TotalIssuance");
+		let issuance = self
+			.storage(None, None, &issuance_key)?
+			.map(|v| decode::<u128>(&v))
+			.transpose()?
+			.unwrap_or_default()
+			.saturating_sub(previous)
+			.saturating_add(amount);
+		self.set_storage([(key, Some(info)), (issuance_key, Some(issuance.encode()))])
+	}
+
+	/// The extrinsics pending inclusion within the next block.
+	pub fn pending(&self) -> impl Iterator<Item = &[u8]> {
+		self.pool.iter().map(|(_, extrinsic)| extrinsic.as_slice())
+	}
+
+	/// Validates an extrinsic against the latest block, adding it to the extrinsics pending
+	/// inclusion within the next block.
+	///
+	/// # Arguments
+	/// * `extrinsic` - The encoded extrinsic.
+	pub fn submit(&mut self, extrinsic: Vec<u8>) -> Result<H256, Error> {
+		let hash = H256(blake2_256(&extrinsic));
+		if self.pool.iter().any(|(pending, _)| *pending == hash) {
+			return Err(Error::ExtrinsicSubmissionError("the extrinsic is already pending".into()));
+		}
+		let data = [&[EXTERNAL_SOURCE], extrinsic.as_slice(), self.head().hash.as_bytes()].concat();
+		// Extrinsics which cannot be decoded cause the runtime to panic.
+		let validity = self
+			.call(None, "TaggedTransactionQueue_validate_transaction", &data)
+			.map_err(|e| Error::ExtrinsicSubmissionError(format!("invalid transaction: {e}")))?;
+		if let Some((1, error)) = validity.split_first() {
+			let error = decode::<TransactionValidityError>(error)
+				.map(|e| format!("{e:?}"))
+				.unwrap_or_else(|_| format!("0x{}", hex(error)));
+			return Err(Error::ExtrinsicSubmissionError(format!("invalid transaction: {error}")));
+		}
+		self.pool.push((hash, extrinsic));
+		Ok(hash)
+	}
+
+	/// Builds a block on top of the latest block, including the pending extrinsics.
+	pub fn build_block(&mut self) -> Result<BlockOutcome, Error> {
+		let pool = std::mem::take(&mut self.pool);
+		match self.author(&pool) {
+			Ok(outcome) => {
+				self.blocks.push(outcome.block.clone());
+				Ok(outcome)
+			},
+			Err(e) => {
+				self.pool = pool;
+				Err(e)
+			},
+		}
+	}
+
+	// Authors a block on top of the latest block, applying the provided extrinsics.
+	fn author(&self, pool: &[(H256, Vec<u8>)]) -> Result<BlockOutcome, Error> {
+		let parent = self.head();
+		let backend = self.backend(parent);
+		let (digest, inherent_data) = parent
+			.runtime
+			.authoring
+			.inherents(&parent.header, |key| backend.storage(key).map_err(Error::ForkError))?;
+		let header = Header {
+			parent_hash: parent.hash,
+			number: parent.header.number + 1,
+			state_root: Default::default(),
+			extrinsics_root: Default::default(),
+			digest,
+		};
+		let mut overlay = OverlayedChanges::default();
+		let call = |overlay: &mut OverlayedChanges<_>, method: &str, data: &[u8]| {
+			execute(&self.executor, &backend, overlay, method, data, CallContext::Onchain)
+		};
+
+		// Runtimes which are migrating only accept inherents.
+		let mode = call(&mut overlay, "Core_initialize_block", &header.encode())?;
+		let only_inherents = mode.first() == Some(&1);
+		let inherents = decode::<Vec<Vec<u8>>>(&call(
+			&mut overlay,
+			"BlockBuilder_inherent_extrinsics",
+			&inherent_data.encode(),
+		)?)?;
+		let mut extrinsics = Vec::new();
+		for inherent in inherents {
+			// Inherents are returned without their length prefix.
+			let inherent = inherent.encode();
+			let result = call(&mut overlay, "BlockBuilder_apply_extrinsic", &inherent)?;
+			if let Some((1, error)) = result.split_first() {
+				let error = decode::<TransactionValidityError>(error)
+					.map(|e| format!("{e:?}"))
+					.unwrap_or_default();
+				return Err(Error::ForkError(format!("an inherent was rejected: {error}")));
+			}
+			extrinsics.push(inherent);
+		}
+		let mut dropped = Vec::new();
+		for (hash, extrinsic) in pool {
+			if only_inherents {
+				dropped.push(*hash);
+				continue;
+			}
+			overlay.start_transaction();
+			match call(&mut overlay, "BlockBuilder_apply_extrinsic", extrinsic) {
+				Ok(result) if result.first() == Some(&0) => {
+					overlay.commit_transaction().expect("a transaction was started; qed");
+					extrinsics.push(extrinsic.clone());
+				},
+				_ => {
+					overlay.rollback_transaction().expect("a transaction was started; qed");
+					dropped.push(*hash);
+				},
+			}
+		}
+		let header = decode::<Header>(&call(&mut overlay, "BlockBuilder_finalize_block", &[])?)?;
+
+		let changes = overlay
+			.drain_storage_changes(&backend, StateVersion::V1)
+			.map_err(|e| Error::ForkError(e.to_string()))?;
+		let upgraded = changes.main_storage_changes.iter().any(|(key, _)| key == CODE);
+		let mut state = Changes::clone(&parent.changes);
+		state.apply(changes);
+		let runtime = match upgraded {
+			true => Arc::new(Runtime::load(
+				&self.executor,
+				&ForkBackend::new(&self.source, &state, header.state_root),
+			)?),
+			false => parent.runtime.clone(),
+		};
+		let block =
+			Block { hash: hash(&header), header, extrinsics, changes: Arc::new(state), runtime };
+		Ok(BlockOutcome { block, dropped })
+	}
+
+	fn backend<'a>(&'a self, block: &'a Block) -> ForkBackend<'a> {
+		ForkBackend::new(&self.source, &block.changes, block.header.state_root)
+	}
+}
+
+// Executes a runtime API function against the state, recording changes within the overlay.
+fn execute(
+	executor: &WasmExecutor<HostFunctions>,
+	backend: &ForkBackend,
+	overlay: &mut OverlayedChanges<BlakeTwo256>,
+	method: &str,
+	data: &[u8],
+	context: CallContext,
+) -> Result<Vec<u8>, Error> {
+	let code = BackendRuntimeCode::new(backend);
+	let runtime_code = code.runtime_code().map_err(|e| Error::ForkError(e.to_string()))?;
+	// Required by runtime upgrades to check the version of the new runtime.
+	let mut extensions = Extensions::default();
+	extensions.register(ReadRuntimeVersionExt::new(executor.clone()));
+	let result = StateMachine::new(
+		backend,
+		overlay,
+		executor,
+		method,
+		data,
+		&mut extensions,
+		&runtime_code,
+		context,
+	)
+	.execute()
+	.map_err(|e| Error::ForkError(format!("{method} failed: {e}")));
+	result
+}
+
+/// Parses storage overrides from a JSON object mapping hex-encoded storage keys to hex-encoded
+/// values, where `null` removes the value.
+///
+/// # Arguments
+/// * `overrides` - The storage overrides, e.g. `{"0x26aa...": "0x01", "0x5c0d...": null}`.
+pub fn parse_storage(overrides: &Value) -> Result<StorageOverrides, Error> {
+	let overrides = overrides
+		.as_object()
+		.ok_or_else(|| Error::ParamParsingError("storage overrides must be an object".into()))?;
+	let bytes = |value: &str| {
+		sp_core::bytes::from_hex(value)
+			.map_err(|e| Error::ParamParsingError(format!("invalid hex value {value}: {e}")))
+	};
+	overrides
+		.iter()
+		.map(|(key, value)| {
+			let value = match value {
+				Value::Null => None,
+				Value::String(value) => Some(bytes(value)?),
+				_ => return Err(Error::ParamParsingError(format!("invalid value for {key}"))),
+			};
+			Ok((bytes(key)?, value))
+		})
+		.collect()
+}
+
+fn child_info(prefixed_key: &[u8]) -> Result<ChildInfo, Error> {
+	match ChildType::from_prefixed_key(PrefixedStorageKey::new_ref(&prefixed_key.to_vec())) {
+		Some((ChildType::ParentKeyId, key)) => Ok(ChildInfo::new_default(key)),
+		None => Err(Error::ForkError(format!("invalid child storage key 0x{}", hex(prefixed_key)))),
+	}
+}
+
+fn decode<T: Decode>(mut data: &[u8]) -> Result<T, Error> {
+	T::decode(&mut data).map_err(|e| Error::ForkError(format!("could not decode value: {e}")))
+}
+
+fn hash(header: &Header) -> H256 {
+	H256(blake2_256(&header.encode()))
+}
+
+fn hex(data: &[u8]) -> String {
+	data.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+// The storage key of a plain storage item of a pallet.
+fn storage_key(pallet: &str, item: &str) -> Vec<u8> {
+	[twox_128(pallet.as_bytes()), twox_128(item.as_bytes())].concat()
+}
+
+// The storage key of the hash of a block, as stored by the system pallet.
+fn block_hash_key(number: u32) -> Vec<u8> {
+	[storage_key("System", "BlockHash"), twox_64(&number.encode()).to_vec(), number.encode()]
+		.concat()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::path::PathBuf;
+
+	// A snapshot of a rollup created from the parachain template.
+	fn snapshot() -> ForkSource {
+		ForkSource::Snapshot(
+			PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+				.join("../../tests/snapshots/base_parachain.snap"),
+		)
+	}
+
+	async fn fork() -> Result<Fork, Error> {
+		let cache = tempfile::tempdir()?;
+		Fork::new(&snapshot(), cache.path()).await
+	}
+
+	// Runs a closure against the fork from a blocking context, as required to execute the runtime.
+	async fn blocking<T: Send + 'static>(
+		mut fork: Fork,
+		f: impl FnOnce(&mut Fork) -> Result<T, Error> + Send + 'static,
+	) -> Result<(Fork, T), Error> {
+		tokio::task::spawn_blocking(move || f(&mut fork).map(|result| (fork, result)))
+			.await
+			.map_err(|e| Error::ForkError(e.to_string()))?
+	}
+
+	#[tokio::test]
+	async fn fork_from_snapshot_works() -> Result<(), Error> {
+		let fork = fork().await?;
+		let head = fork.head();
+		assert_eq!(fork.block(None)?.hash, head.hash);
+		assert_eq!(fork.block(Some(head.hash))?.hash, head.hash);
+		assert!(fork.block(Some(H256::zero())).is_err());
+		assert_eq!(fork.block_hash(head.header.number)?, Some(head.hash));
+		assert_eq!(fork.block_hash(head.header.number + 1)?, None);
+		assert_ne!(fork.genesis_hash(), H256::zero());
+		assert_eq!(fork.chain(), fork.runtime_version(None)?.spec_name.to_string());
+		assert!(!fork.metadata(None)?.is_empty());
+		assert!(fork.storage(None, None, CODE)?.is_some());
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn fork_fails_with_invalid_snapshot() -> Result<(), Error> {
+		let temp_dir = tempfile::tempdir()?;
+		let path = temp_dir.path().join("invalid.snap");
+		std::fs::write(&path, [0x0c, 0x01])?;
+		assert!(matches!(
+			Fork::new(&ForkSource::Snapshot(path), temp_dir.path()).await,
+			Err(Error::ForkError(message)) if message == "unsupported snapshot version 3, expected 4"
+		));
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn keys_works() -> Result<(), Error> {
+		let fork = fork().await?;
+		let prefix = storage_key("System", "Account");
+		let keys = fork.keys(None, None, &prefix, 3, None)?;
+		assert!(!keys.is_empty() && keys.len() <= 3);
+		assert!(keys.iter().all(|k| k.starts_with(&prefix)));
+		assert!(keys.windows(2).all(|w| w[0] < w[1]));
+		// Keys are returned after the start key.
+		let next = fork.keys(None, None, &prefix, 3, Some(&keys[0]))?;
+		assert_eq!(next.first(), keys.get(1));
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn set_storage_works() -> Result<(), Error> {
+		let mut fork = fork().await?;
+		let key = b"key".to_vec();
+		fork.set_storage([(key.clone(), Some(b"value".to_vec()))])?;
+		assert_eq!(fork.storage(None, None, &key)?, Some(b"value".to_vec()));
+		assert!(fork.keys(None, None, b"ke", 1, None)?.contains(&key));
+		fork.set_storage([(key.clone(), None)])?;
+		assert_eq!(fork.storage(None, None, &key)?, None);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn fund_works() -> Result<(), Error> {
+		let mut fork = fork().await?;
+		let account = [1; 32];
+		let issuance_key = storage_key("Balances", "TotalIssuance");
+		let issuance = decode::<u128>(&fork.storage(None, None, &issuance_key)?.unwrap())?;
+		fork.fund(account, 1_000)?;
+		let key =
+			[storage_key("System", "Account"), blake2_128(&account).to_vec(), account.to_vec()]
+				.concat();
+		let info = fork.storage(None, None, &key)?.unwrap();
+		assert_eq!(decode::<u128>(&info[16..32])?, 1_000);
+		assert_eq!(
+			decode::<u128>(&fork.storage(None, None, &issuance_key)?.unwrap())?,
+			issuance + 1_000
+		);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn build_block_works() -> Result<(), Error> {
+		let fork = fork().await?;
+		let parent = fork.head().clone();
+		let (fork, outcome) = blocking(fork, |fork| fork.build_block()).await?;
+		let block = outcome.block;
+		assert_eq!(block.header.parent_hash, parent.hash);
+		assert_eq!(block.header.number, parent.header.number + 1);
+		assert_eq!(fork.head().hash, block.hash);
+		assert_eq!(fork.block_hash(block.header.number)?, Some(block.hash));
+		// The block includes the inherents, which update the timestamp.
+		assert!(!block.extrinsics.is_empty());
+		let now = storage_key("Timestamp", "Now");
+		assert_ne!(fork.storage(None, None, &now)?, fork.storage(Some(parent.hash), None, &now)?);
+
+		// Blocks can be built on top of built blocks.
+		let (fork, outcome) = blocking(fork, |fork| fork.build_block()).await?;
+		assert_eq!(outcome.block.header.parent_hash, block.hash);
+		assert_eq!(fork.head().header.number, parent.header.number + 2);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn submit_rejects_invalid_extrinsic() -> Result<(), Error> {
+		let fork = fork().await?;
+		let (fork, result) = blocking(fork, |fork| Ok(fork.submit(vec![0x04, 0x00]))).await?;
+		assert!(matches!(result, Err(Error::ExtrinsicSubmissionError(_))));
+		assert_eq!(fork.pending().count(), 0);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn set_sudo_works() -> Result<(), Error> {
+		let mut fork = fork().await?;
+		if fork.head().runtime.pallets.iter().any(|p| p == "Sudo") {
+			fork.set_sudo([1; 32])?;
+			assert_eq!(fork.storage(None, None, &storage_key("Sudo", "Key"))?, Some(vec![1; 32]));
+		} else {
+			assert!(matches!(fork.set_sudo([1; 32]), Err(Error::ForkError(_))));
+		}
+		Ok(())
+	}
+
+	#[test]
+	fn parse_storage_works() -> Result<(), Error> {
+		let overrides = json!({ "0x0102": "0x03", "0x04": null });
+		assert_eq!(parse_storage(&overrides)?, vec![(vec![1, 2], Some(vec![3])), (vec![4], None)]);
+		assert!(parse_storage(&json!({ "0x01": 1 })).is_err());
+		assert!(parse_storage(&json!({ "key": "0x01" })).is_err());
+		assert!(parse_storage(&json!(["0x01"])).is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn child_info_works() -> Result<(), Error> {
+		let child = child_info(b":child_storage:default:trie")?;
+		assert_eq!(child.storage_key(), b"trie");
+		assert!(child_info(b"trie").is_err());
+		Ok(())
+	}
+}
@@ -0,0 +1,612 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use super::{parse_storage, Block, BlockOutcome, Fork};
+use crate::errors::Error;
+use jsonrpsee::{
+	core::SubscriptionResult,
+	server::{Server, ServerHandle},
+	types::{error::CALL_EXECUTION_FAILED_CODE, ErrorObject, ErrorObjectOwned, Params},
+	PendingSubscriptionSink, RpcModule, SubscriptionMessage, SubscriptionSink,
+};
+use scale::{Decode, Encode};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Value};
+use sp_core::{blake2_256, Bytes};
+use std::{
+	net::SocketAddr,
+	sync::{Arc, Mutex, MutexGuard},
+};
+use strum_macros::{AsRefStr, EnumString};
+use subxt::utils::H256;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+/// The error code of an invalid transaction, as used by nodes.
+const INVALID_TRANSACTION_CODE: i32 = 1010;
+/// The number of built blocks buffered for each subscriber.
+const CAPACITY: usize = 64;
+/// The maximum number of keys returned by a paged storage query.
+const MAX_KEYS: u32 = 1_000;
+/// The maximum number of parameters of a method.
+const MAX_PARAMS: usize = 5;
+/// The methods which depend on the state root, which is not computed by a fork.
+const UNSUPPORTED: [&str; 3] =
+	["state_getReadProof", "state_getChildReadProof", "state_traceBlock"];
+
+type RpcResult<T> = Result<T, ErrorObjectOwned>;
+
+/// How blocks are sealed by a fork.
+#[derive(AsRefStr, Clone, Copy, Debug, Default, EnumString, Eq, PartialEq, clap::ValueEnum)]
+#[strum(serialize_all = "lowercase")]
+pub enum Seal {
+	/// A block is sealed as soon as an extrinsic is submitted.
+	#[default]
+	Instant,
+	/// Blocks are only sealed on demand, using the `engine_createBlock` method.
+	Manual,
+}
+
+/// A fork served over a local JSON-RPC endpoint.
+pub struct ForkServer {
+	handle: ServerHandle,
+	address: SocketAddr,
+}
+
+impl ForkServer {
+	/// The endpoint of the fork.
+	pub fn url(&self) -> String {
+		format!("ws://{}", self.address)
+	}
+
+	/// Stops serving the fork.
+	pub async fn stop(self) -> Result<(), Error> {
+		self.handle.stop().map_err(|e| Error::ForkError(e.to_string()))?;
+		self.handle.stopped().await;
+		Ok(())
+	}
+}
+
+/// Serves a fork over a local JSON-RPC endpoint, exposing the subset of the node API used by
+/// wallets and tooling, along with the `engine_*` and `dev_*` methods used to control block
+/// production.
+///
+/// # Arguments
+/// * `fork` - The fork.
+/// * `port` - The local port to listen on.
+/// * `seal` - How blocks are built: on submission of an extrinsic or on demand.
+pub async fn serve(fork: Fork, port: u16, seal: Seal) -> Result<ForkServer, Error> {
+	let server = Server::builder()
+		.build(SocketAddr::from(([127, 0, 0, 1], port)))
+		.await
+		.map_err(|e| Error::ForkError(format!("could not listen on port {port}: {e}")))?;
+	let address = server.local_addr().map_err(|e| Error::ForkError(e.to_string()))?;
+	let (blocks, _) = broadcast::channel(CAPACITY);
+	let context = Context { fork: Mutex::new(fork), blocks, seal };
+	let module = module(context).map_err(|e| Error::ForkError(e.to_string()))?;
+	Ok(ForkServer { handle: server.start(module), address })
+}
+
+// The state shared by the methods of the server.
+struct Context {
+	fork: Mutex<Fork>,
+	// Notifies subscribers of built blocks.
+	blocks: broadcast::Sender<BlockOutcome>,
+	seal: Seal,
+}
+
+impl Context {
+	fn fork(&self) -> MutexGuard<'_, Fork> {
+		self.fork.lock().unwrap_or_else(|e| e.into_inner())
+	}
+
+	// Builds a block, notifying subscribers.
+	fn build(&self, fork: &mut Fork) -> Result<BlockOutcome, Error> {
+		let outcome = fork.build_block()?;
+		// Sending only fails when there are no subscribers.
+		let _ = self.blocks.send(outcome.clone());
+		Ok(outcome)
+	}
+
+	// Adds an extrinsic to the pending extrinsics, building a block when sealing instantly.
+	fn submit(&self, extrinsic: Vec<u8>) -> Result<H256, Error> {
+		let mut fork = self.fork();
+		let hash = fork.submit(extrinsic)?;
+		if self.seal == Seal::Instant {
+			self.build(&mut fork)?;
+		}
+		Ok(hash)
+	}
+}
+
+// Runs a closure against the fork on a blocking thread, as executing the runtime or fetching state
+// blocks.
+async fn blocking<T: Send + 'static>(
+	context: Arc<Context>,
+	f: impl FnOnce(&mut Fork) -> Result<T, Error> + Send + 'static,
+) -> Result<T, Error> {
+	tokio::task::spawn_blocking(move || f(&mut context.fork()))
+		.await
+		.map_err(|e| Error::ForkError(e.to_string()))?
+}
+
+fn rpc_error(error: Error) -> ErrorObjectOwned {
+	let code = match error {
+		Error::ExtrinsicSubmissionError(_) => INVALID_TRANSACTION_CODE,
+		_ => CALL_EXECUTION_FAILED_CODE,
+	};
+	ErrorObject::owned(code, error.to_string(), None::<()>)
+}
+
+// Registers a method which accesses the fork from a blocking thread.
+macro_rules! method {
+	($module:ident, $name:expr, |$params:ident, $fork:ident| $body:expr) => {
+		$module.register_blocking_method($name, |$params, context, _| -> RpcResult<Value> {
+			#[allow(unused_mut)]
+			let method = |$params: Params, mut $fork: MutexGuard<'_, Fork>| -> Result<Value, Error> {
+				$body
+			};
+			method($params, context.fork()).map_err(rpc_error)
+		})?;
+	};
+}
+
+// Parses the positional parameters of a method, where trailing optional parameters may be omitted.
+fn parse<T: DeserializeOwned>(params: &Params) -> Result<T, Error> {
+	let mut values: Vec<Value> = match params.as_str() {
+		Some(_) => params.parse().map_err(|e| Error::ParamParsingError(e.to_string()))?,
+		None => vec![],
+	};
+	loop {
+		match serde_json::from_value::<T>(Value::Array(values.clone())) {
+			Ok(value) => return Ok(value),
+			Err(e) if values.len() >= MAX_PARAMS =>
+				return Err(Error::ParamParsingError(e.to_string())),
+			Err(_) => values.push(Value::Null),
+		}
+	}
+}
+
+fn block_json(block: &Block) -> Value {
+	let extrinsics: Vec<Bytes> = block.extrinsics.iter().cloned().map(Bytes).collect();
+	json!({ "block": { "header": block.header, "extrinsics": extrinsics }, "justifications": null })
+}
+
+fn module(context: Context) -> Result<RpcModule<Context>, jsonrpsee::core::RegisterMethodError> {
+	let mut module = RpcModule::new(context);
+
+	// System.
+	module.register_method("system_name", |_, _, _| "pop-fork")?;
+	module.register_method("system_version", |_, _, _| env!("CARGO_PKG_VERSION"))?;
+	module.register_method("system_chainType", |_, _, _| "Development")?;
+	module.register_method(
+		"system_health",
+		|_, _, _| json!({ "peers": 0, "isSyncing": false, "shouldHavePeers": false }),
+	)?;
+	method!(module, "system_chain", |_params, fork| Ok(fork.chain().into()));
+	method!(module, "system_properties", |_params, fork| Ok(fork.properties().clone()));
+	method!(module, "system_accountNextIndex", |params, fork| {
+		let (account,): (String,) = parse(&params)?;
+		let account = pop_common::parse_account(&account)?;
+		let nonce = fork.call(None, "AccountNonceApi_account_nonce", &account.0.encode())?;
+		// Runtimes use either a `u32` or a `u64` nonce.
+		let nonce = match nonce.len() {
+			8 => u64::decode(&mut &nonce[..]),
+			_ => u32::decode(&mut &nonce[..]).map(u64::from),
+		}
+		.map_err(|e| Error::ForkError(e.to_string()))?;
+		Ok(nonce.into())
+	});
+
+	// Chain.
+	method!(module, "chain_getBlockHash", |params, fork| {
+		let (number,): (Option<Value>,) = parse(&params)?;
+		let number = match number {
+			None | Some(Value::Null) => return Ok(json!(fork.head().hash)),
+			Some(Value::Number(n)) => n.as_u64(),
+			Some(Value::String(s)) => u64::from_str_radix(s.trim_start_matches("0x"), 16).ok(),
+			Some(_) => None,
+		}
+		.and_then(|n| u32::try_from(n).ok())
+		.ok_or_else(|| Error::ParamParsingError("invalid block number".into()))?;
+		Ok(json!(fork.block_hash(number)?))
+	});
+	method!(module, "chain_getHeader", |params, fork| {
+		let (at,): (Option<H256>,) = parse(&params)?;
+		Ok(json!(fork.block(at)?.header))
+	});
+	method!(module, "chain_getBlock", |params, fork| {
+		let (at,): (Option<H256>,) = parse(&params)?;
+		Ok(block_json(fork.block(at)?))
+	});
+	method!(module, "chain_getFinalizedHead", |_params, fork| Ok(json!(fork.head().hash)));
+	for (subscribe, notification, unsubscribe) in [
+		("chain_subscribeNewHeads", "chain_newHead", "chain_unsubscribeNewHeads"),
+		("chain_subscribeFinalizedHeads", "chain_finalizedHead", "chain_unsubscribeFinalizedHeads"),
+		("chain_subscribeAllHeads", "chain_allHead", "chain_unsubscribeAllHeads"),
+	] {
+		module.register_subscription(
+			subscribe,
+			notification,
+			unsubscribe,
+			|_, pending, context, _| async move { subscribe_heads(pending, context).await },
+		)?;
+	}
+
+	// State.
+	for name in ["state_getRuntimeVersion", "chain_getRuntimeVersion"] {
+		method!(module, name, |params, fork| {
+			let (at,): (Option<H256>,) = parse(&params)?;
+			Ok(serde_json::to_value(fork.runtime_version(at)?)?)
+		});
+	}
+	module.register_subscription(
+		"state_subscribeRuntimeVersion",
+		"state_runtimeVersion",
+		"state_unsubscribeRuntimeVersion",
+		|_, pending, context, _| async move { subscribe_runtime_version(pending, context).await },
+	)?;
+	method!(module, "state_getMetadata", |params, fork| {
+		let (at,): (Option<H256>,) = parse(&params)?;
+		Ok(json!(Bytes(fork.metadata(at)?.to_vec())))
+	});
+	method!(module, "state_call", |params, fork| {
+		let (method, data, at): (String, Bytes, Option<H256>) = parse(&params)?;
+		Ok(json!(Bytes(fork.call(at, &method, &data)?)))
+	});
+	method!(module, "state_getStorage", |params, fork| {
+		let (key, at): (Bytes, Option<H256>) = parse(&params)?;
+		Ok(json!(fork.storage(at, None, &key)?.map(Bytes)))
+	});
+	method!(module, "state_getStorageHash", |params, fork| {
+		let (key, at): (Bytes, Option<H256>) = parse(&params)?;
+		Ok(json!(fork.storage(at, None, &key)?.map(|v| H256(blake2_256(&v)))))
+	});
+	method!(module, "state_getKeysPaged", |params, fork| {
+		let (prefix, count, start, at): (Option<Bytes>, u32, Option<Bytes>, Option<H256>) =
+			parse(&params)?;
+		let prefix = prefix.map(|p| p.0).unwrap_or_default();
+		let keys = fork.keys(
+			at,
+			None,
+			&prefix,
+			count.min(MAX_KEYS) as usize,
+			start.as_ref().map(|s| s.as_ref()),
+		)?;
+		Ok(json!(keys.into_iter().map(Bytes).collect::<Vec<_>>()))
+	});
+	method!(module, "state_queryStorageAt", |params, fork| {
+		let (keys, at): (Vec<Bytes>, Option<H256>) = parse(&params)?;
+		let block = fork.block(at)?.hash;
+		let mut changes = Vec::new();
+		for key in keys {
+			let value = fork.storage(Some(block), None, &key)?.map(Bytes);
+			changes.push(json!([key, value]));
+		}
+		Ok(json!([{ "block": block, "changes": changes }]))
+	});
+	module.register_subscription(
+		"state_subscribeStorage",
+		"state_storage",
+		"state_unsubscribeStorage",
+		|params, pending, context, _| async move {
+			let keys = match parse::<(Option<Vec<Bytes>>,)>(&params) {
+				Ok((keys,)) => keys.unwrap_or_default(),
+				Err(e) => {
+					pending.reject(rpc_error(e)).await;
+					return Ok(());
+				},
+			};
+			subscribe_storage(pending, context, keys).await
+		},
+	)?;
+
+	// Child state.
+	method!(module, "childstate_getStorage", |params, fork| {
+		let (child, key, at): (Bytes, Bytes, Option<H256>) = parse(&params)?;
+		Ok(json!(fork.storage(at, Some(&child), &key)?.map(Bytes)))
+	});
+	method!(module, "childstate_getKeysPaged", |params, fork| {
+		let (child, prefix, count, start, at): (
+			Bytes,
+			Option<Bytes>,
+			u32,
+			Option<Bytes>,
+			Option<H256>,
+		) = parse(&params)?;
+		let prefix = prefix.map(|p| p.0).unwrap_or_default();
+		let keys = fork.keys(
+			at,
+			Some(&child),
+			&prefix,
+			count.min(MAX_KEYS) as usize,
+			start.as_ref().map(|s| s.as_ref()),
+		)?;
+		Ok(json!(keys.into_iter().map(Bytes).collect::<Vec<_>>()))
+	});
+
+	// Author.
+	module.register_blocking_method("author_submitExtrinsic", |params, context, _| {
+		let (extrinsic,): (Bytes,) = parse(&params).map_err(rpc_error)?;
+		context.submit(extrinsic.0).map_err(rpc_error)
+	})?;
+	module.register_subscription(
+		"author_submitAndWatchExtrinsic",
+		"author_extrinsicUpdate",
+		"author_unwatchExtrinsic",
+		|params, pending, context, _| async move {
+			let extrinsic = match parse::<(Bytes,)>(&params) {
+				Ok((extrinsic,)) => extrinsic.0,
+				Err(e) => {
+					pending.reject(rpc_error(e)).await;
+					return Ok(());
+				},
+			};
+			watch_extrinsic(pending, context, extrinsic).await
+		},
+	)?;
+	method!(module, "author_pendingExtrinsics", |_params, fork| {
+		Ok(json!(fork.pending().map(|e| Bytes(e.to_vec())).collect::<Vec<_>>()))
+	});
+
+	// Block production.
+	module.register_blocking_method("engine_createBlock", |params, context, _| {
+		let (create_empty, _finalize, parent): (bool, Option<bool>, Option<H256>) =
+			parse(&params).map_err(rpc_error)?;
+		let mut fork = context.fork();
+		if parent.is_some_and(|parent| parent != fork.head().hash) {
+			return Err(rpc_error(Error::ForkError(
+				"blocks can only be built on the latest block".into(),
+			)));
+		}
+		if !create_empty && fork.pending().next().is_none() {
+			return Err(rpc_error(Error::ForkError("there are no pending extrinsics".into())));
+		}
+		let outcome = context.build(&mut fork).map_err(rpc_error)?;
+		Ok(json!({
+			"hash": outcome.block.hash,
+			"aux": {
+				"headerOnly": false,
+				"clearJustificationRequests": false,
+				"needsJustification": false,
+				"badJustification": false,
+				"isNewBest": true,
+			},
+		}))
+	})?;
+	// Blocks of a fork are final once built.
+	method!(module, "engine_finalizeBlock", |params, fork| {
+		let (hash, _justification): (H256, Option<Value>) = parse(&params)?;
+		fork.block(Some(hash))?;
+		Ok(true.into())
+	});
+	method!(module, "dev_setStorage", |params, fork| {
+		let (changes,): (Value,) = parse(&params)?;
+		fork.set_storage(parse_storage(&changes)?)?;
+		Ok(json!(fork.head().hash))
+	});
+
+	let mut methods: Vec<_> = module.method_names().map(String::from).collect();
+	methods.push("rpc_methods".into());
+	methods.sort();
+	module.register_method(
+		"rpc_methods",
+		move |_, _, _| json!({ "version": 1, "methods": methods }),
+	)?;
+	// The state roots of blocks built by a fork are derived rather than computed, so methods which
+	// depend on them are refused rather than returning data which cannot be verified.
+	for name in UNSUPPORTED {
+		module.register_method(name, move |_, _, _| -> RpcResult<Value> {
+			Err(rpc_error(Error::ForkError(format!(
+				"{name} is not supported, as the state root of a fork is not computed"
+			))))
+		})?;
+	}
+	Ok(module)
+}
+
+// Notifies the subscriber of the latest block, followed by each block built.
+async fn subscribe_heads(
+	pending: PendingSubscriptionSink,
+	context: Arc<Context>,
+) -> SubscriptionResult {
+	let mut blocks = context.blocks.subscribe();
+	let head = blocking(context.clone(), |fork| Ok(fork.head().header.clone())).await?;
+	let sink = pending.accept().await?;
+	send(&sink, &head).await?;
+	while let Some(outcome) = next(&mut blocks, &sink).await {
+		send(&sink, &outcome.block.header).await?;
+	}
+	Ok(())
+}
+
+// Notifies the subscriber of the runtime version, followed by each change of the version.
+async fn subscribe_runtime_version(
+	pending: PendingSubscriptionSink,
+	context: Arc<Context>,
+) -> SubscriptionResult {
+	let mut blocks = context.blocks.subscribe();
+	let version =
+		|| blocking(context.clone(), |fork| Ok(serde_json::to_value(fork.runtime_version(None)?)?));
+	let mut current = version().await?;
+	let sink = pending.accept().await?;
+	send(&sink, &current).await?;
+	while next(&mut blocks, &sink).await.is_some() {
+		let latest = version().await?;
+		if latest != current {
+			send(&sink, &latest).await?;
+			current = latest;
+		}
+	}
+	Ok(())
+}
+
+// Notifies the subscriber of the values of the storage keys, followed by each change of the values.
+async fn subscribe_storage(
+	pending: PendingSubscriptionSink,
+	context: Arc<Context>,
+	keys: Vec<Bytes>,
+) -> SubscriptionResult {
+	let mut blocks = context.blocks.subscribe();
+	let values = || {
+		let keys = keys.clone();
+		blocking(context.clone(), move |fork| {
+			let head = fork.head().hash;
+			let values = keys
+				.iter()
+				.map(|key| fork.storage(Some(head), None, key))
+				.collect::<Result<Vec<_>, _>>()?;
+			Ok((head, values))
+		})
+	};
+	let (head, mut current) = values().await?;
+	let sink = pending.accept().await?;
+	let changes = |values: &[Option<Vec<u8>>], changed: &dyn Fn(usize) -> bool| -> Vec<Value> {
+		keys.iter()
+			.zip(values)
+			.enumerate()
+			.filter(|(i, _)| changed(*i))
+			.map(|(_, (key, value))| json!([key, value.clone().map(Bytes)]))
+			.collect()
+	};
+	send(&sink, &json!({ "block": head, "changes": changes(&current, &|_| true) })).await?;
+	while next(&mut blocks, &sink).await.is_some() {
+		let (head, latest) = values().await?;
+		let changes = changes(&latest, &|i| latest[i] != current[i]);
+		if !changes.is_empty() {
+			send(&sink, &json!({ "block": head, "changes": changes })).await?;
+		}
+		current = latest;
+	}
+	Ok(())
+}
+
+// Submits an extrinsic, notifying the subscriber of its inclusion.
+async fn watch_extrinsic(
+	pending: PendingSubscriptionSink,
+	context: Arc<Context>,
+	extrinsic: Vec<u8>,
+) -> SubscriptionResult {
+	// Subscribe before submitting, as the extrinsic may be included immediately.
+	let mut blocks = context.blocks.subscribe();
+	let submitted = {
+		let context = context.clone();
+		tokio::task::spawn_blocking(move || context.submit(extrinsic)).await?
+	};
+	let hash = match submitted {
+		Ok(hash) => hash,
+		Err(e) => {
+			pending.reject(rpc_error(e)).await;
+			return Ok(());
+		},
+	};
+	let sink = pending.accept().await?;
+	send(&sink, &"ready").await?;
+	while let Some(outcome) = next(&mut blocks, &sink).await {
+		let block = outcome.block.hash;
+		if outcome.block.extrinsics.iter().any(|e| H256(blake2_256(e)) == hash) {
+			send(&sink, &json!({ "inBlock": block })).await?;
+			send(&sink, &json!({ "finalized": block })).await?;
+			break;
+		}
+		if outcome.dropped.contains(&hash) {
+			send(&sink, &"invalid").await?;
+			break;
+		}
+	}
+	Ok(())
+}
+
+// Waits for the next block built, until the subscriber unsubscribes.
+async fn next(
+	blocks: &mut broadcast::Receiver<BlockOutcome>,
+	sink: &SubscriptionSink,
+) -> Option<BlockOutcome> {
+	loop {
+		tokio::select! {
+			_ = sink.closed() => return None,
+			block = blocks.recv() => match block {
+				Ok(block) => return Some(block),
+				Err(RecvError::Lagged(_)) => continue,
+				Err(RecvError::Closed) => return None,
+			},
+		}
+	}
+}
+
+async fn send(sink: &SubscriptionSink, value: &impl Serialize) -> SubscriptionResult {
+	sink.send(SubscriptionMessage::from_json(value)?).await?;
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::fork::ForkSource;
+	use std::{path::PathBuf, str::FromStr};
+	use subxt::backend::rpc::{rpc_params, RpcClient};
+
+	#[test]
+	fn seal_works() {
+		assert_eq!(Seal::from_str("instant"), Ok(Seal::Instant));
+		assert_eq!(Seal::from_str("manual"), Ok(Seal::Manual));
+		assert_eq!(Seal::Manual.as_ref(), "manual");
+	}
+
+	async fn server(seal: Seal) -> Result<(ForkServer, RpcClient), Error> {
+		let cache = tempfile::tempdir()?;
+		let snapshot = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+			.join("../../tests/snapshots/base_parachain.snap");
+		let fork = Fork::new(&ForkSource::Snapshot(snapshot), cache.path()).await?;
+		let server = serve(fork, 0, seal).await?;
+		let client = RpcClient::from_url(server.url())
+			.await
+			.map_err(|e| Error::ConnectionFailure(e.to_string()))?;
+		Ok((server, client))
+	}
+
+	#[tokio::test]
+	async fn serve_works() -> Result<(), Error> {
+		let (server, client) = server(Seal::Manual).await?;
+		let request = |method: &'static str, params| {
+			let client = client.clone();
+			async move {
+				client
+					.request::<Value>(method, params)
+					.await
+					.map_err(|e| Error::ForkError(e.to_string()))
+			}
+		};
+		let head = request("chain_getHeader", rpc_params![]).await?;
+		let hash = request("chain_getFinalizedHead", rpc_params![]).await?;
+		assert_eq!(request("chain_getBlockHash", rpc_params![head["number"].clone()]).await?, hash);
+		assert!(request("state_getRuntimeVersion", rpc_params![]).await?["specName"].is_string());
+		assert!(request("state_getMetadata", rpc_params![]).await?.is_string());
+		let methods = request("rpc_methods", rpc_params![]).await?;
+		assert!(methods["methods"].as_array().unwrap().contains(&json!("engine_createBlock")));
+		// Methods depending on the state root are refused.
+		assert!(!methods["methods"].as_array().unwrap().contains(&json!("state_getReadProof")));
+		assert!(request("state_getReadProof", rpc_params![vec!["0x0102"]])
+			.await
+			.is_err_and(|e| e.to_string().contains("state root of a fork is not computed")));
+
+		// Blocks are built on demand.
+		let created = request("engine_createBlock", rpc_params![true, true]).await?;
+		let block = request("chain_getBlock", rpc_params![created["hash"].clone()]).await?;
+		assert_eq!(block["block"]["header"]["parentHash"], hash);
+		assert_eq!(request("chain_getFinalizedHead", rpc_params![]).await?, created["hash"]);
+		// Blocks are only built on the latest block.
+		assert!(request("engine_createBlock", rpc_params![true, true, hash]).await.is_err());
+
+		// Storage can be overridden.
+		request("dev_setStorage", rpc_params![json!({ "0x0102": "0x03" })]).await?;
+		assert_eq!(request("state_getStorage", rpc_params!["0x0102"]).await?, json!("0x03"));
+		server.stop().await
+	}
+
+	#[tokio::test]
+	async fn submit_rejects_invalid_extrinsic() -> Result<(), Error> {
+		let (server, client) = server(Seal::Instant).await?;
+		let result = client.request::<Value>("author_submitExtrinsic", rpc_params!["0x0400"]).await;
+		assert!(result.is_err_and(|e| e.to_string().contains("invalid transaction")));
+		server.stop().await
+	}
+}
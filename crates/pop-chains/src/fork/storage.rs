@@ -0,0 +1,559 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use super::Header;
+use crate::errors::Error;
+use scale::{Compact, Decode, Encode};
+use serde::de::DeserializeOwned;
+use sp_core::{blake2_256, Bytes};
+use sp_core_inkv6::storage::ChildInfo;
+use sp_runtime::{traits::BlakeTwo256, StateVersion};
+use sp_state_machine::{
+	Backend, BackendTransaction, InMemoryBackend, IterArgs, StateMachineStats, StorageChanges,
+	StorageIterator, TestExternalities, UsageInfo,
+};
+use sp_trie::{MerkleValue, PrefixedMemoryDB};
+use std::{
+	collections::{BTreeMap, HashMap},
+	fmt,
+	fs::{self, File, OpenOptions},
+	io::{ErrorKind, Write},
+	marker::PhantomData,
+	ops::Bound::{Excluded, Unbounded},
+	path::Path,
+	sync::{Mutex, MutexGuard},
+};
+use subxt::{
+	backend::rpc::{rpc_params, RpcClient, RpcParams},
+	utils::H256,
+};
+use tokio::runtime::Handle;
+
+/// The number of keys fetched at once when iterating over remote state.
+const PAGE_SIZE: u32 = 256;
+/// The version of the snapshot files created by `try-runtime create-snapshot` which are supported.
+const SNAPSHOT_VERSION: u16 = 4;
+
+// A storage location, identified by the storage key of its child trie (if any) and its key.
+type Location = (Option<Vec<u8>>, Vec<u8>);
+// Storage values keyed by storage key, where `None` denotes a removed value.
+type Values = BTreeMap<Vec<u8>, Option<Vec<u8>>>;
+// The key-value pairs of a snapshot, along with their reference counts.
+type RawStorage = Vec<(Vec<u8>, (Vec<u8>, i32))>;
+
+/// The state a chain is forked from.
+pub(crate) enum Source {
+	/// The state of a live chain, fetched lazily.
+	Remote(Remote),
+	/// The state contained within a snapshot.
+	Snapshot(InMemoryBackend<BlakeTwo256>),
+}
+
+impl Source {
+	// Returns the value stored at the location, if any.
+	fn storage(&self, child: Option<&ChildInfo>, key: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+		match self {
+			Source::Remote(remote) => remote.storage(child, key),
+			Source::Snapshot(backend) => match child {
+				Some(child) => backend.child_storage(child, key),
+				None => backend.storage(key),
+			}
+			.map_err(|e| Error::ForkError(e.to_string())),
+		}
+	}
+
+	// Returns the key following the provided key, if any.
+	fn next_key(&self, child: Option<&ChildInfo>, key: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+		match self {
+			Source::Remote(remote) => remote.next_key(child, key),
+			Source::Snapshot(backend) => match child {
+				Some(child) => backend.next_child_storage_key(child, key),
+				None => backend.next_storage_key(key),
+			}
+			.map_err(|e| Error::ForkError(e.to_string())),
+		}
+	}
+}
+
+/// The state of a live chain at a given block, which is fetched on demand and cached on disk.
+pub(crate) struct Remote {
+	client: RpcClient,
+	// The block at which the state is fetched.
+	at: H256,
+	// Used to perform requests from synchronous contexts, such as the execution of the runtime.
+	handle: Handle,
+	cache: Mutex<Cache>,
+}
+
+impl Remote {
+	/// Connects to a live chain, caching state fetched at the provided block within the cache
+	/// directory.
+	///
+	/// # Arguments
+	/// * `client` - The client connected to the live chain.
+	/// * `at` - The block at which state is fetched.
+	/// * `cache` - The directory used to cache fetched state.
+	pub(crate) fn new(client: RpcClient, at: H256, cache: &Path) -> Result<Self, Error> {
+		let cache = Cache::load(&cache.join(format!("{at:?}.cache")))?;
+		Ok(Self { client, at, handle: Handle::current(), cache: Mutex::new(cache) })
+	}
+
+	// Performs a request from a synchronous context.
+	fn request<T: DeserializeOwned>(&self, method: &str, params: RpcParams) -> Result<T, Error> {
+		self.handle
+			.block_on(self.client.request(method, params))
+			.map_err(|e| Error::ForkError(format!("{method} failed: {e}")))
+	}
+
+	// Locks the cache. The lock is never held while fetching state, so that concurrent lookups
+	// are not blocked by a slow request.
+	fn cache(&self) -> MutexGuard<'_, Cache> {
+		self.cache.lock().unwrap_or_else(|e| e.into_inner())
+	}
+
+	fn storage(&self, child: Option<&ChildInfo>, key: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+		let location = (child.map(|c| c.storage_key().to_vec()), key.to_vec());
+		let cached = self.cache().values.get(&location).cloned();
+		if let Some(value) = cached {
+			return Ok(value);
+		}
+		let value: Option<Bytes> = match child {
+			Some(child) => self.request(
+				"childstate_getStorage",
+				rpc_params![
+					Bytes(child.prefixed_storage_key().into_inner()),
+					Bytes(key.to_vec()),
+					self.at
+				],
+			)?,
+			None => self.request("state_getStorage", rpc_params![Bytes(key.to_vec()), self.at])?,
+		};
+		let value = value.map(|v| v.0);
+		self.cache().record(Record::Value(location, value.clone()))?;
+		Ok(value)
+	}
+
+	fn next_key(&self, child: Option<&ChildInfo>, key: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+		let location = (child.map(|c| c.storage_key().to_vec()), key.to_vec());
+		let cached = self.cache().next.get(&location).cloned();
+		if let Some(next) = cached {
+			return Ok(next);
+		}
+		// Fetch a page of keys at once, as keys are typically iterated over.
+		let keys: Vec<Bytes> = match child {
+			Some(child) => self.request(
+				"childstate_getKeysPaged",
+				rpc_params![
+					Bytes(child.prefixed_storage_key().into_inner()),
+					Bytes(vec![]),
+					PAGE_SIZE,
+					Bytes(key.to_vec()),
+					self.at
+				],
+			)?,
+			None => self.request(
+				"state_getKeysPaged",
+				rpc_params![Bytes(vec![]), PAGE_SIZE, Bytes(key.to_vec()), self.at],
+			)?,
+		};
+		// Each key within the page is the next key of the key preceding it.
+		let mut cache = self.cache();
+		let mut previous = location;
+		for next in &keys {
+			let location = (previous.0.clone(), next.0.clone());
+			cache.record(Record::Next(previous, Some(next.0.clone())))?;
+			previous = location;
+		}
+		if keys.len() < PAGE_SIZE as usize {
+			cache.record(Record::Next(previous, None))?;
+		}
+		Ok(keys.into_iter().next().map(|k| k.0))
+	}
+}
+
+// An entry of the cache, as persisted on disk.
+#[derive(Decode, Encode)]
+enum Record {
+	// The value at a location.
+	Value(Location, Option<Vec<u8>>),
+	// The key following a location.
+	Next(Location, Option<Vec<u8>>),
+}
+
+// A cache of fetched state, which is appended to a file so that it can be reused across forks at
+// the same block.
+#[derive(Default)]
+struct Cache {
+	values: HashMap<Location, Option<Vec<u8>>>,
+	next: HashMap<Location, Option<Vec<u8>>>,
+	file: Option<File>,
+}
+
+impl Cache {
+	// Loads the cache from the file, creating it if it does not exist.
+	fn load(path: &Path) -> Result<Self, Error> {
+		let mut cache = Cache::default();
+		let data = match fs::read(path) {
+			Ok(data) => data,
+			Err(e) if e.kind() == ErrorKind::NotFound => Vec::new(),
+			Err(e) => return Err(e.into()),
+		};
+		let mut valid = 0;
+		while valid < data.len() {
+			let mut input = &data[valid..];
+			match Record::decode(&mut input) {
+				Ok(record) => cache.insert(record),
+				// A partially written record is discarded.
+				Err(_) => break,
+			}
+			valid = data.len() - input.len();
+		}
+		if let Some(parent) = path.parent() {
+			fs::create_dir_all(parent)?;
+		}
+		let file = OpenOptions::new().create(true).append(true).open(path)?;
+		file.set_len(valid as u64)?;
+		cache.file = Some(file);
+		Ok(cache)
+	}
+
+	fn insert(&mut self, record: Record) {
+		match record {
+			Record::Value(location, value) => self.values.insert(location, value),
+			Record::Next(location, next) => self.next.insert(location, next),
+		};
+	}
+
+	// Inserts the record, persisting it to disk.
+	fn record(&mut self, record: Record) -> Result<(), Error> {
+		if let Some(file) = &mut self.file {
+			file.write_all(&record.encode())?;
+		}
+		self.insert(record);
+		Ok(())
+	}
+}
+
+// A snapshot created by `try-runtime create-snapshot`, following its version.
+#[derive(Decode)]
+struct Snapshot {
+	state_version: StateVersion,
+	raw_storage: RawStorage,
+	storage_root: H256,
+	header: Header,
+}
+
+/// Loads the state and the header of the block contained within a snapshot file created by
+/// `try-runtime create-snapshot`.
+///
+/// # Arguments
+/// * `path` - The path to the snapshot file.
+pub(crate) fn load_snapshot(path: &Path) -> Result<(InMemoryBackend<BlakeTwo256>, Header), Error> {
+	let data = fs::read(path)
+		.map_err(|e| Error::ForkError(format!("could not read {}: {e}", path.display())))?;
+	let input = &mut &data[..];
+	let version = Compact::<u16>::decode(input)
+		.map_err(|e| Error::ForkError(format!("invalid snapshot: {e}")))?
+		.0;
+	if version != SNAPSHOT_VERSION {
+		return Err(Error::ForkError(format!(
+			"unsupported snapshot version {version}, expected {SNAPSHOT_VERSION}"
+		)));
+	}
+	let snapshot =
+		Snapshot::decode(input).map_err(|e| Error::ForkError(format!("invalid snapshot: {e}")))?;
+	let backend = TestExternalities::<BlakeTwo256>::from_raw_snapshot(
+		snapshot.raw_storage,
+		snapshot.storage_root,
+		snapshot.state_version,
+	)
+	.backend;
+	Ok((backend, snapshot.header))
+}
+
+/// The storage changes made on top of the state a chain was forked from.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Changes {
+	top: Values,
+	// Changes to child tries, keyed by the storage key of the child trie.
+	children: BTreeMap<Vec<u8>, Values>,
+}
+
+impl Changes {
+	/// Sets the value of a key, where `None` removes the value.
+	pub(crate) fn set(&mut self, key: Vec<u8>, value: Option<Vec<u8>>) {
+		self.top.insert(key, value);
+	}
+
+	/// Applies the changes made by the execution of the runtime.
+	pub(crate) fn apply(&mut self, changes: StorageChanges<BlakeTwo256>) {
+		self.top.extend(changes.main_storage_changes);
+		for (child, changes) in changes.child_storage_changes {
+			self.children.entry(child).or_default().extend(changes);
+		}
+	}
+
+	fn values(&self, child: Option<&ChildInfo>) -> Option<&Values> {
+		match child {
+			Some(child) => self.children.get(child.storage_key()),
+			None => Some(&self.top),
+		}
+	}
+
+	fn get(&self, child: Option<&ChildInfo>, key: &[u8]) -> Option<&Option<Vec<u8>>> {
+		self.values(child)?.get(key)
+	}
+
+	// Returns the first key following the provided key which has a value.
+	fn next_key(&self, child: Option<&ChildInfo>, key: &[u8]) -> Option<Vec<u8>> {
+		self.values(child)?
+			.range::<[u8], _>((Excluded(key), Unbounded))
+			.find(|(_, value)| value.is_some())
+			.map(|(key, _)| key.clone())
+	}
+}
+
+/// The state of a fork at a given block: the changes made locally on top of the source state.
+///
+/// As only the state accessed is fetched, the state root of a block built by a fork cannot be
+/// computed. The state roots returned are instead derived from the state root of the parent block
+/// and the changes made, so they identify the state of a block but cannot be used to verify it:
+/// the RPC methods which depend on the state root, such as those providing storage proofs, are
+/// refused.
+pub(crate) struct ForkBackend<'a> {
+	source: &'a Source,
+	changes: &'a Changes,
+	// The state root of the block, from which the state roots of descendants are derived.
+	root: H256,
+}
+
+impl<'a> ForkBackend<'a> {
+	pub(crate) fn new(source: &'a Source, changes: &'a Changes, root: H256) -> Self {
+		Self { source, changes, root }
+	}
+
+	fn value(&self, child: Option<&ChildInfo>, key: &[u8]) -> Result<Option<Vec<u8>>, String> {
+		match self.changes.get(child, key) {
+			Some(value) => Ok(value.clone()),
+			None => self.source.storage(child, key).map_err(|e| e.to_string()),
+		}
+	}
+
+	fn next_key(&self, child: Option<&ChildInfo>, key: &[u8]) -> Result<Option<Vec<u8>>, String> {
+		let local = self.changes.next_key(child, key);
+		// Skip source keys which have been removed locally.
+		let mut cursor = key.to_vec();
+		let source = loop {
+			match self.source.next_key(child, &cursor).map_err(|e| e.to_string())? {
+				Some(next) if matches!(self.changes.get(child, &next), Some(None)) => cursor = next,
+				next => break next,
+			}
+		};
+		Ok(match (local, source) {
+			(Some(local), Some(source)) => Some(local.min(source)),
+			(local, source) => local.or(source),
+		})
+	}
+
+	// Derives a state root from the root of the block and the delta, as the full state is not
+	// available to compute the actual state root. The result is not a trie root: it only
+	// distinguishes states, and no proof can be produced against it.
+	fn derive_root<'b>(
+		&self,
+		child: Option<&ChildInfo>,
+		delta: impl Iterator<Item = (&'b [u8], Option<&'b [u8]>)>,
+	) -> H256 {
+		let delta: Vec<_> = delta.collect();
+		H256(blake2_256(&(self.root, child.map(|c| c.storage_key()), delta).encode()))
+	}
+}
+
+impl fmt::Debug for ForkBackend<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("ForkBackend").field("root", &self.root).finish()
+	}
+}
+
+impl<'a> Backend<BlakeTwo256> for ForkBackend<'a> {
+	type Error = String;
+	type TrieBackendStorage = PrefixedMemoryDB<BlakeTwo256>;
+	type RawIter = RawIter<'a>;
+
+	fn storage(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+		self.value(None, key)
+	}
+
+	fn storage_hash(&self, key: &[u8]) -> Result<Option<H256>, Self::Error> {
+		Ok(self.value(None, key)?.map(|v| H256(blake2_256(&v))))
+	}
+
+	fn closest_merkle_value(&self, key: &[u8]) -> Result<Option<MerkleValue<H256>>, Self::Error> {
+		Ok(self.storage_hash(key)?.map(MerkleValue::Hash))
+	}
+
+	fn child_closest_merkle_value(
+		&self,
+		child_info: &ChildInfo,
+		key: &[u8],
+	) -> Result<Option<MerkleValue<H256>>, Self::Error> {
+		Ok(self.child_storage_hash(child_info, key)?.map(MerkleValue::Hash))
+	}
+
+	fn child_storage(
+		&self,
+		child_info: &ChildInfo,
+		key: &[u8],
+	) -> Result<Option<Vec<u8>>, Self::Error> {
+		self.value(Some(child_info), key)
+	}
+
+	fn child_storage_hash(
+		&self,
+		child_info: &ChildInfo,
+		key: &[u8],
+	) -> Result<Option<H256>, Self::Error> {
+		Ok(self.value(Some(child_info), key)?.map(|v| H256(blake2_256(&v))))
+	}
+
+	fn next_storage_key(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+		self.next_key(None, key)
+	}
+
+	fn next_child_storage_key(
+		&self,
+		child_info: &ChildInfo,
+		key: &[u8],
+	) -> Result<Option<Vec<u8>>, Self::Error> {
+		self.next_key(Some(child_info), key)
+	}
+
+	fn storage_root<'b>(
+		&self,
+		delta: impl Iterator<Item = (&'b [u8], Option<&'b [u8]>)>,
+		_state_version: StateVersion,
+	) -> (H256, BackendTransaction<BlakeTwo256>) {
+		(self.derive_root(None, delta), Default::default())
+	}
+
+	fn child_storage_root<'b>(
+		&self,
+		child_info: &ChildInfo,
+		delta: impl Iterator<Item = (&'b [u8], Option<&'b [u8]>)>,
+		_state_version: StateVersion,
+	) -> (H256, bool, BackendTransaction<BlakeTwo256>) {
+		(self.derive_root(Some(child_info), delta), false, Default::default())
+	}
+
+	fn raw_iter(&self, args: IterArgs) -> Result<Self::RawIter, Self::Error> {
+		let inclusive = args.start_at.is_none() || !args.start_at_exclusive;
+		Ok(RawIter {
+			cursor: args.start_at.or(args.prefix).unwrap_or_default().to_vec(),
+			prefix: args.prefix.map(|p| p.to_vec()),
+			child: args.child_info,
+			inclusive,
+			complete: false,
+			_backend: PhantomData,
+		})
+	}
+
+	fn register_overlay_stats(&self, _stats: &StateMachineStats) {}
+
+	fn usage_info(&self) -> UsageInfo {
+		UsageInfo::empty()
+	}
+}
+
+/// An iterator over the keys of a [`ForkBackend`].
+pub(crate) struct RawIter<'a> {
+	// The last key returned, or the key from which iteration starts.
+	cursor: Vec<u8>,
+	prefix: Option<Vec<u8>>,
+	child: Option<ChildInfo>,
+	// Whether the cursor itself is to be returned, if it exists.
+	inclusive: bool,
+	complete: bool,
+	_backend: PhantomData<ForkBackend<'a>>,
+}
+
+impl<'a> StorageIterator<BlakeTwo256> for RawIter<'a> {
+	type Backend = ForkBackend<'a>;
+	type Error = String;
+
+	fn next_key(&mut self, backend: &Self::Backend) -> Option<Result<Vec<u8>, Self::Error>> {
+		if self.complete {
+			return None;
+		}
+		let child = self.child.as_ref();
+		let next = if self.inclusive && matches!(backend.value(child, &self.cursor), Ok(Some(_))) {
+			Ok(Some(self.cursor.clone()))
+		} else {
+			backend.next_key(child, &self.cursor)
+		};
+		self.inclusive = false;
+		match next {
+			Ok(Some(key)) if self.prefix.as_ref().is_none_or(|p| key.starts_with(p)) => {
+				self.cursor = key.clone();
+				Some(Ok(key))
+			},
+			Ok(_) => {
+				self.complete = true;
+				None
+			},
+			Err(e) => Some(Err(e)),
+		}
+	}
+
+	fn next_pair(
+		&mut self,
+		backend: &Self::Backend,
+	) -> Option<Result<(Vec<u8>, Vec<u8>), Self::Error>> {
+		let key = match self.next_key(backend)? {
+			Ok(key) => key,
+			Err(e) => return Some(Err(e)),
+		};
+		match backend.value(self.child.as_ref(), &key) {
+			Ok(value) => Some(Ok((key, value.unwrap_or_default()))),
+			Err(e) => Some(Err(e)),
+		}
+	}
+
+	fn was_complete(&self) -> bool {
+		self.complete
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn cache_load_works() -> Result<(), Error> {
+		let temp_dir = tempfile::tempdir()?;
+		let path = temp_dir.path().join("cache").join("0x01.cache");
+		let location: Location = (None, vec![1]);
+		let mut cache = Cache::load(&path)?;
+		assert!(path.exists());
+		cache.record(Record::Value(location.clone(), Some(vec![2])))?;
+		cache.record(Record::Next(location.clone(), None))?;
+		drop(cache);
+
+		// A partially written record is discarded.
+		let mut file = OpenOptions::new().append(true).open(&path)?;
+		file.write_all(&[0])?;
+		let cache = Cache::load(&path)?;
+		assert_eq!(cache.values.get(&location), Some(&Some(vec![2])));
+		assert_eq!(cache.next.get(&location), Some(&None));
+		assert_eq!(
+			fs::read(&path)?.len(),
+			Record::Value(location.clone(), Some(vec![2])).encoded_size() +
+				Record::Next(location, None).encoded_size()
+		);
+		Ok(())
+	}
+
+	#[test]
+	fn cache_load_fails_when_unreadable() -> Result<(), Error> {
+		let temp_dir = tempfile::tempdir()?;
+		assert!(matches!(Cache::load(temp_dir.path()), Err(Error::IO(..))));
+		Ok(())
+	}
+}
@@ -11,6 +11,8 @@ mod call;
 /// Deployment providers' metadata and utility functions.
 mod deployer_providers;
 mod errors;
+/// Provides functionality for forking a live chain and serving it locally.
+pub mod fork;
 mod generator;
 mod new_chain;
 mod new_pallet;
@@ -38,16 +40,30 @@ pub use build::{
 	runtime_binary_path, ChainSpec,
 };
 pub use call::{
-	construct_extrinsic, construct_proxy_extrinsic, construct_sudo_extrinsic, decode_call_data,
+	construct_batch_extrinsic, construct_extrinsic, construct_proxy_extrinsic,
+	construct_sudo_extrinsic, decode_call_data, decode_extrinsic,
+	dry_run::{dry_run_extrinsic, DryRunOutcome, DryRunResult},
 	encode_call_data,
+	events::{BlockSubscription, ChainEvent, EventFilter, EventSubscription},
 	metadata::{
 		action::{supported_actions, Action},
-		find_dispatchable_by_name, find_pallet_by_name,
+		find_constant_by_name, find_dispatchable_by_name, find_pallet_by_name,
+		find_storage_by_name,
 		params::{field_to_param, Param},
-		parse_chain_metadata, Function, Pallet,
+		parse_chain_metadata, Constant, Function, Pallet, Storage,
 	},
-	parse_and_format_events, set_up_client, sign_and_submit_extrinsic, submit_signed_extrinsic,
-	CallData,
+	metadata_hash::{
+		create_signed_extrinsic, generate_metadata_hash, metadata_hash, supports_metadata_hash,
+	},
+	multisig::{
+		call_hash, construct_multisig_extrinsic, estimate_call_weight, query_pending_multisig,
+		Multisig, MultisigAction, PendingMultisig, Timepoint, Weight,
+	},
+	offline::{assemble_signed_extrinsic, create_signing_payload, Mortality, SigningPayload},
+	parse_and_format_events, set_up_client, sign_and_submit_extrinsic,
+	storage::query_storage,
+	submit_extrinsic_with_events, submit_signed_extrinsic, BatchMode, CallData, DecodedArg,
+	DecodedCall, DecodedExtrinsic, DecodedValue, SubmittedExtrinsic,
 };
 pub use deployer_providers::{DeploymentProvider, SupportedChains};
 pub use errors::Error;
@@ -63,6 +79,7 @@ pub use try_runtime::{
 pub use subxt::{
 	blocks::ExtrinsicEvents,
 	tx::{DynamicPayload, Payload},
+	utils::H256,
 	OnlineClient, SubstrateConfig,
 };
 pub use templates::{ChainTemplate, Config, Provider};
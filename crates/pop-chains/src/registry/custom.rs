@@ -0,0 +1,352 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use super::{traits::Requires, *};
+use crate::{
+	traits::{Args, Binary},
+	Error,
+};
+use pop_common::{
+	polkadot_sdk::sort_by_latest_semantic_version,
+	sourcing::{traits::Source as SourceT, ArchiveFileSpec, GitHub::ReleaseArchive, Source},
+	target,
+};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::{
+	fs,
+	path::{Path, PathBuf},
+};
+
+/// The name of the file used to define additional rollups.
+pub const ROLLUPS_FILE: &str = "rollups.toml";
+
+/// A rollup defined by the user within a configuration file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Custom(Rollup, Definition);
+
+// The definition of a rollup within a configuration file.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+struct Definition {
+	// The name of the rollup.
+	name: String,
+	// The rollup identifier.
+	id: Id,
+	// The identifier of the chain, as used by the chain specification.
+	chain: String,
+	// The name of the binary, which defaults to the path of a binary sourced locally.
+	binary: Option<String>,
+	// The source of the binary.
+	source: SourceDefinition,
+	// The relay chains the rollup is available for, defaulting to all.
+	#[serde(default)]
+	relays: Vec<String>,
+	// The default arguments to be used when launching a node.
+	#[serde(default)]
+	args: Vec<String>,
+	// The port to be used.
+	port: Option<Port>,
+	// Any overrides to genesis state.
+	genesis_overrides: Option<Map<String, Value>>,
+}
+
+// The source of a binary of a rollup defined within a configuration file.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase", deny_unknown_fields)]
+enum SourceDefinition {
+	// An archive attached to a GitHub release.
+	GitHub {
+		// The owner of the repository.
+		owner: String,
+		// The name of the repository.
+		repository: String,
+		// A pattern used to determine applicable releases - e.g. `node-{version}`.
+		tag_pattern: Option<String>,
+		// Whether pre-releases are to be used.
+		#[serde(default)]
+		prerelease: bool,
+		// The version to use if the latest version cannot be resolved.
+		fallback: String,
+		// The name of the archive, where `{binary}` and `{target}` are replaced accordingly.
+		archive: Option<String>,
+	},
+	// A binary available for download.
+	Url(String),
+	// A local binary.
+	Path(PathBuf),
+}
+
+impl Custom {
+	/// Whether the rollup is available for the provided relay chain.
+	///
+	/// # Arguments
+	/// * `relay` - The relay chain.
+	pub fn supports(&self, relay: &Relay) -> bool {
+		self.1.relays.is_empty() ||
+			self.1.relays.iter().any(|r| r.eq_ignore_ascii_case(relay.name()))
+	}
+}
+
+/// Loads the rollups defined within a configuration file.
+///
+/// # Arguments
+/// * `path` - The path of the configuration file.
+pub(crate) fn load(path: &Path) -> Result<Vec<Custom>, Error> {
+	#[derive(Deserialize)]
+	#[serde(deny_unknown_fields)]
+	struct File {
+		#[serde(default)]
+		rollups: Vec<Definition>,
+	}
+
+	let invalid = |reason: String| Error::Config(format!("{}: {reason}", path.display()));
+	let file: File =
+		toml_edit::de::from_str(&fs::read_to_string(path)?).map_err(|e| invalid(e.to_string()))?;
+	let dir = path.parent().unwrap_or(Path::new(""));
+	file.rollups
+		.into_iter()
+		.map(|mut definition| {
+			// Resolve local binaries relative to the configuration file.
+			if let SourceDefinition::Path(binary) = &definition.source {
+				let binary = std::path::absolute(dir.join(binary))?;
+				definition.binary = Some(binary.to_string_lossy().to_string());
+				definition.source = SourceDefinition::Path(binary);
+			}
+			if definition.binary.is_none() {
+				return Err(invalid(format!(
+					"the binary of `{}` is not specified",
+					definition.name
+				)));
+			}
+			let mut rollup = Rollup::new(&definition.name, definition.id, &definition.chain);
+			rollup.port = definition.port;
+			Ok(Custom(rollup, definition))
+		})
+		.collect()
+}
+
+impl SourceT for Custom {
+	type Error = Error;
+	/// Defines the source of a binary.
+	fn source(&self) -> Result<Source, Error> {
+		let binary = self.binary();
+		match &self.1.source {
+			SourceDefinition::GitHub {
+				owner,
+				repository,
+				tag_pattern,
+				prerelease,
+				fallback,
+				archive,
+			} => Ok(Source::GitHub(ReleaseArchive {
+				owner: owner.clone(),
+				repository: repository.clone(),
+				tag: None,
+				tag_pattern: tag_pattern.as_deref().map(Into::into),
+				prerelease: *prerelease,
+				version_comparator: sort_by_latest_semantic_version,
+				fallback: fallback.clone(),
+				archive: archive
+					.as_deref()
+					.unwrap_or("{binary}-{target}.tar.gz")
+					.replace("{binary}", binary)
+					.replace("{target}", target()?),
+				contents: vec![ArchiveFileSpec::new(binary.into(), None, true)],
+				latest: None,
+			})),
+			SourceDefinition::Url(url) => Ok(Source::Url { url: url.clone(), name: binary.into() }),
+			SourceDefinition::Path(_) =>
+				Err(Error::Config(format!("`{binary}` is a local binary and cannot be sourced"))),
+		}
+	}
+}
+
+impl Binary for Custom {
+	fn binary(&self) -> &str {
+		self.1.binary.as_deref().expect("expected binary to be resolved when loaded")
+	}
+}
+
+impl Requires for Custom {}
+
+impl Args for Custom {
+	fn args(&self) -> Option<Vec<&str>> {
+		(!self.1.args.is_empty()).then(|| self.1.args.iter().map(String::as_str).collect())
+	}
+}
+
+impl GenesisOverrides for Custom {
+	fn genesis_overrides(&self) -> Option<Override> {
+		let overrides = self.1.genesis_overrides.clone()?;
+		Some(Box::new(move |genesis_overrides: &mut Map<String, Value>| {
+			merge(genesis_overrides, &overrides)
+		}))
+	}
+}
+
+impl_rollup!(Custom);
+
+// Merges the source into the target, recursively merging any objects.
+fn merge(target: &mut Map<String, Value>, source: &Map<String, Value>) {
+	for (key, value) in source {
+		match (target.get_mut(key), value) {
+			(Some(Value::Object(target)), Value::Object(source)) => merge(target, source),
+			_ => {
+				target.insert(key.clone(), value.clone());
+			},
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::json;
+
+	const ROLLUPS: &str = r#"
+[[rollups]]
+name = "my-rollup"
+id = 2000
+chain = "my-rollup-local"
+binary = "my-node"
+relays = ["paseo"]
+args = ["-lruntime=debug"]
+port = 9950
+source = { github = { owner = "org", repository = "my-node", tag_pattern = "node-{version}", fallback = "v1.0.0" } }
+
+[rollups.genesis_overrides]
+balances = { balances = [["5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY", 1000]] }
+
+[[rollups]]
+name = "downloaded"
+id = 2001
+chain = "downloaded-local"
+binary = "downloaded-node"
+source = { url = "https://example.com/downloaded-node" }
+
+[[rollups]]
+name = "local"
+id = 2002
+chain = "local-dev"
+source = { path = "./target/release/local-node" }
+"#;
+
+	fn load_rollups() -> anyhow::Result<(tempfile::TempDir, Vec<Custom>)> {
+		let temp_dir = tempfile::tempdir()?;
+		let path = temp_dir.path().join(ROLLUPS_FILE);
+		fs::write(&path, ROLLUPS)?;
+		let rollups = load(&path)?;
+		Ok((temp_dir, rollups))
+	}
+
+	#[test]
+	fn load_works() -> anyhow::Result<()> {
+		let (temp_dir, rollups) = load_rollups()?;
+		assert_eq!(rollups.len(), 3);
+
+		let rollup = &rollups[0];
+		assert_eq!(rollup.name(), "my-rollup");
+		assert_eq!(rollup.id(), 2000);
+		assert_eq!(rollup.chain(), "my-rollup-local");
+		assert_eq!(rollup.binary(), "my-node");
+		assert_eq!(rollup.port(), Some(&9950));
+		assert_eq!(rollup.args(), Some(vec!["-lruntime=debug"]));
+		assert!(rollup.supports(&Relay::Paseo));
+		assert!(!rollup.supports(&Relay::Polkadot));
+		assert!(rollup.requires().is_none());
+
+		let rollup = &rollups[1];
+		assert_eq!(rollup.binary(), "downloaded-node");
+		assert_eq!(rollup.port(), None);
+		assert_eq!(rollup.args(), None);
+		assert!(rollup.genesis_overrides().is_none());
+		assert!(Relay::VARIANTS.iter().all(|relay| rollup.supports(relay)));
+
+		let rollup = &rollups[2];
+		let binary = temp_dir.path().join("target/release/local-node");
+		assert_eq!(rollup.binary(), binary.to_string_lossy());
+		assert!(rollup.binary().starts_with('/'));
+		Ok(())
+	}
+
+	#[test]
+	fn load_fails_with_invalid_definitions() -> anyhow::Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let path = temp_dir.path().join(ROLLUPS_FILE);
+		for definition in [
+			// Missing binary name
+			r#"[[rollups]]
+name = "my-rollup"
+id = 2000
+chain = "my-rollup-local"
+source = { url = "https://example.com/my-node" }"#,
+			// Unknown source
+			r#"[[rollups]]
+name = "my-rollup"
+id = 2000
+chain = "my-rollup-local"
+binary = "my-node"
+source = { git = "https://github.com/org/my-node" }"#,
+			// Unknown field
+			r#"[[rollups]]
+name = "my-rollup"
+id = 2000
+chain = "my-rollup-local"
+binary = "my-node"
+source = { url = "https://example.com/my-node" }
+command = "my-node""#,
+		] {
+			fs::write(&path, definition)?;
+			assert!(matches!(load(&path), Err(Error::Config(..))));
+		}
+		Ok(())
+	}
+
+	#[test]
+	fn source_works() -> anyhow::Result<()> {
+		let (_temp_dir, rollups) = load_rollups()?;
+		assert!(matches!(
+			rollups[0].source()?,
+			Source::GitHub(ReleaseArchive { owner, repository, tag, tag_pattern, prerelease, fallback, archive, contents, latest, .. })
+				if owner == "org" &&
+					repository == "my-node" &&
+					tag.is_none() &&
+					tag_pattern == Some("node-{version}".into()) &&
+					!prerelease &&
+					fallback == "v1.0.0" &&
+					archive == format!("my-node-{}.tar.gz", target()?) &&
+					contents == vec![ArchiveFileSpec::new("my-node".into(), None, true)] &&
+					latest.is_none()
+		));
+		assert_eq!(
+			rollups[1].source()?,
+			Source::Url {
+				url: "https://example.com/downloaded-node".into(),
+				name: "downloaded-node".into()
+			}
+		);
+		assert!(matches!(rollups[2].source(), Err(Error::Config(..))));
+		Ok(())
+	}
+
+	#[test]
+	fn genesis_overrides_works() -> anyhow::Result<()> {
+		let (_temp_dir, rollups) = load_rollups()?;
+		let mut r#override = rollups[0].genesis_overrides().unwrap();
+		let mut overrides = Map::new();
+		overrides.insert("balances".to_string(), json!({ "devAccounts": [1, 1000, null] }));
+		overrides.insert("sudo".to_string(), json!({ "key": null }));
+		r#override(&mut overrides);
+		assert_eq!(
+			Value::Object(overrides),
+			json!({
+				"balances": {
+					"balances": [["5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY", 1000]],
+					"devAccounts": [1, 1000, null]
+				},
+				"sudo": { "key": null }
+			})
+		);
+		Ok(())
+	}
+}
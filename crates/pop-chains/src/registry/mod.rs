@@ -6,13 +6,16 @@ use crate::{
 	},
 	up::Relay,
 };
+pub use custom::{Custom, ROLLUPS_FILE};
 pub use pop::*;
 use pop_common::sourcing::traits::Source;
 use std::{
 	any::{Any, TypeId},
 	collections::HashMap,
+	path::PathBuf,
 	sync::OnceLock,
 };
+use strum::VariantArray;
 pub use system::*;
 
 // Macro for reducing boilerplate code.
@@ -36,12 +39,17 @@ macro_rules! impl_rollup {
 	};
 }
 
+mod custom;
 mod pop;
 mod system;
 
 pub(crate) type RollupTypeId = TypeId;
 type Registry = HashMap<Relay, Vec<Box<dyn traits::Rollup>>>;
 
+static REGISTRY: OnceLock<Registry> = OnceLock::new();
+// Rollups defined by the user, registered prior to the registry being initialized.
+static CUSTOM: OnceLock<Vec<Custom>> = OnceLock::new();
+
 const REGISTRAR: fn(Registry) -> Registry = |mut registry| {
 	use Relay::*;
 	registry.insert(
@@ -100,10 +108,46 @@ const REGISTRAR: fn(Registry) -> Registry = |mut registry| {
 /// # Arguments
 /// * `relay` - The relay chain.
 pub fn rollups(relay: &Relay) -> &'static [Box<dyn traits::Rollup>] {
-	static REGISTRY: OnceLock<HashMap<Relay, Vec<Box<dyn traits::Rollup>>>> = OnceLock::new();
 	static EMPTY: Vec<Box<dyn traits::Rollup>> = Vec::new();
 
-	REGISTRY.get_or_init(|| REGISTRAR(HashMap::new())).get(relay).unwrap_or(&EMPTY)
+	REGISTRY
+		.get_or_init(|| extend(REGISTRAR(HashMap::new()), CUSTOM.get().map_or(&[], |c| c)))
+		.get(relay)
+		.unwrap_or(&EMPTY)
+}
+
+/// Registers the rollups defined within any of the provided configuration files, in addition to
+/// the built-in rollups. A rollup replaces any previously registered rollup with the same name,
+/// with files later in the list therefore taking precedence.
+///
+/// Must be called before the registry is first accessed.
+///
+/// # Arguments
+/// * `paths` - The paths of the configuration files, which are ignored if they do not exist.
+pub fn register(paths: &[PathBuf]) -> Result<(), crate::Error> {
+	let mut rollups: Vec<Custom> = Vec::new();
+	for path in paths.iter().filter(|p| p.exists()) {
+		for rollup in custom::load(path)? {
+			rollups.retain(|r| r.name() != rollup.name());
+			rollups.push(rollup);
+		}
+	}
+	if REGISTRY.get().is_some() || CUSTOM.set(rollups).is_err() {
+		return Err(crate::Error::Config("the rollup registry is already initialized".into()));
+	}
+	Ok(())
+}
+
+// Extends the registry with user-defined rollups, replacing any rollups with the same name.
+fn extend(mut registry: Registry, rollups: &[Custom]) -> Registry {
+	for relay in Relay::VARIANTS {
+		for rollup in rollups.iter().filter(|r| r.supports(relay)) {
+			let registered = registry.entry(relay.clone()).or_default();
+			registered.retain(|r| r.name() != rollup.name());
+			registered.push(rollup.clone().into());
+		}
+	}
+	registry
 }
 
 // A base type, used by rollup implementations to reduce boilerplate code.
@@ -329,6 +373,44 @@ mod tests {
 		assert!(contains::<People>(registry, 1_005));
 	}
 
+	#[test]
+	fn extend_works() -> anyhow::Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let path = temp_dir.path().join(ROLLUPS_FILE);
+		std::fs::write(
+			&path,
+			r#"
+[[rollups]]
+name = "my-rollup"
+id = 2000
+chain = "my-rollup-local"
+binary = "my-node"
+relays = ["paseo"]
+source = { url = "https://example.com/my-node" }
+
+[[rollups]]
+name = "pop"
+id = 4242
+chain = "pop-local"
+binary = "my-pop-node"
+source = { url = "https://example.com/my-pop-node" }
+"#,
+		)?;
+		let registry = extend(REGISTRAR(HashMap::new()), &custom::load(&path)?);
+
+		let paseo = registry.get(&Paseo).unwrap();
+		assert!(contains::<Custom>(paseo, 2_000));
+		assert!(contains::<Custom>(paseo, 4_242));
+		assert!(!contains::<Pop>(paseo, 4_001));
+		assert!(contains::<AssetHub>(paseo, 1_000));
+		let polkadot = registry.get(&Polkadot).unwrap();
+		assert!(!contains::<Custom>(polkadot, 2_000));
+		assert!(contains::<Custom>(polkadot, 4_242));
+		assert!(!contains::<Pop>(polkadot, 3_395));
+		assert!(contains::<Custom>(registry.get(&Kusama).unwrap(), 4_242));
+		Ok(())
+	}
+
 	#[test]
 	fn type_checks() {
 		use std::any::{Any, TypeId};
@@ -51,7 +51,7 @@ impl SourceT for Pop {
 }
 
 impl Binary for Pop {
-	fn binary(&self) -> &'static str {
+	fn binary(&self) -> &str {
 		"pop-node"
 	}
 }
@@ -38,7 +38,7 @@ impl SourceT for System {
 }
 
 impl Binary for System {
-	fn binary(&self) -> &'static str {
+	fn binary(&self) -> &str {
 		"polkadot-parachain"
 	}
 }
@@ -63,7 +63,7 @@ macro_rules! impl_system_rollup {
 		}
 
 		impl Binary for $name {
-			fn binary(&self) -> &'static str {
+			fn binary(&self) -> &str {
 				"polkadot-parachain"
 			}
 		}
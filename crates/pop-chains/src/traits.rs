@@ -18,7 +18,7 @@ pub trait Args {
 /// The binary used to launch a node.
 pub trait Binary {
 	/// The name of the binary.
-	fn binary(&self) -> &'static str;
+	fn binary(&self) -> &str;
 }
 
 /// A specification of a chain, providing the genesis configurations, boot nodes, and other
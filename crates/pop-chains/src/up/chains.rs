@@ -59,6 +59,10 @@ pub(super) async fn from(
 	chain: Option<&str>,
 	cache: &Path,
 ) -> Result<Option<super::Chain>, Error> {
+	// Local binaries, such as those of user-defined rollups, are not sourced.
+	if ["./", "../", "/"].iter().any(|p| command.starts_with(p)) {
+		return Ok(None);
+	}
 	if let Some(para) = registry::rollups(relay).iter().find(|p| p.binary() == command) {
 		let name = para.binary().to_string();
 		let source =
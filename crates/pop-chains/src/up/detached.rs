@@ -0,0 +1,457 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use crate::errors::Error;
+use duct::cmd;
+use serde::{Deserialize, Serialize};
+use std::{
+	fmt::{Display, Formatter},
+	fs,
+	path::{Path, PathBuf},
+	str::FromStr,
+	time::{Duration, Instant},
+};
+use zombienet_sdk::{LocalFileSystem, Network, NetworkNode};
+
+/// The directory within the cache where the state of detached networks is recorded.
+const NETWORKS_DIR: &str = ".networks";
+/// The environment variable identifying the network supervised by a process running in the
+/// background.
+pub const DETACHED_ENV: &str = "POP_NETWORK_DETACHED";
+
+/// A local network running in the background, as recorded within its state file.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct DetachedNetwork {
+	/// The name of the network.
+	pub name: String,
+	/// The identifier of the process supervising the network.
+	pub pid: u32,
+	/// The directory containing the state of the network.
+	pub base_dir: PathBuf,
+	/// The relay chain of the network.
+	pub relay_chain: DetachedChain,
+	/// The rollups of the network, sorted by identifier.
+	pub rollups: Vec<DetachedChain>,
+}
+
+/// A chain of a network running in the background.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct DetachedChain {
+	/// The name of the chain.
+	pub chain: String,
+	/// The identifier of the rollup, if applicable.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub id: Option<u32>,
+	/// The nodes of the chain, sorted by name.
+	pub nodes: Vec<DetachedNode>,
+}
+
+/// A node of a network running in the background.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct DetachedNode {
+	/// The name of the node.
+	pub name: String,
+	/// The websocket endpoint of the node.
+	pub endpoint: String,
+	/// The identifier of the node process, if it could be determined.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub pid: Option<u32>,
+	/// The log file of the node.
+	pub log: PathBuf,
+}
+
+/// A chain of a local network, selected by its role.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum NetworkChain {
+	/// The relay chain.
+	Relay,
+	/// A rollup, by its identifier.
+	Rollup(u32),
+}
+
+impl FromStr for NetworkChain {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"relay" => Ok(Self::Relay),
+			id => id.parse().map(Self::Rollup).map_err(|_| {
+				Error::ParamParsingError(format!(
+					"invalid chain {id}, expected `relay` or the identifier of a rollup"
+				))
+			}),
+		}
+	}
+}
+
+impl Display for NetworkChain {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Relay => write!(f, "the relay chain"),
+			Self::Rollup(id) => write!(f, "rollup {id}"),
+		}
+	}
+}
+
+impl DetachedNetwork {
+	/// Records the state of a launched network.
+	///
+	/// # Arguments
+	/// * `name` - The name of the network.
+	/// * `pid` - The identifier of the process supervising the network.
+	/// * `network` - The launched network.
+	pub fn new(name: &str, pid: u32, network: &Network<LocalFileSystem>) -> Result<Self, Error> {
+		let base_dir = PathBuf::from(network.base_dir().ok_or(Error::PathError)?);
+		let nodes = |nodes: Vec<&NetworkNode>| {
+			let mut nodes: Vec<_> = nodes
+				.into_iter()
+				.map(|node| DetachedNode {
+					name: node.name().to_string(),
+					endpoint: node.ws_uri().to_string(),
+					pid: node_pid(&base_dir, node.name()),
+					log: base_dir.join(node.name()).join(format!("{}.log", node.name())),
+				})
+				.collect();
+			nodes.sort_by(|a, b| a.name.cmp(&b.name));
+			nodes
+		};
+		let relay_chain = DetachedChain {
+			chain: network.relaychain().chain().to_string(),
+			id: None,
+			nodes: nodes(network.relaychain().nodes()),
+		};
+		let mut rollups: Vec<_> = network
+			.parachains()
+			.into_iter()
+			.map(|rollup| DetachedChain {
+				chain: rollup.chain_id().unwrap_or_default().to_string(),
+				id: Some(rollup.para_id()),
+				nodes: nodes(rollup.collators()),
+			})
+			.collect();
+		rollups.sort_by_key(|r| r.id);
+		Ok(Self { name: name.to_string(), pid, base_dir, relay_chain, rollups })
+	}
+
+	/// The directory where the state of detached networks is recorded.
+	///
+	/// # Arguments
+	/// * `cache` - The location used for caching binaries.
+	pub fn dir(cache: &Path) -> PathBuf {
+		cache.join(NETWORKS_DIR)
+	}
+
+	/// The log file of the process supervising a network.
+	///
+	/// # Arguments
+	/// * `dir` - The directory where the state of detached networks is recorded.
+	/// * `name` - The name of the network.
+	pub fn log(dir: &Path, name: &str) -> Result<PathBuf, Error> {
+		check_name(name)?;
+		Ok(dir.join(format!("{name}.log")))
+	}
+
+	/// Loads the state of a network, if recorded.
+	///
+	/// # Arguments
+	/// * `dir` - The directory where the state of detached networks is recorded.
+	/// * `name` - The name of the network.
+	pub fn load(dir: &Path, name: &str) -> Result<Option<Self>, Error> {
+		let path = state_file(dir, name)?;
+		if !path.exists() {
+			return Ok(None);
+		}
+		Ok(Some(serde_json::from_str(&fs::read_to_string(path)?)?))
+	}
+
+	/// Loads the state of all recorded networks, sorted by name.
+	///
+	/// # Arguments
+	/// * `dir` - The directory where the state of detached networks is recorded.
+	pub fn list(dir: &Path) -> Result<Vec<Self>, Error> {
+		if !dir.exists() {
+			return Ok(Vec::new());
+		}
+		let mut networks = Vec::new();
+		for entry in fs::read_dir(dir)? {
+			let path = entry?.path();
+			if path.extension().is_some_and(|e| e == "json") {
+				networks.push(serde_json::from_str::<Self>(&fs::read_to_string(path)?)?);
+			}
+		}
+		networks.sort_by(|a, b| a.name.cmp(&b.name));
+		Ok(networks)
+	}
+
+	/// Records the state of the network.
+	///
+	/// # Arguments
+	/// * `dir` - The directory where the state of detached networks is recorded.
+	pub fn save(&self, dir: &Path) -> Result<(), Error> {
+		fs::create_dir_all(dir)?;
+		fs::write(state_file(dir, &self.name)?, serde_json::to_string_pretty(self)?)?;
+		Ok(())
+	}
+
+	/// Removes the recorded state of the network, along with the log of its supervising process.
+	///
+	/// # Arguments
+	/// * `dir` - The directory where the state of detached networks is recorded.
+	pub fn remove(&self, dir: &Path) -> Result<(), Error> {
+		for path in [state_file(dir, &self.name)?, Self::log(dir, &self.name)?] {
+			if path.exists() {
+				fs::remove_file(path)?;
+			}
+		}
+		Ok(())
+	}
+
+	/// Whether the process supervising the network is still running. A process which has since
+	/// reused its identifier, such as after a reboot, is not considered to be the supervisor.
+	pub fn is_running(&self) -> bool {
+		is_running(self.pid) && is_supervisor(self.pid, &self.name)
+	}
+
+	/// Stops the network, terminating any remaining node processes should the supervising process
+	/// not exit in time, and then removes its recorded state.
+	///
+	/// # Arguments
+	/// * `dir` - The directory where the state of detached networks is recorded.
+	/// * `timeout` - How long to wait for the supervising process to exit.
+	/// * `remove_base_dir` - Whether the directory containing the state of the network should also
+	///   be removed.
+	pub async fn stop(
+		&self,
+		dir: &Path,
+		timeout: Duration,
+		remove_base_dir: bool,
+	) -> Result<(), Error> {
+		if self.is_running() {
+			// Interrupt the supervising process, which tears down the network.
+			signal("INT", self.pid);
+			let start = Instant::now();
+			while self.is_running() && start.elapsed() < timeout {
+				tokio::time::sleep(Duration::from_millis(250)).await;
+			}
+			if self.is_running() {
+				signal("KILL", self.pid);
+			}
+		}
+		// Ensure no node outlives its supervising process.
+		for node in self.relay_chain.nodes.iter().chain(self.rollups.iter().flat_map(|r| &r.nodes))
+		{
+			if let Some(pid) = node_pid(&self.base_dir, &node.name) {
+				signal("KILL", pid);
+			}
+		}
+		if remove_base_dir && self.base_dir.exists() {
+			fs::remove_dir_all(&self.base_dir)?;
+		}
+		self.remove(dir)
+	}
+}
+
+/// Ensures that a name, such as that of a network or snapshot, is a plain file stem, so that the
+/// files named after it remain within their directory.
+///
+/// # Arguments
+/// * `name` - The name.
+pub fn check_name(name: &str) -> Result<(), Error> {
+	if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+		return Err(Error::InvalidName(name.to_string()));
+	}
+	Ok(())
+}
+
+// The path of the state file of a network.
+fn state_file(dir: &Path, name: &str) -> Result<PathBuf, Error> {
+	check_name(name)?;
+	Ok(dir.join(format!("{name}.json")))
+}
+
+// Whether a process is running.
+fn is_running(pid: u32) -> bool {
+	if !is_valid(pid) {
+		return false;
+	}
+	cmd("kill", ["-0", &pid.to_string()])
+		.stdout_null()
+		.stderr_null()
+		.unchecked()
+		.run()
+		.is_ok_and(|output| output.status.success())
+}
+
+// Whether a process supervises the named network, as identified by its environment.
+fn is_supervisor(pid: u32, name: &str) -> bool {
+	let expected = format!("{DETACHED_ENV}={name}");
+	if let Ok(environ) = fs::read(format!("/proc/{pid}/environ")) {
+		return environ.split(|b| *b == 0).any(|variable| variable == expected.as_bytes());
+	}
+	// Without procfs, such as on macOS, the environment is listed after the command by `ps`.
+	cmd("ps", ["eww", "-o", "command=", "-p", &pid.to_string()])
+		.stderr_null()
+		.unchecked()
+		.read()
+		.is_ok_and(|output| output.split_whitespace().any(|arg| arg == expected))
+}
+
+// Whether the identifier can denote a single process. Identifiers beyond the range of a process
+// identifier would be interpreted by `kill` as negative, denoting process groups or all processes.
+fn is_valid(pid: u32) -> bool {
+	pid != 0 && i32::try_from(pid).is_ok()
+}
+
+// Sends a signal to a process, ignoring any failure.
+pub(super) fn signal(signal: &str, pid: u32) {
+	if !is_valid(pid) {
+		return;
+	}
+	let _ = cmd("kill", [format!("-{signal}"), pid.to_string()])
+		.stdout_null()
+		.stderr_null()
+		.unchecked()
+		.run();
+}
+
+// Resolves the process of a node, using the data directory passed to the node on launch.
+pub(super) fn node_pid(base_dir: &Path, name: &str) -> Option<u32> {
+	let pattern = format!("--base-path {}/{name}/data", base_dir.display());
+	cmd("pgrep", ["-f", "--", &pattern])
+		.stderr_null()
+		.unchecked()
+		.read()
+		.ok()
+		.and_then(|output| output.lines().next().and_then(|pid| pid.trim().parse().ok()))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::Result;
+
+	#[test]
+	fn network_chain_works() {
+		assert_eq!("relay".parse::<NetworkChain>().unwrap(), NetworkChain::Relay);
+		assert_eq!("1000".parse::<NetworkChain>().unwrap(), NetworkChain::Rollup(1000));
+		assert!(matches!(
+			"asset-hub".parse::<NetworkChain>(),
+			Err(Error::ParamParsingError(message)) if message == "invalid chain asset-hub, expected `relay` or the identifier of a rollup"
+		));
+		assert_eq!(NetworkChain::Relay.to_string(), "the relay chain");
+		assert_eq!(NetworkChain::Rollup(1000).to_string(), "rollup 1000");
+	}
+
+	fn network(name: &str, pid: u32, base_dir: &Path) -> DetachedNetwork {
+		let node = |name: &str, port: u16| DetachedNode {
+			name: name.to_string(),
+			endpoint: format!("ws://127.0.0.1:{port}"),
+			pid: None,
+			log: base_dir.join(name).join(format!("{name}.log")),
+		};
+		DetachedNetwork {
+			name: name.to_string(),
+			pid,
+			base_dir: base_dir.to_path_buf(),
+			relay_chain: DetachedChain {
+				chain: "paseo-local".to_string(),
+				id: None,
+				nodes: vec![node("alice", 9944), node("bob", 9945)],
+			},
+			rollups: vec![DetachedChain {
+				chain: "asset-hub-paseo-local".to_string(),
+				id: Some(1000),
+				nodes: vec![node("asset-hub", 9946)],
+			}],
+		}
+	}
+
+	#[test]
+	fn state_works() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let dir = DetachedNetwork::dir(temp_dir.path());
+		assert_eq!(dir, temp_dir.path().join(NETWORKS_DIR));
+		assert!(DetachedNetwork::list(&dir)?.is_empty());
+		assert_eq!(DetachedNetwork::load(&dir, "paseo")?, None);
+
+		let paseo = network("paseo", 1, temp_dir.path());
+		let westend = network("westend", 2, temp_dir.path());
+		westend.save(&dir)?;
+		paseo.save(&dir)?;
+		fs::write(DetachedNetwork::log(&dir, "paseo")?, "")?;
+		assert_eq!(DetachedNetwork::load(&dir, "paseo")?, Some(paseo.clone()));
+		assert_eq!(DetachedNetwork::list(&dir)?, vec![paseo.clone(), westend.clone()]);
+
+		paseo.remove(&dir)?;
+		assert_eq!(DetachedNetwork::load(&dir, "paseo")?, None);
+		assert!(!DetachedNetwork::log(&dir, "paseo")?.exists());
+		assert_eq!(DetachedNetwork::list(&dir)?, vec![westend]);
+		Ok(())
+	}
+
+	#[test]
+	fn check_name_works() -> Result<()> {
+		for name in ["paseo", "local-2", "my_network"] {
+			check_name(name)?;
+		}
+		for name in ["", "..", "../x", "a/b", "a.b", "/tmp/x", "name "] {
+			assert!(matches!(check_name(name), Err(Error::InvalidName(n)) if n == name));
+		}
+		let temp_dir = tempfile::tempdir()?;
+		assert!(DetachedNetwork::load(temp_dir.path(), "../x").is_err());
+		assert!(DetachedNetwork::log(temp_dir.path(), "../x").is_err());
+		Ok(())
+	}
+
+	// Spawns a process, identified as the supervisor of the named network.
+	fn supervisor(name: &str) -> Result<std::process::Child> {
+		Ok(std::process::Command::new("sleep").arg("60").env(DETACHED_ENV, name).spawn()?)
+	}
+
+	#[test]
+	fn is_running_works() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let mut child = supervisor("local")?;
+		assert!(network("local", child.id(), temp_dir.path()).is_running());
+		// A process supervising another network, or none at all, is stale.
+		assert!(!network("other", child.id(), temp_dir.path()).is_running());
+		assert!(!network("local", std::process::id(), temp_dir.path()).is_running());
+		assert!(!network("local", u32::MAX, temp_dir.path()).is_running());
+		child.kill()?;
+		child.wait()?;
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn stop_works() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let dir = DetachedNetwork::dir(temp_dir.path());
+		let base_dir = temp_dir.path().join("zombie");
+		fs::create_dir_all(&base_dir)?;
+		let mut child = supervisor("local")?;
+		let network = network("local", child.id(), &base_dir);
+		network.save(&dir)?;
+		// Reap the process once terminated.
+		let child = std::thread::spawn(move || child.wait());
+
+		network.stop(&dir, Duration::from_secs(5), false).await?;
+		assert!(!child.join().expect("expected process to be reaped")?.success());
+		assert!(base_dir.exists());
+		assert_eq!(DetachedNetwork::load(&dir, "local")?, None);
+
+		network.save(&dir)?;
+		network.stop(&dir, Duration::from_secs(5), true).await?;
+		assert!(!base_dir.exists());
+		assert_eq!(DetachedNetwork::load(&dir, "local")?, None);
+
+		// A process which is not the supervisor of the network is left untouched.
+		let mut child = std::process::Command::new("sleep").arg("60").spawn()?;
+		let network = network("local", child.id(), &base_dir);
+		network.save(&dir)?;
+		network.stop(&dir, Duration::from_secs(5), false).await?;
+		assert!(child.try_wait()?.is_none());
+		assert_eq!(DetachedNetwork::load(&dir, "local")?, None);
+		child.kill()?;
+		child.wait()?;
+		Ok(())
+	}
+}
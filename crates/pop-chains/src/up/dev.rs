@@ -0,0 +1,326 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use crate::errors::Error;
+use duct::cmd;
+use pop_common::{
+	git::GitHub,
+	polkadot_sdk::sort_by_latest_stable_version,
+	sourcing::{
+		filters::prefix,
+		traits::{
+			enums::{Source as _, *},
+			Source as SourceT,
+		},
+		ArchiveFileSpec, Binary,
+		GitHub::*,
+		Source,
+	},
+	target,
+};
+use sc_chain_spec::{ChainType, GenericChainSpec, NoExtension};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::{
+	fs::{self, File},
+	path::{Path, PathBuf},
+	process::{Child, Command, Stdio},
+	time::Duration,
+};
+use strum_macros::EnumProperty;
+use subxt::{
+	backend::rpc::{rpc_params, RpcClient},
+	utils::H256,
+};
+
+/// The default port of the RPC server of a development chain.
+pub const DEFAULT_PORT: u16 = 9944;
+/// The identifier of the rollup within a generated development chain specification.
+const DEV_PARA_ID: u32 = 1_000;
+/// The genesis preset used when generating a development chain specification.
+const DEV_PRESET: &str = "development";
+/// The default interval at which a development chain seals blocks, in milliseconds.
+pub const DEFAULT_BLOCK_TIME: u64 = 250;
+/// The interval used when blocks are only sealed on demand, in milliseconds. The node always seals
+/// blocks at an interval, so the longest interval it supports is used.
+const ON_DEMAND_BLOCK_TIME: u64 = u32::MAX as u64;
+
+/// A single-node development chain, which seals its own blocks without a relay chain.
+///
+/// Instant seal, where a block is sealed as soon as an extrinsic is submitted, is not supported:
+/// the node always seals blocks at an interval, so a short interval is used instead.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DevChain {
+	/// The node binary used to run the chain.
+	pub binary: PathBuf,
+	/// The chain specification to be used, or the built-in development chain if not specified.
+	pub chain_spec: Option<PathBuf>,
+	/// The interval at which blocks are sealed, in milliseconds, or `None` if blocks are only
+	/// sealed on demand using [`seal`].
+	pub block_time: Option<u64>,
+	/// The port of the RPC server.
+	pub port: u16,
+}
+
+impl DevChain {
+	/// A development chain using the built-in development chain specification of a node.
+	///
+	/// # Arguments
+	/// * `binary` - The node binary used to run the chain.
+	/// * `block_time` - The interval at which blocks are sealed, in milliseconds, or `None` if
+	///   blocks are only sealed on demand.
+	pub fn new(binary: PathBuf, block_time: Option<u64>) -> Self {
+		Self { binary, chain_spec: None, block_time, port: DEFAULT_PORT }
+	}
+
+	/// The arguments provided to the node.
+	pub fn args(&self) -> Vec<String> {
+		let mut args = vec!["--dev".to_string()];
+		if let Some(chain_spec) = &self.chain_spec {
+			args.push(format!("--chain={}", chain_spec.display()));
+		}
+		args.extend([
+			format!("--dev-block-time={}", self.block_time.unwrap_or(ON_DEMAND_BLOCK_TIME)),
+			format!("--rpc-port={}", self.port),
+			"--rpc-cors=all".to_string(),
+		]);
+		args
+	}
+
+	/// The websocket endpoint of the chain.
+	pub fn url(&self) -> String {
+		format!("ws://127.0.0.1:{}", self.port)
+	}
+
+	/// Spawns the node, writing its output to the specified log file.
+	///
+	/// # Arguments
+	/// * `log` - The file to which the output of the node is written.
+	pub fn spawn(&self, log: &Path) -> Result<Child, Error> {
+		let log = File::create(log)?;
+		Ok(Command::new(&self.binary)
+			.args(self.args())
+			.stdout(Stdio::from(log.try_clone()?))
+			.stderr(Stdio::from(log))
+			.spawn()?)
+	}
+
+	/// Waits until the RPC server of the chain is reachable, failing as soon as the node exits.
+	///
+	/// # Arguments
+	/// * `node` - The process of the node, as spawned by [`Self::spawn`].
+	/// * `timeout` - The maximum duration to wait.
+	pub async fn wait(&self, node: &mut Child, timeout: Duration) -> Result<(), Error> {
+		let url = self.url();
+		tokio::time::timeout(timeout, async {
+			while RpcClient::from_url(&url).await.is_err() {
+				if let Some(status) = node.try_wait()? {
+					return Err(Error::NetworkNotReady(format!("the node exited with {status}")));
+				}
+				tokio::time::sleep(Duration::from_millis(500)).await;
+			}
+			Ok(())
+		})
+		.await
+		.map_err(|_| Error::NetworkNotReady(format!("no response from {url}")))?
+	}
+}
+
+/// Determines whether a node binary supports sealing blocks as a development chain.
+///
+/// # Arguments
+/// * `binary` - The node binary.
+pub fn supports_dev_seal(binary: &Path) -> bool {
+	cmd(binary, ["--help"])
+		.stderr_null()
+		.read()
+		.is_ok_and(|help| help.contains("--dev-block-time"))
+}
+
+/// Generates a development chain specification for a runtime, using its `development` genesis
+/// preset, so that it can be run using `polkadot-omni-node`.
+///
+/// # Arguments
+/// * `runtime` - The path to the runtime binary.
+/// * `output` - The file to which the chain specification is written.
+pub fn generate_dev_chain_spec(runtime: &Path, output: &Path) -> Result<(), Error> {
+	let code = fs::read(runtime)?;
+	let chain_spec = GenericChainSpec::<NoExtension>::builder(&code, None)
+		.with_name("Development")
+		.with_id("dev")
+		.with_chain_type(ChainType::Development)
+		.with_genesis_config_preset_name(DEV_PRESET)
+		.build()
+		.as_json(false)
+		.map_err(Error::BuildSpecError)?;
+	// The node expects the extensions of a rollup chain specification.
+	let mut chain_spec: Value = serde_json::from_str(&chain_spec)?;
+	chain_spec["relay_chain"] = json!("dev");
+	chain_spec["para_id"] = json!(DEV_PARA_ID);
+	fs::write(output, serde_json::to_string_pretty(&chain_spec)?)?;
+	Ok(())
+}
+
+// The block created by the `engine_createBlock` RPC method.
+#[derive(Deserialize)]
+struct CreatedBlock {
+	hash: H256,
+}
+
+/// Seals blocks on a development chain using manual seal, returning their hashes.
+///
+/// # Arguments
+/// * `url` - The endpoint of the chain.
+/// * `blocks` - The number of blocks to seal.
+/// * `finalize` - Whether the sealed blocks should be finalized.
+pub async fn seal(url: &str, blocks: u32, finalize: bool) -> Result<Vec<H256>, Error> {
+	let client = RpcClient::from_url(url)
+		.await
+		.map_err(|e| Error::ConnectionFailure(e.to_string()))?;
+	let mut hashes = Vec::with_capacity(blocks as usize);
+	for _ in 0..blocks {
+		let block: CreatedBlock = client
+			.request("engine_createBlock", rpc_params![true, finalize, None::<H256>])
+			.await
+			.map_err(|e| Error::SealError(e.to_string()))?;
+		hashes.push(block.hash);
+	}
+	Ok(hashes)
+}
+
+#[derive(Debug, EnumProperty, PartialEq)]
+enum DevNode {
+	#[strum(props(
+		Repository = "https://github.com/r0gue-io/polkadot",
+		Binary = "polkadot-omni-node",
+		TagPattern = "polkadot-{version}",
+		Fallback = "stable2503-7"
+	))]
+	OmniNode,
+}
+
+impl SourceT for DevNode {
+	type Error = pop_common::Error;
+	/// Defines the source of the node binary used to run a runtime as a development chain.
+	fn source(&self) -> Result<Source, pop_common::Error> {
+		// Source from GitHub release asset
+		let repo = GitHub::parse(self.repository())?;
+		let binary = self.binary();
+		Ok(Source::GitHub(ReleaseArchive {
+			owner: repo.org,
+			repository: repo.name,
+			tag: None,
+			tag_pattern: self.tag_pattern().map(|t| t.into()),
+			prerelease: false,
+			version_comparator: sort_by_latest_stable_version,
+			fallback: self.fallback().into(),
+			archive: format!("{binary}-{}.tar.gz", target()?),
+			contents: vec![ArchiveFileSpec::new(binary.into(), Some(binary.into()), true)],
+			latest: None,
+		}))
+	}
+}
+
+/// Generate the source of the `polkadot-omni-node` binary on the remote repository.
+///
+/// # Arguments
+/// * `cache` - The path to the directory where the binary should be cached.
+/// * `version` - An optional version string. If `None`, the latest available version is used.
+pub async fn omni_node_generator(
+	cache: PathBuf,
+	version: Option<&str>,
+) -> Result<Binary, pop_common::Error> {
+	let node = DevNode::OmniNode;
+	let name = node.binary().to_string();
+	let source = node
+		.source()?
+		.resolve(&name, version, cache.as_path(), |f| prefix(f, &name))
+		.await
+		.into();
+	let binary = Binary::Source { name, source, cache: cache.to_path_buf() };
+	Ok(binary)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use tempfile::tempdir;
+
+	#[test]
+	fn args_works() {
+		let mut chain = DevChain::new(PathBuf::from("./polkadot-omni-node"), None);
+		assert_eq!(
+			chain.args(),
+			[
+				"--dev".to_string(),
+				format!("--dev-block-time={ON_DEMAND_BLOCK_TIME}"),
+				"--rpc-port=9944".to_string(),
+				"--rpc-cors=all".to_string(),
+			]
+		);
+		chain.chain_spec = Some(PathBuf::from("./dev.json"));
+		chain.block_time = Some(DEFAULT_BLOCK_TIME);
+		chain.port = 9955;
+		assert_eq!(
+			chain.args(),
+			[
+				"--dev".to_string(),
+				"--chain=./dev.json".to_string(),
+				format!("--dev-block-time={DEFAULT_BLOCK_TIME}"),
+				"--rpc-port=9955".to_string(),
+				"--rpc-cors=all".to_string(),
+			]
+		);
+		assert_eq!(chain.url(), "ws://127.0.0.1:9955");
+	}
+
+	#[test]
+	fn supports_dev_seal_works() -> Result<(), Error> {
+		let temp_dir = tempdir()?;
+		assert!(!supports_dev_seal(&temp_dir.path().join("missing")));
+		assert!(!supports_dev_seal(Path::new("true")));
+		Ok(())
+	}
+
+	#[test]
+	fn generate_dev_chain_spec_fails_with_missing_runtime() -> Result<(), Error> {
+		let temp_dir = tempdir()?;
+		assert!(matches!(
+			generate_dev_chain_spec(
+				&temp_dir.path().join("runtime.wasm"),
+				&temp_dir.path().join("dev.json")
+			),
+			Err(Error::IO(..))
+		));
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn wait_times_out() -> Result<(), Error> {
+		let chain = DevChain { port: 1, ..DevChain::new(PathBuf::from("sleep"), None) };
+		let mut node = Command::new("sleep").arg("5").spawn()?;
+		let result = chain.wait(&mut node, Duration::from_millis(100)).await;
+		node.kill()?;
+		node.wait()?;
+		assert!(matches!(result, Err(Error::NetworkNotReady(e)) if e.starts_with("no response")));
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn wait_fails_when_node_exits() -> Result<(), Error> {
+		let chain = DevChain { port: 1, ..DevChain::new(PathBuf::from("false"), None) };
+		let mut node = Command::new("false").spawn()?;
+		assert!(matches!(
+			chain.wait(&mut node, Duration::from_secs(10)).await,
+			Err(Error::NetworkNotReady(e)) if e.starts_with("the node exited")
+		));
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn seal_fails_without_chain() {
+		assert!(matches!(
+			seal("ws://127.0.0.1:1", 1, false).await,
+			Err(Error::ConnectionFailure(..))
+		));
+	}
+}
@@ -0,0 +1,290 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use crate::errors::Error;
+use std::{
+	collections::BTreeSet,
+	fmt::{Display, Formatter},
+	str::FromStr,
+};
+
+/// The default maximum number of messages which can be queued within a channel.
+pub const DEFAULT_MAX_CAPACITY: u32 = 1_000;
+/// The default maximum size of a message sent via a channel.
+pub const DEFAULT_MAX_MESSAGE_SIZE: u32 = 8_000;
+/// The identifier of the rollup used as the hub of a star topology, if not specified.
+const DEFAULT_HUB: u32 = 1_000;
+
+/// The topology of the HRMP channels to be opened between the rollups of a network.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum HrmpTopology {
+	/// No channels are opened.
+	None,
+	/// Channels are opened in both directions between every pair of rollups.
+	#[default]
+	Mesh,
+	/// Channels are opened in both directions between the hub and every other rollup.
+	Star {
+		/// The identifier of the hub rollup.
+		hub: u32,
+	},
+	/// Only the specified channels are opened.
+	Channels(Vec<HrmpChannel>),
+}
+
+/// A unidirectional HRMP channel between two rollups.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HrmpChannel {
+	/// The identifier of the sending rollup.
+	pub sender: u32,
+	/// The identifier of the receiving rollup.
+	pub recipient: u32,
+	/// The maximum number of messages which can be queued within the channel.
+	pub max_capacity: u32,
+	/// The maximum size of a message sent via the channel.
+	pub max_message_size: u32,
+}
+
+impl HrmpChannel {
+	/// A channel between two rollups, using the default capacity and message size.
+	///
+	/// # Arguments
+	/// * `sender` - The identifier of the sending rollup.
+	/// * `recipient` - The identifier of the receiving rollup.
+	pub fn new(sender: u32, recipient: u32) -> Self {
+		Self {
+			sender,
+			recipient,
+			max_capacity: DEFAULT_MAX_CAPACITY,
+			max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+		}
+	}
+}
+
+impl HrmpTopology {
+	/// Resolves the channels to be opened between the provided rollups, validating that each
+	/// channel is between two distinct rollups of the network.
+	///
+	/// # Arguments
+	/// * `rollups` - The identifiers of the rollups within the network.
+	pub fn channels(&self, rollups: &[u32]) -> Result<Vec<HrmpChannel>, Error> {
+		let channels: Vec<_> = match self {
+			HrmpTopology::None => return Ok(Vec::new()),
+			HrmpTopology::Mesh => rollups
+				.iter()
+				.flat_map(|s| rollups.iter().filter(move |r| s != *r).map(move |r| (*s, *r)))
+				.map(|(s, r)| HrmpChannel::new(s, r))
+				.collect(),
+			HrmpTopology::Star { hub } => {
+				if !rollups.contains(hub) {
+					return Err(Error::HrmpTopologyError(format!(
+						"the hub {hub} is not a rollup of the network"
+					)));
+				}
+				rollups
+					.iter()
+					.filter(|r| *r != hub)
+					.flat_map(|r| [HrmpChannel::new(*hub, *r), HrmpChannel::new(*r, *hub)])
+					.collect()
+			},
+			HrmpTopology::Channels(channels) => channels.clone(),
+		};
+
+		let mut opened = BTreeSet::new();
+		for channel in &channels {
+			let HrmpChannel { sender, recipient, max_capacity, max_message_size } = *channel;
+			if let Some(id) = [sender, recipient].into_iter().find(|id| !rollups.contains(id)) {
+				return Err(Error::HrmpTopologyError(format!(
+					"the channel {sender}-{recipient} references {id}, which is not a rollup of the network"
+				)));
+			}
+			if sender == recipient {
+				return Err(Error::HrmpTopologyError(format!(
+					"the channel {sender}-{recipient} must be between two different rollups"
+				)));
+			}
+			if max_capacity == 0 || max_message_size == 0 {
+				return Err(Error::HrmpTopologyError(format!(
+					"the channel {sender}-{recipient} must have a non-zero capacity and message size"
+				)));
+			}
+			if !opened.insert((sender, recipient)) {
+				return Err(Error::HrmpTopologyError(format!(
+					"the channel {sender}-{recipient} is specified more than once"
+				)));
+			}
+		}
+		Ok(channels)
+	}
+}
+
+impl FromStr for HrmpTopology {
+	type Err = Error;
+
+	/// Parses a topology from `none`, `mesh`, `star[:<hub>]` or a comma-separated list of
+	/// `<sender>-<recipient>[:<capacity>:<message size>]` channels.
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let invalid = |reason: &str| Error::HrmpTopologyError(format!("`{s}` {reason}"));
+		let id = |value: &str| {
+			value.trim().parse::<u32>().map_err(|_| invalid("contains an invalid number"))
+		};
+		match s.trim() {
+			"none" => Ok(HrmpTopology::None),
+			"mesh" => Ok(HrmpTopology::Mesh),
+			"star" => Ok(HrmpTopology::Star { hub: DEFAULT_HUB }),
+			value => {
+				if let Some(hub) = value.strip_prefix("star:") {
+					return Ok(HrmpTopology::Star { hub: id(hub)? });
+				}
+				value
+					.split(',')
+					.map(|channel| {
+						let mut parts = channel.split(':');
+						let (sender, recipient) = parts
+							.next()
+							.and_then(|pair| pair.split_once('-'))
+							.ok_or_else(|| invalid("is not a valid topology"))?;
+						let mut channel = HrmpChannel::new(id(sender)?, id(recipient)?);
+						match (parts.next(), parts.next(), parts.next()) {
+							(None, None, None) => {},
+							(Some(capacity), Some(size), None) => {
+								channel.max_capacity = id(capacity)?;
+								channel.max_message_size = id(size)?;
+							},
+							_ => return Err(invalid("is not a valid topology")),
+						}
+						Ok(channel)
+					})
+					.collect::<Result<_, _>>()
+					.map(HrmpTopology::Channels)
+			},
+		}
+	}
+}
+
+impl Display for HrmpTopology {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			HrmpTopology::None => write!(f, "none"),
+			HrmpTopology::Mesh => write!(f, "mesh"),
+			HrmpTopology::Star { hub } => write!(f, "star:{hub}"),
+			HrmpTopology::Channels(channels) => {
+				let channels: Vec<_> = channels
+					.iter()
+					.map(|c| {
+						format!(
+							"{}-{}:{}:{}",
+							c.sender, c.recipient, c.max_capacity, c.max_message_size
+						)
+					})
+					.collect();
+				write!(f, "{}", channels.join(","))
+			},
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const ROLLUPS: [u32; 3] = [1000, 2000, 4001];
+
+	#[test]
+	fn parse_works() -> Result<(), Error> {
+		assert_eq!("none".parse::<HrmpTopology>()?, HrmpTopology::None);
+		assert_eq!("mesh".parse::<HrmpTopology>()?, HrmpTopology::Mesh);
+		assert_eq!("star".parse::<HrmpTopology>()?, HrmpTopology::Star { hub: 1000 });
+		assert_eq!("star:2000".parse::<HrmpTopology>()?, HrmpTopology::Star { hub: 2000 });
+		assert_eq!(
+			"1000-2000,2000-4001:10:1024".parse::<HrmpTopology>()?,
+			HrmpTopology::Channels(vec![
+				HrmpChannel::new(1000, 2000),
+				HrmpChannel {
+					sender: 2000,
+					recipient: 4001,
+					max_capacity: 10,
+					max_message_size: 1024
+				},
+			])
+		);
+		for invalid in ["", "ring", "star:hub", "1000", "1000-", "1000-2000:10", "1000-2000:1:2:3"]
+		{
+			assert!(
+				matches!(invalid.parse::<HrmpTopology>(), Err(Error::HrmpTopologyError(..))),
+				"{invalid}"
+			);
+		}
+		Ok(())
+	}
+
+	#[test]
+	fn display_works() -> Result<(), Error> {
+		for topology in ["none", "mesh", "star:1000", "1000-2000:1000:8000,2000-4001:10:1024"] {
+			assert_eq!(topology.parse::<HrmpTopology>()?.to_string(), topology);
+		}
+		Ok(())
+	}
+
+	#[test]
+	fn none_works() -> Result<(), Error> {
+		assert!(HrmpTopology::None.channels(&ROLLUPS)?.is_empty());
+		Ok(())
+	}
+
+	#[test]
+	fn mesh_works() -> Result<(), Error> {
+		let channels = HrmpTopology::Mesh.channels(&ROLLUPS)?;
+		assert_eq!(channels.len(), ROLLUPS.len() * (ROLLUPS.len() - 1));
+		for sender in ROLLUPS {
+			for recipient in ROLLUPS.into_iter().filter(|r| *r != sender) {
+				assert!(channels.contains(&HrmpChannel::new(sender, recipient)));
+			}
+		}
+		assert!(HrmpTopology::Mesh.channels(&[])?.is_empty());
+		Ok(())
+	}
+
+	#[test]
+	fn star_works() -> Result<(), Error> {
+		assert_eq!(
+			HrmpTopology::Star { hub: 1000 }.channels(&ROLLUPS)?,
+			vec![
+				HrmpChannel::new(1000, 2000),
+				HrmpChannel::new(2000, 1000),
+				HrmpChannel::new(1000, 4001),
+				HrmpChannel::new(4001, 1000),
+			]
+		);
+		assert!(matches!(
+			HrmpTopology::Star { hub: 3000 }.channels(&ROLLUPS),
+			Err(Error::HrmpTopologyError(..))
+		));
+		Ok(())
+	}
+
+	#[test]
+	fn channels_works() -> Result<(), Error> {
+		let channels = vec![HrmpChannel::new(2000, 1000), HrmpChannel::new(4001, 2000)];
+		assert_eq!(HrmpTopology::Channels(channels.clone()).channels(&ROLLUPS)?, channels);
+		Ok(())
+	}
+
+	#[test]
+	fn channels_fails_when_invalid() {
+		for channels in [
+			// Unknown rollup
+			vec![HrmpChannel::new(2000, 3000)],
+			// Same sender and recipient
+			vec![HrmpChannel::new(2000, 2000)],
+			// Zero capacity
+			vec![HrmpChannel { max_capacity: 0, ..HrmpChannel::new(2000, 1000) }],
+			// Duplicate channel
+			vec![HrmpChannel::new(2000, 1000), HrmpChannel::new(2000, 1000)],
+		] {
+			assert!(matches!(
+				HrmpTopology::Channels(channels).channels(&ROLLUPS),
+				Err(Error::HrmpTopologyError(..))
+			));
+		}
+	}
+}
@@ -0,0 +1,490 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use super::DetachedNetwork;
+use crate::errors::Error;
+use regex::Regex;
+use std::{
+	collections::HashMap,
+	fs::File,
+	io::{Read, Seek, SeekFrom},
+	path::{Path, PathBuf},
+	sync::LazyLock,
+};
+use strum::{AsRefStr, EnumString};
+
+// Matches the block import notifications of a node, e.g. `🏆 Imported #12 (0x1234…5678)`.
+static IMPORTED: LazyLock<Regex> =
+	LazyLock::new(|| Regex::new(r"Imported #(\d+)").expect("valid regex; qed"));
+/// The tag of the lines logged by the embedded relay chain node of a collator.
+const RELAY_CHAIN_TAG: &str = "[Relaychain]";
+// The number of bytes read at a time when searching for the tail of a log file.
+const TAIL_CHUNK: u64 = 64 * 1024;
+
+/// The severity of a log line, from most to least severe.
+#[derive(
+	AsRefStr, Clone, Copy, Debug, EnumString, Eq, Ord, PartialEq, PartialOrd, clap::ValueEnum,
+)]
+#[strum(serialize_all = "UPPERCASE", ascii_case_insensitive)]
+pub enum Level {
+	/// An error.
+	Error,
+	/// A warning.
+	Warn,
+	/// Informational.
+	Info,
+	/// Debugging information.
+	Debug,
+	/// Detailed debugging information.
+	Trace,
+}
+
+/// A line logged by a node of a network.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LogLine {
+	/// The identifier of the rollup of the node, or `None` for the relay chain.
+	pub chain: Option<u32>,
+	/// The name of the node.
+	pub node: String,
+	/// The latest block of its chain imported by the node when the line was logged, if known.
+	pub block: Option<u32>,
+	/// The time at which the line was logged, e.g. `2025-01-01 12:00:00.000`. Lines without a
+	/// timestamp, such as the continuation of a multi-line message, inherit the timestamp of the
+	/// preceding line.
+	pub timestamp: Option<String>,
+	/// The severity of the line, if the line follows the format of a node.
+	pub level: Option<Level>,
+	/// The target of the line, e.g. `xcm::process_message`, if the line follows the format of a
+	/// node.
+	pub target: Option<String>,
+	/// The line, as logged.
+	pub text: String,
+}
+
+impl LogLine {
+	/// Parses a line logged by a node, of the format `<date> <time> <level> [<thread>] <target>:
+	/// <message>`.
+	///
+	/// # Arguments
+	/// * `node` - The name of the node.
+	/// * `text` - The line.
+	pub fn parse(node: &str, text: &str) -> Self {
+		let mut line = Self {
+			chain: None,
+			node: node.to_string(),
+			block: None,
+			timestamp: None,
+			level: None,
+			target: None,
+			text: text.to_string(),
+		};
+		let tokens: Vec<_> = text.split_whitespace().take(5).collect();
+		let [date, time, level, ..] = tokens[..] else {
+			return line;
+		};
+		let Ok(level) = level.parse::<Level>() else {
+			return line;
+		};
+		if !date.starts_with(|c: char| c.is_ascii_digit()) {
+			return line;
+		}
+		line.timestamp = Some(format!("{date} {time}"));
+		line.level = Some(level);
+		// The target follows the level, optionally preceded by the name of the thread.
+		line.target = tokens[3..].iter().find_map(|t| t.strip_suffix(':')).map(String::from);
+		line
+	}
+
+	/// The number of the block imported by the node, if the line is an import notification of
+	/// the chain of the node.
+	pub fn imported(&self) -> Option<u32> {
+		if self.text.contains(RELAY_CHAIN_TAG) {
+			return None;
+		}
+		IMPORTED.captures(&self.text)?.get(1)?.as_str().parse().ok()
+	}
+}
+
+/// Filters the lines logged by the nodes of a network.
+#[derive(Clone, Debug, Default)]
+pub struct LogFilter {
+	/// Only lines logged by these nodes, if any are specified.
+	pub nodes: Vec<String>,
+	/// Only lines of these targets or their sub-targets, if any are specified.
+	pub targets: Vec<String>,
+	/// Only lines at least as severe as this level.
+	pub level: Option<Level>,
+	/// Only lines matching this pattern.
+	pub pattern: Option<Regex>,
+	/// Only lines logged once the node imported the given block of its chain, keyed by the
+	/// identifier of the chain: `None` for the relay chain or the identifier of a rollup. The
+	/// lines of the chains without a block are not filtered.
+	pub since: HashMap<Option<u32>, u32>,
+}
+
+impl LogFilter {
+	/// Whether a line passes the filter.
+	///
+	/// # Arguments
+	/// * `line` - The line.
+	pub fn matches(&self, line: &LogLine) -> bool {
+		(self.nodes.is_empty() || self.nodes.contains(&line.node)) &&
+			(self.targets.is_empty() ||
+				line.target.as_deref().is_some_and(|target| {
+					self.targets.iter().any(|t| {
+						target == t ||
+							target.strip_prefix(t.as_str()).is_some_and(|s| s.starts_with("::"))
+					})
+				})) && self.level.is_none_or(|level| line.level.is_some_and(|l| l <= level)) &&
+			self.pattern.as_ref().is_none_or(|p| p.is_match(&line.text)) &&
+			self.since
+				.get(&line.chain)
+				.is_none_or(|since| line.block.is_some_and(|b| b >= *since))
+	}
+}
+
+/// The log file of a node, read incrementally as lines are appended.
+#[derive(Debug)]
+pub struct NodeLog {
+	chain: Option<u32>,
+	node: String,
+	path: PathBuf,
+	// The position up to which the file has been read.
+	offset: u64,
+	// The incomplete last line read, kept as bytes as it may end within a character.
+	partial: Vec<u8>,
+	block: Option<u32>,
+	timestamp: Option<String>,
+}
+
+impl NodeLog {
+	/// Creates a reader of the log file of a node, starting at the beginning of the file.
+	///
+	/// # Arguments
+	/// * `chain` - The identifier of the rollup of the node, or `None` for the relay chain.
+	/// * `node` - The name of the node.
+	/// * `path` - The log file of the node.
+	pub fn new(chain: Option<u32>, node: &str, path: &Path) -> Self {
+		Self {
+			chain,
+			node: node.to_string(),
+			path: path.to_path_buf(),
+			offset: 0,
+			partial: Vec::new(),
+			block: None,
+			timestamp: None,
+		}
+	}
+
+	/// The name of the node.
+	pub fn node(&self) -> &str {
+		&self.node
+	}
+
+	/// Skips to the last lines of the log file, so that only those and the lines appended
+	/// afterwards are read. The blocks imported before the last lines are not known until the
+	/// node imports another block.
+	///
+	/// # Arguments
+	/// * `lines` - The number of lines to keep.
+	pub fn seek_tail(&mut self, lines: usize) -> Result<(), Error> {
+		if !self.path.exists() {
+			return Ok(());
+		}
+		let mut file = File::open(&self.path)?;
+		self.partial.clear();
+		self.offset = 0;
+		let len = file.metadata()?.len();
+		// Search backwards for the line break preceding the last lines, an incomplete last line
+		// being one of them.
+		let mut found = 0;
+		let mut end = len;
+		while end > 0 {
+			let start = end.saturating_sub(TAIL_CHUNK);
+			let mut chunk = vec![0; (end - start) as usize];
+			file.seek(SeekFrom::Start(start))?;
+			file.read_exact(&mut chunk)?;
+			if end == len && chunk.last() != Some(&b'\n') && lines > 0 {
+				found += 1;
+			}
+			for (i, _) in chunk.iter().enumerate().rev().filter(|(_, b)| **b == b'\n') {
+				if found == lines {
+					self.offset = start + i as u64 + 1;
+					return Ok(());
+				}
+				found += 1;
+			}
+			end = start;
+		}
+		Ok(())
+	}
+
+	/// Reads the complete lines appended to the log file since the last read.
+	pub fn read(&mut self) -> Result<Vec<LogLine>, Error> {
+		if !self.path.exists() {
+			return Ok(Vec::new());
+		}
+		let mut file = File::open(&self.path)?;
+		// Start over should the file have been truncated.
+		if file.metadata()?.len() < self.offset {
+			self.offset = 0;
+			self.partial.clear();
+		}
+		file.seek(SeekFrom::Start(self.offset))?;
+		self.offset += file.read_to_end(&mut self.partial)? as u64;
+		let Some(end) = self.partial.iter().rposition(|b| *b == b'\n') else {
+			return Ok(Vec::new());
+		};
+		// A line break never occurs within a character, so the complete lines are decoded whole.
+		let complete: Vec<u8> = self.partial.drain(..=end).collect();
+		Ok(String::from_utf8_lossy(&complete)
+			.lines()
+			.filter(|text| !text.trim().is_empty())
+			.map(|text| {
+				let mut line = LogLine::parse(&self.node, text);
+				line.chain = self.chain;
+				if let Some(block) = line.imported() {
+					self.block = Some(block);
+				}
+				match &line.timestamp {
+					Some(timestamp) => self.timestamp = Some(timestamp.clone()),
+					None => line.timestamp = self.timestamp.clone(),
+				}
+				line.block = self.block;
+				line
+			})
+			.collect())
+	}
+}
+
+/// The logs of all nodes of a network, multiplexed into a single stream ordered by time.
+#[derive(Debug)]
+pub struct NetworkLogs {
+	logs: Vec<NodeLog>,
+}
+
+impl NetworkLogs {
+	/// Creates a reader of the logs of the nodes of a network.
+	///
+	/// # Arguments
+	/// * `network` - The network.
+	pub fn new(network: &DetachedNetwork) -> Self {
+		let logs = std::iter::once(&network.relay_chain)
+			.chain(&network.rollups)
+			.flat_map(|chain| {
+				chain.nodes.iter().map(|node| NodeLog::new(chain.id, &node.name, &node.log))
+			})
+			.collect();
+		Self { logs }
+	}
+
+	/// The names of the nodes of the network.
+	pub fn nodes(&self) -> impl Iterator<Item = &str> {
+		self.logs.iter().map(|log| log.node())
+	}
+
+	/// Skips to the last lines of the log file of each node.
+	///
+	/// # Arguments
+	/// * `lines` - The number of lines to keep per node.
+	pub fn seek_tail(&mut self, lines: usize) -> Result<(), Error> {
+		for log in &mut self.logs {
+			log.seek_tail(lines)?;
+		}
+		Ok(())
+	}
+
+	/// Reads the lines appended to the log files of the nodes since the last read, ordered by
+	/// time.
+	pub fn read(&mut self) -> Result<Vec<LogLine>, Error> {
+		let mut lines = Vec::new();
+		for log in &mut self.logs {
+			lines.extend(log.read()?);
+		}
+		// The sort is stable, preserving the order of lines logged at the same time by a node.
+		lines.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+		Ok(lines)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::up::{DetachedChain, DetachedNode};
+	use std::{fs, io::Write};
+
+	const IMPORTED_LINE: &str = "2025-01-01 12:00:06.000  INFO tokio-runtime-worker substrate: [Parachain] 🏆 Imported #7 (0x1234…5678 → 0x9abc…def0)";
+	const RELAY_IMPORTED_LINE: &str = "2025-01-01 12:00:06.100  INFO tokio-runtime-worker substrate: [Relaychain] ✨ Imported #20 (0x1234…5678 → 0x9abc…def0)";
+
+	#[test]
+	fn level_works() {
+		assert_eq!("INFO".parse::<Level>(), Ok(Level::Info));
+		assert_eq!("warn".parse::<Level>(), Ok(Level::Warn));
+		assert!("NOTICE".parse::<Level>().is_err());
+		assert!(Level::Error < Level::Warn && Level::Debug < Level::Trace);
+	}
+
+	#[test]
+	fn parse_works() {
+		let line = LogLine::parse("alice", IMPORTED_LINE);
+		assert_eq!(line.node, "alice");
+		assert_eq!(line.timestamp.as_deref(), Some("2025-01-01 12:00:06.000"));
+		assert_eq!(line.level, Some(Level::Info));
+		assert_eq!(line.target.as_deref(), Some("substrate"));
+		assert_eq!(line.text, IMPORTED_LINE);
+		assert_eq!(line.imported(), Some(7));
+
+		// The thread name is optional.
+		let line = LogLine::parse(
+			"bob",
+			"2025-01-01 12:00:00.000 DEBUG xcm::process_message: origin: Parent",
+		);
+		assert_eq!(line.level, Some(Level::Debug));
+		assert_eq!(line.target.as_deref(), Some("xcm::process_message"));
+		assert_eq!(line.imported(), None);
+
+		// Imports of the relay chain by a collator are ignored.
+		assert_eq!(LogLine::parse("collator", RELAY_IMPORTED_LINE).imported(), None);
+
+		// Lines which do not follow the format of a node are kept as is.
+		let line = LogLine::parse("alice", "  continuation of a message");
+		assert_eq!((line.timestamp, line.level, line.target), (None, None, None));
+	}
+
+	#[test]
+	fn filter_works() {
+		let line = |node: &str, text: &str, block: Option<u32>| LogLine {
+			block,
+			..LogLine::parse(node, text)
+		};
+		let xcm = line(
+			"alice",
+			"2025-01-01 12:00:00.000  WARN tokio-runtime-worker xcm::process_message: Barrier blocked execution",
+			Some(5),
+		);
+		let imported = line("bob", IMPORTED_LINE, Some(7));
+
+		assert!(LogFilter::default().matches(&xcm));
+		let filter = LogFilter { nodes: vec!["bob".into()], ..Default::default() };
+		assert!(!filter.matches(&xcm) && filter.matches(&imported));
+		let filter = LogFilter { targets: vec!["xcm".into()], ..Default::default() };
+		assert!(filter.matches(&xcm) && !filter.matches(&imported));
+		let filter = LogFilter { targets: vec!["xcm::process".into()], ..Default::default() };
+		assert!(!filter.matches(&xcm));
+		let filter = LogFilter { level: Some(Level::Warn), ..Default::default() };
+		assert!(filter.matches(&xcm) && !filter.matches(&imported));
+		let filter =
+			LogFilter { pattern: Some(Regex::new("Barrier.*").unwrap()), ..Default::default() };
+		assert!(filter.matches(&xcm) && !filter.matches(&imported));
+		let filter = LogFilter { since: HashMap::from([(None, 6)]), ..Default::default() };
+		assert!(!filter.matches(&xcm) && filter.matches(&imported));
+		// The block is specific to the chain of the node.
+		let filter = LogFilter { since: HashMap::from([(Some(1000), 6)]), ..Default::default() };
+		assert!(filter.matches(&xcm) && filter.matches(&imported));
+		let rollup = LogLine { chain: Some(1000), ..imported.clone() };
+		let filter = LogFilter { since: HashMap::from([(Some(1000), 8)]), ..Default::default() };
+		assert!(!filter.matches(&rollup) && filter.matches(&imported));
+	}
+
+	#[test]
+	fn node_log_reads_incrementally() -> Result<(), Error> {
+		let temp_dir = tempfile::tempdir()?;
+		let path = temp_dir.path().join("alice.log");
+		let mut log = NodeLog::new(None, "alice", &path);
+		// The log file may not exist yet.
+		assert!(log.read()?.is_empty());
+
+		let mut file = File::create(&path)?;
+		write!(file, "{IMPORTED_LINE}\nsecond line\n2025-01-01 12:00:07.000 ERROR par")?;
+		let lines = log.read()?;
+		assert_eq!(lines.len(), 2);
+		assert_eq!(lines[0].block, Some(7));
+		// Lines inherit the timestamp of the preceding line, along with the latest block.
+		assert_eq!(lines[1].timestamp, lines[0].timestamp);
+		assert_eq!(lines[1].block, Some(7));
+
+		// Incomplete lines are read once complete.
+		writeln!(file, "tial: done")?;
+		let lines = log.read()?;
+		assert_eq!(lines.len(), 1);
+		assert_eq!(lines[0].text, "2025-01-01 12:00:07.000 ERROR partial: done");
+		assert_eq!(lines[0].level, Some(Level::Error));
+		assert!(log.read()?.is_empty());
+
+		// Characters split across reads are decoded once complete.
+		let bytes = "🏆 done\n".as_bytes();
+		file.write_all(&bytes[..2])?;
+		assert!(log.read()?.is_empty());
+		file.write_all(&bytes[2..])?;
+		assert_eq!(log.read()?[0].text, "🏆 done");
+		Ok(())
+	}
+
+	#[test]
+	fn node_log_seeks_tail() -> Result<(), Error> {
+		let temp_dir = tempfile::tempdir()?;
+		let path = temp_dir.path().join("alice.log");
+		let mut log = NodeLog::new(None, "alice", &path);
+		// The log file may not exist yet.
+		log.seek_tail(1)?;
+
+		fs::write(&path, "first\nsecond\nthird\nfour")?;
+		log.seek_tail(2)?;
+		let mut file = fs::OpenOptions::new().append(true).open(&path)?;
+		writeln!(file, "th")?;
+		let text = |lines: Vec<LogLine>| lines.into_iter().map(|l| l.text).collect::<Vec<_>>();
+		assert_eq!(text(log.read()?), vec!["third", "fourth"]);
+
+		log.seek_tail(1)?;
+		assert_eq!(text(log.read()?), vec!["fourth"]);
+		log.seek_tail(0)?;
+		assert!(log.read()?.is_empty());
+		log.seek_tail(10)?;
+		assert_eq!(text(log.read()?).len(), 4);
+		Ok(())
+	}
+
+	#[test]
+	fn network_logs_are_ordered_by_time() -> Result<(), Error> {
+		let temp_dir = tempfile::tempdir()?;
+		let node = |name: &str, lines: &[&str]| -> Result<DetachedNode, Error> {
+			let log = temp_dir.path().join(format!("{name}.log"));
+			fs::write(&log, lines.iter().map(|l| format!("{l}\n")).collect::<String>())?;
+			Ok(DetachedNode { name: name.into(), endpoint: String::new(), pid: None, log })
+		};
+		let network = DetachedNetwork {
+			name: "network".into(),
+			pid: 0,
+			base_dir: temp_dir.path().to_path_buf(),
+			relay_chain: DetachedChain {
+				chain: "paseo-local".into(),
+				id: None,
+				nodes: vec![node(
+					"alice",
+					&[
+						"2025-01-01 12:00:00.000  INFO main sc_cli::runner: first",
+						"2025-01-01 12:00:02.000  INFO main sc_cli::runner: third",
+					],
+				)?],
+			},
+			rollups: vec![DetachedChain {
+				chain: "asset-hub".into(),
+				id: Some(1000),
+				nodes: vec![node(
+					"collator",
+					&["2025-01-01 12:00:01.000  INFO main sc_cli::runner: second"],
+				)?],
+			}],
+		};
+		let mut logs = NetworkLogs::new(&network);
+		assert_eq!(logs.nodes().collect::<Vec<_>>(), vec!["alice", "collator"]);
+		let lines = logs.read()?;
+		assert_eq!(
+			lines.iter().map(|l| l.node.as_str()).collect::<Vec<_>>(),
+			vec!["alice", "collator", "alice"]
+		);
+		assert!(lines[2].text.ends_with("third"));
+		assert_eq!((lines[0].chain, lines[1].chain), (None, Some(1000)));
+		assert!(logs.read()?.is_empty());
+		Ok(())
+	}
+}
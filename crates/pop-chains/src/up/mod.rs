@@ -28,7 +28,27 @@ use zombienet_sdk::{LocalFileSystem, Network, NetworkConfig, NetworkConfigExt};
 mod chain_specs;
 /// Configuration for supported parachains.
 pub mod chains;
+mod detached;
+mod dev;
+mod hrmp;
+mod logs;
+mod readiness;
 mod relay;
+mod snapshot;
+mod upgrade;
+mod xcm;
+
+pub use detached::{DetachedChain, DetachedNetwork, DetachedNode, NetworkChain, DETACHED_ENV};
+pub use dev::{
+	generate_dev_chain_spec, omni_node_generator, seal, supports_dev_seal, DevChain,
+	DEFAULT_BLOCK_TIME, DEFAULT_PORT,
+};
+pub use hrmp::{HrmpChannel, HrmpTopology, DEFAULT_MAX_CAPACITY, DEFAULT_MAX_MESSAGE_SIZE};
+pub use logs::{Level, LogFilter, LogLine, NetworkLogs, NodeLog};
+pub use readiness::{wait_for_blocks, Readiness};
+pub use snapshot::Snapshot;
+pub use upgrade::{runtime_version, RuntimeUpgrade, UpgradePath};
+pub use xcm::{XcmHop, XcmMessage, XcmProgram, XcmTracer};
 
 const VALIDATORS: [&str; 6] = ["alice", "bob", "charlie", "dave", "eve", "ferdie"];
 
@@ -309,7 +329,14 @@ impl Zombienet {
 
 		// Load from config and spawn network
 		let network_config = self.network_config.adapt(&self.relay_chain, &self.parachains)?;
-		Ok(network_config.spawn_native().await?)
+		let network = network_config.spawn_native().await?;
+
+		// Record how the network was launched, so that its state can later be snapshotted.
+		if let Some(base_dir) = network.base_dir() {
+			snapshot::Launch::new(&self.network_config, Some(version))?
+				.save(Path::new(base_dir))?;
+		}
+		Ok(network)
 	}
 }
 
@@ -326,10 +353,12 @@ impl NetworkConfiguration {
 	/// * `relay_chain` - The relay chain runtime to be used.
 	/// * `port` - The port to be used for the first relay chain validator.
 	/// * `rollups` - The optional rollups to be included.
+	/// * `hrmp` - The topology of the HRMP channels to be opened between the rollups.
 	pub fn build(
 		relay_chain: Relay,
 		port: Option<u16>,
 		rollups: Option<&[Box<dyn Rollup>]>,
+		hrmp: &HrmpTopology,
 	) -> Result<Self, Error> {
 		let validators: Vec<_> = VALIDATORS
 			.into_iter()
@@ -393,20 +422,18 @@ impl NetworkConfiguration {
 					})
 				})
 			}
+		}
 
-			// Open HRMP channels between all rollups
-			let rollups = || rollups.iter().map(|p| p.id());
-			for (sender, recipient) in
-				rollups().flat_map(|s| rollups().filter(move |r| s != *r).map(move |r| (s, r)))
-			{
-				builder = builder.with_hrmp_channel(|channel| {
-					channel
-						.with_sender(sender)
-						.with_recipient(recipient)
-						.with_max_capacity(1_000)
-						.with_max_message_size(8_000)
-				})
-			}
+		// Open HRMP channels between rollups, according to the topology
+		let ids: Vec<_> = rollups.into_iter().flatten().map(|r| r.id()).collect();
+		for channel in hrmp.channels(&ids)? {
+			builder = builder.with_hrmp_channel(|builder| {
+				builder
+					.with_sender(channel.sender)
+					.with_recipient(channel.recipient)
+					.with_max_capacity(channel.max_capacity)
+					.with_max_message_size(channel.max_message_size)
+			})
 		}
 
 		Ok(NetworkConfiguration(
@@ -486,7 +513,13 @@ impl NetworkConfiguration {
 			})
 			// Add global settings
 			.with_global_settings(|settings| {
-				settings.with_network_spawn_timeout(1_000).with_node_spawn_timeout(300)
+				let settings =
+					settings.with_network_spawn_timeout(1_000).with_node_spawn_timeout(300);
+				// Retain any base directory, such as when relaunching from a snapshot
+				match self.0.global_settings().base_dir() {
+					Some(base_dir) => settings.with_base_dir(base_dir),
+					None => settings,
+				}
 			});
 
 		// Process parachains
@@ -1924,8 +1957,12 @@ chain = "paseo-local"
 					.for_each(|(i, rollup)| rollup.set_port(port + i as u16 + 1));
 				let relay_chain = relay.chain();
 
-				let config =
-					NetworkConfiguration::build(relay, Some(port), Some(rollups.as_slice()))?;
+				let config = NetworkConfiguration::build(
+					relay,
+					Some(port),
+					Some(rollups.as_slice()),
+					&HrmpTopology::Mesh,
+				)?;
 
 				let relay_config = config.0.relaychain();
 				assert_eq!(relay_config.chain().as_str(), relay_chain);
@@ -1984,6 +2021,68 @@ chain = "paseo-local"
 			Ok(())
 		}
 
+		#[test]
+		fn build_with_hrmp_topology_works() -> Result<(), Error> {
+			let rollups = rollups(&Paseo).to_vec();
+			let hub = rollups.first().unwrap().id();
+
+			let config = NetworkConfiguration::build(
+				Paseo,
+				None,
+				Some(rollups.as_slice()),
+				&HrmpTopology::None,
+			)?;
+			assert!(config.0.hrmp_channels().is_empty());
+
+			let config = NetworkConfiguration::build(
+				Paseo,
+				None,
+				Some(rollups.as_slice()),
+				&HrmpTopology::Star { hub },
+			)?;
+			let channels = config.0.hrmp_channels();
+			assert_eq!(channels.len(), (rollups.len() - 1) * 2);
+			assert!(channels.iter().all(|c| c.sender() == hub || c.recipient() == hub));
+
+			let other = rollups.last().unwrap().id();
+			let channel = HrmpChannel {
+				sender: other,
+				recipient: hub,
+				max_capacity: 10,
+				max_message_size: 1_024,
+			};
+			let config = NetworkConfiguration::build(
+				Paseo,
+				None,
+				Some(rollups.as_slice()),
+				&HrmpTopology::Channels(vec![channel]),
+			)?;
+			let channels = config.0.hrmp_channels();
+			assert_eq!(channels.len(), 1);
+			let opened = channels.first().unwrap();
+			assert_eq!(
+				(
+					opened.sender(),
+					opened.recipient(),
+					opened.max_capacity(),
+					opened.max_message_size()
+				),
+				(other, hub, 10, 1_024)
+			);
+
+			// Channels must be between rollups of the network.
+			assert!(matches!(
+				NetworkConfiguration::build(
+					Paseo,
+					None,
+					None,
+					&HrmpTopology::Channels(vec![channel])
+				),
+				Err(Error::HrmpTopologyError(..))
+			));
+			Ok(())
+		}
+
 		#[test]
 		fn adapt_works() -> Result<(), Error> {
 			let config = Builder::new().suffix(".toml").tempfile()?;
@@ -0,0 +1,193 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use crate::errors::Error;
+use pop_common::Status;
+use scale_value::{Value, ValueDef};
+use std::time::Duration;
+use subxt::{dynamic, OnlineClient, PolkadotConfig};
+use zombienet_sdk::{LocalFileSystem, Network};
+
+/// The conditions for a launched network to be considered ready.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Readiness {
+	/// The number of blocks to be finalized by the relay chain.
+	pub relay_chain_blocks: u32,
+	/// The number of blocks to be produced by each rollup, once onboarded.
+	pub rollup_blocks: u32,
+	/// The maximum duration to wait for the network to become ready.
+	pub timeout: Duration,
+}
+
+impl Default for Readiness {
+	fn default() -> Self {
+		Self { relay_chain_blocks: 1, rollup_blocks: 1, timeout: Duration::from_secs(300) }
+	}
+}
+
+impl Readiness {
+	/// Waits until the relay chain has finalized the required number of blocks, and each rollup
+	/// has been onboarded and produced the required number of blocks.
+	///
+	/// # Arguments
+	/// * `network` - The launched network.
+	/// * `status` - Used to observe the progress of the checks.
+	pub async fn wait(
+		&self,
+		network: &Network<LocalFileSystem>,
+		status: &impl Status,
+	) -> Result<(), Error> {
+		tokio::time::timeout(self.timeout, async {
+			let relay_chain = network.relaychain();
+			let node = relay_chain
+				.nodes()
+				.into_iter()
+				.next()
+				.ok_or_else(|| Error::NetworkNotReady("the relay chain has no nodes".into()))?;
+			let relay_client = node.wait_client::<PolkadotConfig>().await?;
+			status.update(&format!(
+				"Waiting for {} to finalize {} block(s)...",
+				relay_chain.chain(),
+				self.relay_chain_blocks
+			));
+			wait_for_blocks(&relay_client, self.relay_chain_blocks, true).await?;
+
+			let mut rollups = network.parachains();
+			rollups.sort_by_key(|r| r.para_id());
+			for rollup in rollups {
+				let id = rollup.para_id();
+				status.update(&format!("Waiting for rollup {id} to be onboarded..."));
+				wait_for_onboarding(&relay_client, id).await?;
+				let Some(collator) = rollup.collators().into_iter().next() else {
+					continue;
+				};
+				let client = collator.wait_client::<PolkadotConfig>().await?;
+				status.update(&format!(
+					"Waiting for rollup {id} to produce {} block(s)...",
+					self.rollup_blocks
+				));
+				wait_for_blocks(&client, self.rollup_blocks, false).await?;
+			}
+			Ok::<(), Error>(())
+		})
+		.await
+		.map_err(|_| {
+			Error::NetworkNotReady(format!("timed out after {} seconds", self.timeout.as_secs()))
+		})?
+	}
+}
+
+/// Waits until a chain has produced, or finalized, the specified number of blocks beyond its
+/// latest block when called.
+///
+/// # Arguments
+/// * `client` - The client used to interact with the chain.
+/// * `blocks` - The number of blocks.
+/// * `finalized` - Whether the blocks are to be finalized.
+pub async fn wait_for_blocks(
+	client: &OnlineClient<PolkadotConfig>,
+	blocks: u32,
+	finalized: bool,
+) -> Result<(), Error> {
+	let blocks_client = client.blocks();
+	let mut subscription = match finalized {
+		true => blocks_client.subscribe_finalized().await,
+		false => blocks_client.subscribe_best().await,
+	}
+	.map_err(|e| Error::SubXtError(e.into()))?;
+	let mut count = BlockCount::new(blocks);
+	while let Some(block) = subscription.next().await {
+		if count.reached(block.map_err(|e| Error::SubXtError(e.into()))?.number()) {
+			return Ok(());
+		}
+	}
+	Err(Error::NetworkNotReady("the block subscription ended unexpectedly".into()))
+}
+
+// Counts the blocks observed beyond the first, which is the latest block when subscribing.
+struct BlockCount {
+	start: Option<u32>,
+	blocks: u32,
+}
+
+impl BlockCount {
+	fn new(blocks: u32) -> Self {
+		Self { start: None, blocks }
+	}
+
+	// Whether the required number of blocks has been reached with the observed block.
+	fn reached(&mut self, number: u32) -> bool {
+		let start = *self.start.get_or_insert(number);
+		number >= start.saturating_add(self.blocks)
+	}
+}
+
+// Waits until the relay chain has onboarded a rollup, as of its latest finalized block.
+async fn wait_for_onboarding(client: &OnlineClient<PolkadotConfig>, id: u32) -> Result<(), Error> {
+	let address = dynamic::storage("Paras", "ParaLifecycles", vec![Value::u128(id as u128)]);
+	let mut subscription = client
+		.blocks()
+		.subscribe_finalized()
+		.await
+		.map_err(|e| Error::SubXtError(e.into()))?;
+	while let Some(block) = subscription.next().await {
+		let block = block.map_err(|e| Error::SubXtError(e.into()))?;
+		let lifecycle = block
+			.storage()
+			.fetch(&address)
+			.await
+			.map_err(|e| Error::StorageQueryError(e.to_string()))?
+			.map(|lifecycle| lifecycle.to_value())
+			.transpose()
+			.map_err(|e| Error::StorageQueryError(e.to_string()))?;
+		if lifecycle.as_ref().is_some_and(is_onboarded) {
+			return Ok(());
+		}
+	}
+	Err(Error::NetworkNotReady("the block subscription ended unexpectedly".into()))
+}
+
+// Whether the lifecycle of a para indicates that it has been onboarded as a parachain.
+fn is_onboarded(lifecycle: &Value<u32>) -> bool {
+	matches!(&lifecycle.value, ValueDef::Variant(variant) if variant.name == "Parachain")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn is_onboarded_works() {
+		assert!(is_onboarded(&Value::unnamed_variant("Parachain", vec![]).map_context(|_| 0)));
+		for lifecycle in ["Onboarding", "Parathread", "UpgradingParathread", "OffboardingParachain"]
+		{
+			assert!(!is_onboarded(&Value::unnamed_variant(lifecycle, vec![]).map_context(|_| 0)));
+		}
+		assert!(!is_onboarded(&Value::u128(0).map_context(|_| 0)));
+	}
+
+	#[test]
+	fn block_count_works() {
+		// A chain which had already produced blocks, such as a rollup before being onboarded.
+		let mut count = BlockCount::new(2);
+		assert!(!count.reached(10));
+		assert!(!count.reached(11));
+		// Blocks may be observed more than once, such as following a re-organisation.
+		assert!(!count.reached(11));
+		assert!(count.reached(12));
+
+		let mut count = BlockCount::new(0);
+		assert!(count.reached(10));
+	}
+
+	#[test]
+	fn readiness_default_works() {
+		assert_eq!(
+			Readiness::default(),
+			Readiness {
+				relay_chain_blocks: 1,
+				rollup_blocks: 1,
+				timeout: Duration::from_secs(300)
+			}
+		);
+	}
+}
@@ -0,0 +1,496 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use super::{
+	detached::{check_name, node_pid, signal},
+	DetachedNetwork, NetworkConfiguration,
+};
+use crate::errors::Error;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use std::{
+	collections::BTreeMap,
+	fs::{self, File},
+	iter::once,
+	path::{Path, PathBuf},
+	time::{SystemTime, UNIX_EPOCH},
+};
+use tar::{Archive, Builder, Header};
+use toml_edit::{value, DocumentMut};
+
+/// The directory within the cache where snapshots are stored.
+const SNAPSHOTS_DIR: &str = ".snapshots";
+/// The file recording how a network was launched, within both its directory and snapshots.
+const LAUNCH_FILE: &str = "launch.json";
+/// The file describing a snapshot, within the snapshot archive.
+const MANIFEST_FILE: &str = "snapshot.json";
+/// The network configuration file written when a snapshot is restored.
+const CONFIG_FILE: &str = "network.toml";
+/// The directory containing the chain specifications, within the snapshot archive.
+const SPECS_DIR: &str = "specs";
+/// The directories of each node containing its databases and keystores.
+const NODE_DIRS: [&str; 2] = ["data", "relay-data"];
+
+/// A snapshot of the chain state of a local network, from which the network can be relaunched.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Snapshot {
+	/// The name of the snapshot.
+	pub name: String,
+	/// The name of the network from which the snapshot was taken.
+	pub network: String,
+	/// The relay chain of the network.
+	pub relay_chain: String,
+	/// The identifiers of the rollups of the network.
+	pub rollups: Vec<u32>,
+	/// The version of the relay chain binary used by the network, if known.
+	pub relay_chain_version: Option<String>,
+	/// When the snapshot was taken, in seconds since the Unix epoch.
+	pub created: u64,
+}
+
+/// How a network was launched, as required to later relaunch it from a snapshot.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub(crate) struct Launch {
+	// The version of the relay chain binary, if known.
+	relay_chain_version: Option<String>,
+	// The network configuration, prior to binary paths being resolved.
+	config: String,
+	// The file name of the chain specification of the relay chain.
+	relay_chain_spec: String,
+	// The file names of the chain specifications of the rollups, keyed by identifier.
+	rollup_chain_specs: BTreeMap<u32, String>,
+}
+
+impl Launch {
+	/// Records how a network is being launched.
+	///
+	/// # Arguments
+	/// * `config` - The configuration of the network.
+	/// * `relay_chain_version` - The version of the relay chain binary, if known.
+	pub(crate) fn new(
+		config: &NetworkConfiguration,
+		relay_chain_version: Option<&str>,
+	) -> Result<Self, Error> {
+		let mut toml = config
+			.0
+			.dump_to_toml()
+			.map_err(|e| Error::Config(e.to_string()))?
+			.parse::<DocumentMut>()
+			.map_err(|e| Error::TomlError(e.into()))?;
+		// Retain any rollups configured as EVM based via `force_decorator`.
+		if let Some(parachains) =
+			toml.get_mut("parachains").and_then(|p| p.as_array_of_tables_mut())
+		{
+			for parachain in parachains.iter_mut() {
+				let id = parachain.get("id").and_then(|i| i.as_integer()).map(|i| i as u32);
+				if id.is_some_and(|id| config.1.contains(&id)) {
+					parachain["evm_based"] = value(true);
+				}
+			}
+		}
+		// Chain specifications are written to the network directory using the name of the chain,
+		// falling back to the rollup identifier.
+		let rollup_chain_specs = config
+			.0
+			.parachains()
+			.into_iter()
+			.filter(|p| p.is_cumulus_based())
+			.map(|p| {
+				let name = p
+					.chain()
+					.map(|c| c.as_str().to_string())
+					.filter(|c| !c.is_empty())
+					.unwrap_or_else(|| p.id().to_string());
+				(p.id(), format!("{name}.json"))
+			})
+			.collect();
+		Ok(Self {
+			relay_chain_version: relay_chain_version.map(String::from),
+			config: toml.to_string(),
+			relay_chain_spec: format!("{}.json", config.0.relaychain().chain().as_str()),
+			rollup_chain_specs,
+		})
+	}
+
+	/// Saves the record within the directory of the network.
+	///
+	/// # Arguments
+	/// * `base_dir` - The directory containing the state of the network.
+	pub(crate) fn save(&self, base_dir: &Path) -> Result<(), Error> {
+		fs::write(base_dir.join(LAUNCH_FILE), serde_json::to_string_pretty(self)?)?;
+		Ok(())
+	}
+
+	// Loads the record from the directory of a network.
+	fn load(base_dir: &Path) -> Result<Self, Error> {
+		let path = base_dir.join(LAUNCH_FILE);
+		if !path.exists() {
+			return Err(Error::SnapshotError(format!(
+				"{} not found - the network was launched by an earlier version",
+				path.display()
+			)));
+		}
+		Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+	}
+}
+
+impl Snapshot {
+	/// The directory where snapshots are stored.
+	///
+	/// # Arguments
+	/// * `cache` - The location used for caching binaries.
+	pub fn dir(cache: &Path) -> PathBuf {
+		cache.join(SNAPSHOTS_DIR)
+	}
+
+	/// The archive containing the state of a snapshot.
+	///
+	/// # Arguments
+	/// * `dir` - The directory where snapshots are stored.
+	/// * `name` - The name of the snapshot.
+	pub fn archive(dir: &Path, name: &str) -> Result<PathBuf, Error> {
+		check_name(name)?;
+		Ok(dir.join(format!("{name}.tar.gz")))
+	}
+
+	/// Takes a snapshot of a network running in the background, archiving the databases and
+	/// keystores of its nodes together with its configuration and chain specifications.
+	///
+	/// The nodes are paused whilst their state is archived and resumed afterwards, so the snapshot
+	/// is consistent with the state of each node at the time it was paused.
+	///
+	/// # Arguments
+	/// * `dir` - The directory where snapshots are stored.
+	/// * `name` - The name of the snapshot, replacing any existing snapshot of the same name.
+	/// * `network` - The network running in the background.
+	pub fn create(dir: &Path, name: &str, network: &DetachedNetwork) -> Result<Self, Error> {
+		check_name(name)?;
+		let launch = Launch::load(&network.base_dir)?;
+		let snapshot = Snapshot {
+			name: name.to_string(),
+			network: network.name.clone(),
+			relay_chain: network.relay_chain.chain.clone(),
+			rollups: network.rollups.iter().filter_map(|r| r.id).collect(),
+			relay_chain_version: launch.relay_chain_version.clone(),
+			created: SystemTime::now()
+				.duration_since(UNIX_EPOCH)
+				.map(|d| d.as_secs())
+				.unwrap_or_default(),
+		};
+
+		fs::create_dir_all(dir)?;
+		let partial = dir.join(format!("{name}.tar.gz.partial"));
+		let nodes: Vec<_> = once(&network.relay_chain)
+			.chain(&network.rollups)
+			.flat_map(|c| &c.nodes)
+			.map(|n| n.name.as_str())
+			.collect();
+		let paused =
+			Paused::new(nodes.iter().filter_map(|n| node_pid(&network.base_dir, n)).collect());
+		let result = write_archive(&partial, &snapshot, &launch, &network.base_dir, &nodes);
+		drop(paused);
+		if let Err(e) = result {
+			let _ = fs::remove_file(&partial);
+			return Err(e);
+		}
+
+		fs::rename(&partial, Self::archive(dir, name)?)?;
+		fs::write(manifest(dir, name)?, serde_json::to_string_pretty(&snapshot)?)?;
+		Ok(snapshot)
+	}
+
+	/// Loads a snapshot, if it exists.
+	///
+	/// # Arguments
+	/// * `dir` - The directory where snapshots are stored.
+	/// * `name` - The name of the snapshot.
+	pub fn load(dir: &Path, name: &str) -> Result<Option<Self>, Error> {
+		let path = manifest(dir, name)?;
+		if !path.exists() || !Self::archive(dir, name)?.exists() {
+			return Ok(None);
+		}
+		Ok(Some(serde_json::from_str(&fs::read_to_string(path)?)?))
+	}
+
+	/// Loads all snapshots, sorted by name.
+	///
+	/// # Arguments
+	/// * `dir` - The directory where snapshots are stored.
+	pub fn list(dir: &Path) -> Result<Vec<Self>, Error> {
+		if !dir.exists() {
+			return Ok(Vec::new());
+		}
+		let mut snapshots = Vec::new();
+		for entry in fs::read_dir(dir)? {
+			let path = entry?.path();
+			if path.extension().is_some_and(|e| e == "json") {
+				snapshots.push(serde_json::from_str::<Self>(&fs::read_to_string(path)?)?);
+			}
+		}
+		snapshots.sort_by(|a, b| a.name.cmp(&b.name));
+		Ok(snapshots)
+	}
+
+	/// Removes the snapshot.
+	///
+	/// # Arguments
+	/// * `dir` - The directory where snapshots are stored.
+	pub fn remove(&self, dir: &Path) -> Result<(), Error> {
+		for path in [manifest(dir, &self.name)?, Self::archive(dir, &self.name)?] {
+			if path.exists() {
+				fs::remove_file(path)?;
+			}
+		}
+		Ok(())
+	}
+
+	/// Restores the snapshot into a directory, returning a network configuration which launches
+	/// the network from the restored state.
+	///
+	/// # Arguments
+	/// * `dir` - The directory where snapshots are stored.
+	/// * `target` - The empty directory to restore the snapshot into, which is then used as the
+	///   directory of the relaunched network.
+	pub fn restore(&self, dir: &Path, target: &Path) -> Result<NetworkConfiguration, Error> {
+		if target.exists() && fs::read_dir(target)?.next().is_some() {
+			return Err(Error::SnapshotError(format!("{} is not empty", target.display())));
+		}
+		fs::create_dir_all(target)?;
+		Archive::new(GzDecoder::new(File::open(Self::archive(dir, &self.name)?)?))
+			.unpack(target)?;
+		let launch: Launch = serde_json::from_str(&fs::read_to_string(target.join(LAUNCH_FILE))?)?;
+
+		// Launch the network from the restored state, using the chain specifications of the
+		// snapshot rather than generating new ones.
+		let specs = target.join(SPECS_DIR);
+		let path = |path: PathBuf| value(path.to_string_lossy().as_ref());
+		let mut config =
+			launch.config.parse::<DocumentMut>().map_err(|e| Error::TomlError(e.into()))?;
+		config["settings"]["base_dir"] = path(target.to_path_buf());
+		config["relaychain"]["chain_spec_path"] = path(specs.join(&launch.relay_chain_spec));
+		if let Some(parachains) =
+			config.get_mut("parachains").and_then(|p| p.as_array_of_tables_mut())
+		{
+			for parachain in parachains.iter_mut() {
+				let id = parachain.get("id").and_then(|i| i.as_integer()).map(|i| i as u32);
+				if let Some(spec) = id.and_then(|id| launch.rollup_chain_specs.get(&id)) {
+					parachain["chain_spec_path"] = path(specs.join(spec));
+				}
+			}
+		}
+		let config_path = target.join(CONFIG_FILE);
+		fs::write(&config_path, config.to_string())?;
+		NetworkConfiguration::try_from(config_path.as_path())
+	}
+}
+
+// The path of the file describing a snapshot.
+fn manifest(dir: &Path, name: &str) -> Result<PathBuf, Error> {
+	check_name(name)?;
+	Ok(dir.join(format!("{name}.json")))
+}
+
+// Writes the archive of a snapshot, containing its description, the launch record and chain
+// specifications of the network, and the state of each node.
+fn write_archive(
+	path: &Path,
+	snapshot: &Snapshot,
+	launch: &Launch,
+	base_dir: &Path,
+	nodes: &[&str],
+) -> Result<(), Error> {
+	let mut archive = Builder::new(GzEncoder::new(File::create(path)?, Compression::default()));
+	append_json(&mut archive, MANIFEST_FILE, snapshot)?;
+	append_json(&mut archive, LAUNCH_FILE, launch)?;
+	for spec in once(&launch.relay_chain_spec).chain(launch.rollup_chain_specs.values()) {
+		let path = base_dir.join(spec);
+		if !path.exists() {
+			return Err(Error::SnapshotError(format!(
+				"the chain specification {} could not be found",
+				path.display()
+			)));
+		}
+		archive.append_path_with_name(&path, Path::new(SPECS_DIR).join(spec))?;
+	}
+	for node in nodes {
+		for node_dir in NODE_DIRS {
+			let path = base_dir.join(node).join(node_dir);
+			if path.exists() {
+				archive.append_dir_all(Path::new(node).join(node_dir), &path)?;
+			}
+		}
+	}
+	archive.into_inner()?.finish()?;
+	Ok(())
+}
+
+// Appends a value to an archive as a JSON file.
+fn append_json<W: std::io::Write>(
+	archive: &mut Builder<W>,
+	path: &str,
+	value: &impl Serialize,
+) -> Result<(), Error> {
+	let contents = serde_json::to_vec_pretty(value)?;
+	let mut header = Header::new_gnu();
+	header.set_size(contents.len() as u64);
+	header.set_mode(0o644);
+	header.set_cksum();
+	archive.append_data(&mut header, path, contents.as_slice())?;
+	Ok(())
+}
+
+// Processes which are paused, and then resumed once dropped.
+struct Paused(Vec<u32>);
+
+impl Paused {
+	fn new(pids: Vec<u32>) -> Self {
+		for pid in &pids {
+			signal("STOP", *pid);
+		}
+		Self(pids)
+	}
+}
+
+impl Drop for Paused {
+	fn drop(&mut self) {
+		for pid in &self.0 {
+			signal("CONT", *pid);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		registry::rollups,
+		up::{DetachedChain, DetachedNode, HrmpTopology, Relay::Paseo},
+	};
+	use anyhow::Result;
+
+	// Creates the directory of a launched network, returning the network along with the name of
+	// the chain of its rollup.
+	fn network(base_dir: &Path) -> Result<(DetachedNetwork, String)> {
+		fs::create_dir_all(base_dir)?;
+		let rollups = &rollups(&Paseo)[..1];
+		let config = NetworkConfiguration::build(Paseo, None, Some(rollups), &HrmpTopology::Mesh)?;
+		Launch::new(&config, Some("stable2503"))?.save(base_dir)?;
+		let chain = rollups[0].chain().to_string();
+		fs::write(base_dir.join("paseo-local.json"), "{}")?;
+		fs::write(base_dir.join(format!("{chain}.json")), "{}")?;
+		let node = |name: &str, port: u16| -> Result<DetachedNode> {
+			let data = base_dir.join(name).join("data/chains/local/db");
+			fs::create_dir_all(&data)?;
+			fs::write(data.join("CURRENT"), name)?;
+			fs::write(base_dir.join(name).join(format!("{name}.log")), "")?;
+			Ok(DetachedNode {
+				name: name.to_string(),
+				endpoint: format!("ws://127.0.0.1:{port}"),
+				pid: None,
+				log: base_dir.join(name).join(format!("{name}.log")),
+			})
+		};
+		let collator = format!("{}-collator", rollups[0].name());
+		let network = DetachedNetwork {
+			name: "paseo".to_string(),
+			pid: std::process::id(),
+			base_dir: base_dir.to_path_buf(),
+			relay_chain: DetachedChain {
+				chain: "paseo-local".to_string(),
+				id: None,
+				nodes: vec![node("alice", 9944)?, node("bob", 9945)?],
+			},
+			rollups: vec![DetachedChain {
+				chain: chain.clone(),
+				id: Some(rollups[0].id()),
+				nodes: vec![node(&collator, 9946)?],
+			}],
+		};
+		Ok((network, chain))
+	}
+
+	#[test]
+	fn launch_works() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let rollups = &rollups(&Paseo)[..1];
+		let config = NetworkConfiguration::build(Paseo, None, Some(rollups), &HrmpTopology::Mesh)?;
+		let launch = Launch::new(&config, None)?;
+		assert_eq!(launch.relay_chain_version, None);
+		assert_eq!(launch.relay_chain_spec, "paseo-local.json");
+		assert_eq!(
+			launch.rollup_chain_specs,
+			BTreeMap::from([(rollups[0].id(), format!("{}.json", rollups[0].chain()))])
+		);
+		launch.save(temp_dir.path())?;
+		assert_eq!(Launch::load(temp_dir.path())?, launch);
+		Ok(())
+	}
+
+	#[test]
+	fn create_fails_without_launch_record() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let (network, _) = network(&temp_dir.path().join("network"))?;
+		fs::remove_file(network.base_dir.join(LAUNCH_FILE))?;
+		assert!(matches!(
+			Snapshot::create(&temp_dir.path().join("snapshots"), "test", &network),
+			Err(Error::SnapshotError(..))
+		));
+		Ok(())
+	}
+
+	#[test]
+	fn snapshot_works() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let (network, chain) = network(&temp_dir.path().join("network"))?;
+		let dir = Snapshot::dir(temp_dir.path());
+		assert_eq!(dir, temp_dir.path().join(SNAPSHOTS_DIR));
+		assert!(Snapshot::list(&dir)?.is_empty());
+		assert_eq!(Snapshot::load(&dir, "test")?, None);
+
+		let snapshot = Snapshot::create(&dir, "test", &network)?;
+		assert_eq!(snapshot.name, "test");
+		assert_eq!(snapshot.network, "paseo");
+		assert_eq!(snapshot.relay_chain, "paseo-local");
+		assert_eq!(
+			snapshot.rollups,
+			network.rollups.iter().filter_map(|r| r.id).collect::<Vec<_>>()
+		);
+		assert_eq!(snapshot.relay_chain_version.as_deref(), Some("stable2503"));
+		assert!(Snapshot::archive(&dir, "test")?.exists());
+		assert_eq!(Snapshot::load(&dir, "test")?, Some(snapshot.clone()));
+		assert_eq!(Snapshot::list(&dir)?, vec![snapshot.clone()]);
+
+		// Restore into a new directory.
+		let target = temp_dir.path().join("restored");
+		let config = snapshot.restore(&dir, &target)?;
+		assert_eq!(config.0.global_settings().base_dir(), Some(target.as_path()));
+		assert_eq!(
+			config.0.relaychain().chain_spec_path().map(|p| p.to_string()),
+			Some(target.join(SPECS_DIR).join("paseo-local.json").to_string_lossy().to_string())
+		);
+		let rollup = config.0.parachains();
+		assert_eq!(
+			rollup.first().and_then(|p| p.chain_spec_path()).map(|p| p.to_string()),
+			Some(
+				target
+					.join(SPECS_DIR)
+					.join(format!("{chain}.json"))
+					.to_string_lossy()
+					.to_string()
+			)
+		);
+		for node in ["alice", "bob"] {
+			assert_eq!(
+				fs::read_to_string(target.join(node).join("data/chains/local/db/CURRENT"))?,
+				node
+			);
+			// Logs are not included.
+			assert!(!target.join(node).join(format!("{node}.log")).exists());
+		}
+		assert!(matches!(snapshot.restore(&dir, &target), Err(Error::SnapshotError(..))));
+
+		snapshot.remove(&dir)?;
+		assert_eq!(Snapshot::load(&dir, "test")?, None);
+		assert!(!Snapshot::archive(&dir, "test")?.exists());
+		Ok(())
+	}
+}
@@ -0,0 +1,373 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use super::{
+	xcm::{XcmMessage, XcmProgram},
+	NetworkChain,
+};
+use crate::{
+	call::{construct_sudo_extrinsic, set_up_client, submit_extrinsic_with_events},
+	errors::Error,
+	fork::HostFunctions,
+};
+use sc_executor::WasmExecutor;
+use scale::Decode;
+use sp_core::blake2_256;
+use sp_core_inkv6::traits::ReadRuntimeVersion;
+use sp_state_machine::BasicExternalities;
+use sp_version::RuntimeVersion;
+use std::time::Duration;
+use subxt::{
+	dynamic::{self, Value},
+	tx::{DynamicPayload, Payload},
+	OnlineClient, SubstrateConfig,
+};
+use url::Url;
+
+// The events indicating that a new runtime has been stored, either for immediate enactment or as
+// the pending validation function of a rollup.
+const CODE_STORED: [&str; 2] = ["System::CodeUpdated", "ParachainSystem::ValidationFunctionStored"];
+// The maximum duration to wait for an authorization sent from the relay chain to be processed by
+// a rollup.
+const AUTHORIZATION_TIMEOUT: Duration = Duration::from_secs(120);
+// How often to check whether a rollup has processed an authorization.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How a runtime upgrade is applied to a chain.
+#[derive(Clone, Debug, PartialEq)]
+pub enum UpgradePath {
+	/// The code is set using `System::set_code`, dispatched with `Sudo::sudo_unchecked_weight`.
+	SetCode,
+	/// The code hash is authorized using `System::authorize_upgrade`, dispatched with
+	/// `Sudo::sudo`, before the code is applied using `System::apply_authorized_upgrade`.
+	AuthorizeUpgrade,
+	/// The code of a rollup is upgraded as with [`UpgradePath::AuthorizeUpgrade`] if the rollup
+	/// has a sudo pallet. Otherwise the code hash is authorized by a message sent from the relay
+	/// chain with `Sudo::sudo`, which transacts `System::authorize_upgrade` at the rollup.
+	Rollup {
+		/// Endpoint of a node of the relay chain.
+		relay: Url,
+		/// The identifier of the rollup.
+		id: u32,
+	},
+}
+
+/// A runtime upgrade of a chain within a local network.
+pub struct RuntimeUpgrade {
+	client: OnlineClient<SubstrateConfig>,
+	url: Url,
+	code: Vec<u8>,
+	version: RuntimeVersion,
+	path: UpgradePath,
+	// The relay chain authorizing the upgrade of a rollup without a sudo pallet.
+	relay: Option<(OnlineClient<SubstrateConfig>, Url)>,
+}
+
+impl RuntimeUpgrade {
+	/// Prepares the upgrade of a chain to a new runtime, ensuring that the chain can be upgraded
+	/// and that the spec version of the new runtime exceeds that of the current runtime.
+	///
+	/// # Arguments
+	/// * `url` - Endpoint of a node of the chain.
+	/// * `code` - The code of the new runtime, which may be compressed.
+	/// * `path` - How the upgrade is applied.
+	pub async fn new(url: &Url, code: Vec<u8>, path: UpgradePath) -> Result<Self, Error> {
+		let version = runtime_version(&code)?;
+		let client = set_up_client(url.as_str()).await?;
+		let relay = match &path {
+			UpgradePath::Rollup { relay, .. } if !has_sudo(&client) =>
+				Some((set_up_client(relay.as_str()).await?, relay.clone())),
+			_ => None,
+		};
+		if !has_sudo(relay.as_ref().map_or(&client, |(relay, _)| relay)) {
+			let chain = if relay.is_some() { "relay chain" } else { "chain" };
+			return Err(Error::RuntimeUpgradeError(format!(
+				"the {chain} has no sudo pallet with which to authorize the upgrade"
+			)));
+		}
+		check_version(client.runtime_version().spec_version, version.spec_version)?;
+		Ok(Self { client, url: url.clone(), code, version, path, relay })
+	}
+
+	/// The spec version of the current runtime of the chain.
+	pub fn current_version(&self) -> u32 {
+		self.client.runtime_version().spec_version
+	}
+
+	/// The version of the new runtime.
+	pub fn version(&self) -> &RuntimeVersion {
+		&self.version
+	}
+
+	/// Submits the extrinsics applying the upgrade, returning the hashes of the extrinsics once
+	/// finalized.
+	///
+	/// # Arguments
+	/// * `suri` - The secret URI of the sudo account, used to sign the extrinsics.
+	pub async fn submit(&self, suri: &str) -> Result<Vec<String>, Error> {
+		let mut hashes = Vec::new();
+		match (&self.path, &self.relay) {
+			(UpgradePath::SetCode, _) => {
+				let xt = set_code_call(&self.code);
+				hashes.push(submit(&self.client, &self.url, xt, suri, &CODE_STORED).await?);
+			},
+			(UpgradePath::Rollup { id, .. }, Some((relay, relay_url))) => {
+				let call = authorize_upgrade_call(&self.code)
+					.encode_call_data(&self.client.metadata())
+					.map_err(|e| Error::CallDataEncodingError(e.to_string()))?;
+				let message = XcmMessage {
+					origin: NetworkChain::Relay,
+					destination: NetworkChain::Rollup(*id),
+					program: XcmProgram::Transact { call, superuser: true },
+				};
+				let xt = message.extrinsic(&relay.metadata())?;
+				hashes.push(submit(relay, relay_url, xt, suri, &["XcmPallet::Sent"]).await?);
+				self.wait_for_authorization().await?;
+				let xt = apply_authorized_upgrade_call(&self.code);
+				hashes.push(submit(&self.client, &self.url, xt, suri, &CODE_STORED).await?);
+			},
+			(UpgradePath::AuthorizeUpgrade | UpgradePath::Rollup { .. }, _) => {
+				let xt = construct_sudo_extrinsic(authorize_upgrade_call(&self.code));
+				let expected = ["System::UpgradeAuthorized"];
+				hashes.push(submit(&self.client, &self.url, xt, suri, &expected).await?);
+				let xt = apply_authorized_upgrade_call(&self.code);
+				hashes.push(submit(&self.client, &self.url, xt, suri, &CODE_STORED).await?);
+			},
+		}
+		Ok(hashes)
+	}
+
+	// Waits until the upgrade authorized by a message sent from the relay chain is stored by the
+	// chain.
+	async fn wait_for_authorization(&self) -> Result<(), Error> {
+		let authorized = dynamic::storage("System", "AuthorizedUpgrade", Vec::<Value>::new());
+		tokio::time::timeout(AUTHORIZATION_TIMEOUT, async {
+			loop {
+				let storage = self
+					.client
+					.storage()
+					.at_latest()
+					.await
+					.map_err(|e| Error::SubXtError(e.into()))?;
+				if storage
+					.fetch(&authorized)
+					.await
+					.map_err(|e| Error::SubXtError(e.into()))?
+					.is_some()
+				{
+					return Ok(());
+				}
+				tokio::time::sleep(POLL_INTERVAL).await;
+			}
+		})
+		.await
+		.map_err(|_| {
+			Error::RuntimeUpgradeError(format!(
+				"the upgrade authorized by the relay chain was not stored within {} seconds",
+				AUTHORIZATION_TIMEOUT.as_secs()
+			))
+		})?
+	}
+
+	/// Waits until the new runtime is enacted by the chain, returning its spec version.
+	///
+	/// # Arguments
+	/// * `timeout` - The maximum duration to wait.
+	pub async fn wait_for_enactment(&self, timeout: Duration) -> Result<u32, Error> {
+		let expected = self.version.spec_version;
+		tokio::time::timeout(timeout, async {
+			let mut versions = self
+				.client
+				.backend()
+				.stream_runtime_version()
+				.await
+				.map_err(|e| Error::SubXtError(e.into()))?;
+			while let Some(version) = versions.next().await {
+				let version = version.map_err(|e| Error::SubXtError(e.into()))?;
+				if version.spec_version == expected {
+					return Ok(version.spec_version);
+				}
+			}
+			Err(Error::RuntimeUpgradeError("the runtime version subscription ended".into()))
+		})
+		.await
+		.map_err(|_| {
+			Error::RuntimeUpgradeError(format!(
+				"spec version {expected} was not enacted within {} seconds",
+				timeout.as_secs()
+			))
+		})?
+	}
+
+	/// Waits until the chain has produced the specified number of blocks, returning the number of
+	/// the latest block.
+	///
+	/// # Arguments
+	/// * `blocks` - The number of blocks.
+	/// * `timeout` - The maximum duration to wait.
+	pub async fn wait_for_blocks(&self, blocks: u32, timeout: Duration) -> Result<u32, Error> {
+		tokio::time::timeout(timeout, async {
+			let mut subscription = self
+				.client
+				.blocks()
+				.subscribe_best()
+				.await
+				.map_err(|e| Error::SubXtError(e.into()))?;
+			let mut start = None;
+			while let Some(block) = subscription.next().await {
+				let number = block.map_err(|e| Error::SubXtError(e.into()))?.number();
+				let start = *start.get_or_insert(number);
+				if number >= start + blocks {
+					return Ok(number);
+				}
+			}
+			Err(Error::RuntimeUpgradeError("the block subscription ended".into()))
+		})
+		.await
+		.map_err(|_| {
+			Error::RuntimeUpgradeError(format!(
+				"{blocks} block(s) were not produced within {} seconds",
+				timeout.as_secs()
+			))
+		})?
+	}
+}
+
+// Signs and submits an extrinsic, returning its hash once finalized if it emitted any of the
+// expected events.
+async fn submit(
+	client: &OnlineClient<SubstrateConfig>,
+	url: &Url,
+	xt: DynamicPayload,
+	suri: &str,
+	expected: &[&str],
+) -> Result<String, Error> {
+	let submitted = submit_extrinsic_with_events(client, url, xt, suri).await?;
+	// Calls dispatched using sudo succeed even when the dispatched call fails.
+	if !submitted.events.iter().any(|e| expected.contains(&e.as_str())) {
+		return Err(Error::RuntimeUpgradeError(format!(
+			"the upgrade was not applied by extrinsic {}, which emitted: {}",
+			submitted.extrinsic_hash,
+			submitted.events.join(", ")
+		)));
+	}
+	Ok(submitted.extrinsic_hash)
+}
+
+// Whether the chain has a sudo pallet.
+fn has_sudo(client: &OnlineClient<SubstrateConfig>) -> bool {
+	client.metadata().pallet_by_name("Sudo").is_some()
+}
+
+/// Reads the version of a runtime from its code.
+///
+/// # Arguments
+/// * `code` - The code of the runtime, which may be compressed.
+pub fn runtime_version(code: &[u8]) -> Result<RuntimeVersion, Error> {
+	let executor = WasmExecutor::<HostFunctions>::builder().build();
+	let version = executor
+		.read_runtime_version(code, &mut BasicExternalities::default())
+		.map_err(|e| Error::RuntimeUpgradeError(format!("invalid runtime: {e}")))?;
+	RuntimeVersion::decode(&mut version.as_slice())
+		.map_err(|e| Error::RuntimeUpgradeError(format!("invalid runtime version: {e}")))
+}
+
+// Ensures that the spec version of the new runtime exceeds that of the current runtime, as
+// otherwise the upgrade would be rejected by the chain.
+fn check_version(current: u32, new: u32) -> Result<(), Error> {
+	if new <= current {
+		return Err(Error::RuntimeUpgradeError(format!(
+			"the spec version of the new runtime ({new}) must exceed that of the current runtime ({current})"
+		)));
+	}
+	Ok(())
+}
+
+// Sets the code, bypassing the weight of the call as it exceeds that permitted by `Sudo::sudo`.
+fn set_code_call(code: &[u8]) -> DynamicPayload {
+	let set_code = dynamic::tx("System", "set_code", vec![Value::from_bytes(code)]);
+	let weight =
+		Value::named_composite([("ref_time", Value::u128(0)), ("proof_size", Value::u128(0))]);
+	dynamic::tx("Sudo", "sudo_unchecked_weight", vec![set_code.into_value(), weight])
+}
+
+fn authorize_upgrade_call(code: &[u8]) -> DynamicPayload {
+	dynamic::tx("System", "authorize_upgrade", vec![Value::from_bytes(blake2_256(code))])
+}
+
+fn apply_authorized_upgrade_call(code: &[u8]) -> DynamicPayload {
+	dynamic::tx("System", "apply_authorized_upgrade", vec![Value::from_bytes(code)])
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::path::PathBuf;
+
+	// A runtime built from the parachain template.
+	fn runtime() -> Result<Vec<u8>, Error> {
+		Ok(std::fs::read(
+			PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+				.join("../../tests/runtimes/base_parachain_try_runtime.wasm"),
+		)?)
+	}
+
+	#[test]
+	fn runtime_version_works() -> Result<(), Error> {
+		let version = runtime_version(&runtime()?)?;
+		assert!(!version.spec_name.is_empty());
+		assert!(version.spec_version > 0);
+		assert!(matches!(
+			runtime_version(&[0, 1, 2, 3]),
+			Err(Error::RuntimeUpgradeError(message)) if message.starts_with("invalid runtime")
+		));
+		Ok(())
+	}
+
+	#[test]
+	fn check_version_works() {
+		assert!(check_version(1_000, 1_001).is_ok());
+		for new in [999, 1_000] {
+			assert!(matches!(
+				check_version(1_000, new),
+				Err(Error::RuntimeUpgradeError(message)) if message == format!("the spec version of the new runtime ({new}) must exceed that of the current runtime (1000)")
+			));
+		}
+	}
+
+	#[test]
+	fn calls_work() {
+		let code = vec![1, 2, 3];
+		assert_eq!(
+			set_code_call(&code),
+			dynamic::tx(
+				"Sudo",
+				"sudo_unchecked_weight",
+				vec![
+					dynamic::tx("System", "set_code", vec![Value::from_bytes(&code)]).into_value(),
+					Value::named_composite([
+						("ref_time", Value::u128(0)),
+						("proof_size", Value::u128(0)),
+					]),
+				],
+			)
+		);
+		assert_eq!(
+			authorize_upgrade_call(&code),
+			dynamic::tx("System", "authorize_upgrade", vec![Value::from_bytes(blake2_256(&code))])
+		);
+		assert_eq!(
+			apply_authorized_upgrade_call(&code),
+			dynamic::tx("System", "apply_authorized_upgrade", vec![Value::from_bytes(&code)])
+		);
+	}
+
+	#[tokio::test]
+	async fn new_fails_without_chain() -> Result<(), Error> {
+		let url = Url::parse("ws://127.0.0.1:1").expect("valid url");
+		assert!(matches!(
+			RuntimeUpgrade::new(&url, runtime()?, UpgradePath::SetCode).await,
+			Err(Error::ConnectionFailure(_))
+		));
+		Ok(())
+	}
+}
@@ -0,0 +1,686 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use crate::{
+	call::{
+		construct_sudo_extrinsic, format_value, metadata_hash::create_signed_extrinsic, sudo_error,
+	},
+	errors::Error,
+	up::NetworkChain,
+};
+use pop_common::create_signer;
+use scale_value::{Composite, Primitive, ValueDef};
+use sp_core::blake2_256;
+use std::time::Duration;
+use subxt::{
+	backend::{legacy::LegacyRpcMethods, rpc::RpcClient},
+	dynamic::{self, Value},
+	tx::DynamicPayload,
+	utils::H256,
+	Metadata, OnlineClient, SubstrateConfig,
+};
+use url::Url;
+
+// The pallets providing the XCM extrinsics of a relay chain and of a rollup respectively.
+const XCM_PALLETS: [&str; 2] = ["XcmPallet", "PolkadotXcm"];
+// The pallets whose events describe the handling of a message.
+const MESSAGING_PALLETS: [&str; 6] =
+	["XcmPallet", "PolkadotXcm", "XcmpQueue", "MessageQueue", "ParachainSystem", "Sudo"];
+// The weight reserved for a call transacted at the destination, as its reference time and proof
+// size.
+const TRANSACT_WEIGHT: (u128, u128) = (5_000_000_000, 500_000);
+// How often to check whether a chain has produced the next block.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A common XCM program, sent using the XCM pallet of the origin.
+#[derive(Clone, Debug, PartialEq)]
+pub enum XcmProgram {
+	/// Transfers the relay chain token to a beneficiary at the destination, as a reserve
+	/// transfer.
+	ReserveTransfer {
+		/// The account receiving the token.
+		beneficiary: [u8; 32],
+		/// The amount transferred.
+		amount: u128,
+	},
+	/// Teleports the relay chain token to a beneficiary at the destination.
+	Teleport {
+		/// The account receiving the token.
+		beneficiary: [u8; 32],
+		/// The amount teleported.
+		amount: u128,
+	},
+	/// Transacts an encoded call at the destination, without paying for its execution.
+	Transact {
+		/// The encoded call.
+		call: Vec<u8>,
+		/// Whether the message is sent using sudo, so that the call is dispatched with a
+		/// superuser origin rather than the sovereign account of the sender.
+		superuser: bool,
+	},
+}
+
+/// A message sent between the chains of a local network.
+#[derive(Clone, Debug, PartialEq)]
+pub struct XcmMessage {
+	/// The chain from which the message is sent.
+	pub origin: NetworkChain,
+	/// The chain to which the message is sent.
+	pub destination: NetworkChain,
+	/// The program sent.
+	pub program: XcmProgram,
+}
+
+impl XcmMessage {
+	/// Constructs the extrinsic sending the message from the origin.
+	///
+	/// # Arguments
+	/// * `metadata` - The metadata of the origin.
+	pub fn extrinsic(&self, metadata: &Metadata) -> Result<DynamicPayload, Error> {
+		let pallet = XCM_PALLETS
+			.into_iter()
+			.find(|p| metadata.pallet_by_name(p).is_some())
+			.ok_or_else(|| Error::XcmError(format!("{} has no XCM pallet", self.origin)))?;
+		let destination = versioned(location(self.origin, self.destination)?);
+		let transfer = |function: &str, beneficiary: &[u8; 32], amount: u128| {
+			let beneficiary = versioned(account(beneficiary));
+			// The relay chain token, as seen from the origin.
+			let id = match self.origin {
+				NetworkChain::Relay => here(0),
+				NetworkChain::Rollup(_) => here(1),
+			};
+			let asset = Value::named_composite([
+				("id", Value::unnamed_composite([id])),
+				("fun", Value::unnamed_variant("Fungible", [Value::u128(amount)])),
+			]);
+			let assets = versioned(Value::unnamed_composite([Value::unnamed_composite([asset])]));
+			dynamic::tx(
+				pallet,
+				function,
+				vec![destination.clone(), beneficiary, assets, Value::u128(0), unlimited()],
+			)
+		};
+		Ok(match &self.program {
+			XcmProgram::ReserveTransfer { beneficiary, amount } =>
+				transfer("limited_reserve_transfer_assets", beneficiary, *amount),
+			XcmProgram::Teleport { beneficiary, amount } =>
+				transfer("limited_teleport_assets", beneficiary, *amount),
+			XcmProgram::Transact { call, superuser } => {
+				let origin_kind = if *superuser { "Superuser" } else { "SovereignAccount" };
+				let message = Value::unnamed_composite([
+					Value::named_variant(
+						"UnpaidExecution",
+						[
+							("weight_limit", unlimited()),
+							("check_origin", Value::unnamed_variant("None", [])),
+						],
+					),
+					Value::named_variant(
+						"Transact",
+						[
+							("origin_kind", Value::unnamed_variant(origin_kind, [])),
+							(
+								"require_weight_at_most",
+								Value::named_composite([
+									("ref_time", Value::u128(TRANSACT_WEIGHT.0)),
+									("proof_size", Value::u128(TRANSACT_WEIGHT.1)),
+								]),
+							),
+							(
+								"call",
+								Value::named_composite([("encoded", Value::from_bytes(call))]),
+							),
+						],
+					),
+				]);
+				let send = dynamic::tx(pallet, "send", vec![destination, versioned(message)]);
+				match superuser {
+					true => construct_sudo_extrinsic(send),
+					false => send,
+				}
+			},
+		})
+	}
+}
+
+/// The handling of a message by a chain along its route.
+#[derive(Clone, Debug, PartialEq)]
+pub struct XcmHop {
+	/// The chain.
+	pub chain: NetworkChain,
+	/// The number of the block in which the message was handled.
+	pub block: u32,
+	/// The events of the block emitted by the messaging pallets, formatted as
+	/// `Pallet::Event { .. }`.
+	pub events: Vec<String>,
+	/// The reason the message failed, if it did.
+	pub error: Option<String>,
+}
+
+/// Sends a message between the chains of a local network, tracing it through the relay chain to
+/// its destination.
+pub struct XcmTracer {
+	message: XcmMessage,
+	origin: Connection,
+	relay: Connection,
+	destination: Connection,
+	// The block of the origin containing the extrinsic which sent the message, along with the
+	// identifier of the message.
+	sent: Option<(H256, [u8; 32])>,
+	// The blocks of the relay chain and of the destination from which the message is traced.
+	from: (u32, u32),
+}
+
+impl XcmTracer {
+	/// Connects to the chains along the route of a message.
+	///
+	/// # Arguments
+	/// * `message` - The message.
+	/// * `relay` - Endpoint of a node of the relay chain.
+	/// * `origin` - Endpoint of a node of the origin.
+	/// * `destination` - Endpoint of a node of the destination.
+	pub async fn new(
+		message: XcmMessage,
+		relay: &Url,
+		origin: &Url,
+		destination: &Url,
+	) -> Result<Self, Error> {
+		// Ensure the message can be routed before connecting.
+		location(message.origin, message.destination)?;
+		Ok(Self {
+			origin: Connection::new(message.origin, origin).await?,
+			relay: Connection::new(NetworkChain::Relay, relay).await?,
+			destination: Connection::new(message.destination, destination).await?,
+			message,
+			sent: None,
+			from: (0, 0),
+		})
+	}
+
+	/// Sends the message, returning its handling by the origin once the extrinsic sending it is
+	/// finalized.
+	///
+	/// # Arguments
+	/// * `suri` - The secret URI of the account sending the message.
+	pub async fn send(&mut self, suri: &str) -> Result<XcmHop, Error> {
+		// The message may be handled before the extrinsic sending it is finalized.
+		self.from = (self.relay.latest().await?, self.destination.latest().await?);
+		let xt = self.message.extrinsic(&self.origin.client.metadata())?;
+		let signer = create_signer(suri)?;
+		let submitted =
+			create_signed_extrinsic(&self.origin.client, &self.origin.url, &xt, &signer)
+				.await?
+				.submit_and_watch()
+				.await
+				.map_err(|e| Error::ExtrinsicSubmissionError(format!("{:?}", e)))?
+				.wait_for_finalized()
+				.await
+				.map_err(|e| Error::ExtrinsicSubmissionError(e.to_string()))?;
+		let block_hash = submitted.block_hash();
+		let events = submitted
+			.wait_for_success()
+			.await
+			.map_err(|e| Error::ExtrinsicSubmissionError(e.to_string()))?;
+		let mut decoded = Vec::new();
+		for event in events.iter() {
+			let event = event.map_err(|e| Error::SubXtError(e.into()))?;
+			let fields = event.field_values().map_err(|e| Error::XcmError(e.to_string()))?;
+			decoded.push((
+				event.pallet_name().to_string(),
+				event.variant_name().to_string(),
+				fields,
+			));
+		}
+		let (id, error) = sent(&decoded);
+		if let Some(id) = id {
+			self.sent = Some((block_hash, id));
+		}
+		let block = self.origin.client.blocks().at(block_hash).await.map_err(Box::new)?.number();
+		Ok(XcmHop {
+			chain: self.message.origin,
+			block,
+			events: decoded
+				.iter()
+				.filter(|(pallet, ..)| MESSAGING_PALLETS.contains(&pallet.as_str()))
+				.map(|(pallet, event, fields)| format_event(pallet, event, fields))
+				.collect(),
+			error,
+		})
+	}
+
+	/// Waits until the block of the origin which sent the message is included by the relay chain.
+	/// Returns `None` when the message was sent by the relay chain.
+	///
+	/// # Arguments
+	/// * `timeout` - The maximum duration to wait.
+	pub async fn inclusion(&self, timeout: Duration) -> Result<Option<XcmHop>, Error> {
+		if self.message.origin == NetworkChain::Relay {
+			return Ok(None);
+		}
+		let (block_hash, _) = self.sent()?;
+		self.relay.scan(self.from.0, timeout, included(block_hash)).await.map(Some)
+	}
+
+	/// Waits until the message is processed by the destination.
+	///
+	/// # Arguments
+	/// * `timeout` - The maximum duration to wait.
+	pub async fn processing(&self, timeout: Duration) -> Result<XcmHop, Error> {
+		let (_, id) = self.sent()?;
+		self.destination.scan(self.from.1, timeout, processed(id)).await
+	}
+
+	fn sent(&self) -> Result<(H256, [u8; 32]), Error> {
+		self.sent.ok_or_else(|| Error::XcmError("the message has not been sent".into()))
+	}
+}
+
+// Whether an event indicates the outcome of handling the message, and if so, the reason the
+// message failed if it did.
+type Outcome = Option<Result<(), String>>;
+
+// A connection to a chain along the route of a message.
+struct Connection {
+	chain: NetworkChain,
+	url: Url,
+	client: OnlineClient<SubstrateConfig>,
+	rpc: LegacyRpcMethods<SubstrateConfig>,
+}
+
+impl Connection {
+	async fn new(chain: NetworkChain, url: &Url) -> Result<Self, Error> {
+		let rpc = RpcClient::from_url(url.as_str())
+			.await
+			.map_err(|e| Error::ConnectionFailure(e.to_string()))?;
+		let client = OnlineClient::<SubstrateConfig>::from_rpc_client(rpc.clone())
+			.await
+			.map_err(|e| Error::ConnectionFailure(e.to_string()))?;
+		Ok(Self { chain, url: url.clone(), client, rpc: LegacyRpcMethods::new(rpc) })
+	}
+
+	// The number of the latest block.
+	async fn latest(&self) -> Result<u32, Error> {
+		Ok(self.client.blocks().at_latest().await.map_err(Box::new)?.number())
+	}
+
+	// Scans the blocks of the chain, starting at the specified block and continuing as blocks are
+	// produced, until an event indicates the outcome of handling the message.
+	async fn scan(
+		&self,
+		from: u32,
+		timeout: Duration,
+		outcome: impl Fn(&str, &str, &Composite<u32>) -> Outcome,
+	) -> Result<XcmHop, Error> {
+		tokio::time::timeout(timeout, async {
+			let mut number = from;
+			loop {
+				let Some(hash) =
+					self.rpc.chain_get_block_hash(Some(number.into())).await.map_err(Box::new)?
+				else {
+					tokio::time::sleep(POLL_INTERVAL).await;
+					continue;
+				};
+				let block = self.client.blocks().at(hash).await.map_err(Box::new)?;
+				let mut hop =
+					XcmHop { chain: self.chain, block: number, events: vec![], error: None };
+				let mut handled = false;
+				for event in block.events().await.map_err(Box::new)?.iter() {
+					let event = event.map_err(|e| Error::XcmError(e.to_string()))?;
+					let fields =
+						event.field_values().map_err(|e| Error::XcmError(e.to_string()))?;
+					let (pallet, name) = (event.pallet_name(), event.variant_name());
+					let result = outcome(pallet, name, &fields);
+					if result.is_some() || MESSAGING_PALLETS.contains(&pallet) {
+						hop.events.push(format_event(pallet, name, &fields));
+					}
+					if let Some(result) = result.filter(|_| !handled) {
+						handled = true;
+						hop.error = result.err();
+					}
+				}
+				if handled {
+					return Ok(hop);
+				}
+				number += 1;
+			}
+		})
+		.await
+		.map_err(|_| {
+			Error::XcmError(format!(
+				"the message was not handled by {} within {} seconds",
+				self.chain,
+				timeout.as_secs()
+			))
+		})?
+	}
+}
+
+// The identifier of the message sent, along with the reason it failed to be sent, as indicated by
+// the events of the extrinsic sending it.
+fn sent(events: &[(String, String, Composite<u32>)]) -> (Option<[u8; 32]>, Option<String>) {
+	let mut id = None;
+	for (pallet, event, fields) in events {
+		match (pallet.as_str(), event.as_str()) {
+			("Sudo", "Sudid") =>
+				if let Some(error) = sudo_error(fields) {
+					return (None, Some(format!("the sudo call failed: {error}")));
+				},
+			(pallet, "Attempted") if XCM_PALLETS.contains(&pallet) => {
+				if let Some(outcome) = field(fields, "outcome") {
+					if !matches!(&outcome.value, ValueDef::Variant(v) if v.name == "Complete") {
+						return (
+							None,
+							Some(format!(
+								"the message failed to execute locally: {}",
+								format_value(outcome)
+							)),
+						);
+					}
+				}
+			},
+			(pallet, "Sent") if XCM_PALLETS.contains(&pallet) => {
+				id = field(fields, "message_id").and_then(bytes).and_then(|b| b.try_into().ok());
+			},
+			_ => {},
+		}
+	}
+	match id {
+		Some(id) => (Some(id), None),
+		None => (None, Some("no message was sent".into())),
+	}
+}
+
+// Whether the relay chain has included the candidate of the rollup block which sent the message.
+fn included(block_hash: H256) -> impl Fn(&str, &str, &Composite<u32>) -> Outcome {
+	move |pallet, event, fields| {
+		if (pallet, event) != ("ParaInclusion", "CandidateIncluded") {
+			return None;
+		}
+		// The head data of the candidate is the encoded header of the rollup block.
+		let head = fields.values().nth(1).and_then(bytes)?;
+		(blake2_256(&head) == block_hash.0).then_some(Ok(()))
+	}
+}
+
+// Whether the destination has processed the message, and if so, whether successfully.
+fn processed(id: [u8; 32]) -> impl Fn(&str, &str, &Composite<u32>) -> Outcome {
+	move |pallet, event, fields| {
+		if pallet != "MessageQueue" || field(fields, "id").and_then(bytes)? != id {
+			return None;
+		}
+		match event {
+			"Processed" => Some(match field(fields, "success").map(|s| &s.value) {
+				Some(ValueDef::Primitive(Primitive::Bool(true))) => Ok(()),
+				_ => Err("the message could not be executed".into()),
+			}),
+			"ProcessingFailed" => Some(Err(format!(
+				"the message could not be processed: {}",
+				field(fields, "error").map(format_value).unwrap_or_default()
+			))),
+			_ => None,
+		}
+	}
+}
+
+// The location of the destination, as seen from the origin.
+fn location(origin: NetworkChain, destination: NetworkChain) -> Result<Value, Error> {
+	let parachain = |id: u32| {
+		Value::named_composite([
+			("parents", Value::u128(0)),
+			(
+				"interior",
+				Value::unnamed_variant(
+					"X1",
+					[Value::unnamed_composite([Value::unnamed_variant(
+						"Parachain",
+						[Value::u128(id as u128)],
+					)])],
+				),
+			),
+		])
+	};
+	match (origin, destination) {
+		(origin, destination) if origin == destination =>
+			Err(Error::XcmError(format!("a message cannot be sent from {origin} to itself"))),
+		(NetworkChain::Relay, NetworkChain::Rollup(id)) => Ok(parachain(id)),
+		(NetworkChain::Rollup(_), NetworkChain::Relay) => Ok(here(1)),
+		(NetworkChain::Rollup(_), NetworkChain::Rollup(id)) => {
+			let mut location = parachain(id);
+			if let ValueDef::Composite(Composite::Named(fields)) = &mut location.value {
+				fields[0].1 = Value::u128(1);
+			}
+			Ok(location)
+		},
+		(NetworkChain::Relay, NetworkChain::Relay) => unreachable!("handled above"),
+	}
+}
+
+// The location of an account, as seen from the chain of the account.
+fn account(id: &[u8; 32]) -> Value {
+	let junction = Value::named_variant(
+		"AccountId32",
+		[("network", Value::unnamed_variant("None", [])), ("id", Value::from_bytes(id))],
+	);
+	Value::named_composite([
+		("parents", Value::u128(0)),
+		("interior", Value::unnamed_variant("X1", [Value::unnamed_composite([junction])])),
+	])
+}
+
+// The location of the chain itself, or of an ancestor.
+fn here(parents: u8) -> Value {
+	Value::named_composite([
+		("parents", Value::u128(parents as u128)),
+		("interior", Value::unnamed_variant("Here", [])),
+	])
+}
+
+fn unlimited() -> Value {
+	Value::unnamed_variant("Unlimited", [])
+}
+
+// Wraps a location, assets or a message as version 4 of the XCM format.
+fn versioned(value: Value) -> Value {
+	Value::unnamed_variant("V4", [value])
+}
+
+fn field<'a, T>(fields: &'a Composite<T>, name: &str) -> Option<&'a scale_value::Value<T>> {
+	match fields {
+		Composite::Named(fields) => fields.iter().find(|(n, _)| n == name).map(|(_, v)| v),
+		Composite::Unnamed(_) => None,
+	}
+}
+
+// The bytes of a decoded value, seeing through any wrapping types such as `H256(..)`.
+fn bytes<T>(value: &scale_value::Value<T>) -> Option<Vec<u8>> {
+	let ValueDef::Composite(composite) = &value.value else {
+		return None;
+	};
+	let values: Vec<_> = composite.values().collect();
+	let bytes: Option<Vec<u8>> = values
+		.iter()
+		.map(|v| match v.value {
+			ValueDef::Primitive(Primitive::U128(byte)) => u8::try_from(byte).ok(),
+			_ => None,
+		})
+		.collect();
+	match (bytes, values.as_slice()) {
+		(Some(bytes), _) => Some(bytes),
+		(None, [value]) => self::bytes(value),
+		_ => None,
+	}
+}
+
+fn format_event(pallet: &str, event: &str, fields: &Composite<u32>) -> String {
+	let fields = format_value(&scale_value::Value {
+		value: ValueDef::Composite(fields.clone()),
+		context: 0,
+	});
+	match fields.as_str() {
+		"" | "()" => format!("{pallet}::{event}"),
+		fields => format!("{pallet}::{event} {fields}"),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const ALICE: [u8; 32] = [1; 32];
+
+	// Decoded event fields, as provided by a chain.
+	fn fields(fields: Value) -> Composite<u32> {
+		match fields.map_context(|_| 0).value {
+			ValueDef::Composite(fields) => fields,
+			_ => unreachable!("composite expected"),
+		}
+	}
+
+	fn hash(bytes: [u8; 32]) -> Value {
+		Value::unnamed_composite([Value::from_bytes(bytes)])
+	}
+
+	#[test]
+	fn location_works() -> Result<(), Error> {
+		use NetworkChain::*;
+		let parachain = |parents: u128, id: u128| {
+			Value::named_composite([
+				("parents", Value::u128(parents)),
+				(
+					"interior",
+					Value::unnamed_variant(
+						"X1",
+						[Value::unnamed_composite([Value::unnamed_variant(
+							"Parachain",
+							[Value::u128(id)],
+						)])],
+					),
+				),
+			])
+		};
+		assert_eq!(location(Relay, Rollup(1000))?, parachain(0, 1000));
+		assert_eq!(location(Rollup(1000), Rollup(2000))?, parachain(1, 2000));
+		assert_eq!(location(Rollup(1000), Relay)?, here(1));
+		for chain in [Relay, Rollup(1000)] {
+			assert!(matches!(
+				location(chain, chain),
+				Err(Error::XcmError(message)) if message == format!("a message cannot be sent from {chain} to itself")
+			));
+		}
+		Ok(())
+	}
+
+	#[test]
+	fn sent_works() {
+		let event = |pallet: &str, event: &str, value: Value| {
+			(pallet.to_string(), event.to_string(), fields(value))
+		};
+		let sent = event(
+			"PolkadotXcm",
+			"Sent",
+			Value::named_composite([("message_id", Value::from_bytes([2; 32]))]),
+		);
+		let attempted = |outcome: &str| {
+			event(
+				"PolkadotXcm",
+				"Attempted",
+				Value::named_composite([("outcome", Value::unnamed_variant(outcome, []))]),
+			)
+		};
+		assert_eq!(super::sent(&[attempted("Complete"), sent.clone()]), (Some([2; 32]), None));
+		assert_eq!(
+			super::sent(&[attempted("Incomplete"), sent.clone()]),
+			(None, Some("the message failed to execute locally: Incomplete ()".into()))
+		);
+		let sudid = event(
+			"Sudo",
+			"Sudid",
+			Value::named_composite([(
+				"sudo_result",
+				Value::unnamed_variant("Err", [Value::unnamed_variant("BadOrigin", [])]),
+			)]),
+		);
+		assert_eq!(
+			super::sent(&[sudid]),
+			(None, Some("the sudo call failed: BadOrigin ()".into()))
+		);
+		assert_eq!(super::sent(&[]), (None, Some("no message was sent".into())));
+	}
+
+	#[test]
+	fn included_works() {
+		let head = vec![7u8; 16];
+		let candidate = |head: &[u8]| {
+			fields(Value::unnamed_composite([
+				Value::u128(0),
+				Value::unnamed_composite([Value::from_bytes(head)]),
+			]))
+		};
+		let included = included(H256(blake2_256(&head)));
+		assert_eq!(included("ParaInclusion", "CandidateIncluded", &candidate(&head)), Some(Ok(())));
+		assert_eq!(included("ParaInclusion", "CandidateIncluded", &candidate(&[8; 16])), None);
+		assert_eq!(included("ParaInclusion", "CandidateBacked", &candidate(&head)), None);
+	}
+
+	#[test]
+	fn processed_works() {
+		let processed = processed([2; 32]);
+		let event = |id: [u8; 32], success: bool| {
+			fields(Value::named_composite([("id", hash(id)), ("success", Value::bool(success))]))
+		};
+		assert_eq!(processed("MessageQueue", "Processed", &event([2; 32], true)), Some(Ok(())));
+		assert_eq!(
+			processed("MessageQueue", "Processed", &event([2; 32], false)),
+			Some(Err("the message could not be executed".into()))
+		);
+		assert_eq!(processed("MessageQueue", "Processed", &event([3; 32], true)), None);
+		assert_eq!(processed("XcmpQueue", "Processed", &event([2; 32], true)), None);
+		let failed = fields(Value::named_composite([
+			("id", hash([2; 32])),
+			("error", Value::unnamed_variant("Overweight", [])),
+		]));
+		assert_eq!(
+			processed("MessageQueue", "ProcessingFailed", &failed),
+			Some(Err("the message could not be processed: Overweight ()".into()))
+		);
+	}
+
+	#[test]
+	fn format_event_works() {
+		assert_eq!(
+			format_event("System", "Remarked", &fields(Value::unnamed_composite([]))),
+			"System::Remarked"
+		);
+		assert_eq!(
+			format_event(
+				"MessageQueue",
+				"Processed",
+				&fields(Value::named_composite([("success", Value::bool(true))]))
+			),
+			"MessageQueue::Processed { success: true }"
+		);
+	}
+
+	#[tokio::test]
+	async fn extrinsic_fails_without_xcm_pallet() -> Result<(), Error> {
+		use crate::fork::{Fork, ForkSource};
+		use scale::Decode;
+
+		// The metadata of a chain without an XCM pallet.
+		let cache = tempfile::tempdir()?;
+		let snapshot = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+			.join("../../tests/snapshots/base_parachain.snap");
+		let fork = Fork::new(&ForkSource::Snapshot(snapshot), cache.path()).await?;
+		let metadata = Metadata::decode(&mut fork.metadata(None)?)
+			.map_err(|e| Error::MetadataParsingError(e.to_string()))?;
+		let message = XcmMessage {
+			origin: NetworkChain::Rollup(1000),
+			destination: NetworkChain::Relay,
+			program: XcmProgram::ReserveTransfer { beneficiary: ALICE, amount: 100 },
+		};
+		assert!(matches!(
+			message.extrinsic(&metadata),
+			Err(Error::XcmError(message)) if message == "rollup 1000 has no XCM pallet"
+		));
+		Ok(())
+	}
+}
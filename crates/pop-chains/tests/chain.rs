@@ -1,7 +1,15 @@
 // SPDX-License-Identifier: GPL-3.0
 
 use anyhow::Result;
-use pop_chains::{generate_pallet_benchmarks, get_preset_names, up::Zombienet};
+use pop_chains::{
+	generate_pallet_benchmarks, get_preset_names,
+	up::{omni_node_generator, Zombienet},
+};
+use pop_common::{
+	polkadot_sdk::sort_by_latest_stable_version,
+	sourcing::{ArchiveFileSpec, Binary, GitHub::ReleaseArchive, Source},
+	target,
+};
 use std::path::Path;
 
 const BINARY_VERSION: &str = "stable2412";
@@ -175,3 +183,27 @@ fn get_preset_names_works() -> Result<()> {
 	);
 	Ok(())
 }
+
+#[tokio::test]
+async fn omni_node_generator_works() -> Result<(), pop_common::Error> {
+	let temp_dir = tempfile::tempdir()?;
+	let temp_dir_path = temp_dir.into_path();
+	let version = "polkadot-stable2412-4";
+	let binary = omni_node_generator(temp_dir_path.clone(), Some(version)).await?;
+	assert!(matches!(binary, Binary::Source { name: _, source, cache }
+			if source == Source::GitHub(ReleaseArchive {
+				owner: "r0gue-io".to_string(),
+				repository: "polkadot".to_string(),
+				tag: Some(version.to_string()),
+				tag_pattern: Some("polkadot-{version}".into()),
+				prerelease: false,
+				version_comparator: sort_by_latest_stable_version,
+				fallback: "stable2503-7".to_string(),
+				archive: format!("polkadot-omni-node-{}.tar.gz", target()?),
+				contents: ["polkadot-omni-node"].map(|b| ArchiveFileSpec::new(b.into(), Some(b.into()), true)).to_vec(),
+				latest: binary.latest().map(|l| l.to_string()),
+			}).into() &&
+			cache == temp_dir_path.as_path()
+	));
+	Ok(())
+}
@@ -2,14 +2,26 @@
 
 use anyhow::Result;
 use pop_chains::{
-	construct_extrinsic, construct_proxy_extrinsic, decode_call_data, encode_call_data,
-	field_to_param, find_dispatchable_by_name, find_pallet_by_name, parse_chain_metadata,
-	set_up_client, sign_and_submit_extrinsic, Error, Function, Payload,
+	assemble_signed_extrinsic, construct_extrinsic, construct_proxy_extrinsic,
+	construct_sudo_extrinsic, create_signing_payload, decode_call_data, decode_extrinsic,
+	dry_run_extrinsic, encode_call_data, field_to_param, find_constant_by_name,
+	find_dispatchable_by_name, find_pallet_by_name, find_storage_by_name, generate_metadata_hash,
+	metadata_hash, parse_chain_metadata, query_storage, set_up_client, sign_and_submit_extrinsic,
+	submit_extrinsic_with_events, submit_signed_extrinsic, supports_metadata_hash,
+	BlockSubscription, DecodedValue, DryRunResult, Error, EventFilter, EventSubscription, Function,
+	Payload,
 };
-use pop_common::test_env::TestNode;
+use pop_common::{create_signer, test_env::TestNode};
+use scale::Encode;
+use sp_core::{
+	bytes::to_hex,
+	crypto::{AccountId32, Ss58Codec},
+};
+use std::time::Duration;
 use url::Url;
 
 const ALICE_SURI: &str = "//Alice";
+const ALICE_ADDRESS: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
 const POLKADOT_NETWORK_URL: &str = "wss://polkadot-rpc.publicnode.com";
 
 #[tokio::test]
@@ -32,6 +44,17 @@ async fn construct_proxy_extrinsic_work() -> Result<()> {
 	Ok(())
 }
 
+#[tokio::test]
+async fn metadata_hash_works() -> Result<()> {
+	let client = set_up_client(POLKADOT_NETWORK_URL).await?;
+	let url = Url::parse(POLKADOT_NETWORK_URL)?;
+	assert!(supports_metadata_hash(&client));
+	let hash = generate_metadata_hash(&client, &url).await?;
+	// The Polkadot runtime is built with its metadata hash, which is therefore verified.
+	assert_eq!(metadata_hash(&client, &url).await?, Some(hash));
+	Ok(())
+}
+
 #[tokio::test]
 async fn encode_and_decode_call_data_works() -> Result<()> {
 	let node = TestNode::spawn().await?;
@@ -70,6 +93,142 @@ async fn sign_and_submit_wrong_extrinsic_fails() -> Result<()> {
 	Ok(())
 }
 
+#[tokio::test]
+async fn submit_extrinsic_with_events_works() -> Result<()> {
+	let node = TestNode::spawn().await?;
+	let client = set_up_client(node.ws_url()).await?;
+	let pallets = parse_chain_metadata(&client)?;
+	let function = find_dispatchable_by_name(&pallets, "System", "remark_with_event")?;
+	let xt = construct_extrinsic(function, vec!["0x11".to_string()])?;
+	let result =
+		submit_extrinsic_with_events(&client, &Url::parse(node.ws_url())?, xt, ALICE_SURI).await?;
+	assert!(result.extrinsic_hash.starts_with("0x"));
+	assert!(result.events.contains(&"System::Remarked".to_string()));
+	assert!(result.events.contains(&"System::ExtrinsicSuccess".to_string()));
+	// Dispatch errors are reported as submission errors.
+	let function = find_dispatchable_by_name(&pallets, "System", "set_code")?;
+	let xt = construct_extrinsic(function, vec!["0x11".to_string()])?;
+	assert!(matches!(
+		submit_extrinsic_with_events(&client, &Url::parse(node.ws_url())?, xt, ALICE_SURI).await,
+		Err(Error::ExtrinsicSubmissionError(_))
+	));
+	Ok(())
+}
+
+#[tokio::test]
+async fn offline_signing_works() -> Result<()> {
+	let node = TestNode::spawn().await?;
+	let client = set_up_client(node.ws_url()).await?;
+	let pallets = parse_chain_metadata(&client)?;
+	let function = find_dispatchable_by_name(&pallets, "System", "remark_with_event")?;
+	let xt = construct_extrinsic(function, vec!["0x11".to_string()])?;
+	let payload =
+		create_signing_payload(&client, &Url::parse(node.ws_url())?, &xt, ALICE_ADDRESS, Some(64))
+			.await?;
+	assert_eq!(payload.call_data, encode_call_data(&client, &xt)?);
+	assert_eq!(payload.mortality.as_ref().map(|m| m.period), Some(64));
+	// Sign the payload as an offline device would.
+	let signature = create_signer(ALICE_SURI)?.sign(&decode_call_data(&payload.payload)?);
+	let signature = to_hex(&signature.encode(), false);
+	let extrinsic = assemble_signed_extrinsic(&client, &payload, &signature)?;
+	let events = submit_signed_extrinsic(client.clone(), extrinsic).await?;
+	assert!(events.iter().any(|e| e.is_ok_and(|e| e.variant_name() == "Remarked")));
+	// A payload which has been tampered with is rejected.
+	let mut tampered = payload.clone();
+	tampered.nonce += 1;
+	assert!(matches!(
+		assemble_signed_extrinsic(&client, &tampered, &signature),
+		Err(Error::OfflineSigningError(_))
+	));
+	Ok(())
+}
+
+#[tokio::test]
+async fn event_subscription_works() -> Result<()> {
+	let node = TestNode::spawn().await?;
+	let client = set_up_client(node.ws_url()).await?;
+	let pallets = parse_chain_metadata(&client)?;
+	let filter = EventFilter {
+		pallet: Some("system".to_string()),
+		event: Some("Remarked".to_string()),
+		account: Some(AccountId32::from_ss58check(ALICE_ADDRESS)?),
+	};
+	let mut subscription = EventSubscription::new(&client, BlockSubscription::Best, filter).await?;
+	let function = find_dispatchable_by_name(&pallets, "System", "remark_with_event")?;
+	let xt = construct_extrinsic(function, vec!["0x11".to_string()])?;
+	submit_extrinsic_with_events(&client, &Url::parse(node.ws_url())?, xt, ALICE_SURI).await?;
+	let events = tokio::time::timeout(Duration::from_secs(60), async {
+		loop {
+			match subscription.next().await {
+				Some(Ok(events)) if !events.is_empty() => return Ok(events),
+				Some(Ok(_)) => continue,
+				Some(Err(e)) => return Err(e),
+				None => return Ok(vec![]),
+			}
+		}
+	})
+	.await??;
+	assert_eq!(events.len(), 1);
+	assert_eq!(events[0].pallet, "System");
+	assert_eq!(events[0].event, "Remarked");
+	assert!(events[0].extrinsic_index.is_some());
+	Ok(())
+}
+
+#[tokio::test]
+async fn decode_extrinsic_works() -> Result<()> {
+	let node = TestNode::spawn().await?;
+	let client = set_up_client(node.ws_url()).await?;
+	let pallets = parse_chain_metadata(&client)?;
+	let remark = find_dispatchable_by_name(&pallets, "System", "remark")?;
+	// Call data.
+	let decoded = decode_extrinsic(&client, "0x00000411")?;
+	assert_eq!(decoded.signer, None);
+	assert_eq!(decoded.call.to_string(), "System::remark\n  remark: 0x11");
+	// Nested call data.
+	let xt = construct_sudo_extrinsic(construct_extrinsic(remark, vec!["0x11".to_string()])?);
+	let decoded = decode_extrinsic(&client, &encode_call_data(&client, &xt)?)?;
+	assert_eq!(decoded.call.to_string(), "Sudo::sudo\n  call: System::remark\n    remark: 0x11");
+	assert!(
+		matches!(&decoded.call.args[0].value, DecodedValue::Call(call) if call.function == "remark")
+	);
+	// Signed extrinsic.
+	let xt = construct_extrinsic(remark, vec!["0x11".to_string()])?;
+	let signed = client
+		.tx()
+		.create_signed(&xt, &subxt_signer::sr25519::dev::alice(), Default::default())
+		.await?;
+	let decoded = decode_extrinsic(&client, &sp_core::bytes::to_hex(signed.encoded(), false))?;
+	assert_eq!(decoded.signer.as_deref(), Some("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"));
+	assert!(decoded.extensions.iter().any(|e| e.name == "CheckNonce"));
+	assert_eq!(decoded.call.to_string(), "System::remark\n  remark: 0x11");
+	// Invalid data.
+	assert!(matches!(decode_extrinsic(&client, "0x1234"), Err(Error::CallDataDecodingError(_))));
+	Ok(())
+}
+
+#[tokio::test]
+async fn dry_run_extrinsic_works() -> Result<()> {
+	let node = TestNode::spawn().await?;
+	let client = set_up_client(node.ws_url()).await?;
+	let url = Url::parse(node.ws_url())?;
+	let pallets = parse_chain_metadata(&client)?;
+	let remark = find_dispatchable_by_name(&pallets, "System", "remark")?;
+	let xt = construct_extrinsic(remark, vec!["0x11".to_string()])?;
+	let outcome = dry_run_extrinsic(&client, &url, xt, ALICE_SURI).await?;
+	assert_eq!(outcome.result, DryRunResult::Success);
+	// Only `Root` can set the runtime code.
+	let set_code = find_dispatchable_by_name(&pallets, "System", "set_code")?;
+	let xt = construct_extrinsic(set_code, vec!["0x11".to_string()])?;
+	let outcome = dry_run_extrinsic(&client, &url, xt, ALICE_SURI).await?;
+	assert!(matches!(outcome.result, DryRunResult::DispatchError(e) if e.contains("origin")));
+	// Alice is the sudo key on the dev chain.
+	let xt = construct_sudo_extrinsic(construct_extrinsic(remark, vec!["0x11".to_string()])?);
+	let outcome = dry_run_extrinsic(&client, &url, xt, ALICE_SURI).await?;
+	assert_eq!(outcome.result, DryRunResult::Success);
+	Ok(())
+}
+
 #[tokio::test]
 async fn parse_chain_metadata_works() -> Result<()> {
 	let node = TestNode::spawn().await?;
@@ -133,6 +292,58 @@ async fn find_dispatchable_by_name_works() -> Result<()> {
 	Ok(())
 }
 
+#[tokio::test]
+async fn find_storage_and_constant_by_name_works() -> Result<()> {
+	let node = TestNode::spawn().await?;
+	let client = set_up_client(node.ws_url()).await?;
+	let pallets = parse_chain_metadata(&client)?;
+	assert!(matches!(
+		find_storage_by_name(&pallets, "System", "WrongName"),
+		Err(Error::StorageNotFound(name)) if name == "System::WrongName"
+	));
+	let storage = find_storage_by_name(&pallets, "System", "Account")?;
+	assert_eq!(storage.docs, "The full account information for a particular account ID.");
+	assert!(storage.is_supported);
+	assert_eq!(storage.keys.len(), 1);
+	assert_eq!(storage.keys[0].type_name, "AccountId32 ([u8;32])");
+	assert!(find_storage_by_name(&pallets, "System", "Number")?.keys.is_empty());
+	assert!(matches!(
+		find_constant_by_name(&pallets, "Balances", "WrongName"),
+		Err(Error::ConstantNotFound(name)) if name == "Balances::WrongName"
+	));
+	let constant = find_constant_by_name(&pallets, "Balances", "ExistentialDeposit")?;
+	assert_eq!(constant.type_name, "u128");
+	assert!(constant.value.as_ref().is_some_and(|v| !v.is_empty()));
+	Ok(())
+}
+
+#[tokio::test]
+async fn query_storage_works() -> Result<()> {
+	let node = TestNode::spawn().await?;
+	let client = set_up_client(node.ws_url()).await?;
+	let pallets = parse_chain_metadata(&client)?;
+	// Storage value, at a given block.
+	let number = find_storage_by_name(&pallets, "System", "Number")?;
+	assert_eq!(
+		query_storage(&client, number, vec![], Some(client.genesis_hash())).await?,
+		Some("0".to_string())
+	);
+	// Storage map.
+	let account = find_storage_by_name(&pallets, "System", "Account")?;
+	let alice = query_storage(&client, account, vec![ALICE_ADDRESS.to_string()], None)
+		.await?
+		.unwrap();
+	assert!(alice.contains("free"));
+	// Optional storage item.
+	let key = find_storage_by_name(&pallets, "Sudo", "Key")?;
+	assert!(query_storage(&client, key, vec![], None).await?.is_some());
+	assert!(matches!(
+		query_storage(&client, account, vec![], None).await,
+		Err(Error::StorageQueryError(_))
+	));
+	Ok(())
+}
+
 #[tokio::test]
 async fn field_to_param_works() -> Result<()> {
 	let node = TestNode::spawn().await?;
@@ -1,7 +1,8 @@
 // SPDX-License-Identifier: GPL-3.0
 
 use crate::cli;
-use pop_contracts::{build_smart_contract, Verbosity};
+use cliclack::spinner;
+use pop_contracts::{build_smart_contract, ContainerEngine, Verbosity, VerifiableBuilder};
 use std::path::PathBuf;
 
 /// Configuration for building a smart contract.
@@ -10,6 +11,8 @@ pub struct BuildContract {
 	pub(crate) path: Option<PathBuf>,
 	/// Build profile: `true` for release mode, `false` for debug mode.
 	pub(crate) release: bool,
+	/// Whether to build the contract within a pinned container image, so that it can be verified.
+	pub(crate) verifiable: bool,
 }
 
 impl BuildContract {
@@ -24,6 +27,9 @@ impl BuildContract {
 	/// * `cli` - The CLI implementation to be used.
 	fn build(self, cli: &mut impl cli::traits::Cli) -> anyhow::Result<&'static str> {
 		cli.intro("Building your contract")?;
+		if self.verifiable {
+			return self.build_verifiable(cli);
+		}
 		// Build contract.
 		let build_result =
 			build_smart_contract(self.path.as_deref(), self.release, Verbosity::Default)?;
@@ -31,4 +37,26 @@ impl BuildContract {
 		cli.outro("Build completed successfully!")?;
 		Ok("contract")
 	}
+
+	// Builds the contract within a pinned container image, recording the image in its metadata.
+	fn build_verifiable(self, cli: &mut impl cli::traits::Cli) -> anyhow::Result<&'static str> {
+		let engine = ContainerEngine::detect().map_err(|_| anyhow::anyhow!("No container engine detected. A supported containerization solution (Docker or Podman) is required."))?;
+		let spinner = spinner();
+		spinner.start("Building the contract within a container. This may take a while...");
+		let builder = VerifiableBuilder::new(engine, self.path.as_deref())?;
+		let artifact = match builder.build() {
+			Ok(artifact) => artifact,
+			Err(e) => {
+				spinner.error(format!("An error occurred building your contract: {e}"));
+				return Err(e.into());
+			},
+		};
+		spinner.stop(format!("Contract built using the image {}", builder.image()));
+		cli.success(format!(
+			"Your verifiable contract artifacts are ready. You can find them in: {}",
+			artifact.parent().unwrap_or(&artifact).display()
+		))?;
+		cli.outro("Build completed successfully!")?;
+		Ok("contract")
+	}
 }
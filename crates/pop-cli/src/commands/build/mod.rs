@@ -29,6 +29,8 @@ pub(crate) mod spec;
 
 #[cfg(feature = "chain")]
 const CHAIN_HELP_HEADER: &str = "Chain options";
+#[cfg(any(feature = "polkavm-contracts", feature = "wasm-contracts"))]
+const CONTRACT_HELP_HEADER: &str = "Contract options";
 #[cfg(feature = "chain")]
 const RUNTIME_HELP_HEADER: &str = "Runtime options";
 const PACKAGE: &str = "package";
@@ -62,6 +64,10 @@ pub(crate) struct BuildArgs {
 	/// List of features that project is built with, separated by commas.
 	#[clap(short, long)]
 	pub(crate) features: Option<String>,
+	/// Build a smart contract within a pinned container image, so that it can be verified.
+	#[clap(long, help_heading = CONTRACT_HELP_HEADER)]
+	#[cfg(any(feature = "polkavm-contracts", feature = "wasm-contracts"))]
+	pub(crate) verifiable: bool,
 	/// For benchmarking, always build with `runtime-benchmarks` feature.
 	#[clap(short, long, help_heading = CHAIN_HELP_HEADER)]
 	#[cfg(feature = "chain")]
@@ -116,7 +122,7 @@ impl Command {
 				Some(profile) => profile.into(),
 				None => args.release,
 			};
-			BuildContract { path: project_path, release }.execute()?;
+			BuildContract { path: project_path, release, verifiable: args.verifiable }.execute()?;
 			return Ok(Contract);
 		}
 
@@ -342,6 +348,8 @@ mod tests {
 				#[cfg(feature = "chain")]
 				deterministic,
 				features: Some(features.join(",")),
+				#[cfg(any(feature = "polkavm-contracts", feature = "wasm-contracts"))]
+				verifiable: false,
 				#[cfg(feature = "chain")]
 				only_runtime: false
 			},
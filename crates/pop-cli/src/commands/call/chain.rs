@@ -1,24 +1,34 @@
 // SPDX-License-Identifier: GPL-3.0
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use crate::{
 	cli::{self, traits::*},
 	common::{
 		chain::{self, Chain},
 		prompt::display_message,
+		signer::unlock_signer,
 		urls,
 		wallet::{self, prompt_to_use_wallet},
 	},
 };
 use anyhow::{anyhow, Result};
-use clap::Args;
+use clap::{ArgGroup, Args};
 use pop_chains::{
-	construct_extrinsic, construct_sudo_extrinsic, decode_call_data, encode_call_data,
-	find_dispatchable_by_name, find_pallet_by_name, sign_and_submit_extrinsic, supported_actions,
-	Action, CallData, DynamicPayload, Function, OnlineClient, Pallet, Param, Payload,
-	SubstrateConfig,
+	assemble_signed_extrinsic, call_hash, construct_batch_extrinsic, construct_extrinsic,
+	construct_multisig_extrinsic, construct_sudo_extrinsic, create_signing_payload,
+	decode_call_data, decode_extrinsic, encode_call_data, estimate_call_weight,
+	find_constant_by_name, find_dispatchable_by_name, find_pallet_by_name, find_storage_by_name,
+	parse_and_format_events, query_pending_multisig, query_storage, sign_and_submit_extrinsic,
+	submit_extrinsic_with_events, submit_signed_extrinsic, supported_actions, Action, BatchMode,
+	BlockSubscription, CallData, DryRunResult, DynamicPayload, EventFilter, EventSubscription,
+	Function, Multisig, MultisigAction, OnlineClient, Pallet, Param, Payload, SigningPayload,
+	SubstrateConfig, H256,
 };
+use pop_common::create_signer;
+use qrcode::{render::unicode::Dense1x2, QrCode};
+use serde::{de::DeserializeOwned, Deserialize};
+use sp_core::crypto::{AccountId32, Ss58Codec};
 use url::Url;
 
 const DEFAULT_URI: &str = "//Alice";
@@ -27,6 +37,7 @@ const ENCODED_CALL_DATA_MAX_LEN: usize = 500; // Maximum length of encoded call
 /// Command to construct and execute extrinsics with configurable pallets, functions, arguments, and
 /// signing options.
 #[derive(Args, Clone, Default)]
+#[command(group(ArgGroup::new("json_output").args(["decode", "watch"])))]
 pub struct CallChainCommand {
 	/// The pallet containing the dispatchable function to execute.
 	#[arg(short, long, value_parser = parse_pallet_name)]
@@ -45,6 +56,9 @@ pub struct CallChainCommand {
 	/// e.g.
 	/// - for a dev account "//Alice"
 	/// - with a password "//Alice///SECRET_PASSWORD"
+	/// - with a key type "ed25519://Alice" or "ecdsa://Alice"
+	/// - a key stored in the keyring "keyring:alice" (see `pop keys`)
+	/// - an encrypted JSON keystore exported from polkadot-js "json:./alice.json"
 	#[arg(short, long)]
 	suri: Option<String>,
 	/// Use a browser extension wallet to sign the extrinsic.
@@ -65,13 +79,133 @@ pub struct CallChainCommand {
 	/// Automatically signs and submits the extrinsic without prompting for confirmation.
 	#[arg(short = 'y', long)]
 	skip_confirm: bool,
+	/// Perform a dry-run of the extrinsic, showing the dispatch result and emitted events. This
+	/// does not submit the extrinsic.
+	#[arg(short = 'D', long, conflicts_with = "use-wallet")]
+	dry_run: bool,
+	/// SCALE encoded call data or signed extrinsic to decode, showing the pallet, function and
+	/// arguments. Nothing is submitted.
+	#[arg(long, conflicts_with_all = ["pallet", "function", "args", "call"])]
+	decode: Option<String>,
+	/// Output the decoded call, or the watched events as newline-delimited JSON, in JSON format.
+	/// Only JSON is written to stdout, so the URL of the chain must be provided.
+	#[arg(long, requires = "json_output", requires = "url")]
+	json: bool,
+	/// Batch multiple calls into a single extrinsic, using the specified dispatchable function of
+	/// the `Utility` pallet.
+	#[arg(long, value_enum, conflicts_with_all = ["call", "decode"])]
+	batch: Option<BatchMode>,
+	/// Path to a TOML or JSON file listing the calls to batch, each with a `pallet`, `function`
+	/// and `args`.
+	#[arg(long, requires = "batch", conflicts_with_all = ["pallet", "function", "args"])]
+	batch_file: Option<PathBuf>,
+	/// Path to a TOML or JSON script listing an ordered sequence of calls to execute. Each step
+	/// specifies a `pallet`, `function` and `args`, and optionally a `signer`, `sudo` flag and
+	/// `expected_events`. Steps without a `signer` are signed using `--suri`, which is prompted
+	/// for when not specified. Fails if any step fails.
+	#[arg(
+		long,
+		conflicts_with_all = ["pallet", "function", "args", "call", "decode", "batch", "use-wallet", "dry_run"]
+	)]
+	script: Option<PathBuf>,
+	/// Query a storage item, specified as `Pallet::Item` followed by any keys (e.g. `--storage
+	/// System::Account <address>`). Prompts for the storage item and keys when not specified.
+	/// Nothing is submitted.
+	#[arg(
+		long,
+		num_args = 0..,
+		value_name = "ITEM [KEYS]",
+		conflicts_with_all = ["pallet", "function", "args", "call", "decode", "batch", "script"]
+	)]
+	storage: Option<Vec<String>>,
+	/// Inspect a constant, specified as `Pallet::Constant` (e.g. `--constant
+	/// Balances::ExistentialDeposit`). Prompts for the constant when not specified.
+	#[arg(
+		long,
+		num_args = 0..=1,
+		conflicts_with_all = ["pallet", "function", "args", "call", "decode", "batch", "script", "storage"]
+	)]
+	constant: Option<Option<String>>,
+	/// The hash of the block at which to query storage. Defaults to the latest block.
+	#[arg(long, value_parser = parse_block_hash, requires = "storage")]
+	at: Option<H256>,
+	/// Dispatch the call from a multisig account, requiring the given number of approvals. The
+	/// signer approves the call as one of the signatories, which dispatches it once the threshold
+	/// is reached.
+	#[arg(
+		long,
+		value_name = "THRESHOLD",
+		requires = "signatories",
+		conflicts_with_all = ["use-wallet", "call", "decode", "batch", "script", "storage", "constant"]
+	)]
+	multisig: Option<u16>,
+	/// The addresses of all signatories of the multisig, including the signer, separated by
+	/// commas.
+	#[arg(long, value_delimiter = ',', requires = "multisig")]
+	signatories: Vec<String>,
+	/// Cancel the pending multisig operation for the call, refunding the deposit to the signer.
+	#[arg(long, requires = "multisig")]
+	cancel_multisig: bool,
+	/// Stream the events of new blocks until interrupted, from either finalized (default) or best
+	/// blocks. Events can be filtered using `--pallet`, `--event` and `--account`.
+	#[arg(
+		long,
+		value_enum,
+		num_args = 0..=1,
+		default_missing_value = "finalized",
+		conflicts_with_all = [
+			"function", "args", "call", "decode", "batch", "script", "storage", "constant",
+			"multisig", "dry_run", "use-wallet", "sudo"
+		]
+	)]
+	watch: Option<BlockSubscription>,
+	/// Only show watched events with the given name.
+	#[arg(long, requires = "watch")]
+	event: Option<String>,
+	/// Only show watched events involving the given account.
+	#[arg(long, requires = "watch")]
+	account: Option<String>,
+	/// Export the unsigned payload of the extrinsic to a file, for signing on an offline device.
+	/// Nothing is submitted.
+	#[arg(
+		long,
+		value_name = "FILE",
+		requires = "signer_address",
+		conflicts_with_all = ["suri", "use-wallet", "dry_run", "decode", "batch", "script", "storage", "constant", "watch"]
+	)]
+	export_payload: Option<PathBuf>,
+	/// The address of the account which signs the exported payload offline.
+	#[arg(long, requires = "export_payload")]
+	signer_address: Option<String>,
+	/// The number of blocks for which the exported payload is valid. The extrinsic is immortal
+	/// if not specified.
+	#[arg(long, value_name = "BLOCKS", requires = "export_payload")]
+	mortality: Option<u64>,
+	/// Submit an extrinsic signed offline, using a payload previously exported with
+	/// `--export-payload` together with its `--signature`.
+	#[arg(
+		long,
+		value_name = "FILE",
+		requires = "signature",
+		conflicts_with_all = [
+			"pallet", "function", "args", "call", "suri", "use-wallet", "dry_run", "decode", "batch",
+			"script", "storage", "constant", "watch", "multisig", "export_payload"
+		]
+	)]
+	submit_signed: Option<PathBuf>,
+	/// The signature of the exported payload, encoded as hex or provided as a path to a file
+	/// containing it.
+	#[arg(long, requires = "submit_signed")]
+	signature: Option<String>,
 }
 
 impl CallChainCommand {
 	/// Executes the command.
 	pub(crate) async fn execute(mut self) -> Result<()> {
 		let mut cli = cli::Cli;
-		cli.intro("Call a chain")?;
+		if !self.json {
+			cli.intro("Call a chain")?;
+		}
 		// Check if all fields are specified via the command line.
 		let prompt_to_repeat_call = self.requires_user_input();
 		// Configure the chain.
@@ -82,6 +216,53 @@ impl CallChainCommand {
 			&mut cli,
 		)
 		.await?;
+		// Decode the data if provided, without submitting anything.
+		if let Some(data) = self.decode.as_ref() {
+			// Failures are returned rather than displayed, to keep the output machine-readable.
+			if self.json {
+				return decode(&chain.client, data, true, &mut cli);
+			}
+			if let Err(e) = decode(&chain.client, data, false, &mut cli) {
+				display_message(&e.to_string(), false, &mut cli)?;
+			}
+			return Ok(());
+		}
+		// Submit an extrinsic which was signed offline.
+		if let Some(path) = self.submit_signed.as_ref() {
+			if let Err(e) = self.submit_offline_signed(path, &chain, &mut cli).await {
+				display_message(&e.to_string(), false, &mut cli)?;
+			} else {
+				display_message("Call complete.", true, &mut cli)?;
+			}
+			return Ok(());
+		}
+		// Stream the events of new blocks until interrupted.
+		if let Some(subscription) = self.watch {
+			return self.watch_events(subscription, &chain, &mut cli).await;
+		}
+		// Query a storage item or constant, without submitting anything.
+		if self.storage.is_some() || self.constant.is_some() {
+			if let Err(e) = self.query(&chain, &mut cli).await {
+				display_message(&e.to_string(), false, &mut cli)?;
+			} else {
+				display_message("Query complete.", true, &mut cli)?;
+			}
+			return Ok(());
+		}
+		// Execute the steps of the script, returning an error if any step fails.
+		if let Some(path) = self.script.as_ref() {
+			return execute_script(path, &chain, self.suri.as_deref(), self.skip_confirm, &mut cli)
+				.await;
+		}
+		// Configure the calls and submit them as a single batch extrinsic.
+		if let Some(mode) = self.batch {
+			if let Err(e) = self.execute_batch(mode, &chain, &mut cli).await {
+				display_message(&e.to_string(), false, &mut cli)?;
+			} else {
+				display_message("Call complete.", true, &mut cli)?;
+			}
+			return Ok(());
+		}
 		// Execute the call if call_data is provided.
 		if let Some(call_data) = self.call_data.as_ref() {
 			if let Err(e) = self
@@ -117,17 +298,36 @@ impl CallChainCommand {
 				},
 			};
 
-			// Sign and submit the extrinsic.
-			let result = if self.use_wallet {
-				let call_data = xt.encode_call_data(&chain.client.metadata())?;
-				wallet::submit_extrinsic(&chain.client, &chain.url, call_data, &mut cli)
+			// Wrap the call in a multisig operation, if specified.
+			let xt = match self.multisig {
+				Some(threshold) => match self
+					.prepare_multisig_extrinsic(threshold, &chain, xt, &call.suri, &mut cli)
 					.await
-					.map(|_| ()) // Mapping to `()` since we don't need events returned
-			} else {
-				call.submit_extrinsic(&chain.client, &chain.url, xt, &mut cli).await
+				{
+					Ok(payload) => payload,
+					Err(e) => {
+						display_message(&e.to_string(), false, &mut cli)?;
+						break;
+					},
+				},
+				None => xt,
 			};
 
-			if let Err(e) = result {
+			// Export the payload to be signed offline instead of submitting the extrinsic.
+			if let Some(path) = self.export_payload.as_ref() {
+				if let Err(e) = self
+					.export_signing_payload(path, &chain.client, &chain.url, &xt, &mut cli)
+					.await
+				{
+					display_message(&e.to_string(), false, &mut cli)?;
+				} else {
+					display_message("Payload exported.", true, &mut cli)?;
+				}
+				break;
+			}
+
+			// Sign and submit the extrinsic.
+			if let Err(e) = call.execute(&chain, xt, &mut cli).await {
 				display_message(&e.to_string(), false, &mut cli)?;
 				break;
 			}
@@ -204,7 +404,11 @@ impl CallChainCommand {
 				self.check_sudo(chain, cli)?;
 			}
 
-			let (use_wallet, suri) = self.determine_signing_method(cli)?;
+			// The payload is signed offline when exported, so no signer is resolved.
+			let (use_wallet, suri) = match self.export_payload {
+				Some(_) => (false, String::new()),
+				None => self.determine_signing_method(cli)?,
+			};
 			self.use_wallet = use_wallet;
 
 			return Ok(Call {
@@ -214,10 +418,335 @@ impl CallChainCommand {
 				skip_confirm: self.skip_confirm,
 				sudo: self.sudo,
 				use_wallet: self.use_wallet,
+				dry_run: self.dry_run,
 			});
 		}
 	}
 
+	// Configures the calls to batch and submits them as a single extrinsic, signed once.
+	async fn execute_batch(
+		&mut self,
+		mode: BatchMode,
+		chain: &Chain,
+		cli: &mut impl Cli,
+	) -> Result<()> {
+		let function = find_dispatchable_by_name(&chain.pallets, "Utility", mode.as_ref())?.clone();
+		// Resolve the signer once for all calls in the batch.
+		let (use_wallet, suri) = self.determine_signing_method(cli)?;
+		self.use_wallet = use_wallet;
+		self.suri = Some(suri.clone());
+		// When using sudo, the batch as a whole is dispatched with `Root` origin.
+		let sudo = std::mem::take(&mut self.sudo);
+		let calls = match self.batch_file.as_ref() {
+			Some(path) => load_batch_file(path, &chain.pallets)?,
+			None => self.configure_batch_calls(chain, cli)?,
+		};
+		if sudo {
+			self.sudo = true;
+			self.check_sudo(chain, cli)?;
+		}
+		cli.info(format!("Batching {} calls using `Utility::{}`.", calls.len(), mode.as_ref()))?;
+		let xt = construct_batch_extrinsic(&chain.pallets, mode, calls)?;
+		let xt = if self.sudo { construct_sudo_extrinsic(xt) } else { xt };
+		let encoded_data = encode_call_data(&chain.client, &xt)?;
+		if encoded_data.len() < ENCODED_CALL_DATA_MAX_LEN {
+			cli.info(format!("Encoded call data: {}", encoded_data))?;
+		}
+		let mut call = Call {
+			function,
+			args: vec![],
+			suri,
+			use_wallet,
+			skip_confirm: self.skip_confirm,
+			sudo: self.sudo,
+			dry_run: self.dry_run,
+		};
+		call.execute(chain, xt, cli).await
+	}
+
+	// Prompts the user to configure the calls to include in a batch.
+	fn configure_batch_calls(
+		&mut self,
+		chain: &Chain,
+		cli: &mut impl Cli,
+	) -> Result<Vec<DynamicPayload>> {
+		let mut calls = Vec::new();
+		loop {
+			let call = self.configure_call(chain, cli)?;
+			cli.info(call.display(chain))?;
+			calls.push(call.prepare_extrinsic(&chain.client, cli)?);
+			if !cli
+				.confirm("Do you want to add another call to the batch?")
+				.initial_value(true)
+				.interact()?
+			{
+				return Ok(calls);
+			}
+			self.pallet = None;
+			self.function = None;
+			self.args.clear();
+		}
+	}
+
+	// Wraps the call in the multisig operation to be performed by the signer, based on the
+	// approvals of any pending operation for the call. When exporting the payload to be signed
+	// offline, the signer is the account of the signer address.
+	async fn prepare_multisig_extrinsic(
+		&self,
+		threshold: u16,
+		chain: &Chain,
+		xt: DynamicPayload,
+		suri: &str,
+		cli: &mut impl Cli,
+	) -> Result<DynamicPayload> {
+		let multisig = Multisig::new(threshold, &self.signatories)?;
+		let signer = match (&self.export_payload, &self.signer_address) {
+			(Some(_), Some(address)) => AccountId32::from_ss58check(address)
+				.map_err(|_| anyhow!("Invalid signer address: {address}"))?,
+			_ => AccountId32::from(create_signer(suri)?.account_id().0),
+		};
+		cli.info(format!("Multisig account: {}", multisig.account().to_ss58check()))?;
+		let pending = if threshold > 1 {
+			query_pending_multisig(&chain.client, &multisig, call_hash(&chain.client, &xt)?).await?
+		} else {
+			None
+		};
+		let action = match pending {
+			_ if threshold == 1 && !self.cancel_multisig =>
+				MultisigAction::AsMulti { timepoint: None, max_weight: Default::default() },
+			Some(pending) if self.cancel_multisig => {
+				cli.info("Cancelling the pending multisig operation.")?;
+				MultisigAction::CancelAsMulti { timepoint: pending.when }
+			},
+			None if self.cancel_multisig =>
+				return Err(anyhow!(
+					"There is no pending multisig operation for the call to cancel."
+				)),
+			Some(pending) if pending.approvals.contains(&signer) =>
+				return Err(anyhow!(
+					"{} has already approved the pending multisig operation for the call.",
+					signer.to_ss58check()
+				)),
+			pending => {
+				let approvals = pending.as_ref().map_or(0, |p| p.approvals.len());
+				let timepoint = pending.map(|p| p.when);
+				cli.info(format!("Approvals: {}/{threshold}", approvals + 1))?;
+				if approvals + 1 >= threshold as usize {
+					// The final approval dispatches the call, so its weight must be provided.
+					let max_weight = estimate_call_weight(&chain.client, &xt).await?;
+					MultisigAction::AsMulti { timepoint, max_weight }
+				} else {
+					MultisigAction::ApproveAsMulti { timepoint }
+				}
+			},
+		};
+		Ok(construct_multisig_extrinsic(&chain.client, &multisig, &signer, action, xt)?)
+	}
+
+	// Streams the events of new blocks which match the filters, until interrupted.
+	async fn watch_events(
+		&self,
+		subscription: BlockSubscription,
+		chain: &Chain,
+		cli: &mut impl Cli,
+	) -> Result<()> {
+		let account = self
+			.account
+			.as_deref()
+			.map(|address| {
+				AccountId32::from_ss58check(address)
+					.map_err(|_| anyhow!("Invalid account address: {address}"))
+			})
+			.transpose()?;
+		let filter =
+			EventFilter { pallet: self.pallet.clone(), event: self.event.clone(), account };
+		let mut events = EventSubscription::new(&chain.client, subscription, filter).await?;
+		if !self.json {
+			cli.info(format!(
+				"Watching events of {} blocks. Press Ctrl+C to stop.",
+				subscription.as_ref()
+			))?;
+		}
+		loop {
+			tokio::select! {
+				next = events.next() => match next {
+					Some(Ok(events)) =>
+						for event in events {
+							if self.json {
+								cli.plain(serde_json::to_string(&event)?)?;
+							} else {
+								cli.plain(event)?;
+							}
+						},
+					Some(Err(e)) => return Err(anyhow!("{}", format!("{e:?}"))),
+					None => break,
+				},
+				_ = tokio::signal::ctrl_c() => break,
+			}
+		}
+		if !self.json {
+			display_message("Stopped watching events.", true, cli)?;
+		}
+		Ok(())
+	}
+
+	// Exports the payload of an extrinsic to a file, to be signed on an offline device.
+	async fn export_signing_payload<Xt: Payload>(
+		&self,
+		path: &Path,
+		client: &OnlineClient<SubstrateConfig>,
+		url: &Url,
+		xt: &Xt,
+		cli: &mut impl Cli,
+	) -> Result<()> {
+		let signer = self
+			.signer_address
+			.as_deref()
+			.ok_or_else(|| anyhow!("The address of the signer is required to export a payload."))?;
+		let payload = create_signing_payload(client, url, xt, signer, self.mortality)
+			.await
+			.map_err(|err| anyhow!("{}", format!("{err:?}")))?;
+		payload.save(path).map_err(|err| anyhow!("{}", format!("{err:?}")))?;
+		cli.info(format!(
+			"Payload to sign with nonce {} exported to {}:\n{}",
+			payload.nonce,
+			path.display(),
+			payload.payload
+		))?;
+		match render_qr_code(&payload.payload) {
+			Some(code) => cli.plain(format!("Scan the payload to sign:\n{code}"))?,
+			None => cli.warning(
+				"The payload is too large to be displayed as a QR code, sign the exported file instead.",
+			)?,
+		}
+		cli.plain(format!(
+			"Sign the payload on your offline device, then submit it with `--submit-signed {} --signature <SIGNATURE>`.",
+			path.display()
+		))?;
+		Ok(())
+	}
+
+	// Assembles an extrinsic from a payload signed offline and submits it.
+	async fn submit_offline_signed(
+		&self,
+		path: &Path,
+		chain: &Chain,
+		cli: &mut impl Cli,
+	) -> Result<()> {
+		let payload =
+			SigningPayload::load(path).map_err(|err| anyhow!("{}", format!("{err:?}")))?;
+		let signature = self
+			.signature
+			.as_deref()
+			.ok_or_else(|| anyhow!("The signature of the payload is required."))?;
+		// The signature may be provided within a file, as returned by the offline device.
+		let signature = match Path::new(signature).is_file() {
+			true => std::fs::read_to_string(signature)?,
+			false => signature.to_string(),
+		};
+		let extrinsic = assemble_signed_extrinsic(&chain.client, &payload, &signature)
+			.map_err(|err| anyhow!("{}", format!("{err:?}")))?;
+		cli.info(format!("Call data: {}", payload.call_data))?;
+		if !self.skip_confirm &&
+			!cli.confirm("Do you want to submit the extrinsic?")
+				.initial_value(true)
+				.interact()?
+		{
+			return Err(anyhow!("Extrinsic signed by {} was not submitted.", payload.signer));
+		}
+		let spinner = cliclack::spinner();
+		spinner
+			.start("Submitting the extrinsic and waiting for finalization, please be patient...");
+		let result = submit_signed_extrinsic(chain.client.clone(), extrinsic)
+			.await
+			.map_err(|err| anyhow!("{}", format!("{err:?}")))?;
+		let events = parse_and_format_events(&chain.client, &chain.url, &result)
+			.await
+			.map_err(|err| anyhow!("{}", format!("{err:?}")))?;
+		spinner.stop(format!(
+			"Extrinsic submitted with hash: {:?}\n{}",
+			result.extrinsic_hash(),
+			events
+		));
+		Ok(())
+	}
+
+	// Queries a storage item or inspects a constant, prompting for any details not provided.
+	async fn query(&self, chain: &Chain, cli: &mut impl Cli) -> Result<()> {
+		if let Some(constant) = self.constant.as_ref() {
+			let constant = match constant {
+				Some(name) => {
+					let (pallet, name) = parse_item_name(name)?;
+					find_constant_by_name(&chain.pallets, &pallet, &name)?
+				},
+				None => {
+					let pallets = chain.pallets.iter().filter(|p| !p.constants.is_empty());
+					let pallet = prompt_for_pallet("Select the pallet to inspect:", pallets, cli)?;
+					let mut prompt = cli.select("Select the constant to inspect:");
+					for constant in &pallet.constants {
+						prompt = prompt.item(constant, &constant.name, &constant.docs);
+					}
+					prompt.interact()?
+				},
+			};
+			let value = constant.value.as_ref().ok_or_else(|| {
+				anyhow!("The value of {}::{} could not be decoded.", constant.pallet, constant.name)
+			})?;
+			cli.info(format!("{}::{}: {value}", constant.pallet, constant.name))?;
+			return Ok(());
+		}
+
+		let items = self.storage.clone().unwrap_or_default();
+		let (storage, keys) = match items.split_first() {
+			Some((name, keys)) => {
+				let (pallet, name) = parse_item_name(name)?;
+				(
+					find_storage_by_name(&chain.pallets, &pallet, &name)?,
+					expand_file_arguments(keys)?,
+				)
+			},
+			None => {
+				let pallets = chain.pallets.iter().filter(|p| !p.storage.is_empty());
+				let pallet = prompt_for_pallet("Select the pallet to query:", pallets, cli)?;
+				let mut prompt = cli.select("Select the storage item to query:");
+				for storage in &pallet.storage {
+					prompt = prompt.item(storage, &storage.name, &storage.docs);
+				}
+				(prompt.interact()?, vec![])
+			},
+		};
+		// Certain storage items are not supported yet due to the complexity of their keys.
+		if !storage.is_supported {
+			return Err(anyhow!("The storage item `{}` is not supported yet.", storage.name));
+		}
+		// Prompt for the keys of a storage map when not provided.
+		let keys = if keys.is_empty() {
+			storage
+				.keys
+				.iter()
+				.map(|key| prompt_for_param(cli, key))
+				.collect::<Result<_>>()?
+		} else {
+			keys
+		};
+		let item = if keys.is_empty() {
+			format!("{}::{}", storage.pallet, storage.name)
+		} else {
+			format!("{}::{}({})", storage.pallet, storage.name, keys.join(", "))
+		};
+		let spinner = cliclack::spinner();
+		spinner.start(format!("Querying {item}..."));
+		let value = query_storage(&chain.client, storage, keys, self.at)
+			.await
+			.map_err(|err| anyhow!("{}", format!("{err:?}")));
+		spinner.clear();
+		match value? {
+			Some(value) => cli.info(format!("{item}: {value}"))?,
+			None => cli.warning(format!("No value is stored at {item}."))?,
+		}
+		Ok(())
+	}
+
 	// Submits an extrinsic to the chain using the provided encoded call data.
 	async fn submit_extrinsic_from_call_data(
 		&self,
@@ -226,8 +755,28 @@ impl CallChainCommand {
 		call_data: &str,
 		cli: &mut impl Cli,
 	) -> Result<()> {
+		// Export the payload to be signed offline and return early, without submitting anything.
+		if let Some(path) = self.export_payload.as_ref() {
+			let call_data_bytes =
+				decode_call_data(call_data).map_err(|err| anyhow!("{}", format!("{err:?}")))?;
+			self.export_signing_payload(path, client, url, &CallData::new(call_data_bytes), cli)
+				.await?;
+			display_message("Payload exported.", true, cli)?;
+			return Ok(());
+		}
+
 		let (use_wallet, suri) = self.determine_signing_method(cli)?;
 
+		// Dry run the call data and return early, without submitting anything.
+		if self.dry_run {
+			let call_data_bytes =
+				decode_call_data(call_data).map_err(|err| anyhow!("{}", format!("{err:?}")))?;
+			cli.info(format!("Encoded call data: {}", call_data))?;
+			dry_run_extrinsic(client, url, CallData::new(call_data_bytes), &suri, cli).await?;
+			display_message("Dry run complete.", true, cli)?;
+			return Ok(());
+		}
+
 		// Perform signing steps with wallet integration and return early.
 		if use_wallet {
 			let call_data_bytes =
@@ -264,15 +813,15 @@ impl CallChainCommand {
 		Ok(())
 	}
 
-	// Resolve who is signing the extrinsic. If a `suri` was provided via the command line,
-	// skip the prompt.
+	// Resolve who is signing the extrinsic, unlocking the signer if encrypted. If a `suri` was
+	// provided via the command line, skip the prompt.
 	fn determine_signing_method(&self, cli: &mut impl Cli) -> Result<(bool, String)> {
 		let mut use_wallet = self.use_wallet;
 		let suri = match self.suri.as_ref() {
 			Some(suri) => suri.clone(),
 			None =>
 				if !self.use_wallet {
-					if prompt_to_use_wallet(cli)? {
+					if !self.dry_run && prompt_to_use_wallet(cli)? {
 						use_wallet = true;
 						DEFAULT_URI.to_string()
 					} else {
@@ -284,6 +833,9 @@ impl CallChainCommand {
 					DEFAULT_URI.to_string()
 				},
 		};
+		if !use_wallet {
+			unlock_signer(&suri, cli)?;
+		}
 		Ok((use_wallet, suri))
 	}
 
@@ -327,17 +879,7 @@ impl CallChainCommand {
 
 	/// Replaces file arguments with their contents, leaving other arguments unchanged.
 	fn expand_file_arguments(&self) -> Result<Vec<String>> {
-		self.args
-			.iter()
-			.map(|arg| {
-				if std::fs::metadata(arg).map(|m| m.is_file()).unwrap_or(false) {
-					std::fs::read_to_string(arg)
-						.map_err(|err| anyhow!("Failed to read file {}", err.to_string()))
-				} else {
-					Ok(arg.clone())
-				}
-			})
-			.collect()
+		expand_file_arguments(&self.args)
 	}
 }
 
@@ -361,9 +903,30 @@ pub(crate) struct Call {
 	pub(crate) skip_confirm: bool,
 	/// Whether to dispatch the function call with `Root` origin.
 	pub(crate) sudo: bool,
+	/// Whether to dry run the extrinsic instead of submitting it.
+	pub(crate) dry_run: bool,
 }
 
 impl Call {
+	// Dry runs the extrinsic, or signs and submits it using the configured signing method.
+	async fn execute(
+		&mut self,
+		chain: &Chain,
+		xt: DynamicPayload,
+		cli: &mut impl Cli,
+	) -> Result<()> {
+		if self.dry_run {
+			dry_run_extrinsic(&chain.client, &chain.url, xt, &self.suri, cli).await
+		} else if self.use_wallet {
+			let call_data = xt.encode_call_data(&chain.client.metadata())?;
+			wallet::submit_extrinsic(&chain.client, &chain.url, call_data, cli)
+				.await
+				.map(|_| ()) // Mapping to `()` since we don't need events returned
+		} else {
+			self.submit_extrinsic(&chain.client, &chain.url, xt, cli).await
+		}
+	}
+
 	// Prepares the extrinsic.
 	pub(crate) fn prepare_extrinsic(
 		&self,
@@ -437,16 +1000,242 @@ impl Call {
 		full_message.push_str(&format!(" --url {}", chain.url));
 		if self.use_wallet {
 			full_message.push_str(" --use-wallet");
-		} else {
+		} else if !self.suri.is_empty() {
 			full_message.push_str(&format!(" --suri {}", self.suri));
 		}
 		if self.sudo {
 			full_message.push_str(" --sudo");
 		}
+		if self.dry_run {
+			full_message.push_str(" --dry-run");
+		}
 		full_message
 	}
 }
 
+// A file listing the calls to batch.
+#[derive(Deserialize)]
+struct BatchFile {
+	calls: Vec<BatchCall>,
+}
+
+// A call listed within a batch file.
+#[derive(Deserialize)]
+struct BatchCall {
+	pallet: String,
+	function: String,
+	#[serde(default)]
+	args: Vec<String>,
+}
+
+// Loads the calls to batch from a TOML or JSON file.
+fn load_batch_file(path: &Path, pallets: &[Pallet]) -> Result<Vec<DynamicPayload>> {
+	let batch: BatchFile = read_calls_file(path)?;
+	batch
+		.calls
+		.into_iter()
+		.map(|call| construct_call(pallets, &call.pallet, &call.function, call.args))
+		.collect()
+}
+
+// A script listing an ordered sequence of calls to execute.
+#[derive(Deserialize)]
+struct CallScript {
+	steps: Vec<ScriptStep>,
+}
+
+// A step within a call script.
+#[derive(Deserialize)]
+struct ScriptStep {
+	#[serde(default)]
+	name: Option<String>,
+	pallet: String,
+	function: String,
+	#[serde(default)]
+	args: Vec<String>,
+	#[serde(default)]
+	signer: Option<String>,
+	#[serde(default)]
+	sudo: bool,
+	#[serde(default)]
+	expected_events: Vec<String>,
+}
+
+// Executes the steps of a call script in order, reporting whether each step passed or failed.
+// Steps without a signer are signed by `suri`, which is prompted for when not provided, unless
+// confirmation is skipped.
+async fn execute_script(
+	path: &Path,
+	chain: &Chain,
+	suri: Option<&str>,
+	skip_confirm: bool,
+	cli: &mut impl Cli,
+) -> Result<()> {
+	let script: CallScript = read_calls_file(path)?;
+	let total = script.steps.len();
+	if total == 0 {
+		return Err(anyhow!("The script at {} does not contain any steps.", path.display()));
+	}
+	let suri = match suri {
+		Some(suri) => suri.to_string(),
+		None if script.steps.iter().all(|step| step.signer.is_some()) => String::new(),
+		None if skip_confirm => {
+			return Err(anyhow!(
+				"The script at {} has steps without a `signer`. Specify their signer using `--suri`.",
+				path.display()
+			));
+		},
+		None => cli
+			.input("Signer of the steps without a `signer`:")
+			.default_input(DEFAULT_URI)
+			.interact()?,
+	};
+	// Unlock any encrypted signers before executing the steps.
+	for step in &script.steps {
+		unlock_signer(step.signer.as_deref().unwrap_or(&suri), cli)?;
+	}
+	let mut failed = 0;
+	for (i, step) in script.steps.iter().enumerate() {
+		let name = step
+			.name
+			.clone()
+			.unwrap_or_else(|| format!("{}::{}", step.pallet, step.function));
+		let label = format!("Step {}/{total} `{name}`", i + 1);
+		let spinner = cliclack::spinner();
+		spinner.start(format!("{label}: submitting and waiting for finalization..."));
+		let result = execute_step(step, chain, &suri).await;
+		spinner.clear();
+		match result {
+			Ok(()) => cli.success(format!("{label} passed."))?,
+			Err(e) => {
+				failed += 1;
+				cli.warning(format!("{label} failed: {e}"))?;
+			},
+		}
+	}
+	if failed > 0 {
+		return Err(anyhow!("{failed} of {total} steps failed."));
+	}
+	display_message(&format!("All {total} steps passed."), true, cli)
+}
+
+// Signs and submits the call of a script step, checking that the expected events were emitted.
+async fn execute_step(step: &ScriptStep, chain: &Chain, suri: &str) -> Result<()> {
+	let args = expand_file_arguments(&step.args)?;
+	let xt = construct_call(&chain.pallets, &step.pallet, &step.function, args)?;
+	let xt = if step.sudo { construct_sudo_extrinsic(xt) } else { xt };
+	let suri = step.signer.as_deref().unwrap_or(suri);
+	let result = submit_extrinsic_with_events(&chain.client, &chain.url, xt, suri)
+		.await
+		.map_err(|err| anyhow!("{}", format!("{err:?}")))?;
+	let missing: Vec<_> = step
+		.expected_events
+		.iter()
+		.filter(|e| !result.events.contains(e))
+		.cloned()
+		.collect();
+	if !missing.is_empty() {
+		return Err(anyhow!(
+			"expected events were not emitted: {} (emitted: {})",
+			missing.join(", "),
+			result.events.join(", ")
+		));
+	}
+	Ok(())
+}
+
+// Constructs the extrinsic for a call specified by its pallet and function names.
+fn construct_call(
+	pallets: &[Pallet],
+	pallet: &str,
+	function: &str,
+	args: Vec<String>,
+) -> Result<DynamicPayload> {
+	let pallet = parse_pallet_name(pallet).map_err(|e| anyhow!(e))?;
+	let function = parse_function_name(function).map_err(|e| anyhow!(e))?;
+	let function = find_dispatchable_by_name(pallets, &pallet, &function)?;
+	construct_extrinsic(function, args).map_err(|e| anyhow!("Error: {}", e))
+}
+
+// Reads a file listing calls, parsed as TOML or JSON based on its extension.
+fn read_calls_file<T: DeserializeOwned>(path: &Path) -> Result<T> {
+	let contents = std::fs::read_to_string(path)
+		.map_err(|err| anyhow!("Failed to read file {}", err.to_string()))?;
+	Ok(match path.extension().and_then(|e| e.to_str()) {
+		Some("toml") => toml::from_str(&contents)?,
+		_ => serde_json::from_str(&contents)?,
+	})
+}
+
+// Replaces file arguments with their contents, leaving other arguments unchanged.
+fn expand_file_arguments(args: &[String]) -> Result<Vec<String>> {
+	args.iter()
+		.map(|arg| {
+			if std::fs::metadata(arg).map(|m| m.is_file()).unwrap_or(false) {
+				std::fs::read_to_string(arg)
+					.map_err(|err| anyhow!("Failed to read file {}", err.to_string()))
+			} else {
+				Ok(arg.clone())
+			}
+		})
+		.collect()
+}
+
+// Decodes call data or an extrinsic and displays the resulting call tree.
+fn decode(
+	client: &OnlineClient<SubstrateConfig>,
+	data: &str,
+	json: bool,
+	cli: &mut impl Cli,
+) -> Result<()> {
+	let decoded =
+		decode_extrinsic(client, data).map_err(|err| anyhow!("{}", format!("{err:?}")))?;
+	if json {
+		cli.plain(serde_json::to_string_pretty(&decoded)?)?;
+		return Ok(());
+	}
+	cli.info(decoded)?;
+	display_message("Decoding complete.", true, cli)
+}
+
+// Renders data as a QR code to be scanned by an offline device, if it fits within a QR code.
+fn render_qr_code(data: &str) -> Option<String> {
+	let code = QrCode::new(data.as_bytes()).ok()?;
+	Some(code.render::<Dense1x2>().quiet_zone(true).build())
+}
+
+// Dry runs an extrinsic and displays the dispatch result and any events it would emit.
+async fn dry_run_extrinsic(
+	client: &OnlineClient<SubstrateConfig>,
+	url: &Url,
+	xt: impl Payload,
+	suri: &str,
+	cli: &mut impl Cli,
+) -> Result<()> {
+	let spinner = cliclack::spinner();
+	spinner.start("Dry running the extrinsic...");
+	let outcome = pop_chains::dry_run_extrinsic(client, url, xt, suri)
+		.await
+		.map_err(|err| anyhow!("{}", format!("{err:?}")));
+	spinner.clear();
+	let outcome = outcome?;
+	match outcome.result {
+		DryRunResult::Success => cli.success("The call would be dispatched successfully.")?,
+		DryRunResult::DispatchError(error) =>
+			cli.warning(format!("The call would fail to dispatch: {error}"))?,
+		DryRunResult::TransactionValidityError(error) =>
+			cli.warning(format!("The extrinsic would be rejected as invalid: {error}"))?,
+	}
+	match outcome.events {
+		Some(events) if !events.is_empty() =>
+			cli.info(format!("Events that would be emitted:\n{}", events.join("\n")))?,
+		Some(_) => cli.info("No events would be emitted.")?,
+		None => cli.info("Events are not reported by the runtime without the `DryRunApi`.")?,
+	}
+	cli.info("The extrinsic was not submitted.")?;
+	Ok(())
+}
+
 // Prompts the user for some predefined actions.
 fn prompt_predefined_actions(pallets: &[Pallet], cli: &mut impl Cli) -> Result<Option<Action>> {
 	let mut predefined_action = cli.select("What would you like to do?");
@@ -461,6 +1250,19 @@ fn prompt_predefined_actions(pallets: &[Pallet], cli: &mut impl Cli) -> Result<O
 	Ok(predefined_action.interact()?)
 }
 
+// Prompts the user to select a pallet from those provided.
+fn prompt_for_pallet<'a>(
+	message: &str,
+	pallets: impl Iterator<Item = &'a Pallet>,
+	cli: &mut impl Cli,
+) -> Result<&'a Pallet> {
+	let mut prompt = cli.select(message);
+	for pallet in pallets {
+		prompt = prompt.item(pallet, &pallet.name, &pallet.docs);
+	}
+	Ok(prompt.interact()?)
+}
+
 // Prompts the user for the value of a parameter.
 fn prompt_for_param(cli: &mut impl Cli, param: &Param) -> Result<String> {
 	if param.is_optional {
@@ -604,6 +1406,22 @@ fn parse_function_name(name: &str) -> Result<String, String> {
 	Ok(name.to_ascii_lowercase())
 }
 
+// Parses the name of a storage item or constant, specified as `Pallet::Item`.
+fn parse_item_name(name: &str) -> Result<(String, String)> {
+	let (pallet, item) = name
+		.split_once("::")
+		.ok_or_else(|| anyhow!("Invalid name `{name}`, expected the format `Pallet::Item`."))?;
+	Ok((parse_pallet_name(pallet).map_err(|e| anyhow!(e))?, item.to_string()))
+}
+
+// Parses a block hash.
+fn parse_block_hash(hash: &str) -> Result<H256, String> {
+	match sp_core::bytes::from_hex(hash) {
+		Ok(bytes) if bytes.len() == 32 => Ok(H256::from_slice(&bytes)),
+		_ => Err(format!("Invalid block hash: {hash}")),
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -615,6 +1433,27 @@ mod tests {
 
 	const BOB_SURI: &str = "//Bob";
 
+	#[test]
+	fn json_requires_decode_or_watch_and_url() {
+		use clap::Parser;
+
+		#[derive(Parser)]
+		struct Command {
+			#[command(flatten)]
+			call: CallChainCommand,
+		}
+
+		let parse = |args: &[&str]| Command::try_parse_from(std::iter::once(&"call").chain(args));
+		assert!(parse(&["--json"]).is_err());
+		assert!(parse(&["--json", "--pallet", "System"]).is_err());
+		// The URL is required, as nothing but JSON may be written to stdout.
+		assert!(parse(&["--json", "--decode", "0x0000"]).is_err());
+		assert!(parse(&["--json", "--decode", "0x0000", "--url", "ws://localhost:9944"])
+			.is_ok_and(|c| c.call.json));
+		assert!(parse(&["--json", "--watch", "--url", "ws://localhost:9944"])
+			.is_ok_and(|c| c.call.json));
+	}
+
 	#[tokio::test]
 	async fn guide_user_to_call_chain_works() -> Result<()> {
 		let node = TestNode::spawn().await?;
@@ -772,6 +1611,7 @@ mod tests {
 			use_wallet: false,
 			skip_confirm: false,
 			sudo: false,
+			dry_run: false,
 		};
 		let mut cli = MockCli::new();
 		// Error, wrong name of the pallet.
@@ -816,6 +1656,26 @@ mod tests {
 			skip_confirm: false,
 			call_data: Some("0x00000411".to_string()),
 			sudo: false,
+			dry_run: false,
+			decode: None,
+			json: false,
+			batch: None,
+			batch_file: None,
+			script: None,
+			storage: None,
+			constant: None,
+			at: None,
+			multisig: None,
+			signatories: vec![],
+			cancel_multisig: false,
+			watch: None,
+			event: None,
+			account: None,
+			export_payload: None,
+			signer_address: None,
+			mortality: None,
+			submit_signed: None,
+			signature: None,
 		};
 		let mut cli = MockCli::new()
 			.expect_confirm(USE_WALLET_PROMPT, false)
@@ -834,6 +1694,14 @@ mod tests {
 		cli.verify()
 	}
 
+	#[test]
+	fn determine_signing_method_skips_wallet_for_dry_run() -> Result<()> {
+		let call_config = CallChainCommand { dry_run: true, ..Default::default() };
+		let mut cli = MockCli::new().expect_input("Signer of the extrinsic:", BOB_SURI.into());
+		assert_eq!(call_config.determine_signing_method(&mut cli)?, (false, BOB_SURI.to_string()));
+		cli.verify()
+	}
+
 	#[test]
 	fn reset_for_new_call_works() -> Result<()> {
 		let mut call_config = CallChainCommand {
@@ -846,6 +1714,26 @@ mod tests {
 			skip_confirm: false,
 			call_data: None,
 			sudo: true,
+			dry_run: false,
+			decode: None,
+			json: false,
+			batch: None,
+			batch_file: None,
+			script: None,
+			storage: None,
+			constant: None,
+			at: None,
+			multisig: None,
+			signatories: vec![],
+			cancel_multisig: false,
+			watch: None,
+			event: None,
+			account: None,
+			export_payload: None,
+			signer_address: None,
+			mortality: None,
+			submit_signed: None,
+			signature: None,
 		};
 		call_config.reset_for_new_call();
 		assert_eq!(call_config.pallet, None);
@@ -868,6 +1756,26 @@ mod tests {
 			skip_confirm: false,
 			call_data: None,
 			sudo: false,
+			dry_run: false,
+			decode: None,
+			json: false,
+			batch: None,
+			batch_file: None,
+			script: None,
+			storage: None,
+			constant: None,
+			at: None,
+			multisig: None,
+			signatories: vec![],
+			cancel_multisig: false,
+			watch: None,
+			event: None,
+			account: None,
+			export_payload: None,
+			signer_address: None,
+			mortality: None,
+			submit_signed: None,
+			signature: None,
 		};
 		assert!(!call_config.requires_user_input());
 		call_config.pallet = None;
@@ -887,6 +1795,26 @@ mod tests {
 			call_data: None,
 			skip_confirm: false,
 			sudo: false,
+			dry_run: false,
+			decode: None,
+			json: false,
+			batch: None,
+			batch_file: None,
+			script: None,
+			storage: None,
+			constant: None,
+			at: None,
+			multisig: None,
+			signatories: vec![],
+			cancel_multisig: false,
+			watch: None,
+			event: None,
+			account: None,
+			export_payload: None,
+			signer_address: None,
+			mortality: None,
+			submit_signed: None,
+			signature: None,
 		};
 		assert_eq!(
 			call_config.expand_file_arguments()?,
@@ -914,6 +1842,201 @@ mod tests {
 		Ok(())
 	}
 
+	#[tokio::test]
+	async fn load_batch_file_works() -> Result<()> {
+		let node = TestNode::spawn().await?;
+		let client = set_up_client(node.ws_url()).await?;
+		let pallets = parse_chain_metadata(&client)?;
+		let temp_dir = tempdir()?;
+		let toml_file = temp_dir.path().join("calls.toml");
+		std::fs::write(
+			&toml_file,
+			"[[calls]]\npallet = \"system\"\nfunction = \"remark\"\nargs = [\"0x11\"]\n\n[[calls]]\npallet = \"System\"\nfunction = \"remark_with_event\"\nargs = [\"0x22\"]\n",
+		)?;
+		let calls = load_batch_file(&toml_file, &pallets)?;
+		assert_eq!(calls.len(), 2);
+		assert_eq!(encode_call_data(&client, &calls[0])?, "0x00000411");
+		assert_eq!(encode_call_data(&client, &calls[1])?, "0x00070422");
+
+		let json_file = temp_dir.path().join("calls.json");
+		std::fs::write(
+			&json_file,
+			r#"{ "calls": [{ "pallet": "System", "function": "remark", "args": ["0x11"] }] }"#,
+		)?;
+		let calls = load_batch_file(&json_file, &pallets)?;
+		assert_eq!(calls.len(), 1);
+		assert_eq!(encode_call_data(&client, &calls[0])?, "0x00000411");
+
+		std::fs::write(
+			&json_file,
+			r#"{ "calls": [{ "pallet": "Unknown", "function": "remark" }] }"#,
+		)?;
+		assert!(load_batch_file(&json_file, &pallets).is_err());
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn execute_script_works() -> Result<()> {
+		let node = TestNode::spawn().await?;
+		let node_url = Url::parse(node.ws_url())?;
+		let client = set_up_client(node.ws_url()).await?;
+		let chain = Chain { url: node_url, pallets: parse_chain_metadata(&client)?, client };
+		let temp_dir = tempdir()?;
+		let script = temp_dir.path().join("script.toml");
+		std::fs::write(
+			&script,
+			r#"
+[[steps]]
+name = "Remark with event"
+pallet = "System"
+function = "remark_with_event"
+args = ["0x11"]
+expected_events = ["System::Remarked", "System::ExtrinsicSuccess"]
+
+[[steps]]
+pallet = "System"
+function = "remark"
+args = ["0x22"]
+signer = "//Bob"
+expected_events = ["System::Remarked"]
+"#,
+		)?;
+		let mut cli = MockCli::new().expect_success("Step 1/2 `Remark with event` passed.");
+		let result = execute_script(&script, &chain, Some(DEFAULT_URI), true, &mut cli).await;
+		assert_eq!(result.unwrap_err().to_string(), "1 of 2 steps failed.");
+		cli.verify()?;
+
+		// A sudo step fails when the dispatched call fails, even though `Sudo::sudo` succeeds.
+		std::fs::write(
+			&script,
+			r#"
+[[steps]]
+pallet = "Balances"
+function = "force_transfer"
+args = ["Id(5C4hrfjw9DjXZTzV3MwzrrAr9P1MJhSrvWGWqi1eSuyUpnhM)", "Id(5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY)", "1000000000000"]
+sudo = true
+"#,
+		)?;
+		let result =
+			execute_script(&script, &chain, Some(DEFAULT_URI), true, &mut MockCli::new()).await;
+		assert_eq!(result.unwrap_err().to_string(), "1 of 1 steps failed.");
+
+		// The signer of steps without a `signer` is prompted for, rather than assumed.
+		std::fs::write(
+			&script,
+			r#"
+[[steps]]
+pallet = "System"
+function = "remark"
+args = ["0x33"]
+"#,
+		)?;
+		let result = execute_script(&script, &chain, None, true, &mut MockCli::new()).await;
+		assert_eq!(
+			result.unwrap_err().to_string(),
+			format!(
+				"The script at {} has steps without a `signer`. Specify their signer using `--suri`.",
+				script.display()
+			)
+		);
+		let mut cli = MockCli::new()
+			.expect_input("Signer of the steps without a `signer`:", "//Bob".into())
+			.expect_success("Step 1/1 `System::remark` passed.")
+			.expect_outro("All 1 steps passed.");
+		execute_script(&script, &chain, None, false, &mut cli).await?;
+		cli.verify()?;
+
+		std::fs::write(&script, "steps = []")?;
+		assert!(execute_script(&script, &chain, Some(DEFAULT_URI), true, &mut MockCli::new())
+			.await
+			.is_err());
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn query_works() -> Result<()> {
+		let node = TestNode::spawn().await?;
+		let node_url = Url::parse(node.ws_url())?;
+		let client = set_up_client(node.ws_url()).await?;
+		let chain = Chain { url: node_url, pallets: parse_chain_metadata(&client)?, client };
+		// Inspect a constant.
+		let existential_deposit =
+			find_constant_by_name(&chain.pallets, "Balances", "ExistentialDeposit")?;
+		let call_config = CallChainCommand {
+			constant: Some(Some("balances::ExistentialDeposit".to_string())),
+			..Default::default()
+		};
+		let mut cli = MockCli::new().expect_info(format!(
+			"Balances::ExistentialDeposit: {}",
+			existential_deposit.value.as_ref().unwrap()
+		));
+		call_config.query(&chain, &mut cli).await?;
+		cli.verify()?;
+		// Query a storage item interactively, at the genesis block.
+		let pallets_with_storage: Vec<_> =
+			chain.pallets.iter().filter(|p| !p.storage.is_empty()).collect();
+		let system = pallets_with_storage.iter().position(|p| p.name == "System").unwrap();
+		let number = pallets_with_storage[system].storage.iter().position(|s| s.name == "Number");
+		let call_config = CallChainCommand {
+			storage: Some(vec![]),
+			at: Some(chain.client.genesis_hash()),
+			..Default::default()
+		};
+		let mut cli = MockCli::new()
+			.expect_select("Select the pallet to query:", Some(true), true, None, system, None)
+			.expect_select(
+				"Select the storage item to query:",
+				Some(true),
+				true,
+				None,
+				number.unwrap(),
+				None,
+			)
+			.expect_info("System::Number: 0");
+		call_config.query(&chain, &mut cli).await?;
+		cli.verify()?;
+		// Query a storage map, with the key provided.
+		let call_config = CallChainCommand {
+			storage: Some(vec![
+				"System::Account".to_string(),
+				"5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY".to_string(),
+			]),
+			..Default::default()
+		};
+		call_config.query(&chain, &mut MockCli::new()).await?;
+		// Invalid storage item.
+		let call_config =
+			CallChainCommand { storage: Some(vec!["System".to_string()]), ..Default::default() };
+		assert!(call_config.query(&chain, &mut MockCli::new()).await.is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn parse_item_name_works() -> Result<()> {
+		assert_eq!(
+			parse_item_name("system::Account")?,
+			("System".to_string(), "Account".to_string())
+		);
+		assert!(parse_item_name("System").is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn parse_block_hash_works() -> Result<()> {
+		let hash = format!("0x{}", "11".repeat(32));
+		assert_eq!(parse_block_hash(&hash), Ok(H256::from([0x11; 32])));
+		assert!(parse_block_hash("0x1234").is_err());
+		assert!(parse_block_hash("invalid").is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn render_qr_code_works() {
+		assert!(render_qr_code("0x0000041100").is_some_and(|code| !code.is_empty()));
+		// The data exceeds the capacity of a QR code.
+		assert!(render_qr_code(&"11".repeat(4096)).is_none());
+	}
+
 	#[test]
 	fn parse_pallet_name_works() -> Result<()> {
 		assert_eq!(parse_pallet_name("system").unwrap(), "System");
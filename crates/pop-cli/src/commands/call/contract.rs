@@ -6,6 +6,7 @@ use crate::{
 		builds::get_project_path,
 		contracts::{has_contract_been_built, normalize_call_args, request_contract_function_args},
 		prompt::display_message,
+		signer::unlock_signer,
 		urls,
 		wallet::{prompt_to_use_wallet, request_signature},
 	},
@@ -15,13 +16,14 @@ use clap::Args;
 use cliclack::spinner;
 #[cfg(feature = "wasm-contracts")]
 use pop_common::parse_account;
-use pop_common::{DefaultConfig, Keypair};
+use pop_common::{DefaultConfig, Signer};
 use pop_contracts::{
 	build_smart_contract, call_smart_contract, call_smart_contract_from_signed_payload,
-	dry_run_call, dry_run_gas_estimate_call, get_call_payload, get_message, get_messages,
-	set_up_call, CallExec, CallOpts, DefaultEnvironment, Verbosity, Weight,
+	dry_run_call, dry_run_gas_estimate_call, get_call_payload, get_contract_name, get_message,
+	get_messages, network_key, set_up_call, BlockId, CallExec, CallOpts, ContractEventSubscription,
+	ContractStorage, DefaultEnvironment, Deployments, Verbosity, Weight,
 };
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 #[cfg(feature = "polkavm-contracts")]
 use {crate::common::contracts::map_account, pop_common::parse_h160_account};
 
@@ -36,7 +38,8 @@ pub struct CallContractCommand {
 	/// Directory path without flag for your project [default: current directory]
 	#[arg(value_name = "PATH", index = 1, conflicts_with = "path")]
 	pub(crate) path_pos: Option<PathBuf>,
-	/// The address of the contract to call.
+	/// The address of the contract to call, or the name of a contract whose deployment has been
+	/// recorded by `pop up contract` for the network.
 	#[arg(short, long, env = "CONTRACT")]
 	contract: Option<String>,
 	/// The name of the contract message to call.
@@ -65,6 +68,9 @@ pub struct CallContractCommand {
 	/// e.g.
 	/// - for a dev account "//Alice"
 	/// - with a password "//Alice///SECRET_PASSWORD"
+	/// - with a key type "ed25519://Alice" or "ecdsa://Alice"
+	/// - a key stored in the keyring "keyring:alice" (see `pop keys`)
+	/// - an encrypted JSON keystore exported from polkadot-js "json:./alice.json"
 	#[arg(short, long, default_value = DEFAULT_URI)]
 	suri: String,
 	/// Use a browser extension wallet to sign the extrinsic.
@@ -86,6 +92,25 @@ pub struct CallContractCommand {
 	/// Recommended for testing and local development only.
 	#[arg(name = "dev", short, long, default_value = "false")]
 	dev_mode: bool,
+	/// Inspect the storage of the contract, printing its decoded storage tree. Nothing is
+	/// submitted.
+	#[arg(long, conflicts_with_all = ["message", "args", "execute", "dry_run", "use-wallet"])]
+	storage: bool,
+	/// Look up an entry of a `Mapping`, specified as the path to the mapping within the storage
+	/// layout followed by the key (e.g. `--lookup balances <address>`).
+	#[arg(long, num_args = 2, value_names = ["FIELD", "KEY"], requires = "storage")]
+	lookup: Option<Vec<String>>,
+	/// The block at which to read the storage, as a number or hash. Defaults to the best block.
+	#[arg(long, requires = "storage")]
+	at: Option<BlockId>,
+	/// Stream the events emitted by the contract in new finalized blocks, decoded using its
+	/// metadata, until interrupted. Without `--contract`, the events of every contract matching
+	/// the metadata are streamed. Nothing is submitted.
+	#[arg(
+		long,
+		conflicts_with_all = ["message", "args", "execute", "dry_run", "use-wallet", "storage"]
+	)]
+	events: bool,
 }
 
 impl Default for CallContractCommand {
@@ -105,6 +130,10 @@ impl Default for CallContractCommand {
 			execute: false,
 			dry_run: false,
 			dev_mode: false,
+			storage: false,
+			lookup: None,
+			at: None,
+			events: false,
 		}
 	}
 }
@@ -112,6 +141,28 @@ impl Default for CallContractCommand {
 impl CallContractCommand {
 	/// Executes the command.
 	pub(crate) async fn execute(mut self) -> Result<()> {
+		// Stream the events emitted by contracts until interrupted.
+		if self.events {
+			cli::Cli.intro("Watch contract events")?;
+			if let Err(e) = self.resolve_contract(&mut cli::Cli).await {
+				display_message(&e.to_string(), false, &mut cli::Cli)?;
+				return Ok(());
+			}
+			if let Err(e) = self.watch_events(&mut cli::Cli).await {
+				display_message(&e.to_string(), false, &mut cli::Cli)?;
+			}
+			return Ok(());
+		}
+		// Inspect the storage of the contract, without submitting anything.
+		if self.storage {
+			cli::Cli.intro("Inspect contract storage")?;
+			if let Err(e) = self.inspect_storage(&mut cli::Cli).await {
+				display_message(&e.to_string(), false, &mut cli::Cli)?;
+			} else {
+				display_message("Storage inspection complete.", true, &mut cli::Cli)?;
+			}
+			return Ok(());
+		}
 		// Check if message specified via command line argument.
 		let prompt_to_repeat_call = self.message.is_none();
 		// Configure the call based on command line arguments/call UI.
@@ -236,7 +287,7 @@ impl CallContractCommand {
 
 		// If message has been specified via command line arguments, return early.
 		if self.message.is_some() {
-			return Ok(());
+			return self.resolve_contract(cli).await;
 		}
 
 		// Resolve path.
@@ -281,29 +332,10 @@ impl CallContractCommand {
 			self.url = url::Url::parse(&url)?
 		};
 
-		// Resolve contract address.
+		// Resolve contract address, using any recorded deployment of the contract.
+		self.resolve_contract(cli).await?;
 		if self.contract.is_none() {
-			// Prompt for contract address.
-			let contract_address: String = cli
-				.input("Provide the on-chain contract address:")
-				.placeholder(
-					#[cfg(feature = "wasm-contracts")]
-					"e.g. 5DYs7UGBm2LuX4ryvyqfksozNAW5V47tPbGiVgnjYWCZ29bt",
-					#[cfg(feature = "polkavm-contracts")]
-					"e.g. 0x48550a4bb374727186c55365b7c9c0a1a31bdafe",
-				)
-				.validate(|input: &String| {
-					#[cfg(feature = "wasm-contracts")]
-					let account = parse_account(input);
-					#[cfg(feature = "polkavm-contracts")]
-					let account = parse_h160_account(input);
-					match account {
-						Ok(_) => Ok(()),
-						Err(_) => Err("Invalid address."),
-					}
-				})
-				.interact()?;
-			self.contract = Some(contract_address);
+			self.contract = Some(prompt_for_contract_address(cli)?);
 		};
 
 		// Resolve message.
@@ -391,6 +423,109 @@ impl CallContractCommand {
 		Ok(())
 	}
 
+	/// Reads the storage of the contract, displaying its decoded storage tree or the entry of a
+	/// mapping when a key is looked up.
+	async fn inspect_storage(&mut self, cli: &mut impl Cli) -> Result<()> {
+		let project_path = get_project_path(self.path.clone(), self.path_pos.clone())
+			.unwrap_or_else(|| PathBuf::from("./"));
+		self.resolve_contract(cli).await?;
+		if self.contract.is_none() {
+			self.contract = Some(prompt_for_contract_address(cli)?);
+		}
+		let contract = self.contract.as_deref().expect("contract is prompted for when None; qed");
+
+		let spinner = spinner();
+		spinner.start("Reading the contract storage...");
+		let result = async {
+			let storage = ContractStorage::new(&project_path, contract, &self.url, self.at).await?;
+			let value = match self.lookup.as_deref() {
+				Some([field, key]) => storage.lookup(field, key).await?,
+				_ => Some(storage.read().await?),
+			};
+			Ok::<_, pop_contracts::Error>((storage.at(), value))
+		}
+		.await;
+		spinner.clear();
+		let (at, value) = result?;
+		let item = match self.lookup.as_deref() {
+			Some([field, key]) => format!("{field}[{key}]"),
+			_ => format!("Storage of {contract}"),
+		};
+		match value {
+			Some(value) => cli.info(format!("{item} at block {at:?}:\n{value}"))?,
+			None => cli.warning(format!("No value is stored at {item}."))?,
+		}
+		Ok(())
+	}
+
+	/// Resolves the address of the contract from the deployments recorded for the project, when
+	/// the contract is specified by name or not specified at all.
+	async fn resolve_contract(&mut self, cli: &mut impl Cli) -> Result<()> {
+		if self.contract.as_deref().is_some_and(is_address) {
+			return Ok(());
+		}
+		let project_path = get_project_path(self.path.clone(), self.path_pos.clone())
+			.unwrap_or_else(|| PathBuf::from("./"));
+		// Deployments are recorded within the project, rather than alongside a contract artifact.
+		let project_dir = if project_path.is_file() {
+			project_path.parent().map(Path::to_path_buf).unwrap_or_default()
+		} else {
+			project_path.clone()
+		};
+		let deployments = Deployments::load(&project_dir)?;
+		let name = match self.contract.clone() {
+			Some(name) => name,
+			None => match get_contract_name(&project_path) {
+				Ok(name) if !deployments.is_empty() => name,
+				_ => return Ok(()),
+			},
+		};
+		let network = network_key(&self.url).await;
+		match deployments.get(&network, &name) {
+			Some(deployment) => {
+				cli.info(format!("Using the deployment of `{name}` at {}.", deployment.address))?;
+				self.contract = Some(deployment.address.clone());
+			},
+			None if self.contract.is_some() => {
+				return Err(anyhow!(
+					"No deployment of `{name}` has been recorded for {}. Provide the address of the contract instead.",
+					self.url
+				));
+			},
+			None => {},
+		}
+		Ok(())
+	}
+
+	/// Streams the decoded events emitted by contracts in new blocks, until interrupted.
+	async fn watch_events(&self, cli: &mut impl Cli) -> Result<()> {
+		let project_path = get_project_path(self.path.clone(), self.path_pos.clone())
+			.unwrap_or_else(|| PathBuf::from("./"));
+		let mut events =
+			ContractEventSubscription::new(&project_path, &self.url, self.contract.as_deref())
+				.await?;
+		cli.info(match &self.contract {
+			Some(contract) => format!("Watching the events of {contract}. Press Ctrl+C to stop."),
+			None => "Watching the events of contracts matching the metadata. Press Ctrl+C to stop."
+				.to_string(),
+		})?;
+		loop {
+			tokio::select! {
+				next = events.next() => match next {
+					Some(Ok(events)) =>
+						for event in events {
+							cli.plain(event)?;
+						},
+					Some(Err(e)) => return Err(anyhow!("{}", format!("{e:?}"))),
+					None => break,
+				},
+				_ = tokio::signal::ctrl_c() => break,
+			}
+		}
+		display_message("Stopped watching events.", true, cli)?;
+		Ok(())
+	}
+
 	/// Execute the call.
 	async fn execute_call(
 		&mut self,
@@ -421,6 +556,9 @@ impl CallContractCommand {
 			},
 		};
 		normalize_call_args(&mut self.args, &message_metadata);
+		if !self.use_wallet {
+			unlock_signer(&self.suri, cli)?;
+		}
 		let call_exec = match set_up_call(CallOpts {
 			path: project_path,
 			contract,
@@ -532,7 +670,7 @@ impl CallContractCommand {
 	/// Execute the smart contract call using wallet integration.
 	async fn execute_with_wallet(
 		&self,
-		call_exec: CallExec<DefaultConfig, DefaultEnvironment, Keypair>,
+		call_exec: CallExec<DefaultConfig, DefaultEnvironment, Signer>,
 		cli: &mut impl Cli,
 	) -> Result<()> {
 		#[cfg(feature = "polkavm-contracts")]
@@ -550,7 +688,7 @@ impl CallContractCommand {
 		})?;
 
 		let maybe_payload =
-			request_signature(call_data, self.url.to_string()).await?.signed_payload;
+			request_signature(call_data, self.url.to_string(), None).await?.signed_payload;
 		if let Some(payload) = maybe_payload {
 			cli.success("Signed payload received.")?;
 			let spinner = spinner();
@@ -572,7 +710,7 @@ impl CallContractCommand {
 	// Get the call data.
 	fn get_contract_data(
 		&self,
-		call_exec: &CallExec<DefaultConfig, DefaultEnvironment, Keypair>,
+		call_exec: &CallExec<DefaultConfig, DefaultEnvironment, Signer>,
 		#[cfg(feature = "polkavm-contracts")] storage_deposit_limit: u128,
 	) -> anyhow::Result<Vec<u8>> {
 		let weight_limit = if self.gas_limit.is_some() && self.proof_size.is_some() {
@@ -597,6 +735,37 @@ impl CallContractCommand {
 	}
 }
 
+// Whether the input is the address of a contract, rather than the name of a contract.
+fn is_address(input: &str) -> bool {
+	#[cfg(feature = "wasm-contracts")]
+	return parse_account(input).is_ok();
+	#[cfg(feature = "polkavm-contracts")]
+	parse_h160_account(input).is_ok()
+}
+
+// Prompts for the address of a deployed contract.
+fn prompt_for_contract_address(cli: &mut impl Cli) -> Result<String> {
+	Ok(cli
+		.input("Provide the on-chain contract address:")
+		.placeholder(
+			#[cfg(feature = "wasm-contracts")]
+			"e.g. 5DYs7UGBm2LuX4ryvyqfksozNAW5V47tPbGiVgnjYWCZ29bt",
+			#[cfg(feature = "polkavm-contracts")]
+			"e.g. 0x48550a4bb374727186c55365b7c9c0a1a31bdafe",
+		)
+		.validate(|input: &String| {
+			#[cfg(feature = "wasm-contracts")]
+			let account = parse_account(input);
+			#[cfg(feature = "polkavm-contracts")]
+			let account = parse_h160_account(input);
+			match account {
+				Ok(_) => Ok(()),
+				Err(_) => Err("Invalid address."),
+			}
+		})
+		.interact()?)
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -664,6 +833,10 @@ mod tests {
 			dry_run: false,
 			execute: false,
 			dev_mode: false,
+			storage: false,
+			lookup: None,
+			at: None,
+			events: false,
 		};
 		call_config.configure(&mut cli, false).await?;
 		assert_eq!(call_config.contract, Some("CONTRACT_ADDRESS".to_string()));
@@ -749,6 +922,10 @@ mod tests {
 			dry_run: false,
 			execute: false,
 			dev_mode: false,
+			storage: false,
+			lookup: None,
+			at: None,
+			events: false,
 		};
 		call_config.configure(&mut cli, false).await?;
 		assert_eq!(call_config.contract, Some("CONTRACT_ADDRESS".to_string()));
@@ -832,6 +1009,10 @@ mod tests {
 			dry_run: false,
 			execute: false,
 			dev_mode: true,
+			storage: false,
+			lookup: None,
+			at: None,
+			events: false,
 		};
 		call_config.configure(&mut cli, false).await?;
 		assert_eq!(call_config.contract, Some("CONTRACT_ADDRESS".to_string()));
@@ -892,6 +1073,10 @@ mod tests {
 			dry_run: false,
 			execute: false,
 			dev_mode: false,
+			storage: false,
+			lookup: None,
+			at: None,
+			events: false,
 		};
 		let mut cli = MockCli::new();
 		assert!(
@@ -943,6 +1128,10 @@ mod tests {
 				dry_run: false,
 				execute: false,
 				dev_mode: false,
+				storage: false,
+				lookup: None,
+				at: None,
+				events: false,
 			}.execute_call(&mut cli, false).await,
 			anyhow::Result::Err(message) if message.to_string() == "Please specify the message to call."
 		));
@@ -963,6 +1152,10 @@ mod tests {
 				dry_run: false,
 				execute: false,
 				dev_mode: false,
+				storage: false,
+				lookup: None,
+				at: None,
+				events: false,
 			}.execute_call(&mut cli, false).await,
 			anyhow::Result::Err(message) if message.to_string() == "Please specify the contract address."
 		));
@@ -988,6 +1181,10 @@ mod tests {
 			dry_run: false,
 			execute: false,
 			dev_mode: false,
+			storage: false,
+			lookup: None,
+			at: None,
+			events: false,
 		};
 		// Contract is not deployed.
 		let mut cli =
@@ -1020,6 +1217,10 @@ mod tests {
 			dry_run: false,
 			execute: false,
 			dev_mode: false,
+			storage: false,
+			lookup: None,
+			at: None,
+			events: false,
 		};
 		// Contract not build. Build is required.
 		assert!(call_config.is_contract_build_required());
@@ -1034,4 +1235,34 @@ mod tests {
 		assert!(!call_config.is_contract_build_required());
 		Ok(())
 	}
+
+	#[tokio::test]
+	async fn inspect_storage_prompts_for_contract_address() -> Result<()> {
+		let temp_dir = new_environment("testing")?;
+		let mut cli = MockCli::new()
+			.expect_input("Provide the on-chain contract address:", "CONTRACT_ADDRESS".into());
+		let mut call_config = CallContractCommand {
+			path: Some(temp_dir.path().join("testing")),
+			storage: true,
+			..Default::default()
+		};
+		// The contract has not been built, so its storage layout cannot be loaded.
+		assert!(call_config.inspect_storage(&mut cli).await.is_err());
+		assert_eq!(call_config.contract, Some("CONTRACT_ADDRESS".to_string()));
+		cli.verify()
+	}
+
+	#[tokio::test]
+	async fn watch_events_fails_not_built() -> Result<()> {
+		let temp_dir = new_environment("testing")?;
+		let mut cli = MockCli::new();
+		let call_config = CallContractCommand {
+			path: Some(temp_dir.path().join("testing")),
+			events: true,
+			..Default::default()
+		};
+		// The contract has not been built, so its event specs cannot be loaded.
+		assert!(call_config.watch_events(&mut cli).await.is_err());
+		cli.verify()
+	}
 }
@@ -0,0 +1,163 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use crate::cli::traits::*;
+use anyhow::{anyhow, Result};
+use clap::{Args, Subcommand};
+use pop_common::{signer::KEYRING_PREFIX, KeyType, Keyring};
+use std::fmt::{Display, Formatter};
+
+/// Arguments for managing the keys of the local keyring.
+#[derive(Args)]
+pub(crate) struct KeysArgs {
+	#[command(subcommand)]
+	pub(crate) command: Command,
+}
+
+/// Manage the keys of the local keyring.
+#[derive(Subcommand)]
+pub(crate) enum Command {
+	/// Add a key to the keyring, encrypted with a password.
+	#[clap(alias = "a")]
+	Add {
+		/// The name of the key, used to reference it as a signer (e.g. "keyring:alice").
+		name: String,
+		/// The cryptographic scheme of the key.
+		#[arg(short, long, default_value = "sr25519")]
+		key_type: KeyType,
+	},
+	/// List the keys stored in the keyring.
+	#[clap(alias = "l")]
+	List,
+	/// Remove a key from the keyring.
+	#[clap(alias = "r")]
+	Remove {
+		/// The name of the key.
+		name: String,
+	},
+}
+
+impl Command {
+	/// Executes the command.
+	pub(crate) fn execute(&self, cli: &mut impl Cli) -> Result<()> {
+		let keyring = Keyring::new(Keyring::default_path()?);
+		self.execute_with(&keyring, cli)
+	}
+
+	// Executes the command against the given keyring.
+	fn execute_with(&self, keyring: &Keyring, cli: &mut impl Cli) -> Result<()> {
+		match self {
+			Command::Add { name, key_type } => {
+				let suri = cli.password("Enter the secret URI of the key:").interact()?;
+				let password = cli.password("Enter a password to encrypt the key:").interact()?;
+				if password.is_empty() {
+					return Err(anyhow!("The password must not be empty."));
+				}
+				if cli.password("Confirm the password:").interact()? != password {
+					return Err(anyhow!("The passwords do not match."));
+				}
+				let entry = keyring.add(name, &suri, *key_type, &password)?;
+				cli.success(format!(
+					"Added {} ({}) to the keyring. Use it as a signer with `{KEYRING_PREFIX}{}`.",
+					entry.address,
+					entry.key_type.as_ref(),
+					entry.name
+				))?;
+			},
+			Command::List => {
+				let entries = keyring.entries()?;
+				if entries.is_empty() {
+					cli.info("The keyring is empty. Add a key using `pop keys add <NAME>`.")?;
+				}
+				for entry in entries {
+					cli.plain(format!(
+						"{KEYRING_PREFIX}{} {} ({})",
+						entry.name,
+						entry.address,
+						entry.key_type.as_ref()
+					))?;
+				}
+			},
+			Command::Remove { name } => {
+				let entry = keyring.remove(name)?;
+				cli.success(format!("Removed {} from the keyring.", entry.name))?;
+			},
+		}
+		Ok(())
+	}
+}
+
+impl Display for Command {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Command::Add { .. } => write!(f, "add"),
+			Command::List => write!(f, "list"),
+			Command::Remove { .. } => write!(f, "remove"),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::cli::MockCli;
+
+	const ALICE: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+
+	#[test]
+	fn keys_command_works() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let keyring = Keyring::new(temp_dir.path().join("keyring.json"));
+
+		let mut cli = MockCli::new()
+			.expect_info("The keyring is empty. Add a key using `pop keys add <NAME>`.");
+		Command::List.execute_with(&keyring, &mut cli)?;
+		cli.verify()?;
+
+		let add = Command::Add { name: "alice".into(), key_type: KeyType::Sr25519 };
+		let mut cli = MockCli::new()
+			.expect_password("Enter the secret URI of the key:", "//Alice".into())
+			.expect_password("Enter a password to encrypt the key:", "password".into())
+			.expect_password("Confirm the password:", "password".into())
+			.expect_success(format!(
+				"Added {ALICE} (sr25519) to the keyring. Use it as a signer with `keyring:alice`."
+			));
+		add.execute_with(&keyring, &mut cli)?;
+		cli.verify()?;
+
+		let mut cli = MockCli::new().expect_plain(format!("keyring:alice {ALICE} (sr25519)"));
+		Command::List.execute_with(&keyring, &mut cli)?;
+		cli.verify()?;
+
+		let mut cli = MockCli::new().expect_success("Removed alice from the keyring.");
+		Command::Remove { name: "alice".into() }.execute_with(&keyring, &mut cli)?;
+		cli.verify()?;
+		assert!(keyring.entries()?.is_empty());
+		Ok(())
+	}
+
+	#[test]
+	fn add_fails_when_passwords_do_not_match() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let keyring = Keyring::new(temp_dir.path().join("keyring.json"));
+		let add = Command::Add { name: "alice".into(), key_type: KeyType::Sr25519 };
+		let mut cli = MockCli::new()
+			.expect_password("Enter the secret URI of the key:", "//Alice".into())
+			.expect_password("Enter a password to encrypt the key:", "password".into())
+			.expect_password("Confirm the password:", "other".into());
+		assert!(add
+			.execute_with(&keyring, &mut cli)
+			.is_err_and(|e| e.to_string() == "The passwords do not match."));
+		assert!(keyring.entries()?.is_empty());
+		cli.verify()
+	}
+
+	#[test]
+	fn command_display_works() {
+		assert_eq!(
+			Command::Add { name: "alice".into(), key_type: KeyType::Ecdsa }.to_string(),
+			"add"
+		);
+		assert_eq!(Command::List.to_string(), "list");
+		assert_eq!(Command::Remove { name: "alice".into() }.to_string(), "remove");
+	}
+}
@@ -0,0 +1,261 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use crate::{cli::traits::*, style::style};
+use anyhow::{anyhow, Result};
+use clap::Args;
+use console::Color;
+use pop_chains::up::{DetachedNetwork, Level, LogFilter, LogLine, NetworkChain, NetworkLogs};
+use regex::Regex;
+use std::{path::Path, time::Duration};
+
+// The number of lines shown from the end of the log of each node by default.
+const DEFAULT_TAIL: usize = 100;
+// How often the log files are checked for new lines when following.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+// The colours used to distinguish the nodes of a network.
+const COLORS: [Color; 6] =
+	[Color::Cyan, Color::Green, Color::Yellow, Color::Magenta, Color::Blue, Color::Red];
+
+/// Arguments for viewing the logs of a local network.
+#[derive(Args, Clone, Default)]
+pub(crate) struct LogsArgs {
+	/// The name of the network, as launched using `pop up network --detach`.
+	pub(crate) name: String,
+	/// Only show the lines logged by the specified node. Can be specified multiple times.
+	#[arg(short, long = "node")]
+	pub(crate) nodes: Vec<String>,
+	/// Only show the lines of the specified log target and its sub-targets, e.g. `xcm` or
+	/// `runtime::system`. Can be specified multiple times.
+	#[arg(short, long = "target")]
+	pub(crate) targets: Vec<String>,
+	/// Only show the lines at least as severe as the specified level.
+	#[arg(short, long, value_enum, ignore_case = true)]
+	pub(crate) level: Option<Level>,
+	/// Only show the lines matching the specified regular expression.
+	#[arg(short, long)]
+	pub(crate) grep: Option<Regex>,
+	/// Only show the lines logged once the nodes of a chain imported the specified block, as
+	/// `<CHAIN>=<BLOCK>` where the chain is `relay` or the identifier of a rollup. Can be
+	/// specified multiple times. The logs are then read from the start.
+	#[arg(long, value_parser = parse_since)]
+	pub(crate) since: Vec<(NetworkChain, u32)>,
+	/// The number of lines to show from the end of the log of each node, unless `--since` is
+	/// specified.
+	#[arg(long, default_value_t = DEFAULT_TAIL)]
+	pub(crate) tail: usize,
+	/// Keep following the logs as lines are appended.
+	#[arg(short, long)]
+	pub(crate) follow: bool,
+}
+
+impl LogsArgs {
+	/// Executes the command.
+	pub(crate) async fn execute(self, cli: &mut impl Cli) -> Result<()> {
+		self.execute_with(&crate::cache()?, cli).await
+	}
+
+	// Executes the command against the networks recorded within the given cache.
+	async fn execute_with(self, cache: &Path, cli: &mut impl Cli) -> Result<()> {
+		let name = &self.name;
+		let network = DetachedNetwork::load(&DetachedNetwork::dir(cache), name)?
+			.ok_or_else(|| anyhow!("No local network named `{name}` was found."))?;
+		let mut logs = NetworkLogs::new(&network);
+		let nodes: Vec<_> = logs.nodes().map(String::from).collect();
+		if let Some(node) = self.nodes.iter().find(|n| !nodes.contains(n)) {
+			return Err(anyhow!(
+				"The network `{name}` has no node named `{node}`. Available nodes: {}.",
+				nodes.join(", ")
+			));
+		}
+		for (chain, _) in &self.since {
+			if let NetworkChain::Rollup(id) = chain {
+				if !network.rollups.iter().any(|r| r.id == Some(*id)) {
+					return Err(anyhow!(
+						"The network `{name}` has no rollup with the identifier {id}."
+					));
+				}
+			}
+		}
+		// Lines are only related to blocks when read from the start.
+		if self.since.is_empty() {
+			logs.seek_tail(self.tail)?;
+		}
+		let filter = LogFilter {
+			nodes: self.nodes,
+			targets: self.targets,
+			level: self.level,
+			pattern: self.grep,
+			since: self
+				.since
+				.into_iter()
+				.map(|(chain, block)| match chain {
+					NetworkChain::Relay => (None, block),
+					NetworkChain::Rollup(id) => (Some(id), block),
+				})
+				.collect(),
+		};
+		let width = nodes.iter().map(|n| n.len()).max().unwrap_or_default();
+		loop {
+			for line in logs.read()?.iter().filter(|l| filter.matches(l)) {
+				let color = nodes.iter().position(|n| *n == line.node).unwrap_or_default();
+				cli.plain(format_line(line, width, COLORS[color % COLORS.len()]))?;
+			}
+			if !self.follow {
+				return Ok(());
+			}
+			tokio::select! {
+				_ = tokio::signal::ctrl_c() => return Ok(()),
+				_ = tokio::time::sleep(POLL_INTERVAL) => {},
+			}
+		}
+	}
+}
+
+// Parses the block of a chain from which lines are shown.
+fn parse_since(value: &str) -> Result<(NetworkChain, u32), String> {
+	let (chain, block) = value
+		.split_once('=')
+		.ok_or_else(|| format!("invalid value {value}, expected <CHAIN>=<BLOCK>"))?;
+	let chain = chain.parse().map_err(|e: pop_chains::Error| e.to_string())?;
+	Ok((chain, block.parse().map_err(|_| format!("invalid block: {block}"))?))
+}
+
+// Formats a line, prefixed by the name of its node and the latest block imported by the node.
+fn format_line(line: &LogLine, width: usize, color: Color) -> String {
+	let block = line.block.map(|b| format!("#{b}")).unwrap_or_default();
+	format!(
+		"{} {} {}",
+		style(format!("{:<width$}", line.node)).fg(color).bold(),
+		style(format!("{block:>8}")).dim(),
+		line.text
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::cli::MockCli;
+	use clap::Parser;
+	use pop_chains::up::{DetachedChain, DetachedNode};
+	use std::fs;
+
+	#[derive(Parser)]
+	struct Command {
+		#[command(flatten)]
+		logs: LogsArgs,
+	}
+
+	fn parse(args: &[&str]) -> Result<LogsArgs, clap::Error> {
+		Command::try_parse_from(std::iter::once(&"logs").chain(args)).map(|c| c.logs)
+	}
+
+	fn args(name: &str) -> LogsArgs {
+		parse(&[name]).expect("valid arguments")
+	}
+
+	// Records a network whose nodes have logged the provided lines.
+	fn network(cache: &Path, nodes: &[(&str, &[&str])]) -> Result<()> {
+		let nodes = nodes
+			.iter()
+			.map(|(name, lines)| {
+				let log = cache.join(format!("{name}.log"));
+				fs::write(&log, lines.iter().map(|l| format!("{l}\n")).collect::<String>())?;
+				Ok(DetachedNode { name: name.to_string(), endpoint: String::new(), pid: None, log })
+			})
+			.collect::<Result<Vec<_>>>()?;
+		DetachedNetwork {
+			name: "paseo".into(),
+			pid: u32::MAX,
+			base_dir: cache.to_path_buf(),
+			relay_chain: DetachedChain { chain: "paseo-local".into(), id: None, nodes },
+			rollups: vec![],
+		}
+		.save(&DetachedNetwork::dir(cache))?;
+		Ok(())
+	}
+
+	#[test]
+	fn parsing_works() -> Result<()> {
+		let args = parse(&[
+			"paseo", "-n", "alice", "--node", "bob", "-t", "xcm", "--level", "warn", "--grep",
+			"Barrier", "--since", "relay=10", "--since", "1000=5", "--tail", "5", "-f",
+		])?;
+		assert_eq!(args.name, "paseo");
+		assert_eq!(args.nodes, vec!["alice", "bob"]);
+		assert_eq!(args.targets, vec!["xcm"]);
+		assert_eq!(args.level, Some(Level::Warn));
+		assert_eq!(args.grep.map(|r| r.to_string()), Some("Barrier".into()));
+		assert_eq!(args.since, vec![(NetworkChain::Relay, 10), (NetworkChain::Rollup(1000), 5)]);
+		assert_eq!(args.tail, 5);
+		assert_eq!(parse(&["paseo"])?.tail, DEFAULT_TAIL);
+		assert!(args.follow);
+
+		assert!(parse(&[]).is_err());
+		assert!(parse(&["paseo", "--grep", "("]).is_err());
+		assert!(parse(&["paseo", "--level", "notice"]).is_err());
+		assert!(parse(&["paseo", "--since", "10"]).is_err());
+		assert!(parse(&["paseo", "--since", "asset-hub=10"]).is_err());
+		assert!(parse(&["paseo", "--since", "relay=latest"]).is_err());
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn logs_works() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let imported = "2025-01-01 12:00:06.000  INFO tokio-runtime-worker substrate: 🏆 Imported #7 (0x1234…5678 → 0x9abc…def0)";
+		let warning =
+			"2025-01-01 12:00:07.000  WARN tokio-runtime-worker xcm::barriers: Barrier blocked execution";
+		let started = "2025-01-01 12:00:00.000  INFO main sc_cli::runner: Parity Polkadot";
+		network(temp_dir.path(), &[("alice", &[started, imported, warning]), ("bob", &[started])])?;
+
+		let line = |node: &str, block: &str, text: &str| format!("{node:<5} {block:>8} {text}");
+		let mut cli = MockCli::new()
+			.expect_plain(line("alice", "", started))
+			.expect_plain(line("bob", "", started))
+			.expect_plain(line("alice", "#7", imported))
+			.expect_plain(line("alice", "#7", warning));
+		args("paseo").execute_with(temp_dir.path(), &mut cli).await?;
+		cli.verify()?;
+
+		// Only lines matching the filter are shown.
+		let mut cli = MockCli::new().expect_plain(line("alice", "#7", warning));
+		LogsArgs {
+			level: Some(Level::Warn),
+			since: vec![(NetworkChain::Relay, 7)],
+			..args("paseo")
+		}
+		.execute_with(temp_dir.path(), &mut cli)
+		.await?;
+		cli.verify()?;
+
+		// Only the last lines are shown by default.
+		let mut cli = MockCli::new()
+			.expect_plain(line("bob", "", started))
+			.expect_plain(line("alice", "", warning));
+		LogsArgs { tail: 1, ..args("paseo") }
+			.execute_with(temp_dir.path(), &mut cli)
+			.await?;
+		cli.verify()
+	}
+
+	#[tokio::test]
+	async fn logs_fails_with_unknown_network_or_node() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		assert!(args("paseo")
+			.execute_with(temp_dir.path(), &mut MockCli::new())
+			.await
+			.is_err_and(|e| e.to_string() == "No local network named `paseo` was found."));
+
+		network(temp_dir.path(), &[("alice", &[]), ("bob", &[])])?;
+		assert!(LogsArgs { nodes: vec!["charlie".into()], ..args("paseo") }
+			.execute_with(temp_dir.path(), &mut MockCli::new())
+			.await
+			.is_err_and(|e| e.to_string() ==
+				"The network `paseo` has no node named `charlie`. Available nodes: alice, bob."));
+		let args = LogsArgs { since: vec![(NetworkChain::Rollup(1000), 1)], ..args("paseo") };
+		assert!(args.execute_with(temp_dir.path(), &mut MockCli::new()).await.is_err_and(|e| e
+			.to_string() ==
+			"The network `paseo` has no rollup with the identifier 1000."));
+		Ok(())
+	}
+}
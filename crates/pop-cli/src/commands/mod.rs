@@ -21,10 +21,18 @@ pub(crate) mod hash;
 #[cfg(any(feature = "chain", feature = "polkavm-contracts", feature = "wasm-contracts"))]
 pub(crate) mod install;
 #[cfg(any(feature = "chain", feature = "polkavm-contracts", feature = "wasm-contracts"))]
+pub(crate) mod keys;
+#[cfg(feature = "chain")]
+pub(crate) mod logs;
+#[cfg(feature = "chain")]
+pub(crate) mod network;
+#[cfg(any(feature = "chain", feature = "polkavm-contracts", feature = "wasm-contracts"))]
 pub(crate) mod new;
 pub(crate) mod test;
 #[cfg(any(feature = "chain", feature = "polkavm-contracts", feature = "wasm-contracts"))]
 pub(crate) mod up;
+#[cfg(any(feature = "polkavm-contracts", feature = "wasm-contracts"))]
+pub(crate) mod verify;
 
 #[derive(Subcommand)]
 #[command(subcommand_required = true)]
@@ -61,6 +69,22 @@ pub(crate) enum Command {
 	/// Convert between different formats.
 	#[clap(alias = "cv")]
 	Convert(convert::ConvertArgs),
+	/// Manage the encrypted keys used to sign transactions.
+	#[clap(alias = "k")]
+	#[cfg(any(feature = "chain", feature = "polkavm-contracts", feature = "wasm-contracts"))]
+	Keys(keys::KeysArgs),
+	/// Verify that a deployed smart contract was built from source.
+	#[clap(alias = "v")]
+	#[cfg(any(feature = "polkavm-contracts", feature = "wasm-contracts"))]
+	Verify(verify::VerifyArgs),
+	/// Manage local networks running in the background.
+	#[clap(alias = "N")]
+	#[cfg(feature = "chain")]
+	Network(network::NetworkArgs),
+	/// View the logs of all nodes of a local network running in the background.
+	#[clap(alias = "l")]
+	#[cfg(feature = "chain")]
+	Logs(logs::LogsArgs),
 }
 
 /// Help message for the build command.
@@ -105,6 +129,8 @@ fn about_up() -> &'static str {
 impl Command {
 	/// Executes the command.
 	pub(crate) async fn execute(self) -> anyhow::Result<Data> {
+		// Encrypted signers unlocked by the command are locked again once it completes.
+		let _signers = pop_common::SignerScope::new();
 		match self {
 			#[cfg(any(
 				feature = "chain",
@@ -182,6 +208,11 @@ impl Command {
 						#[cfg(feature = "chain")]
 						up::Command::Network(cmd) => cmd.execute(&mut Cli).await.map(|_| Up(Network)),
 						#[cfg(feature = "chain")]
+						up::Command::Chain(cmd) =>
+							cmd.execute(&mut Cli).await.map(|_| Up(crate::common::Project::Chain)),
+						#[cfg(feature = "chain")]
+						up::Command::Fork(cmd) => cmd.execute(&mut Cli).await.map(|_| Up(Network)),
+						#[cfg(feature = "chain")]
 						up::Command::Paseo(mut cmd) => cmd.execute(Paseo, &mut Cli).await.map(|_| Up(Network)),
 						#[cfg(feature = "chain")]
 						up::Command::Kusama(mut cmd) => cmd.execute(Kusama, &mut Cli).await.map(|_| Up(Network)),
@@ -248,6 +279,32 @@ impl Command {
 				env_logger::init();
 				args.command.execute(&mut Cli).map(|_| Null)
 			},
+			#[cfg(any(
+				feature = "chain",
+				feature = "polkavm-contracts",
+				feature = "wasm-contracts"
+			))]
+			Command::Keys(args) => {
+				env_logger::init();
+				args.command.execute(&mut Cli).map(|_| Null)
+			},
+			#[cfg(any(feature = "polkavm-contracts", feature = "wasm-contracts"))]
+			Command::Verify(args) => {
+				env_logger::init();
+				match args.command {
+					verify::Command::Contract(cmd) => cmd.execute(&mut Cli).await.map(|_| Null),
+				}
+			},
+			#[cfg(feature = "chain")]
+			Command::Network(args) => {
+				env_logger::init();
+				args.command.execute(&mut Cli).await.map(|_| Null)
+			},
+			#[cfg(feature = "chain")]
+			Command::Logs(args) => {
+				env_logger::init();
+				args.execute(&mut Cli).await.map(|_| Null)
+			},
 		}
 	}
 }
@@ -322,6 +379,18 @@ impl Display for Command {
 			Self::Bench(args) => write!(f, "bench {}", args.command),
 			Command::Hash(args) => write!(f, "hash {}", args.command),
 			Command::Convert(args) => write!(f, "convert {}", args.command),
+			#[cfg(any(
+				feature = "chain",
+				feature = "polkavm-contracts",
+				feature = "wasm-contracts"
+			))]
+			Command::Keys(args) => write!(f, "keys {}", args.command),
+			#[cfg(any(feature = "polkavm-contracts", feature = "wasm-contracts"))]
+			Command::Verify(args) => write!(f, "verify {}", args.command),
+			#[cfg(feature = "chain")]
+			Command::Network(args) => write!(f, "network {}", args.command),
+			#[cfg(feature = "chain")]
+			Command::Logs(_) => write!(f, "logs"),
 		}
 	}
 }
@@ -429,6 +498,8 @@ mod tests {
 				}),
 				"bench pallet",
 			),
+			// Logs.
+			(Command::Logs(Default::default()), "logs"),
 		];
 
 		for (command, expected) in test_cases {
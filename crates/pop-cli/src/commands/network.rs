@@ -0,0 +1,844 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use crate::{
+	cli::traits::*,
+	common::{signer::unlock_signer, urls},
+	style::style,
+};
+use anyhow::{anyhow, Result};
+use clap::{Args, Subcommand, ValueEnum};
+use pop_chains::{
+	decode_call_data,
+	up::{
+		seal, DetachedChain, DetachedNetwork, NetworkChain, RuntimeUpgrade, Snapshot, UpgradePath,
+		XcmHop, XcmMessage, XcmProgram, XcmTracer,
+	},
+};
+use pop_common::{create_signer, parse_account};
+use serde_json::{json, Value};
+use std::{
+	fmt::{Display, Formatter},
+	fs,
+	path::{Path, PathBuf},
+	time::Duration,
+};
+use url::Url;
+
+// How long to wait for a network to be torn down before its processes are killed.
+const STOP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Arguments for managing local networks running in the background.
+#[derive(Args)]
+pub(crate) struct NetworkArgs {
+	#[command(subcommand)]
+	pub(crate) command: Command,
+}
+
+/// Manage local networks launched in the background using `pop up network --detach`.
+#[derive(Subcommand)]
+pub(crate) enum Command {
+	/// List the local networks running in the background.
+	#[clap(alias = "l")]
+	List,
+	/// Output the endpoints of a local network running in the background, as JSON.
+	#[clap(alias = "e")]
+	Endpoints {
+		/// The name of the network.
+		name: String,
+	},
+	/// Stop a local network running in the background.
+	#[clap(alias = "s")]
+	Stop {
+		/// The name of the network.
+		name: String,
+		/// Remove the state of the network once stopped.
+		#[clap(long = "rm")]
+		auto_remove: bool,
+	},
+	/// Snapshot the chain state of a local network running in the background, so that it can be
+	/// relaunched using `pop up network --snapshot`.
+	Snapshot {
+		/// The name of the network.
+		name: String,
+		/// The name of the snapshot, replacing any existing snapshot with the same name. Defaults
+		/// to the name of the network.
+		#[arg(long = "as")]
+		snapshot: Option<String>,
+	},
+	/// List the snapshots of local networks.
+	Snapshots,
+	/// Seal blocks on demand on a development chain launched using `pop up chain --dev
+	/// --on-demand`, or a fork launched using `pop up fork --seal manual`.
+	Seal {
+		/// The websocket endpoint of the development chain.
+		#[arg(long, default_value = urls::LOCAL)]
+		url: Url,
+		/// The number of blocks to seal.
+		#[arg(short, long, default_value_t = 1)]
+		blocks: u32,
+		/// Finalize the sealed blocks.
+		#[arg(long)]
+		finalize: bool,
+	},
+	/// Rehearse a runtime upgrade on a local network running in the background, waiting for the
+	/// new runtime to be enacted and for blocks to be produced with it.
+	Upgrade {
+		/// The name of the network.
+		name: String,
+		/// The runtime to upgrade to, e.g. as built using `pop build --deterministic`.
+		#[arg(long)]
+		runtime: PathBuf,
+		/// The identifier of the rollup to upgrade [default: the relay chain].
+		#[arg(long)]
+		id: Option<u32>,
+		/// The secret URI of the sudo account, used to sign the extrinsics. Rollups without a
+		/// sudo pallet are authorized by the sudo account of the relay chain.
+		#[arg(long, default_value = "//Alice")]
+		suri: String,
+		/// The number of blocks to be produced once the new runtime is enacted.
+		#[arg(short, long, default_value_t = 2)]
+		blocks: u32,
+		/// The maximum number of seconds to wait for each stage of the upgrade.
+		#[arg(long, default_value_t = 600)]
+		timeout: u64,
+	},
+	/// Send a cross-consensus message between the chains of a local network running in the
+	/// background, tracing it through the relay chain to its destination.
+	Xcm {
+		/// The name of the network.
+		name: String,
+		/// The program to send.
+		#[arg(value_enum)]
+		program: Program,
+		/// The chain from which the message is sent: `relay` or the identifier of a rollup.
+		#[arg(long, value_parser = parse_chain)]
+		from: NetworkChain,
+		/// The chain to which the message is sent: `relay` or the identifier of a rollup.
+		#[arg(long, value_parser = parse_chain)]
+		to: NetworkChain,
+		/// The amount of the relay chain token to transfer.
+		#[arg(long, required_if_eq_any = [("program", "reserve-transfer"), ("program", "teleport")])]
+		amount: Option<u128>,
+		/// The account receiving the transferred token [default: the sender].
+		#[arg(long)]
+		beneficiary: Option<String>,
+		/// The hex-encoded call to transact at the destination.
+		#[arg(long, required_if_eq("program", "transact"))]
+		call: Option<String>,
+		/// Send the message using sudo, so that the call is transacted with a superuser origin.
+		#[arg(long)]
+		sudo: bool,
+		/// The secret URI of the account sending the message.
+		#[arg(long, default_value = "//Alice")]
+		suri: String,
+		/// The maximum number of seconds to wait for the message to be handled by each chain.
+		#[arg(long, default_value_t = 120)]
+		timeout: u64,
+	},
+}
+
+/// A common XCM program.
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub(crate) enum Program {
+	/// Transfer the relay chain token, as a reserve transfer.
+	ReserveTransfer,
+	/// Teleport the relay chain token.
+	Teleport,
+	/// Transact a call, without paying for its execution.
+	Transact,
+}
+
+impl Command {
+	/// Executes the command.
+	pub(crate) async fn execute(&self, cli: &mut impl Cli) -> Result<()> {
+		self.execute_with(&crate::cache()?, cli).await
+	}
+
+	// Executes the command against the networks and snapshots recorded within the given cache.
+	async fn execute_with(&self, cache: &Path, cli: &mut impl Cli) -> Result<()> {
+		let networks = &DetachedNetwork::dir(cache);
+		match self {
+			Command::List => {
+				let mut running = Vec::new();
+				for network in DetachedNetwork::list(networks)? {
+					// Remove the state of any network whose supervising process has since exited.
+					if network.is_running() {
+						running.push(network);
+					} else {
+						network.remove(networks)?;
+					}
+				}
+				if running.is_empty() {
+					cli.info("No local networks are running in the background. Launch one using `pop up network --detach`.")?;
+				}
+				for network in running {
+					let mut chains = network.relay_chain.chain.clone();
+					if !network.rollups.is_empty() {
+						let ids: Vec<_> = network
+							.rollups
+							.iter()
+							.filter_map(|r| r.id)
+							.map(|id| id.to_string())
+							.collect();
+						chains.push_str(&format!(" + {}", ids.join(", ")));
+					}
+					cli.plain(format!(
+						"{} ({chains}) pid: {} dir: {}",
+						network.name,
+						network.pid,
+						network.base_dir.display()
+					))?;
+				}
+			},
+			Command::Endpoints { name } => {
+				let network = running(networks, name)?;
+				cli.plain(serde_json::to_string_pretty(&endpoints(&network))?)?;
+			},
+			Command::Stop { name, auto_remove } => {
+				cli.intro(format!("Stop the `{name}` network"))?;
+				let network = running(networks, name)?;
+				let progress = cliclack::spinner();
+				progress.start("Tearing down the network...");
+				network.stop(networks, STOP_TIMEOUT, *auto_remove).await?;
+				progress.stop(format!("Network `{name}` stopped."));
+				cli.outro("Done")?;
+			},
+			Command::Snapshot { name, snapshot } => {
+				cli.intro(format!("Snapshot the `{name}` network"))?;
+				let network = running(networks, name)?;
+				let snapshot = snapshot.as_deref().unwrap_or(name);
+				let snapshots = Snapshot::dir(cache);
+				let progress = cliclack::spinner();
+				progress.start("Taking snapshot, pausing the network meanwhile...");
+				if let Err(e) = Snapshot::create(&snapshots, snapshot, &network) {
+					progress.error(format!("🚫 Could not take snapshot: {e}"));
+					return Err(e.into());
+				}
+				progress.stop(format!(
+					"Snapshot `{snapshot}` saved to {}",
+					Snapshot::archive(&snapshots, snapshot)?.display()
+				));
+				cli.outro(format!(
+					"Use `pop up network --snapshot {snapshot}` to relaunch the network from the snapshot."
+				))?;
+			},
+			Command::Snapshots => {
+				let snapshots = Snapshot::dir(cache);
+				let snapshots = Snapshot::list(&snapshots)?
+					.into_iter()
+					.map(|s| Ok((Snapshot::archive(&snapshots, &s.name)?, s)))
+					.collect::<Result<Vec<_>, pop_chains::Error>>()?;
+				if snapshots.is_empty() {
+					cli.info("No snapshots found. Take one of a network running in the background using `pop network snapshot`.")?;
+				}
+				for (archive, snapshot) in snapshots {
+					let mut chains = snapshot.relay_chain.clone();
+					if !snapshot.rollups.is_empty() {
+						let ids: Vec<_> =
+							snapshot.rollups.iter().map(|id| id.to_string()).collect();
+						chains.push_str(&format!(" + {}", ids.join(", ")));
+					}
+					cli.plain(format!(
+						"{} ({chains}) network: {} archive: {}",
+						snapshot.name,
+						snapshot.network,
+						archive.display()
+					))?;
+				}
+			},
+			Command::Seal { url, blocks, finalize } => {
+				cli.intro(format!("Seal {blocks} block(s) on {url}"))?;
+				let hashes = seal(url.as_str(), *blocks, *finalize)
+					.await
+					.map_err(|e| anyhow!("Could not seal blocks: {e}"))?;
+				for hash in hashes {
+					cli.info(format!("Sealed block {hash:?}"))?;
+				}
+				cli.outro("Done")?;
+			},
+			Command::Xcm {
+				name,
+				program,
+				from,
+				to,
+				amount,
+				beneficiary,
+				call,
+				sudo,
+				suri,
+				timeout,
+			} => {
+				cli.intro(format!("Send a message from {from} to {to} of the `{name}` network"))?;
+				let network = running(networks, name)?;
+				unlock_signer(suri, cli)?;
+				let program = match program {
+					Program::ReserveTransfer | Program::Teleport => {
+						let beneficiary = match beneficiary {
+							Some(account) => parse_account(account)?.0,
+							None => create_signer(suri)?.account_id().0,
+						};
+						let amount = amount.ok_or_else(|| anyhow!("An amount is required."))?;
+						match program {
+							Program::Teleport => XcmProgram::Teleport { beneficiary, amount },
+							_ => XcmProgram::ReserveTransfer { beneficiary, amount },
+						}
+					},
+					Program::Transact => XcmProgram::Transact {
+						call: decode_call_data(
+							call.as_deref().ok_or_else(|| anyhow!("A call is required."))?,
+						)?,
+						superuser: *sudo,
+					},
+				};
+				let message = XcmMessage { origin: *from, destination: *to, program };
+				let (relay, origin, destination) = (
+					endpoint(&network, NetworkChain::Relay)?,
+					endpoint(&network, *from)?,
+					endpoint(&network, *to)?,
+				);
+				let timeout = Duration::from_secs(*timeout);
+
+				let progress = cliclack::spinner();
+				progress.start(format!("Sending the message from {from}..."));
+				let result = async {
+					let mut tracer = XcmTracer::new(message, &relay, &origin, &destination).await?;
+					let hop = tracer.send(suri).await?;
+					let failed = hop.error.is_some();
+					let mut hops = vec![("Sent", hop)];
+					if failed {
+						return Ok::<_, anyhow::Error>(hops);
+					}
+					progress.set_message("Waiting for the relay chain to include the message...");
+					if let Some(hop) = tracer.inclusion(timeout).await? {
+						hops.push(("Included", hop));
+					}
+					progress.set_message(format!("Waiting for {to} to process the message..."));
+					hops.push(("Processed", tracer.processing(timeout).await?));
+					Ok(hops)
+				}
+				.await;
+				let hops = result.map_err(|e| {
+					progress.error("The message could not be traced");
+					anyhow!("Could not trace the message: {e}")
+				})?;
+				progress.stop("Message traced");
+				for (stage, hop) in &hops {
+					let output = format_hop(stage, hop);
+					match hop.error {
+						None => cli.success(output)?,
+						Some(_) => cli.warning(output)?,
+					}
+				}
+				if let Some((hop, error)) =
+					hops.last().and_then(|(_, hop)| hop.error.as_ref().map(|e| (hop, e)))
+				{
+					cli.info(format!(
+						"Use `pop logs {name} -t xcm` to inspect the logs of the network."
+					))?;
+					return Err(anyhow!(
+						"The message failed on {} in block #{}: {error}",
+						hop.chain,
+						hop.block
+					));
+				}
+				cli.outro(format!("The message was processed by {to}."))?;
+			},
+			Command::Upgrade { name, runtime, id, suri, blocks, timeout } => {
+				let chain = id.map_or("the relay chain".into(), |id| format!("rollup {id}"));
+				cli.intro(format!("Upgrade the runtime of {chain} of the `{name}` network"))?;
+				let network = running(networks, name)?;
+				unlock_signer(suri, cli)?;
+				let endpoint = |chain: &DetachedChain| {
+					chain
+						.nodes
+						.first()
+						.ok_or_else(|| anyhow!("The chain `{}` has no nodes.", chain.chain))
+						.and_then(|node| Ok(Url::parse(&node.endpoint)?))
+				};
+				// Rollups authorize the upgrade before applying it, so that the relay chain can
+				// schedule the new validation function. Rollups without a sudo pallet are
+				// authorized by the relay chain.
+				let (chain, path) = match id {
+					Some(id) => (
+						network.rollups.iter().find(|r| r.id == Some(*id)).ok_or_else(|| {
+							anyhow!("The network `{name}` has no rollup with id {id}.")
+						})?,
+						UpgradePath::Rollup { relay: endpoint(&network.relay_chain)?, id: *id },
+					),
+					None => (&network.relay_chain, UpgradePath::SetCode),
+				};
+				let url = endpoint(chain)?;
+				let code = fs::read(runtime).map_err(|e| {
+					anyhow!("Could not read the runtime at {}: {e}", runtime.display())
+				})?;
+				let timeout = Duration::from_secs(*timeout);
+
+				let progress = cliclack::spinner();
+				progress.start("Checking the new runtime...");
+				let result = async {
+					let upgrade = RuntimeUpgrade::new(&url, code, path).await?;
+					let (current, new) =
+						(upgrade.current_version(), upgrade.version().spec_version);
+					progress
+						.set_message(format!("Upgrading from spec version {current} to {new}..."));
+					upgrade.submit(suri).await?;
+					progress
+						.set_message(format!("Waiting for spec version {new} to be enacted..."));
+					upgrade.wait_for_enactment(timeout).await?;
+					progress
+						.set_message(format!("Waiting for {blocks} block(s) to be produced..."));
+					let block = upgrade.wait_for_blocks(*blocks, timeout).await?;
+					Ok::<_, anyhow::Error>((current, new, block))
+				}
+				.await;
+				let (current, new, block) = result.map_err(|e| {
+					progress.error("The runtime could not be upgraded");
+					anyhow!("Could not upgrade the runtime: {e}")
+				})?;
+				progress.stop(format!(
+					"Runtime upgraded from spec version {current} to {new}, with blocks produced up to #{block}."
+				));
+				cli.outro("Done")?;
+			},
+		}
+		Ok(())
+	}
+}
+
+impl Display for Command {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Command::List => write!(f, "list"),
+			Command::Endpoints { .. } => write!(f, "endpoints"),
+			Command::Stop { .. } => write!(f, "stop"),
+			Command::Snapshot { .. } => write!(f, "snapshot"),
+			Command::Snapshots => write!(f, "snapshots"),
+			Command::Seal { .. } => write!(f, "seal"),
+			Command::Upgrade { .. } => write!(f, "upgrade"),
+			Command::Xcm { .. } => write!(f, "xcm"),
+		}
+	}
+}
+
+// Loads a network which is running in the background, removing the state of a network whose
+// supervising process has since exited.
+fn running(networks: &Path, name: &str) -> Result<DetachedNetwork> {
+	match DetachedNetwork::load(networks, name)? {
+		Some(network) if network.is_running() => Ok(network),
+		stale => {
+			if let Some(network) = stale {
+				network.remove(networks)?;
+			}
+			Err(anyhow!("No local network named `{name}` is running in the background."))
+		},
+	}
+}
+
+// The endpoint of the first node of a chain of the network.
+fn endpoint(network: &DetachedNetwork, chain: NetworkChain) -> Result<Url> {
+	let nodes = match chain {
+		NetworkChain::Relay => &network.relay_chain.nodes,
+		NetworkChain::Rollup(id) =>
+			&network
+				.rollups
+				.iter()
+				.find(|r| r.id == Some(id))
+				.ok_or_else(|| {
+					anyhow!("The network `{}` has no rollup with id {id}.", network.name)
+				})?
+				.nodes,
+	};
+	let node = nodes.first().ok_or_else(|| anyhow!("{chain} has no nodes."))?;
+	Ok(Url::parse(&node.endpoint)?)
+}
+
+// Formats the handling of a message by a chain, along with the events of the messaging pallets.
+fn format_hop(stage: &str, hop: &XcmHop) -> String {
+	let mut output = format!("{stage}: {} #{}", hop.chain, hop.block);
+	for event in &hop.events {
+		output.push_str(&format!("\n{}", style(format!("   {event}")).dim()));
+	}
+	output
+}
+
+fn parse_chain(value: &str) -> Result<NetworkChain, String> {
+	value.parse().map_err(|e: pop_chains::Error| e.to_string())
+}
+
+// The endpoints of each node of the network, keyed by node name.
+fn endpoints(network: &DetachedNetwork) -> Value {
+	let chain = |chain: &DetachedChain| {
+		let nodes: serde_json::Map<_, _> = chain
+			.nodes
+			.iter()
+			.map(|n| (n.name.clone(), Value::String(n.endpoint.clone())))
+			.collect();
+		match chain.id {
+			Some(id) => json!({ "id": id, "chain": chain.chain, "nodes": nodes }),
+			None => json!({ "chain": chain.chain, "nodes": nodes }),
+		}
+	};
+	json!({
+		"name": network.name,
+		"relay_chain": chain(&network.relay_chain),
+		"rollups": network.rollups.iter().map(chain).collect::<Vec<_>>(),
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::cli::MockCli;
+	use pop_chains::up::{DetachedNode, DETACHED_ENV};
+	use std::path::PathBuf;
+
+	// A process identified as the supervisor of the `paseo` network, terminated once dropped.
+	struct Supervisor(std::process::Child);
+
+	impl Supervisor {
+		fn spawn() -> Result<Self> {
+			let child = std::process::Command::new("sleep")
+				.arg("60")
+				.env(DETACHED_ENV, "paseo")
+				.spawn()?;
+			Ok(Self(child))
+		}
+
+		fn id(&self) -> u32 {
+			self.0.id()
+		}
+	}
+
+	impl Drop for Supervisor {
+		fn drop(&mut self) {
+			let _ = self.0.kill();
+			let _ = self.0.wait();
+		}
+	}
+
+	fn network(pid: u32) -> DetachedNetwork {
+		let node = |name: &str, port: u16| DetachedNode {
+			name: name.to_string(),
+			endpoint: format!("ws://127.0.0.1:{port}"),
+			pid: None,
+			log: PathBuf::from(format!("/tmp/zombie/{name}/{name}.log")),
+		};
+		DetachedNetwork {
+			name: "paseo".to_string(),
+			pid,
+			base_dir: PathBuf::from("/tmp/zombie"),
+			relay_chain: DetachedChain {
+				chain: "paseo-local".to_string(),
+				id: None,
+				nodes: vec![node("alice", 9944), node("bob", 9945)],
+			},
+			rollups: vec![DetachedChain {
+				chain: "asset-hub-paseo-local".to_string(),
+				id: Some(1000),
+				nodes: vec![node("asset-hub", 9946)],
+			}],
+		}
+	}
+
+	#[tokio::test]
+	async fn list_works() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let supervisor = Supervisor::spawn()?;
+		let mut cli = MockCli::new().expect_info(
+			"No local networks are running in the background. Launch one using `pop up network --detach`.",
+		);
+		Command::List.execute_with(temp_dir.path(), &mut cli).await?;
+		cli.verify()?;
+
+		network(supervisor.id()).save(&DetachedNetwork::dir(temp_dir.path()))?;
+		let mut cli = MockCli::new().expect_plain(format!(
+			"paseo (paseo-local + 1000) pid: {} dir: /tmp/zombie",
+			supervisor.id()
+		));
+		Command::List.execute_with(temp_dir.path(), &mut cli).await?;
+		cli.verify()
+	}
+
+	#[tokio::test]
+	async fn list_removes_stopped_networks() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let networks = DetachedNetwork::dir(temp_dir.path());
+		network(u32::MAX).save(&networks)?;
+		let mut cli = MockCli::new().expect_info(
+			"No local networks are running in the background. Launch one using `pop up network --detach`.",
+		);
+		Command::List.execute_with(temp_dir.path(), &mut cli).await?;
+		assert!(DetachedNetwork::list(&networks)?.is_empty());
+		cli.verify()
+	}
+
+	#[tokio::test]
+	async fn stop_removes_stale_network() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let networks = DetachedNetwork::dir(temp_dir.path());
+		// The recorded process is not the supervisor of the network.
+		network(std::process::id()).save(&networks)?;
+		let stop = Command::Stop { name: "paseo".into(), auto_remove: false };
+		let mut cli = MockCli::new().expect_intro("Stop the `paseo` network");
+		assert!(stop.execute_with(temp_dir.path(), &mut cli).await.is_err_and(
+			|e| e.to_string() == "No local network named `paseo` is running in the background."
+		));
+		assert_eq!(DetachedNetwork::load(&networks, "paseo")?, None);
+		cli.verify()
+	}
+
+	#[tokio::test]
+	async fn endpoints_works() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let supervisor = Supervisor::spawn()?;
+		let endpoints = Command::Endpoints { name: "paseo".into() };
+		assert!(endpoints.execute_with(temp_dir.path(), &mut MockCli::new()).await.is_err_and(
+			|e| e.to_string() == "No local network named `paseo` is running in the background."
+		));
+
+		let network = network(supervisor.id());
+		network.save(&DetachedNetwork::dir(temp_dir.path()))?;
+		let expected = json!({
+			"name": "paseo",
+			"relay_chain": {
+				"chain": "paseo-local",
+				"nodes": { "alice": "ws://127.0.0.1:9944", "bob": "ws://127.0.0.1:9945" }
+			},
+			"rollups": [{
+				"id": 1000,
+				"chain": "asset-hub-paseo-local",
+				"nodes": { "asset-hub": "ws://127.0.0.1:9946" }
+			}]
+		});
+		let mut cli = MockCli::new().expect_plain(serde_json::to_string_pretty(&expected)?);
+		endpoints.execute_with(temp_dir.path(), &mut cli).await?;
+		cli.verify()
+	}
+
+	#[tokio::test]
+	async fn snapshot_fails_when_network_not_running() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let snapshot = Command::Snapshot { name: "paseo".into(), snapshot: None };
+		let mut cli = MockCli::new().expect_intro("Snapshot the `paseo` network");
+		assert!(snapshot.execute_with(temp_dir.path(), &mut cli).await.is_err_and(|e| e
+			.to_string() ==
+			"No local network named `paseo` is running in the background."));
+		cli.verify()
+	}
+
+	#[tokio::test]
+	async fn snapshots_works() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let mut cli = MockCli::new().expect_info(
+			"No snapshots found. Take one of a network running in the background using `pop network snapshot`.",
+		);
+		Command::Snapshots.execute_with(temp_dir.path(), &mut cli).await?;
+		cli.verify()?;
+
+		let snapshots = Snapshot::dir(temp_dir.path());
+		std::fs::create_dir_all(&snapshots)?;
+		let snapshot = Snapshot {
+			name: "channels-open".to_string(),
+			network: "paseo".to_string(),
+			relay_chain: "paseo-local".to_string(),
+			rollups: vec![1000, 4001],
+			relay_chain_version: None,
+			created: 0,
+		};
+		std::fs::write(snapshots.join("channels-open.json"), serde_json::to_string(&snapshot)?)?;
+		let mut cli = MockCli::new().expect_plain(format!(
+			"channels-open (paseo-local + 1000, 4001) network: paseo archive: {}",
+			Snapshot::archive(&snapshots, "channels-open")?.display()
+		));
+		Command::Snapshots.execute_with(temp_dir.path(), &mut cli).await?;
+		cli.verify()
+	}
+
+	#[tokio::test]
+	async fn seal_fails_without_chain() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let seal =
+			Command::Seal { url: Url::parse("ws://127.0.0.1:1")?, blocks: 2, finalize: false };
+		let mut cli = MockCli::new().expect_intro("Seal 2 block(s) on ws://127.0.0.1:1/");
+		assert!(seal.execute_with(temp_dir.path(), &mut cli).await.is_err_and(|e| e
+			.to_string()
+			.starts_with("Could not seal blocks: Failed to establish a connection")));
+		cli.verify()
+	}
+
+	#[tokio::test]
+	async fn upgrade_fails_with_unknown_rollup() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let supervisor = Supervisor::spawn()?;
+		let upgrade = Command::Upgrade {
+			name: "paseo".into(),
+			runtime: temp_dir.path().join("runtime.wasm"),
+			id: Some(2000),
+			suri: "//Alice".into(),
+			blocks: 2,
+			timeout: 600,
+		};
+		let mut cli = MockCli::new()
+			.expect_intro("Upgrade the runtime of rollup 2000 of the `paseo` network");
+		assert!(upgrade.execute_with(temp_dir.path(), &mut cli).await.is_err_and(|e| e
+			.to_string() ==
+			"No local network named `paseo` is running in the background."));
+		cli.verify()?;
+
+		network(supervisor.id()).save(&DetachedNetwork::dir(temp_dir.path()))?;
+		let mut cli = MockCli::new()
+			.expect_intro("Upgrade the runtime of rollup 2000 of the `paseo` network");
+		assert!(upgrade
+			.execute_with(temp_dir.path(), &mut cli)
+			.await
+			.is_err_and(|e| e.to_string() == "The network `paseo` has no rollup with id 2000."));
+		cli.verify()
+	}
+
+	#[tokio::test]
+	async fn upgrade_fails_without_runtime() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let supervisor = Supervisor::spawn()?;
+		network(supervisor.id()).save(&DetachedNetwork::dir(temp_dir.path()))?;
+		let runtime = temp_dir.path().join("runtime.wasm");
+		let upgrade = Command::Upgrade {
+			name: "paseo".into(),
+			runtime: runtime.clone(),
+			id: None,
+			suri: "//Alice".into(),
+			blocks: 2,
+			timeout: 600,
+		};
+		let mut cli = MockCli::new()
+			.expect_intro("Upgrade the runtime of the relay chain of the `paseo` network");
+		assert!(upgrade.execute_with(temp_dir.path(), &mut cli).await.is_err_and(|e| e
+			.to_string()
+			.starts_with(&format!("Could not read the runtime at {}", runtime.display()))));
+		cli.verify()
+	}
+
+	#[test]
+	fn parsing_xcm_works() -> Result<()> {
+		#[derive(clap::Parser)]
+		struct Cli {
+			#[command(subcommand)]
+			command: Command,
+		}
+		let parse = |args: &[&str]| {
+			<Cli as clap::Parser>::try_parse_from(["network", "xcm", "paseo"].iter().chain(args))
+				.map(|c| c.command)
+		};
+		let Command::Xcm { program, from, to, amount, .. } =
+			parse(&["teleport", "--from", "relay", "--to", "1000", "--amount", "100"])?
+		else {
+			panic!("expected xcm command");
+		};
+		assert_eq!(
+			(program, from, to, amount),
+			(Program::Teleport, NetworkChain::Relay, NetworkChain::Rollup(1000), Some(100))
+		);
+		let Command::Xcm { program, call, sudo, .. } =
+			parse(&["transact", "--from", "relay", "--to", "1000", "--call", "0x0000", "--sudo"])?
+		else {
+			panic!("expected xcm command");
+		};
+		assert_eq!((program, call.as_deref(), sudo), (Program::Transact, Some("0x0000"), true));
+
+		// Transfers require an amount, and calls are required to be transacted.
+		assert!(parse(&["reserve-transfer", "--from", "1000", "--to", "2000"]).is_err());
+		assert!(parse(&["transact", "--from", "1000", "--to", "relay"]).is_err());
+		assert!(
+			parse(&["teleport", "--from", "asset-hub", "--to", "relay", "--amount", "1"]).is_err()
+		);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn xcm_fails_with_unknown_rollup() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let supervisor = Supervisor::spawn()?;
+		network(supervisor.id()).save(&DetachedNetwork::dir(temp_dir.path()))?;
+		let xcm = Command::Xcm {
+			name: "paseo".into(),
+			program: Program::Transact,
+			from: NetworkChain::Relay,
+			to: NetworkChain::Rollup(2000),
+			amount: None,
+			beneficiary: None,
+			call: Some("0x0000".into()),
+			sudo: true,
+			suri: "//Alice".into(),
+			timeout: 120,
+		};
+		let mut cli = MockCli::new().expect_intro(
+			"Send a message from the relay chain to rollup 2000 of the `paseo` network",
+		);
+		assert!(xcm
+			.execute_with(temp_dir.path(), &mut cli)
+			.await
+			.is_err_and(|e| e.to_string() == "The network `paseo` has no rollup with id 2000."));
+		cli.verify()
+	}
+
+	#[test]
+	fn format_hop_works() {
+		let hop = XcmHop {
+			chain: NetworkChain::Rollup(1000),
+			block: 12,
+			events: vec!["MessageQueue::Processed { success: true }".into()],
+			error: None,
+		};
+		assert_eq!(
+			format_hop("Processed", &hop),
+			format!(
+				"Processed: rollup 1000 #12\n{}",
+				style("   MessageQueue::Processed { success: true }").dim()
+			)
+		);
+	}
+
+	#[test]
+	fn command_display_works() {
+		assert_eq!(Command::List.to_string(), "list");
+		assert_eq!(Command::Endpoints { name: "paseo".into() }.to_string(), "endpoints");
+		assert_eq!(Command::Stop { name: "paseo".into(), auto_remove: false }.to_string(), "stop");
+		assert_eq!(
+			Command::Snapshot { name: "paseo".into(), snapshot: None }.to_string(),
+			"snapshot"
+		);
+		assert_eq!(Command::Snapshots.to_string(), "snapshots");
+		assert_eq!(
+			Command::Seal { url: Url::parse(urls::LOCAL).unwrap(), blocks: 1, finalize: false }
+				.to_string(),
+			"seal"
+		);
+		assert_eq!(
+			Command::Upgrade {
+				name: "paseo".into(),
+				runtime: PathBuf::from("runtime.wasm"),
+				id: None,
+				suri: "//Alice".into(),
+				blocks: 2,
+				timeout: 600,
+			}
+			.to_string(),
+			"upgrade"
+		);
+		assert_eq!(
+			Command::Xcm {
+				name: "paseo".into(),
+				program: Program::Teleport,
+				from: NetworkChain::Relay,
+				to: NetworkChain::Rollup(1000),
+				amount: Some(1),
+				beneficiary: None,
+				call: None,
+				sudo: false,
+				suri: "//Alice".into(),
+				timeout: 120,
+			}
+			.to_string(),
+			"xcm"
+		);
+	}
+}
@@ -0,0 +1,251 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use super::network::ConfigFileCommand;
+use crate::{
+	cli::traits::*,
+	common::{
+		binary::{check_and_prompt, BinaryGenerator},
+		builds::ensure_node_binary_exists,
+		runtime::ensure_runtime_binary_exists,
+	},
+	impl_binary_generator,
+	style::style,
+};
+use clap::Args;
+use console::Emoji;
+use pop_chains::up::{
+	generate_dev_chain_spec, omni_node_generator, supports_dev_seal, DevChain, DEFAULT_BLOCK_TIME,
+	DEFAULT_PORT,
+};
+use pop_common::{sourcing::Binary, Profile};
+use std::{
+	fs,
+	path::{Path, PathBuf},
+	time::Duration,
+};
+
+const BINARY_NAME: &str = "polkadot-omni-node";
+const HELP_HEADER: &str = "Development chain options";
+// How long to wait for the RPC server of a development chain to become reachable.
+const TIMEOUT: Duration = Duration::from_secs(120);
+
+impl_binary_generator!(OmniNodeGenerator, omni_node_generator);
+
+/// Launch a local network by specifying a network configuration file, or the project as a
+/// single-node development chain.
+#[derive(Args, Clone, Default)]
+pub(crate) struct ChainCommand {
+	#[command(flatten)]
+	pub(crate) network: ConfigFileCommand,
+	/// Launch the project as a single-node development chain, which seals its own blocks without
+	/// a relay chain. Blocks are sealed at an interval or on demand: instant seal is not
+	/// supported. The path of the project can be provided in place of the network configuration
+	/// file, defaulting to the current directory.
+	#[arg(long, conflicts_with_all = ["file", "snapshot", "detach"])]
+	pub(crate) dev: bool,
+	/// The interval at which the development chain seals blocks, in milliseconds.
+	#[arg(long, value_name = "MS", default_value_t = DEFAULT_BLOCK_TIME, requires = "dev", help_heading = HELP_HEADER)]
+	pub(crate) block_time: u64,
+	/// Only seal blocks on demand, using `pop network seal`, rather than at an interval.
+	#[arg(long, requires = "dev", conflicts_with = "block_time", help_heading = HELP_HEADER)]
+	pub(crate) on_demand: bool,
+	/// The port of the RPC server of the development chain.
+	#[arg(long, default_value_t = DEFAULT_PORT, requires = "dev", help_heading = HELP_HEADER)]
+	pub(crate) port: u16,
+	/// Run the runtime of the project using `polkadot-omni-node`, rather than the node of the
+	/// project.
+	#[arg(long, requires = "dev", help_heading = HELP_HEADER)]
+	pub(crate) omni_node: bool,
+	/// Build profile [default: release].
+	#[arg(long, value_enum, requires = "dev", help_heading = HELP_HEADER)]
+	pub(crate) profile: Option<Profile>,
+}
+
+impl ChainCommand {
+	/// Executes the command.
+	pub(crate) async fn execute(self, cli: &mut impl Cli) -> anyhow::Result<()> {
+		if !self.dev {
+			return self.network.execute(cli).await;
+		}
+		cli.intro("Launch a development chain")?;
+		let project_path = self.network.path.clone().unwrap_or_else(|| PathBuf::from("./"));
+		if !project_path.join("Cargo.toml").is_file() {
+			cli.outro_cancel(format!(
+				"🚫 No project was found at {}. Provide the path of a chain project.",
+				project_path.display()
+			))?;
+			return Ok(());
+		}
+		let base_dir = std::env::temp_dir().join(format!("pop-dev-{}", std::process::id()));
+		fs::create_dir_all(&base_dir)?;
+		let result = self.launch(&project_path, &base_dir, cli).await;
+		fs::remove_dir_all(&base_dir)?;
+		result.map_err(|e| anyhow::anyhow!("Could not launch the development chain: {e}"))?;
+		cli.outro("Done")?;
+		Ok(())
+	}
+
+	// Launches the development chain within the base directory, until interrupted.
+	async fn launch(
+		&self,
+		project_path: &Path,
+		base_dir: &Path,
+		cli: &mut impl Cli,
+	) -> anyhow::Result<()> {
+		let mut chain = self.resolve(project_path, base_dir, cli).await?;
+		chain.port = self.port;
+		let log = base_dir.join("node.log");
+		let mut node = chain.spawn(&log)?;
+
+		let spinner = cliclack::spinner();
+		spinner.start("Waiting for the development chain to start...");
+		if let Err(e) = chain.wait(&mut node, TIMEOUT).await {
+			spinner.error("The development chain did not start");
+			node.kill()?;
+			cli.warning(fs::read_to_string(&log).unwrap_or_default())?;
+			return Err(e.into());
+		}
+		spinner.stop(format!(
+			"Development chain launched 🚀\n{}",
+			style(format!(
+				"{bar}  node: {}\n{bar}  endpoint: {}\n{bar}  block time: {}\n{bar}  logs: tail -f {}",
+				chain.binary.display(),
+				chain.url(),
+				chain
+					.block_time
+					.map(|block_time| format!("{block_time}ms"))
+					.unwrap_or_else(|| "on demand".to_string()),
+				log.display(),
+				bar = Emoji("│", "|"),
+			))
+			.dim()
+		));
+		if chain.block_time.is_none() {
+			cli.info(format!(
+				"Seal blocks on demand using `pop network seal --url {}`.",
+				chain.url()
+			))?;
+		}
+		cli.info("Press Ctrl+C to stop the development chain.")?;
+
+		tokio::signal::ctrl_c().await?;
+		node.kill()?;
+		node.wait()?;
+		Ok(())
+	}
+
+	// Resolves the node used to run the development chain, either the node of the project or
+	// `polkadot-omni-node` with a chain specification generated from the runtime of the project.
+	async fn resolve(
+		&self,
+		project_path: &Path,
+		base_dir: &Path,
+		cli: &mut impl Cli,
+	) -> anyhow::Result<DevChain> {
+		let profile = self.profile.clone().unwrap_or(Profile::Release);
+		if !self.omni_node && project_path.join("node").is_dir() {
+			let binary = ensure_node_binary_exists(cli, project_path, &profile, vec![])?;
+			if supports_dev_seal(&binary) {
+				return Ok(DevChain::new(binary, self.block_time()));
+			}
+			cli.warning(format!(
+				"The node of the project does not support sealing its own blocks, so the runtime will be run using {BINARY_NAME}."
+			))?;
+		}
+		let (runtime, _) =
+			ensure_runtime_binary_exists(cli, project_path, &profile, &[], false, false, &None)?;
+		let binary =
+			source_omni_node_binary(cli, &crate::cache()?, self.network.skip_confirm).await?;
+		let chain_spec = base_dir.join("dev.json");
+		generate_dev_chain_spec(&runtime, &chain_spec)?;
+		Ok(DevChain { chain_spec: Some(chain_spec), ..DevChain::new(binary, self.block_time()) })
+	}
+
+	// The interval at which blocks are sealed, or `None` if only sealed on demand.
+	fn block_time(&self) -> Option<u64> {
+		(!self.on_demand).then_some(self.block_time)
+	}
+}
+
+/// Checks the status of the `polkadot-omni-node` binary, sources it if necessary, and prompts the
+/// user to update it if the existing binary is not the latest version.
+///
+/// # Arguments
+/// * `cli`: Command line interface.
+/// * `cache_path`: The cache directory path.
+/// * `skip_confirm`: A boolean indicating whether to skip confirmation prompts.
+async fn source_omni_node_binary(
+	cli: &mut impl Cli,
+	cache_path: &Path,
+	skip_confirm: bool,
+) -> anyhow::Result<PathBuf> {
+	check_and_prompt::<OmniNodeGenerator>(cli, BINARY_NAME, cache_path, skip_confirm).await
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::cli::MockCli;
+	use clap::Parser;
+	use tempfile::tempdir;
+
+	#[derive(Parser)]
+	struct Command {
+		#[command(flatten)]
+		chain: ChainCommand,
+	}
+
+	fn parse(args: &[&str]) -> Result<ChainCommand, clap::Error> {
+		Command::try_parse_from(std::iter::once(&"chain").chain(args)).map(|c| c.chain)
+	}
+
+	#[test]
+	fn parsing_works() -> anyhow::Result<()> {
+		let command = parse(&["--dev", "./my-chain", "--on-demand", "--port", "9955"])?;
+		assert!(command.dev);
+		assert_eq!(command.network.path, Some(PathBuf::from("./my-chain")));
+		assert_eq!(command.block_time(), None);
+		assert_eq!(command.port, 9955);
+		assert!(!command.omni_node);
+
+		let command = parse(&["--dev", "--block-time", "1000"])?;
+		assert_eq!(command.block_time(), Some(1000));
+
+		let command = parse(&["network.toml"])?;
+		assert!(!command.dev);
+		assert_eq!(command.block_time(), Some(DEFAULT_BLOCK_TIME));
+		assert_eq!(command.port, DEFAULT_PORT);
+
+		// Blocks are either sealed at an interval or on demand.
+		assert!(parse(&["--dev", "--block-time", "1000", "--on-demand"]).is_err());
+		// Development chain options require `--dev`.
+		assert!(parse(&["--on-demand"]).is_err());
+		assert!(parse(&["--block-time", "1000"]).is_err());
+		assert!(parse(&["--omni-node"]).is_err());
+		// A development chain cannot be launched from a snapshot or in the background.
+		assert!(parse(&["--dev", "--snapshot", "my-snapshot"]).is_err());
+		assert!(parse(&["--dev", "--detach"]).is_err());
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn execute_fails_without_project() -> anyhow::Result<()> {
+		let temp_dir = tempdir()?;
+		let command = ChainCommand {
+			network: ConfigFileCommand {
+				path: Some(temp_dir.path().to_path_buf()),
+				..Default::default()
+			},
+			dev: true,
+			..Default::default()
+		};
+		let mut cli = MockCli::new()
+			.expect_intro("Launch a development chain")
+			.expect_outro_cancel(format!(
+				"🚫 No project was found at {}. Provide the path of a chain project.",
+				temp_dir.path().display()
+			));
+		command.execute(&mut cli).await?;
+		cli.verify()
+	}
+}
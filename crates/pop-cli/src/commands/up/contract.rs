@@ -1,5 +1,7 @@
 // SPDX-License-Identifier: GPL-3.0
 
+#[cfg(feature = "polkavm-contracts")]
+use crate::common::contracts::map_account;
 use crate::{
 	cli::{traits::Cli as _, Cli},
 	common::{
@@ -7,14 +9,17 @@ use crate::{
 			check_contracts_node_and_prompt, has_contract_been_built, normalize_call_args,
 			request_contract_function_args, terminate_node,
 		},
+		signer::unlock_signer,
 		urls,
 		wallet::request_signature,
 	},
 	style::style,
 };
+use anyhow::anyhow;
 use clap::Args;
 use cliclack::{confirm, log, log::error, spinner, ProgressBar};
 use console::{Emoji, Style};
+use pop_common::create_signer;
 #[cfg(feature = "wasm-contracts")]
 use pop_contracts::get_code_hash_from_event;
 #[cfg(any(feature = "polkavm-contracts", feature = "wasm-contracts"))]
@@ -25,12 +30,16 @@ use pop_contracts::{
 	set_up_deployment, set_up_upload, upload_contract_signed, upload_smart_contract, Bytes, UpOpts,
 	Verbosity, Weight,
 };
-use pop_contracts::{extract_function, FunctionType};
-use std::path::PathBuf;
+use pop_contracts::{
+	call_smart_contract, compare_storage_layouts, dry_run_gas_estimate_call, extract_function,
+	find_block, get_contract_name, network_key, set_code, set_up_call, CallOpts, Deployment,
+	Deployments, FunctionType, DEPLOYMENTS_FILE,
+};
+use sp_core::bytes::to_hex;
+use std::path::{Path, PathBuf};
+use subxt::utils::H256;
 use tempfile::NamedTempFile;
 use url::Url;
-#[cfg(feature = "polkavm-contracts")]
-use {crate::common::contracts::map_account, sp_core::bytes::to_hex};
 
 const COMPLETE: &str = "🚀 Deployment complete";
 const DEFAULT_PORT: u16 = 9944;
@@ -73,6 +82,9 @@ pub struct UpContractCommand {
 	/// e.g.
 	/// - for a dev account "//Alice"
 	/// - with a password "//Alice///SECRET_PASSWORD"
+	/// - with a key type "ed25519://Alice" or "ecdsa://Alice"
+	/// - a key stored in the keyring "keyring:alice" (see `pop keys`)
+	/// - an encrypted JSON keystore exported from polkadot-js "json:./alice.json"
 	#[clap(short, long, default_value = "//Alice")]
 	pub(crate) suri: String,
 	/// Use a browser extension wallet to sign the extrinsic.
@@ -90,6 +102,26 @@ pub struct UpContractCommand {
 	/// Uploads the contract only, without instantiation.
 	#[clap(short = 'U', long)]
 	pub(crate) upload_only: bool,
+	/// Upgrades the contract deployed at the specified address to the new code, rather than
+	/// instantiating a new contract.
+	#[clap(long, value_name = "ADDRESS", conflicts_with_all = ["use-wallet", "dry_run", "upload_only"])]
+	pub(crate) upgrade: Option<String>,
+	/// The message of the deployed contract which sets its code hash, called with the hash of
+	/// the new code. Defaults to `set_code`.
+	#[clap(long, value_name = "MESSAGE", requires = "upgrade", conflicts_with = "sudo")]
+	pub(crate) upgrade_message: Option<String>,
+	/// Upgrades the contract using the `set_code` call of the contracts pallet, dispatched by the
+	/// sudo account, rather than a message of the contract.
+	#[clap(long, requires = "upgrade")]
+	pub(crate) sudo: bool,
+	/// Path to the metadata of the deployed contract, used to check that its storage layout is
+	/// compatible with that of the new code. Defaults to the metadata recorded when the contract
+	/// was deployed.
+	#[clap(long, value_name = "PATH", requires = "upgrade")]
+	pub(crate) deployed_metadata: Option<PathBuf>,
+	/// Upgrades the contract even when the storage layouts are incompatible or cannot be compared.
+	#[clap(long, requires = "upgrade")]
+	pub(crate) force: bool,
 	/// Automatically source or update the needed binary required without prompting for
 	/// confirmation.
 	#[clap(short = 'y', long)]
@@ -119,6 +151,14 @@ impl UpContractCommand {
 			));
 		}
 
+		// Unlock the signer, if encrypted.
+		if !self.use_wallet {
+			if let Err(e) = unlock_signer(&self.suri, &mut Cli) {
+				Cli.outro_cancel(e.to_string())?;
+				return Ok(());
+			}
+		}
+
 		// Check if specified chain is accessible
 		let process = if !is_chain_alive(self.url.clone()).await? {
 			if !self.skip_confirm {
@@ -189,6 +229,23 @@ impl UpContractCommand {
 			None
 		};
 
+		// Upgrade an existing contract. Returns early.
+		if let Some(address) = self.upgrade.clone() {
+			let result = self.upgrade_contract(&address).await;
+			terminate_node(&mut Cli, process).await?;
+			result.map_err(|e| anyhow!("Upgrade failed: {e}"))?;
+			Cli.outro(COMPLETE)?;
+			return Ok(());
+		}
+
+		// Warn when identical code and salt were already deployed to the network.
+		let network = network_key(&self.url).await;
+		if !self.upload_only {
+			if let Err(e) = self.warn_if_deployed(&network) {
+				Cli.warning(format!("NOTE: unable to check previous deployments: {e}"))?;
+			}
+		}
+
 		// Run steps for signing with wallet integration. Returns early.
 		if self.use_wallet {
 			let (call_data, hash) = match self.get_contract_data().await {
@@ -202,7 +259,7 @@ impl UpContractCommand {
 			};
 
 			let maybe_signature_request =
-				request_signature(call_data, self.url.to_string()).await?;
+				request_signature(call_data, self.url.to_string(), None).await?;
 			if let Some(payload) = maybe_signature_request.signed_payload {
 				log::success("Signed payload received.")?;
 				let spinner = spinner();
@@ -278,17 +335,16 @@ impl UpContractCommand {
 
 					let hash = contract_info.code_hash.map(|code_hash| format!("{:?}", code_hash));
 					#[cfg(feature = "wasm-contracts")]
-					display_contract_info(
-						&spinner,
-						contract_info.contract_address.to_string(),
-						hash,
-					);
+					let address = contract_info.contract_address.to_string();
 					#[cfg(feature = "polkavm-contracts")]
-					display_contract_info(
-						&spinner,
-						format!("{:?}", contract_info.contract_address),
-						hash,
-					);
+					let address = format!("{:?}", contract_info.contract_address);
+					display_contract_info(&spinner, address.clone(), hash);
+					let extrinsic_hash = contract_info.events.extrinsic_hash();
+					if let Err(e) =
+						self.record_deployment(&network, address, extrinsic_hash, None).await
+					{
+						Cli.warning(format!("NOTE: unable to record the deployment: {e}"))?;
+					}
 				};
 
 				if self.upload_only {
@@ -372,6 +428,21 @@ impl UpContractCommand {
 				contract_info.address.to_string(),
 				contract_info.code_hash,
 			);
+			let deployer = create_signer(&self.suri).ok().map(|s| s.account_id().to_string());
+			if let Err(e) = self
+				.record_deployment(
+					&network,
+					contract_info.address,
+					contract_info.extrinsic_hash,
+					deployer,
+				)
+				.await
+			{
+				Cli.warning(format!("NOTE: unable to record the deployment: {e}"))?;
+			}
+			for event in contract_info.events {
+				Cli.info(format!("Contract Event {event}"))?;
+			}
 
 			Cli.outro(COMPLETE)?;
 			terminate_node(&mut Cli, process).await?;
@@ -415,6 +486,183 @@ impl UpContractCommand {
 		Ok(())
 	}
 
+	/// Upgrades a deployed contract to the new code.
+	async fn upgrade_contract(&self, address: &str) -> anyhow::Result<()> {
+		// Refuse to upgrade when the storage of the deployed contract is incompatible, using the
+		// metadata recorded when the contract was deployed unless provided.
+		let network = network_key(&self.url).await;
+		let deployed = match self.deployed_metadata.clone() {
+			Some(path) => Some(path),
+			None => self.recorded_metadata(&network, address)?,
+		};
+		self.check_storage_layout(deployed.as_deref())?;
+
+		let code_hash = to_hex(&get_contract_code(self.path.as_ref())?.code_hash(), false);
+		let upload_exec = set_up_upload(self.clone().into()).await?;
+		let upload = spinner();
+		upload.start("Uploading the new code...");
+		match upload_smart_contract(&upload_exec).await {
+			Ok(_) => upload.stop(format!("Code uploaded: The code hash is {code_hash}")),
+			// The code may already have been uploaded, such as by a previous upgrade attempt.
+			Err(pop_contracts::Error::UploadContractError(message))
+				if message.contains("already been uploaded") =>
+				upload.stop(format!("Code already uploaded: The code hash is {code_hash}")),
+			Err(e) => {
+				upload.error(format!("An error occurred uploading the new code: {e}"));
+				return Err(e.into());
+			},
+		}
+
+		let spinner = spinner();
+		if self.sudo {
+			spinner.start("Setting the code of the contract using sudo...");
+			set_code(&self.url, &self.suri, address, &code_hash).await?;
+			spinner.stop(format!("Contract upgraded: The code hash of {address} is {code_hash}"));
+			return self.record_upgrade(&network, address, &code_hash);
+		}
+		// The upgrade message is called using the metadata of the deployed contract, if known.
+		let call_exec = set_up_call(CallOpts {
+			path: deployed.or_else(|| self.path.clone()),
+			contract: address.to_string(),
+			message: self.upgrade_message.clone().unwrap_or_else(|| "set_code".to_string()),
+			args: vec![code_hash.clone()],
+			value: "0".to_string(),
+			gas_limit: self.gas_limit,
+			proof_size: self.proof_size,
+			url: self.url.clone(),
+			suri: self.suri.clone(),
+			execute: true,
+		})
+		.await?;
+		#[cfg(feature = "polkavm-contracts")]
+		map_account(call_exec.opts(), &mut Cli).await?;
+		spinner.start("Doing a dry run to estimate the gas...");
+		let weight_limit = dry_run_gas_estimate_call(&call_exec).await?;
+		spinner.set_message("Calling the upgrade message of the contract...");
+		let output = call_smart_contract(call_exec, weight_limit, &self.url).await?;
+		spinner.stop(format!("Contract upgraded: The code hash of {address} is {code_hash}"));
+		Cli.info(output)?;
+		self.record_upgrade(&network, address, &code_hash)
+	}
+
+	/// Returns the metadata recorded when the contract at the address was deployed, if any.
+	fn recorded_metadata(&self, network: &str, address: &str) -> anyhow::Result<Option<PathBuf>> {
+		let project_path = self.project_path();
+		let deployments = Deployments::load(&project_path)?;
+		Ok(deployments
+			.find_by_address(network, address)
+			.map(|(_, deployment)| Deployments::metadata_path(&project_path, &deployment.code_hash))
+			.filter(|path| path.exists()))
+	}
+
+	/// Records the new code of an upgraded contract, if its deployment was recorded.
+	fn record_upgrade(&self, network: &str, address: &str, code_hash: &str) -> anyhow::Result<()> {
+		let project_path = self.project_path();
+		let mut deployments = Deployments::load(&project_path)?;
+		let Some((name, deployment)) = deployments
+			.find_by_address(network, address)
+			.map(|(name, deployment)| (name.to_string(), deployment.clone()))
+		else {
+			return Ok(());
+		};
+		deployments.record(
+			network,
+			&name,
+			Deployment { code_hash: code_hash.to_string(), ..deployment },
+		);
+		deployments.save(&project_path)?;
+		Deployments::record_metadata(&project_path, code_hash)?;
+		Ok(())
+	}
+
+	/// Ensures that the storage layout of the new code is compatible with that of the deployed
+	/// contract, unless forced.
+	///
+	/// # Arguments
+	/// * `deployed` - The metadata of the deployed contract, if known.
+	fn check_storage_layout(&self, deployed: Option<&Path>) -> anyhow::Result<()> {
+		let Some(deployed) = deployed else {
+			if self.force {
+				Cli.warning("NOTE: the storage layouts of the contracts have not been compared.")?;
+				return Ok(());
+			}
+			return Err(anyhow!(
+				"no metadata has been recorded for the deployed contract: provide it with `--deployed-metadata` to check the compatibility of its storage, or use `--force`"
+			));
+		};
+		let changes = compare_storage_layouts(deployed, &self.project_path())?;
+		if changes.is_empty() {
+			return Ok(());
+		}
+		let changes: Vec<_> = changes.iter().map(|change| format!("- {change}")).collect();
+		if self.force {
+			Cli.warning(format!(
+				"NOTE: upgrading despite an incompatible storage layout:\n{}",
+				changes.join("\n")
+			))?;
+			return Ok(());
+		}
+		Err(anyhow!(
+			"the storage layout is incompatible with that of the deployed contract, use `--force` to upgrade regardless:\n{}",
+			changes.join("\n")
+		))
+	}
+
+	/// Warns when a contract with identical code and salt was already deployed to the network.
+	fn warn_if_deployed(&self, network: &str) -> anyhow::Result<()> {
+		let deployments = Deployments::load(&self.project_path())?;
+		let code_hash = to_hex(&get_contract_code(self.path.as_ref())?.code_hash(), false);
+		let salt = self.salt.as_ref().map(|salt| to_hex(salt, false));
+		if let Some((name, deployment)) = deployments.find(network, &code_hash, salt.as_deref()) {
+			Cli.warning(format!(
+				"NOTE: `{name}` has already been deployed with identical code and salt at {}.",
+				deployment.address
+			))?;
+		}
+		Ok(())
+	}
+
+	/// Records the deployment of the contract in the deployments file of the project.
+	async fn record_deployment(
+		&self,
+		network: &str,
+		address: String,
+		extrinsic_hash: H256,
+		deployer: Option<String>,
+	) -> anyhow::Result<()> {
+		let project_path = self.project_path();
+		let name = get_contract_name(&project_path)?;
+		let code_hash = to_hex(&get_contract_code(self.path.as_ref())?.code_hash(), false);
+		let mut deployments = Deployments::load(&project_path)?;
+		// Record the metadata of the code, so its storage layout can be checked when upgrading.
+		Deployments::record_metadata(&project_path, &code_hash)?;
+		let previous = deployments.record(
+			network,
+			&name,
+			Deployment {
+				address,
+				code_hash,
+				constructor: self.constructor.clone(),
+				args: self.args.clone(),
+				salt: self.salt.as_ref().map(|salt| to_hex(salt, false)),
+				deployer,
+				block: find_block(&self.url, extrinsic_hash).await,
+			},
+		);
+		if let Some(previous) = previous {
+			Cli.warning(format!(
+				"NOTE: `{name}` was previously deployed at {}, which is kept in the history of {DEPLOYMENTS_FILE}.",
+				previous.address
+			))?;
+		}
+		Ok(deployments.save(&project_path)?)
+	}
+
+	// The path of the project, within which deployments are recorded.
+	fn project_path(&self) -> PathBuf {
+		self.path.clone().unwrap_or_else(|| PathBuf::from("./"))
+	}
+
 	// get the call data and contract code hash
 	async fn get_contract_data(&self) -> anyhow::Result<(Vec<u8>, [u8; 32])> {
 		let contract_code = get_contract_code(self.path.as_ref())?;
@@ -497,6 +745,11 @@ impl Default for UpContractCommand {
 			use_wallet: false,
 			dry_run: false,
 			upload_only: false,
+			upgrade: None,
+			upgrade_message: None,
+			sudo: false,
+			deployed_metadata: None,
+			force: false,
 			skip_confirm: false,
 		}
 	}
@@ -505,6 +758,8 @@ impl Default for UpContractCommand {
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use pop_contracts::{mock_build_process, new_environment};
+	use std::env;
 	use url::Url;
 
 	#[test]
@@ -527,4 +782,114 @@ mod tests {
 		);
 		Ok(())
 	}
+
+	#[tokio::test]
+	async fn record_deployment_works() -> anyhow::Result<()> {
+		let temp_dir = new_environment("testing")?;
+		let mut current_dir = env::current_dir().expect("Failed to get current directory");
+		current_dir.pop();
+		mock_build_process(
+			temp_dir.path().join("testing"),
+			current_dir.join("pop-contracts/tests/files/testing.contract"),
+			current_dir.join("pop-contracts/tests/files/testing.json"),
+		)?;
+		let command = UpContractCommand {
+			path: Some(temp_dir.path().join("testing")),
+			args: vec!["true".to_string()],
+			salt: Some(Bytes(vec![1])),
+			..Default::default()
+		};
+		command.record_deployment("0x01", "ADDRESS".into(), H256::zero(), None).await?;
+
+		let deployments = Deployments::load(&temp_dir.path().join("testing"))?;
+		let deployment = deployments.get("0x01", "testing").expect("deployment is recorded");
+		assert_eq!(deployment.address, "ADDRESS");
+		assert_eq!(deployment.constructor, "new");
+		assert_eq!(deployment.args, vec!["true".to_string()]);
+		assert_eq!(deployment.salt, Some("0x01".to_string()));
+		// The block cannot be found without a running node.
+		assert_eq!(deployment.block, None);
+		assert_eq!(
+			deployments
+				.find("0x01", &deployment.code_hash, Some("0x01"))
+				.map(|(name, _)| name),
+			Some("testing")
+		);
+
+		// The metadata of the deployed code is recorded, for checking its storage when upgrading.
+		let metadata = command.recorded_metadata("0x01", "ADDRESS")?.expect("metadata is recorded");
+		assert_eq!(
+			metadata,
+			Deployments::metadata_path(&temp_dir.path().join("testing"), &deployment.code_hash)
+		);
+		assert_eq!(command.recorded_metadata("0x02", "ADDRESS")?, None);
+		command.record_upgrade("0x01", "ADDRESS", "0x02")?;
+		let deployments = Deployments::load(&temp_dir.path().join("testing"))?;
+		assert_eq!(deployments.get("0x01", "testing").map(|d| d.code_hash.as_str()), Some("0x02"));
+		assert!(command.recorded_metadata("0x01", "ADDRESS")?.is_some());
+		// A redeployment keeps the previous deployment in the history of the contract.
+		command
+			.record_deployment("0x01", "NEW_ADDRESS".into(), H256::zero(), None)
+			.await?;
+		let deployments = Deployments::load(&temp_dir.path().join("testing"))?;
+		assert_eq!(
+			deployments.get("0x01", "testing").map(|d| d.address.as_str()),
+			Some("NEW_ADDRESS")
+		);
+		assert_eq!(
+			deployments
+				.history("0x01", "testing")
+				.iter()
+				.map(|d| d.address.as_str())
+				.collect::<Vec<_>>(),
+			["ADDRESS", "ADDRESS"]
+		);
+		Ok(())
+	}
+
+	#[test]
+	fn check_storage_layout_works() -> anyhow::Result<()> {
+		let temp_dir = new_environment("testing")?;
+		let mut current_dir = env::current_dir().expect("Failed to get current directory");
+		current_dir.pop();
+		mock_build_process(
+			temp_dir.path().join("testing"),
+			current_dir.join("pop-contracts/tests/files/testing.contract"),
+			current_dir.join("pop-contracts/tests/files/testing.json"),
+		)?;
+		let mut command = UpContractCommand {
+			path: Some(temp_dir.path().join("testing")),
+			upgrade: Some("ADDRESS".to_string()),
+			..Default::default()
+		};
+		// The storage layouts cannot be compared without the metadata of the deployed contract.
+		assert!(command
+			.check_storage_layout(None)
+			.is_err_and(|e| e.to_string().contains("--deployed-metadata")));
+		command.force = true;
+		assert!(command.check_storage_layout(None).is_ok());
+
+		command.force = false;
+		let deployed = current_dir.join("pop-contracts/tests/files/testing.json");
+		assert!(command.check_storage_layout(Some(&deployed)).is_ok());
+
+		// Remove a field from the storage of the deployed contract.
+		let mut metadata: serde_json::Value = serde_json::from_slice(&std::fs::read(
+			current_dir.join("pop-contracts/tests/files/testing.json"),
+		)?)?;
+		metadata["storage"]["root"]["layout"]["struct"]["fields"]
+			.as_array_mut()
+			.expect("fields are an array")
+			.push(
+				serde_json::json!({"name": "owner", "layout": {"leaf": {"key": "0x00000000", "ty": 8}}}),
+			);
+		let deployed = temp_dir.path().join("deployed.json");
+		std::fs::write(&deployed, serde_json::to_vec(&metadata)?)?;
+		assert!(command
+			.check_storage_layout(Some(&deployed))
+			.is_err_and(|e| e.to_string().contains("- `owner` was removed")));
+		command.force = true;
+		assert!(command.check_storage_layout(Some(&deployed)).is_ok());
+		Ok(())
+	}
 }
@@ -0,0 +1,228 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use crate::{cli::traits::*, style::style};
+use anyhow::{anyhow, Result};
+use clap::Args;
+use console::Emoji;
+use pop_chains::{
+	fork::{parse_storage, serve, Fork, ForkSource, Seal},
+	up::DEFAULT_PORT,
+	H256,
+};
+use pop_common::parse_account;
+use std::{fs, path::PathBuf};
+use url::Url;
+
+const HELP_HEADER: &str = "Storage overrides";
+/// The free balance of a funded account, when no amount is specified.
+const DEFAULT_FUNDS: u128 = 1_000_000_000_000_000_000_000;
+
+/// Fork a live chain, or a snapshot of its state, serving it locally.
+#[derive(Args, Clone, Default)]
+pub(crate) struct ForkCommand {
+	/// The RPC endpoint of the chain to fork.
+	#[arg(long, required_unless_present = "snapshot")]
+	pub(crate) url: Option<Url>,
+	/// The hash of the block to fork from [default: latest finalized block].
+	#[arg(long, requires = "url", conflicts_with = "snapshot")]
+	pub(crate) at: Option<H256>,
+	/// A snapshot created by `try-runtime create-snapshot`, used as the state of the fork in place
+	/// of a live chain.
+	#[arg(long, conflicts_with = "url")]
+	pub(crate) snapshot: Option<PathBuf>,
+	/// How blocks are built: once an extrinsic is submitted, or on demand using `pop network
+	/// seal`.
+	#[arg(long, value_enum, default_value_t)]
+	pub(crate) seal: Seal,
+	/// The port of the RPC server of the fork.
+	#[arg(long, default_value_t = DEFAULT_PORT)]
+	pub(crate) port: u16,
+	/// The account granted sudo privileges.
+	#[arg(long, help_heading = HELP_HEADER)]
+	pub(crate) sudo: Option<String>,
+	/// An account to fund, optionally with its free balance, e.g. `<account>=<amount>`. Can be
+	/// specified multiple times.
+	#[arg(long, value_parser = parse_funds, help_heading = HELP_HEADER)]
+	pub(crate) fund: Vec<(String, u128)>,
+	/// A JSON file of storage keys and values overriding the state of the fork, e.g.
+	/// `{"0x...": "0x...", "0x...": null}`.
+	#[arg(long, help_heading = HELP_HEADER)]
+	pub(crate) storage: Option<PathBuf>,
+}
+
+impl ForkCommand {
+	/// Executes the command.
+	pub(crate) async fn execute(self, cli: &mut impl Cli) -> Result<()> {
+		cli.intro("Fork a chain")?;
+		let source = match (&self.url, &self.snapshot) {
+			(_, Some(snapshot)) if !snapshot.is_file() => {
+				cli.outro_cancel(format!("🚫 No snapshot was found at {}.", snapshot.display()))?;
+				return Ok(());
+			},
+			(_, Some(snapshot)) => ForkSource::Snapshot(snapshot.clone()),
+			(Some(url), None) => ForkSource::Live { url: url.clone(), at: self.at },
+			(None, None) => return Err(anyhow!("either a url or a snapshot is required")),
+		};
+
+		let spinner = cliclack::spinner();
+		spinner.start("Forking the chain...");
+		let fork = match self.fork(&source).await {
+			Ok(fork) => fork,
+			Err(e) => {
+				spinner.error("The chain could not be forked");
+				cli.outro_cancel(format!("🚫 Could not fork the chain: {e}"))?;
+				return Ok(());
+			},
+		};
+		let (chain, head) = (fork.chain().to_string(), fork.head().clone());
+		let server = match serve(fork, self.port, self.seal).await {
+			Ok(server) => server,
+			Err(e) => {
+				spinner.error("The fork could not be served");
+				cli.outro_cancel(format!("🚫 {e}"))?;
+				return Ok(());
+			},
+		};
+		spinner.stop(format!(
+			"{chain} forked 🍴\n{}",
+			style(format!(
+				"{bar}  endpoint: {}\n{bar}  block: #{} ({:?})\n{bar}  seal: {}",
+				server.url(),
+				head.header.number,
+				head.hash,
+				self.seal.as_ref(),
+				bar = Emoji("│", "|"),
+			))
+			.dim()
+		));
+		if self.seal == Seal::Manual {
+			cli.info(format!(
+				"Seal blocks on demand using `pop network seal --url {}`.",
+				server.url()
+			))?;
+		}
+		cli.info("Press Ctrl+C to stop the fork.")?;
+
+		tokio::signal::ctrl_c().await?;
+		server.stop().await?;
+		cli.outro("Done")?;
+		Ok(())
+	}
+
+	// Forks the chain, applying the storage overrides.
+	async fn fork(&self, source: &ForkSource) -> Result<Fork> {
+		let cache = crate::cache()?.join("forks");
+		fs::create_dir_all(&cache)?;
+		let mut fork = Fork::new(source, &cache).await?;
+		// Overriding storage may execute the runtime or fetch state, which blocks.
+		let command = self.clone();
+		tokio::task::spawn_blocking(move || {
+			command.apply_overrides(&mut fork)?;
+			Ok(fork)
+		})
+		.await?
+	}
+
+	// Applies the storage overrides to the fork.
+	fn apply_overrides(&self, fork: &mut Fork) -> Result<()> {
+		if let Some(path) = &self.storage {
+			let overrides = serde_json::from_str(&fs::read_to_string(path)?)?;
+			fork.set_storage(parse_storage(&overrides)?)?;
+		}
+		if let Some(sudo) = &self.sudo {
+			fork.set_sudo(parse_account(sudo)?.0)?;
+		}
+		for (account, amount) in &self.fund {
+			fork.fund(parse_account(account)?.0, *amount)?;
+		}
+		Ok(())
+	}
+}
+
+// Parses an account to fund, along with its free balance.
+fn parse_funds(value: &str) -> Result<(String, u128), String> {
+	let (account, amount) = match value.split_once('=') {
+		Some((account, amount)) =>
+			(account, amount.parse().map_err(|_| format!("invalid amount: {amount}"))?),
+		None => (value, DEFAULT_FUNDS),
+	};
+	parse_account(account).map_err(|e| e.to_string())?;
+	Ok((account.to_string(), amount))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::cli::MockCli;
+	use clap::Parser;
+
+	const ALICE: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+
+	#[derive(Parser)]
+	struct Command {
+		#[command(flatten)]
+		fork: ForkCommand,
+	}
+
+	fn parse(args: &[&str]) -> Result<ForkCommand, clap::Error> {
+		Command::try_parse_from(std::iter::once(&"fork").chain(args)).map(|c| c.fork)
+	}
+
+	#[test]
+	fn parsing_works() -> Result<()> {
+		let at = format!("{:?}", H256::repeat_byte(1));
+		let fund = format!("{ALICE}=100");
+		let command = parse(&[
+			"--url",
+			"wss://rpc.polkadot.io",
+			"--at",
+			&at,
+			"--seal",
+			"manual",
+			"--port",
+			"9955",
+			"--sudo",
+			ALICE,
+			"--fund",
+			ALICE,
+			"--fund",
+			&fund,
+			"--storage",
+			"overrides.json",
+		])?;
+		assert_eq!(command.url, Some(Url::parse("wss://rpc.polkadot.io")?));
+		assert_eq!(command.at, Some(H256::repeat_byte(1)));
+		assert_eq!(command.seal, Seal::Manual);
+		assert_eq!(command.port, 9955);
+		assert_eq!(command.sudo.as_deref(), Some(ALICE));
+		assert_eq!(command.fund, vec![(ALICE.into(), DEFAULT_FUNDS), (ALICE.into(), 100)]);
+		assert_eq!(command.storage, Some(PathBuf::from("overrides.json")));
+
+		let command = parse(&["--snapshot", "chain.snap"])?;
+		assert_eq!(command.snapshot, Some(PathBuf::from("chain.snap")));
+		assert_eq!(command.seal, Seal::Instant);
+		assert_eq!(command.port, DEFAULT_PORT);
+
+		// Either a url or a snapshot is required, but not both.
+		assert!(parse(&[]).is_err());
+		assert!(parse(&["--url", "ws://localhost:9944", "--snapshot", "chain.snap"]).is_err());
+		// A block can only be specified when forking a live chain.
+		assert!(parse(&["--snapshot", "chain.snap", "--at", &at]).is_err());
+		// Funded accounts must be valid.
+		assert!(parse(&["--snapshot", "chain.snap", "--fund", "invalid"]).is_err());
+		assert!(parse(&["--snapshot", "chain.snap", "--fund", &format!("{ALICE}=x")]).is_err());
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn execute_fails_without_snapshot() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let snapshot = temp_dir.path().join("chain.snap");
+		let command = ForkCommand { snapshot: Some(snapshot.clone()), ..Default::default() };
+		let mut cli = MockCli::new()
+			.expect_intro("Fork a chain")
+			.expect_outro_cancel(format!("🚫 No snapshot was found at {}.", snapshot.display()));
+		command.execute(&mut cli).await?;
+		cli.verify()
+	}
+}
@@ -15,9 +15,13 @@ use {
 	std::fmt::{Display, Formatter, Result},
 };
 
+#[cfg(feature = "chain")]
+mod chain;
 #[cfg(any(feature = "polkavm-contracts", feature = "wasm-contracts"))]
 mod contract;
 #[cfg(feature = "chain")]
+mod fork;
+#[cfg(feature = "chain")]
 pub(super) mod network;
 #[cfg(feature = "chain")]
 mod rollup;
@@ -61,8 +65,17 @@ pub(crate) struct UpArgs {
 pub(crate) enum Command {
 	/// Launch a local network by specifying a network configuration file.
 	#[cfg(feature = "chain")]
-	#[clap(aliases = ["n", "chain"])]
+	#[clap(alias = "n")]
 	Network(network::ConfigFileCommand),
+	/// Launch a local network by specifying a network configuration file, or the project as a
+	/// single-node development chain using `--dev`.
+	#[cfg(feature = "chain")]
+	#[clap()]
+	Chain(chain::ChainCommand),
+	/// Fork a live chain, serving it locally with its state fetched on demand.
+	#[cfg(feature = "chain")]
+	#[clap()]
+	Fork(fork::ForkCommand),
 	/// Launch a local Paseo network.
 	#[cfg(feature = "chain")]
 	#[clap()]
@@ -129,6 +142,8 @@ impl Display for Command {
 	fn fmt(&self, f: &mut Formatter<'_>) -> Result {
 		match self {
 			Command::Network(_) => write!(f, "network"),
+			Command::Chain(_) => write!(f, "chain"),
+			Command::Fork(_) => write!(f, "fork"),
 			Command::Paseo(_) => write!(f, "paseo"),
 			Command::Kusama(_) => write!(f, "kusama"),
 			Command::Polkadot(_) => write!(f, "polkadot"),
@@ -170,6 +185,11 @@ mod tests {
 				use_wallet: false,
 				dry_run: true,
 				upload_only: true,
+				upgrade: None,
+				upgrade_message: None,
+				sudo: false,
+				deployed_metadata: None,
+				force: false,
 				skip_confirm: false,
 			},
 			#[cfg(feature = "chain")]
@@ -239,5 +259,9 @@ mod tests {
 	fn command_display_works() {
 		#[cfg(feature = "chain")]
 		assert_eq!(Command::Network(Default::default()).to_string(), "network");
+		#[cfg(feature = "chain")]
+		assert_eq!(Command::Chain(Default::default()).to_string(), "chain");
+		#[cfg(feature = "chain")]
+		assert_eq!(Command::Fork(Default::default()).to_string(), "fork");
 	}
 }
@@ -16,30 +16,46 @@ pub(crate) use pop_chains::up::Relay;
 use pop_chains::{
 	clear_dmpq,
 	registry::{self, traits::Rollup},
-	up::{NetworkConfiguration, Zombienet},
+	up::{
+		DetachedNetwork, HrmpTopology, NetworkConfiguration, Readiness, Snapshot, Zombienet,
+		DETACHED_ENV,
+	},
 	Error, IndexSet, NetworkNode, RelayChain,
 };
 use pop_common::Status;
 use std::{
 	collections::HashMap,
 	ffi::OsStr,
+	fs::File,
+	iter::once,
 	path::{Path, PathBuf},
-	time::Duration,
+	process::Stdio,
+	time::{Duration, Instant},
 };
 use tokio::time::sleep;
 
+// How long to wait for a network launched in the background to be recorded as launched, in
+// addition to any time allowed for it to become ready.
+const DETACHED_LAUNCH_TIMEOUT: Duration = Duration::from_secs(600);
+// The number of lines of the log of the supervising process shown should a launch time out.
+const DETACHED_LOG_LINES: usize = 20;
+
 /// Launch a local network by specifying a network configuration file.
 #[derive(Args, Clone, Default)]
 pub(crate) struct ConfigFileCommand {
 	/// The Zombienet network configuration file to be used.
-	#[arg(value_name = "FILE", conflicts_with = "file")]
+	#[arg(value_name = "FILE", conflicts_with_all = ["file", "snapshot"])]
 	pub path: Option<PathBuf>,
 	/// [DEPRECATED] The Zombienet network configuration file to be used (will be removed in
 	/// v0.10.0).
-	#[arg(short = 'f', long = "file")]
+	#[arg(short = 'f', long = "file", conflicts_with = "snapshot")]
 	#[deprecated(since = "0.9.0", note = "will be removed in v0.10.0")]
 	#[allow(rustdoc::broken_intra_doc_links)]
 	pub(crate) file: Option<PathBuf>,
+	/// Relaunch a network from a snapshot taken using `pop network snapshot`, continuing from its
+	/// saved chain state.
+	#[arg(long)]
+	pub(crate) snapshot: Option<String>,
 	/// The version of the binary to be used for the relay chain, as per the release tag (e.g.
 	/// "stable2503"). See <https://github.com/paritytech/polkadot-sdk/releases> for more details.
 	#[arg(short, long)]
@@ -73,6 +89,16 @@ pub(crate) struct ConfigFileCommand {
 	/// Automatically remove the state upon tearing down the network.
 	#[clap(long = "rm")]
 	pub(crate) auto_remove: bool,
+	/// Launch the network in the background, recording its state so that it can be listed and
+	/// stopped using `pop network`.
+	#[arg(short, long)]
+	pub(crate) detach: bool,
+	/// The name of the network when launched in the background. Defaults to the name of the
+	/// network configuration file.
+	#[arg(long, requires = "detach")]
+	pub(crate) name: Option<String>,
+	#[command(flatten)]
+	pub(crate) readiness: ReadinessArgs,
 }
 
 impl ConfigFileCommand {
@@ -88,22 +114,55 @@ impl ConfigFileCommand {
 			)?;
 		}
 
-		// Determine network config from args.
-		let network_config = match self.path.as_ref() {
-			Some(path) => path,
-			#[allow(deprecated)]
-			None => match self.file.as_deref() {
+		// Determine network config from args, restoring from a snapshot if specified.
+		let (network_config, default_name, relay_chain, restored): (NetworkConfiguration, _, _, _) =
+			match self.snapshot.as_deref() {
+				Some(snapshot) => {
+					let snapshots = Snapshot::dir(&crate::cache()?);
+					let Some(snapshot) = Snapshot::load(&snapshots, snapshot)? else {
+						cli.outro_cancel(format!("🚫 No snapshot named `{snapshot}` was found. Use `pop network snapshots` to list the available snapshots."))?;
+						return Ok(());
+					};
+					// Restore into a directory specific to this process, as a network launched in
+					// the background is relaunched by a separate process.
+					let base_dir = std::env::temp_dir().join(format!(
+						"pop-snapshot-{}-{}",
+						snapshot.name,
+						std::process::id()
+					));
+					let network_config = snapshot.restore(&snapshots, &base_dir)?;
+					cli.info(format!(
+						"Relaunching from snapshot `{}` of the `{}` network.",
+						snapshot.name, snapshot.network
+					))?;
+					// Default to the relay chain version used when the snapshot was taken.
+					let relay_chain = self.relay_chain.clone().or(snapshot.relay_chain_version);
+					(network_config, snapshot.name, relay_chain, Some(base_dir))
+				},
 				None => {
-					cli.outro_cancel("🚫 A network configuration file must be specified. See `pop up network --help` for usage.".to_string())?;
-					return Ok(())
+					let path: &Path = match self.path.as_ref() {
+						Some(path) => path,
+						#[allow(deprecated)]
+						None => match self.file.as_deref() {
+							None => {
+								cli.outro_cancel("🚫 A network configuration file must be specified. See `pop up network --help` for usage.".to_string())?;
+								return Ok(())
+							},
+							Some(path) => path,
+						},
+					};
+					let name = path
+						.file_stem()
+						.map_or("network".into(), |stem| stem.to_string_lossy().to_string());
+					(path.try_into()?, name, self.relay_chain.clone(), None)
 				},
-				Some(path) => path,
-			},
-		};
+			};
 
-		spawn(
-			network_config.try_into()?,
-			self.relay_chain.as_deref(),
+		let name = self.detach.then(|| self.name.clone().unwrap_or(default_name));
+
+		let result = spawn(
+			network_config,
+			relay_chain.as_deref(),
 			self.relay_chain_runtime.as_deref(),
 			self.system_parachain.as_deref(),
 			self.system_parachain_runtime.as_deref(),
@@ -112,9 +171,20 @@ impl ConfigFileCommand {
 			self.skip_confirm,
 			self.auto_remove,
 			self.command.as_deref(),
+			name.as_deref(),
+			self.readiness.readiness(),
 			cli,
 		)
-		.await
+		.await;
+
+		// A restored snapshot is only used by the process supervising a network launched in the
+		// background.
+		if let Some(base_dir) =
+			restored.filter(|_| self.detach && std::env::var(DETACHED_ENV).is_err())
+		{
+			let _ = std::fs::remove_dir_all(base_dir);
+		}
+		result
 	}
 }
 
@@ -146,6 +216,11 @@ pub(crate) struct BuildCommand<const FILTER: u8> {
 	/// The port to be used for the first relay chain validator.
 	#[clap(short = 'P', long)]
 	port: Option<u16>,
+	/// The topology of the HRMP channels to be opened between parachains: `none`, `mesh`,
+	/// `star[:<hub>]` (e.g. `star:1000` for AssetHub), or a comma-separated list of
+	/// `<sender>-<recipient>[:<capacity>:<message size>]` channels (e.g. `2000-1000:500:4096`).
+	#[arg(long, default_value_t = HrmpTopology::Mesh, value_parser = parse_hrmp_topology)]
+	hrmp: HrmpTopology,
 	/// The command to run after the network has been launched.
 	#[clap(name = "cmd", short, long)]
 	command: Option<String>,
@@ -158,6 +233,16 @@ pub(crate) struct BuildCommand<const FILTER: u8> {
 	/// Automatically remove the state upon tearing down the network.
 	#[clap(long = "rm")]
 	auto_remove: bool,
+	/// Launch the network in the background, recording its state so that it can be listed and
+	/// stopped using `pop network`.
+	#[arg(short, long)]
+	detach: bool,
+	/// The name of the network when launched in the background. Defaults to the name of the relay
+	/// chain.
+	#[arg(long, requires = "detach")]
+	name: Option<String>,
+	#[command(flatten)]
+	readiness: ReadinessArgs,
 }
 
 impl<const FILTER: u8> BuildCommand<FILTER> {
@@ -196,7 +281,11 @@ impl<const FILTER: u8> BuildCommand<FILTER> {
 			}
 		}
 
-		let network_config = NetworkConfiguration::build(relay, self.port, rollups.as_deref())?;
+		let name = self
+			.detach
+			.then(|| self.name.clone().unwrap_or_else(|| relay.name().to_lowercase()));
+		let network_config =
+			NetworkConfiguration::build(relay, self.port, rollups.as_deref(), &self.hrmp)?;
 
 		spawn(
 			network_config,
@@ -209,12 +298,48 @@ impl<const FILTER: u8> BuildCommand<FILTER> {
 			self.skip_confirm,
 			self.auto_remove,
 			self.command.as_deref(),
+			name.as_deref(),
+			self.readiness.readiness(),
 			cli,
 		)
 		.await
 	}
 }
 
+/// Arguments for waiting until a launched network is ready.
+#[derive(Args, Clone, Default)]
+pub(crate) struct ReadinessArgs {
+	/// Wait until the relay chain has finalized blocks and each rollup has been onboarded and
+	/// produced blocks, failing should the network not be ready in time.
+	#[arg(long)]
+	pub(crate) wait: bool,
+	/// The number of blocks to be finalized by the relay chain for the network to be ready.
+	#[arg(long, default_value_t = 1, requires = "wait")]
+	pub(crate) relay_chain_blocks: u32,
+	/// The number of blocks to be produced by each rollup for the network to be ready.
+	#[arg(long, default_value_t = 1, requires = "wait")]
+	pub(crate) rollup_blocks: u32,
+	/// The maximum number of seconds to wait for the network to be ready.
+	#[arg(long, default_value_t = 300, requires = "wait")]
+	pub(crate) timeout: u64,
+}
+
+impl ReadinessArgs {
+	// The conditions for the network to be considered ready, if required.
+	fn readiness(&self) -> Option<Readiness> {
+		self.wait.then(|| Readiness {
+			relay_chain_blocks: self.relay_chain_blocks,
+			rollup_blocks: self.rollup_blocks,
+			timeout: Duration::from_secs(self.timeout),
+		})
+	}
+}
+
+// Parses the topology of the HRMP channels to be opened between parachains.
+fn parse_hrmp_topology(value: &str) -> Result<HrmpTopology, String> {
+	value.parse().map_err(|e: Error| e.to_string())
+}
+
 #[derive(Clone)]
 struct SupportedRollups<const FILTER: u8>(PossibleValuesParser);
 
@@ -296,10 +421,23 @@ pub(crate) async fn spawn(
 	skip_confirm: bool,
 	auto_remove: bool,
 	command: Option<&str>,
+	detach: Option<&str>,
+	readiness: Option<Readiness>,
 	cli: &mut impl cli::traits::Cli,
 ) -> anyhow::Result<()> {
 	// Initialize from arguments
 	let cache = crate::cache()?;
+	let networks = DetachedNetwork::dir(&cache);
+	// A network launched in the background is run by a separate supervising process, which has no
+	// terminal to prompt with.
+	let supervisor = detach.is_some() && std::env::var(DETACHED_ENV).is_ok();
+	let skip_confirm = skip_confirm || supervisor;
+	if let Some(name) = detach.filter(|_| !supervisor) {
+		if DetachedNetwork::load(&networks, name)?.is_some_and(|n| n.is_running()) {
+			cli.outro_cancel(format!("🚫 A network named `{name}` is already running. Stop it using `pop network stop {name}` or provide another name using `--name`."))?;
+			return Ok(());
+		}
+	}
 	let mut zombienet = match Zombienet::new(
 		&cache,
 		config,
@@ -351,11 +489,22 @@ pub(crate) async fn spawn(
 		cli.info(format!("Binaries used: {}", binaries.join(", ")))?;
 	}
 
+	if let Some(name) = detach.filter(|_| !supervisor) {
+		return launch_detached(name, &networks, readiness.as_ref(), cli).await;
+	}
+
 	// Finally, spawn the network and wait for a signal to terminate
 	let progress = spinner();
 	progress.start("🚀 Launching local network...");
 	match zombienet.spawn().await {
 		Ok(network) => {
+			if let Some(readiness) = &readiness {
+				if let Err(e) = readiness.wait(&network, &MessageReporter(&progress)).await {
+					progress.error(format!("🚫 {e}"));
+					return Err(e.into());
+				}
+			}
+
 			let mut result = "🚀 Network launched successfully - Ctrl+C to terminate".to_string();
 			let base_dir = network.base_dir().expect("base_dir expected to exist");
 			let bar = Style::new().magenta().dim().apply_to(Emoji("│", "|"));
@@ -408,7 +557,7 @@ pub(crate) async fn spawn(
 			}
 
 			if let Some(command) = command {
-				run_custom_command(&progress, command).await?;
+				run_custom_command(&progress, command, readiness.is_some()).await?;
 			}
 
 			progress.stop(result);
@@ -423,8 +572,10 @@ pub(crate) async fn spawn(
 					Some(_) => {
 						let progress = spinner();
 						progress.start("Connecting to relay chain to prepare channels...");
-						// Allow relay node time to start
-						sleep(Duration::from_secs(10)).await;
+						// Allow relay node time to start, unless already known to be ready.
+						if readiness.is_none() {
+							sleep(Duration::from_secs(10)).await;
+						}
 						progress.set_message("Preparing channels...");
 						let relay_endpoint = network.relaychain().nodes()[0].wait_client().await?;
 						let ids: Vec<_> =
@@ -441,8 +592,23 @@ pub(crate) async fn spawn(
 				}
 			}
 
+			// Record the state of the network, so it can be managed while running in the
+			// background.
+			let state = match detach {
+				Some(name) if supervisor => {
+					let state = DetachedNetwork::new(name, std::process::id(), &network)?;
+					state.save(&networks)?;
+					Some(state)
+				},
+				_ => None,
+			};
+
 			tokio::signal::ctrl_c().await?;
 
+			if let Some(state) = state {
+				state.remove(&networks)?;
+			}
+
 			if auto_remove {
 				// Remove zombienet directory after network is terminated
 				if let Err(e) = std::fs::remove_dir_all(&base_dir) {
@@ -460,6 +626,96 @@ pub(crate) async fn spawn(
 	Ok(())
 }
 
+// Launches the network in the background, by relaunching the current command as a separate process
+// which supervises the network and records its state once launched.
+async fn launch_detached(
+	name: &str,
+	networks: &Path,
+	readiness: Option<&Readiness>,
+	cli: &mut impl cli::traits::Cli,
+) -> anyhow::Result<()> {
+	std::fs::create_dir_all(networks)?;
+	let log_path = DetachedNetwork::log(networks, name)?;
+	let log = File::create(&log_path)?;
+	let mut command = std::process::Command::new(std::env::current_exe()?);
+	command
+		.args(std::env::args_os().skip(1))
+		.env(DETACHED_ENV, name)
+		.stdin(Stdio::null())
+		.stdout(log.try_clone()?)
+		.stderr(log);
+	// Use a separate process group, so the network is unaffected by Ctrl+C within the terminal.
+	#[cfg(unix)]
+	std::os::unix::process::CommandExt::process_group(&mut command, 0);
+	let mut supervisor = command.spawn()?;
+
+	let progress = spinner();
+	progress.start("🚀 Launching local network in the background...");
+	// The supervisor waits for the network to be ready, if required, before recording it.
+	let timeout = DETACHED_LAUNCH_TIMEOUT + readiness.map_or(Duration::ZERO, |r| r.timeout);
+	let start = Instant::now();
+	let network = loop {
+		if let Some(status) = supervisor.try_wait()? {
+			progress.error(format!(
+				"Network supervisor exited: {status}. Last lines of {}:\n{}",
+				log_path.display(),
+				log_tail(&log_path, DETACHED_LOG_LINES)
+			));
+			anyhow::bail!("network `{name}` could not be launched");
+		}
+		if let Some(network) =
+			DetachedNetwork::load(networks, name)?.filter(|n| n.pid == supervisor.id())
+		{
+			break network;
+		}
+		if start.elapsed() > timeout {
+			// Terminate the process group of the supervisor, which includes the nodes it spawned.
+			let _ = cmd("kill", ["-KILL", "--", &format!("-{}", supervisor.id())])
+				.stderr_null()
+				.unchecked()
+				.run();
+			let _ = supervisor.kill();
+			let _ = supervisor.wait();
+			progress.error(format!(
+				"Network was not launched within {}s. Last lines of {}:\n{}",
+				timeout.as_secs(),
+				log_path.display(),
+				log_tail(&log_path, DETACHED_LOG_LINES)
+			));
+			anyhow::bail!("timed out waiting for network `{name}` to launch");
+		}
+		sleep(Duration::from_secs(1)).await;
+	};
+
+	let bar = Style::new().magenta().dim().apply_to(Emoji("│", "|"));
+	let mut result = format!("🚀 Network `{name}` launched successfully in the background");
+	for chain in once(&network.relay_chain).chain(&network.rollups) {
+		result.push_str(&format!(
+			"\n{bar}  ⛓️ {}",
+			match chain.id {
+				Some(id) if chain.chain.is_empty() => format!("id: {id}"),
+				Some(id) => format!("{}: {id}", chain.chain),
+				None => chain.chain.clone(),
+			}
+		));
+		for node in &chain.nodes {
+			result.push_str(&format!("\n{bar}       {}: {}", node.name, node.endpoint));
+		}
+	}
+	progress.stop(result);
+	cli.outro(format!(
+		"Use `pop network endpoints {name}` to output its endpoints, `pop logs {name}` to view the logs of its nodes and `pop network stop {name}` to terminate it."
+	))?;
+	Ok(())
+}
+
+// Returns the last lines of a log file.
+fn log_tail(path: &Path, lines: usize) -> String {
+	let contents = std::fs::read_to_string(path).unwrap_or_default();
+	let tail: Vec<_> = contents.lines().rev().take(lines).collect();
+	tail.into_iter().rev().collect::<Vec<_>>().join("\n")
+}
+
 async fn source_binaries(
 	zombienet: &mut Zombienet,
 	cache: &Path,
@@ -634,10 +890,16 @@ async fn source_binaries(
 	Ok(false)
 }
 
-async fn run_custom_command(spinner: &ProgressBar, command: &str) -> Result<(), anyhow::Error> {
+async fn run_custom_command(
+	spinner: &ProgressBar,
+	command: &str,
+	ready: bool,
+) -> Result<(), anyhow::Error> {
 	spinner.set_message(format!("Spinning up network & running command: {}", command));
-	#[cfg(not(test))]
-	sleep(Duration::from_secs(15)).await;
+	// Allow the network time to spin up, unless already known to be ready.
+	if !ready && !cfg!(test) {
+		sleep(Duration::from_secs(15)).await;
+	}
 
 	// Split the command into the base command and arguments
 	let mut parts = command.split_whitespace();
@@ -661,6 +923,15 @@ impl Status for ProgressReporter {
 	}
 }
 
+/// Reports any observed status updates as the message of a progress bar.
+struct MessageReporter<'a>(&'a ProgressBar);
+
+impl Status for MessageReporter<'_> {
+	fn update(&self, status: &str) {
+		self.0.set_message(status)
+	}
+}
+
 /// Reports any observed status updates as indented messages.
 #[derive(Copy, Clone)]
 struct VerboseReporter;
@@ -685,6 +956,17 @@ impl Status for VerboseReporter {
 mod tests {
 	use super::*;
 
+	#[test]
+	fn log_tail_works() -> anyhow::Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let path = temp_dir.path().join("paseo.log");
+		assert_eq!(log_tail(&path, 2), "");
+		std::fs::write(&path, "one\ntwo\nthree\n")?;
+		assert_eq!(log_tail(&path, 2), "two\nthree");
+		assert_eq!(log_tail(&path, 5), "one\ntwo\nthree");
+		Ok(())
+	}
+
 	#[tokio::test]
 	async fn test_run_custom_command() -> Result<(), anyhow::Error> {
 		let spinner = ProgressBar::new(1);
@@ -693,8 +975,17 @@ mod tests {
 		let command = "echo 2 + 2";
 
 		// Call the run_custom_command function
-		run_custom_command(&spinner, command).await?;
+		run_custom_command(&spinner, command, false).await?;
 
 		Ok(())
 	}
+
+	#[test]
+	fn parse_hrmp_topology_works() {
+		assert_eq!(parse_hrmp_topology("star:1000"), Ok(HrmpTopology::Star { hub: 1000 }));
+		assert_eq!(
+			parse_hrmp_topology("ring"),
+			Err("Invalid HRMP topology: `ring` is not a valid topology".to_string())
+		);
+	}
 }
@@ -8,8 +8,7 @@ use crate::{
 	cli::traits::*,
 	common::{
 		chain::{configure, Chain},
-		urls,
-		wallet::submit_extrinsic,
+		signer, urls, wallet,
 	},
 	deployment_api::{DeployRequest, DeployResponse, DeploymentApi},
 	style::{format_step_prefix, format_url, style},
@@ -19,7 +18,7 @@ use clap::Args;
 use cliclack::spinner;
 use pop_chains::{
 	construct_proxy_extrinsic, find_dispatchable_by_name, Action, ChainTemplate,
-	DeploymentProvider, Payload, Reserved, SupportedChains,
+	DeploymentProvider, ExtrinsicEvents, Payload, Reserved, SubstrateConfig, SupportedChains,
 };
 use pop_common::{parse_account, templates::Template, Profile};
 use std::{
@@ -64,6 +63,10 @@ pub struct UpCommand {
 	/// account.
 	#[arg(long = "proxy")]
 	pub(crate) proxied_address: Option<String>,
+	/// Secret key URI, or a reference to an encrypted key (e.g. "keyring:alice"), of the account
+	/// signing the registration extrinsics. A browser extension wallet is used if not specified.
+	#[arg(long, value_name = "SURI")]
+	pub(crate) signer: Option<String>,
 	/// Build profile [default: release].
 	#[clap(long, value_enum)]
 	pub(crate) profile: Option<Profile>,
@@ -183,7 +186,7 @@ impl UpCommand {
 		let genesis_artifacts = self
 			.resolve_genesis_files(deployment_config, id, show_deployment_steps, cli)
 			.await?;
-		Ok(Registration { id, genesis_artifacts, chain, proxy })
+		Ok(Registration { id, genesis_artifacts, chain, proxy, signer: self.signer.clone() })
 	}
 
 	// Retrieves the proxied address, prompting the user if none is specified.
@@ -225,7 +228,7 @@ impl UpCommand {
 			Some(id) => Ok(id),
 			None => {
 				cli.info(format!("{}You will need to sign a transaction to reserve an ID on {} using the `Registrar::reserve` function.", format_step_prefix(2,5, show_deployment_steps), chain.url))?;
-				reserve(chain, proxy, cli).await
+				reserve(chain, proxy, self.signer.as_deref(), cli).await
 			},
 		}
 	}
@@ -317,13 +320,14 @@ struct Registration {
 	genesis_artifacts: GenesisArtifacts,
 	chain: Chain,
 	proxy: Proxy,
+	signer: Option<String>,
 }
 impl Registration {
 	// Registers by submitting an extrinsic.
 	async fn register(&self, show_deployment_steps: bool, cli: &mut impl Cli) -> Result<()> {
 		cli.info(format!("{}You will need to sign a transaction to register on {}, using the `Registrar::register` function.",format_step_prefix(4,5, show_deployment_steps), self.chain.url))?;
 		let call_data = self.prepare_register_call_data(cli)?;
-		submit_extrinsic(&self.chain.client, &self.chain.url, call_data, cli)
+		submit_extrinsic(&self.chain, call_data, self.signer.as_deref(), cli)
 			.await
 			.map_err(|e| anyhow::anyhow!("Registration failed: {}", e))?;
 		Ok(())
@@ -354,9 +358,14 @@ impl Registration {
 }
 
 // Reserves an ID by submitting an extrinsic.
-async fn reserve(chain: &Chain, proxy: &Proxy, cli: &mut impl Cli) -> Result<u32> {
+async fn reserve(
+	chain: &Chain,
+	proxy: &Proxy,
+	signer: Option<&str>,
+	cli: &mut impl Cli,
+) -> Result<u32> {
 	let call_data = prepare_reserve_call_data(chain, proxy, cli)?;
-	let events = submit_extrinsic(&chain.client, &chain.url, call_data, cli)
+	let events = submit_extrinsic(chain, call_data, signer, cli)
 		.await
 		.map_err(|e| anyhow::anyhow!("ID reservation failed: {}", e))?;
 	let id = events
@@ -367,6 +376,20 @@ async fn reserve(chain: &Chain, proxy: &Proxy, cli: &mut impl Cli) -> Result<u32
 	Ok(id)
 }
 
+// Submits an extrinsic, signed with the given signer or otherwise using the wallet.
+async fn submit_extrinsic(
+	chain: &Chain,
+	call_data: Vec<u8>,
+	signer: Option<&str>,
+	cli: &mut impl Cli,
+) -> Result<ExtrinsicEvents<SubstrateConfig>> {
+	match signer {
+		Some(suri) =>
+			signer::submit_extrinsic(&chain.client, &chain.url, call_data, suri, cli).await,
+		None => wallet::submit_extrinsic(&chain.client, &chain.url, call_data, cli).await,
+	}
+}
+
 // Prepares and returns the encoded call data for reserving an ID.
 fn prepare_reserve_call_data(chain: &Chain, proxy: &Proxy, cli: &mut impl Cli) -> Result<Vec<u8>> {
 	let dispatchable = find_dispatchable_by_name(
@@ -807,6 +830,7 @@ mod tests {
 			},
 			chain,
 			proxy: None,
+			signer: None,
 		};
 
 		// Encoded call data for a register extrinsic with the above values.
@@ -0,0 +1,117 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use crate::{
+	cli::traits::*,
+	common::{builds::get_project_path, urls},
+};
+use anyhow::{anyhow, Result};
+use clap::Args;
+use cliclack::spinner;
+use pop_contracts::{
+	recorded_image, verify_contract, ContainerEngine, VerifiableBuilder, Verification,
+};
+use std::path::PathBuf;
+use url::Url;
+
+/// Verify that a deployed smart contract was built from the source of a project.
+#[derive(Args, Clone)]
+pub(crate) struct VerifyContractCommand {
+	/// Path to the contract project.
+	#[arg(long)]
+	path: Option<PathBuf>,
+	/// Directory path without flag for your project [default: current directory]
+	#[arg(value_name = "PATH", index = 1, conflicts_with = "path")]
+	path_pos: Option<PathBuf>,
+	/// The address of the deployed contract.
+	#[arg(short, long)]
+	address: String,
+	/// Websocket endpoint of a node.
+	#[arg(short, long, value_parser, default_value = urls::LOCAL)]
+	url: Url,
+	/// The image used to build the deployed contract, pinned by its digest. Defaults to the image
+	/// recorded within the metadata of the built contract.
+	#[arg(long)]
+	image: Option<String>,
+}
+
+impl VerifyContractCommand {
+	/// Executes the command.
+	pub(crate) async fn execute(&self, cli: &mut impl Cli) -> Result<()> {
+		cli.intro("Verify a smart contract")?;
+		let project_path = get_project_path(self.path.clone(), self.path_pos.clone())
+			.unwrap_or_else(|| PathBuf::from("./"));
+
+		// Rebuild the contract within the image used to build the deployed contract.
+		let image = match self.image.clone() {
+			Some(image) => image,
+			None => recorded_image(&project_path).ok().flatten().ok_or_else(|| {
+				anyhow!("No image is recorded within the metadata of the contract. Provide the image used to build the deployed contract using `--image`.")
+			})?,
+		};
+		let engine = ContainerEngine::detect().map_err(|_| anyhow!("No container engine detected. A supported containerization solution (Docker or Podman) is required."))?;
+		let spinner = spinner();
+		spinner.start("Building the contract within a container. This may take a while...");
+		let builder = match VerifiableBuilder::with_image(engine, Some(&project_path), &image)
+			.and_then(|builder| builder.build().map(|_| builder))
+		{
+			Ok(builder) => builder,
+			Err(e) => {
+				spinner.error(format!("An error occurred building your contract: {e}"));
+				return Err(e.into());
+			},
+		};
+		spinner.stop(format!("Contract built using the image {}", builder.image()));
+
+		let verification = verify_contract(&project_path, &self.address, &self.url).await?;
+		let message = display_verification(&self.address, &verification);
+		if !verification.is_verified() {
+			cli.error(message)?;
+			return Err(anyhow!("The deployed contract could not be verified."));
+		}
+		cli.success(message)?;
+		cli.outro("Verification completed successfully!")?;
+		Ok(())
+	}
+}
+
+// Describes the outcome of verifying a deployed contract.
+fn display_verification(address: &str, verification: &Verification) -> String {
+	let image = verification.image.as_deref().unwrap_or("unknown");
+	if verification.is_verified() {
+		format!(
+			"The code of {address} matches the code built from source.\n  code hash: {:?}\n  image: {image}",
+			verification.built
+		)
+	} else {
+		format!(
+			"The code of {address} does not match the code built from source.\n  deployed code hash: {:?}\n  built code hash: {:?}\n  image: {image}",
+			verification.deployed, verification.built
+		)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use subxt::utils::H256;
+
+	#[test]
+	fn display_verification_works() {
+		let hash = H256::repeat_byte(1);
+		let verification =
+			Verification { deployed: hash, built: hash, image: Some("image@sha256:1234".into()) };
+		assert_eq!(
+			display_verification("ADDRESS", &verification),
+			format!(
+				"The code of ADDRESS matches the code built from source.\n  code hash: {hash:?}\n  image: image@sha256:1234"
+			)
+		);
+		let built = H256::repeat_byte(2);
+		assert_eq!(
+			display_verification("ADDRESS", &Verification { built, image: None, ..verification }),
+			format!(
+				"The code of ADDRESS does not match the code built from source.\n  deployed code hash: {hash:?}\n  built code hash: {built:?}\n  image: unknown"
+			)
+		);
+	}
+}
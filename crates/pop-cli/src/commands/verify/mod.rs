@@ -0,0 +1,29 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use clap::{Args, Subcommand};
+use std::fmt::{Display, Formatter, Result};
+
+pub(crate) mod contract;
+
+/// Arguments for verifying a deployment.
+#[derive(Args)]
+pub(crate) struct VerifyArgs {
+	#[command(subcommand)]
+	pub command: Command,
+}
+
+/// Verify that a deployment was built from source.
+#[derive(Subcommand)]
+pub(crate) enum Command {
+	/// Verify that a deployed smart contract was built from the source of a project.
+	#[clap(alias = "c")]
+	Contract(contract::VerifyContractCommand),
+}
+
+impl Display for Command {
+	fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+		match self {
+			Command::Contract(_) => write!(f, "contract"),
+		}
+	}
+}
@@ -15,7 +15,7 @@ use tempfile::NamedTempFile;
 #[cfg(feature = "polkavm-contracts")]
 use {
 	crate::style::style,
-	pop_common::{DefaultConfig, Keypair},
+	pop_common::{DefaultConfig, Signer},
 	pop_contracts::{AccountMapper, DefaultEnvironment, ExtrinsicOpts},
 };
 
@@ -149,7 +149,7 @@ fn ensure_double_quoted(s: &str) -> String {
 
 #[cfg(feature = "polkavm-contracts")]
 pub(crate) async fn map_account(
-	extrinsic_opts: &ExtrinsicOpts<DefaultConfig, DefaultEnvironment, Keypair>,
+	extrinsic_opts: &ExtrinsicOpts<DefaultConfig, DefaultEnvironment, Signer>,
 	cli: &mut impl Cli,
 ) -> anyhow::Result<()> {
 	let mapper = AccountMapper::new(extrinsic_opts).await?;
@@ -16,6 +16,9 @@ pub mod prompt;
 /// Contains runtime utilities.
 #[cfg(feature = "chain")]
 pub mod runtime;
+/// Contains utilities for resolving signers.
+#[cfg(any(feature = "chain", feature = "polkavm-contracts", feature = "wasm-contracts"))]
+pub mod signer;
 /// Contains try-runtime utilities.
 #[cfg(feature = "chain")]
 pub mod try_runtime;
@@ -0,0 +1,98 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use crate::cli::traits::*;
+use anyhow::{anyhow, Result};
+use pop_common::{is_encrypted, signer::SIGNER_PASSWORD_ENV};
+#[cfg(feature = "chain")]
+use {
+	pop_chains::{
+		create_signed_extrinsic, parse_and_format_events, CallData, ExtrinsicEvents, OnlineClient,
+		SubstrateConfig,
+	},
+	pop_common::create_signer,
+	url::Url,
+};
+
+/// Unlocks an encrypted signer, such as a key stored in the keyring or a JSON keystore, by
+/// prompting for its password. Secret URIs are left untouched. The signer remains unlocked for the
+/// remainder of the command.
+///
+/// The password is not prompted if provided by the `POP_SIGNER_PASSWORD` environment variable.
+///
+/// # Arguments
+/// * `suri` - Secret URI string, or a reference to an encrypted key.
+/// * `cli` - The CLI instance.
+pub(crate) fn unlock_signer(suri: &str, cli: &mut impl Cli) -> Result<()> {
+	if !is_encrypted(suri) || std::env::var(SIGNER_PASSWORD_ENV).is_ok() {
+		return Ok(());
+	}
+	let password = cli.password(format!("Enter the password to unlock {suri}:")).interact()?;
+	pop_common::unlock_signer(suri, &password)
+		.map_err(|e| anyhow!("Failed to unlock {suri}: {e}"))?;
+	Ok(())
+}
+
+/// Signs and submits an extrinsic with the given signer, returning its events once finalized.
+///
+/// # Arguments
+/// * `client` - The client used to interact with the chain.
+/// * `url` - Endpoint of the node.
+/// * `call_data` - The call data of the extrinsic.
+/// * `suri` - Secret URI string, or a reference to an encrypted key, of the signer.
+/// * `cli` - The CLI instance.
+#[cfg(feature = "chain")]
+pub(crate) async fn submit_extrinsic(
+	client: &OnlineClient<SubstrateConfig>,
+	url: &Url,
+	call_data: Vec<u8>,
+	suri: &str,
+	cli: &mut impl Cli,
+) -> Result<ExtrinsicEvents<SubstrateConfig>> {
+	unlock_signer(suri, cli)?;
+	let signer = create_signer(suri)?;
+	let spinner = cliclack::spinner();
+	spinner.start("Signing and submitting the extrinsic and then waiting for finalization, please be patient...");
+	let result = create_signed_extrinsic(client, url, &CallData::new(call_data), &signer)
+		.await?
+		.submit_and_watch()
+		.await?
+		.wait_for_finalized_success()
+		.await?;
+	let events = parse_and_format_events(client, url, &result).await?;
+	spinner.stop(format!(
+		"Extrinsic submitted with hash: {:?}\n{}",
+		result.extrinsic_hash(),
+		events
+	));
+	Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::cli::MockCli;
+
+	#[test]
+	fn unlock_signer_ignores_secret_uris() -> Result<()> {
+		let mut cli = MockCli::new();
+		unlock_signer("//Alice", &mut cli)?;
+		unlock_signer("ed25519://Alice", &mut cli)?;
+		cli.verify()
+	}
+
+	#[test]
+	fn unlock_signer_fails_with_invalid_password() -> Result<()> {
+		if std::env::var(SIGNER_PASSWORD_ENV).is_ok() {
+			return Ok(());
+		}
+		let temp_dir = tempfile::tempdir()?;
+		let path = temp_dir.path().join("alice.json");
+		std::fs::write(&path, "{}")?;
+		let suri = format!("json:{}", path.display());
+		let mut cli = MockCli::new()
+			.expect_password(format!("Enter the password to unlock {suri}:"), "password".into());
+		assert!(unlock_signer(&suri, &mut cli)
+			.is_err_and(|e| e.to_string().starts_with(&format!("Failed to unlock {suri}"))));
+		cli.verify()
+	}
+}
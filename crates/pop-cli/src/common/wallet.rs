@@ -11,9 +11,10 @@ use cliclack::{log, spinner};
 use {
 	anyhow::{anyhow, Result},
 	pop_chains::{
-		parse_and_format_events, submit_signed_extrinsic, ExtrinsicEvents, OnlineClient,
-		SubstrateConfig,
+		metadata_hash, parse_and_format_events, submit_signed_extrinsic, ExtrinsicEvents,
+		OnlineClient, SubstrateConfig,
 	},
+	sp_core::bytes::to_hex,
 	url::Url,
 };
 
@@ -24,13 +25,18 @@ pub const USE_WALLET_PROMPT: &str = "Do you want to use your browser wallet to s
 ///
 /// # Arguments
 /// * `call_data` - The call data to be signed.
-/// * `url` - Chain rpc.
+/// * `rpc` - Chain rpc.
+/// * `metadata_hash` - The metadata hash, if the chain includes the `CheckMetadataHash` extension.
 /// # Returns
 /// * The signed payload and the associated contract address, if provided by the wallet.
-pub async fn request_signature(call_data: Vec<u8>, rpc: String) -> anyhow::Result<SubmitRequest> {
+pub async fn request_signature(
+	call_data: Vec<u8>,
+	rpc: String,
+	metadata_hash: Option<String>,
+) -> anyhow::Result<SubmitRequest> {
 	let ui = FrontendFromString::new(include_str!("../assets/index.html").to_string());
 
-	let transaction_data = TransactionData::new(rpc, call_data);
+	let transaction_data = TransactionData::new(rpc, call_data, metadata_hash);
 	// Starts server with port 9090.
 	let mut wallet = WalletIntegrationManager::new(ui, transaction_data, Some(9090));
 	let url = format!("http://{}", &wallet.server_url);
@@ -87,7 +93,10 @@ pub(crate) async fn submit_extrinsic(
 	call_data: Vec<u8>,
 	cli: &mut impl Cli,
 ) -> Result<ExtrinsicEvents<SubstrateConfig>> {
-	let maybe_payload = request_signature(call_data, url.to_string()).await?.signed_payload;
+	let metadata_hash = metadata_hash(client, url).await?.map(|hash| to_hex(&hash, false));
+	let maybe_payload = request_signature(call_data, url.to_string(), metadata_hash)
+		.await?
+		.signed_payload;
 	let payload = maybe_payload.ok_or_else(|| anyhow!("No signed payload received."))?;
 	cli.success("Signed payload received.")?;
 	let spinner = cliclack::spinner();
@@ -30,6 +30,12 @@ async fn main() -> Result<()> {
 	#[cfg(feature = "telemetry")]
 	let maybe_tel = init().unwrap_or(None);
 
+	// Register any user-defined rollups, which may be referenced when parsing arguments.
+	#[cfg(feature = "chain")]
+	if let Err(e) = register_rollups() {
+		cliclack::log::warning(format!("⚠️ Could not register user-defined rollups: {e}"))?;
+	}
+
 	let cli = Cli::parse();
 	#[cfg(feature = "telemetry")]
 	let event = cli.command.to_string();
@@ -67,6 +73,19 @@ fn cache() -> Result<PathBuf> {
 	Ok(cache_path)
 }
 
+/// Registers the rollups defined by the user, within the Pop config directory and the current
+/// directory, with the latter taking precedence.
+#[cfg(feature = "chain")]
+fn register_rollups() -> Result<()> {
+	use pop_chains::registry::{register, ROLLUPS_FILE};
+	let mut paths = Vec::new();
+	if let Some(config_dir) = dirs::config_dir() {
+		paths.push(config_dir.join("pop").join(ROLLUPS_FILE));
+	}
+	paths.push(PathBuf::from(ROLLUPS_FILE));
+	Ok(register(&paths)?)
+}
+
 /// Initializes telemetry.
 #[cfg(feature = "telemetry")]
 fn init() -> Result<Option<Telemetry>> {
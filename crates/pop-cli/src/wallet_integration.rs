@@ -28,6 +28,11 @@ pub trait Frontend {
 pub struct TransactionData {
 	chain_rpc: String,
 	call_data: Vec<u8>,
+	/// The metadata hash to be included by the `CheckMetadataHash` signed extension, allowing
+	/// hardware wallets to decode the transaction.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	#[cfg_attr(test, serde(default))]
+	metadata_hash: Option<String>,
 }
 
 impl TransactionData {
@@ -35,10 +40,11 @@ impl TransactionData {
 	/// # Arguments
 	/// * `chain_rpc`: The RPC of the chain.
 	/// * `call_data`: the call data.
+	/// * `metadata_hash`: the metadata hash, if the chain includes `CheckMetadataHash`.
 	/// # Returns
 	/// The transaction payload to be sent to frontend for signing.
-	pub fn new(chain_rpc: String, call_data: Vec<u8>) -> Self {
-		Self { chain_rpc, call_data }
+	pub fn new(chain_rpc: String, call_data: Vec<u8>, metadata_hash: Option<String>) -> Self {
+		Self { chain_rpc, call_data, metadata_hash }
 	}
 }
 
@@ -312,7 +318,11 @@ mod tests {
 	}
 
 	fn default_payload() -> TransactionData {
-		TransactionData { chain_rpc: "localhost:9944".to_string(), call_data: vec![1, 2, 3] }
+		TransactionData {
+			chain_rpc: "localhost:9944".to_string(),
+			call_data: vec![1, 2, 3],
+			metadata_hash: None,
+		}
 	}
 
 	#[tokio::test]
@@ -363,12 +373,28 @@ mod tests {
 	fn new_transaction_data_works() {
 		let chain_rpc = "localhost:9944".to_string();
 		let call_data = vec![1, 2, 3];
-		let transaction_data = TransactionData::new(chain_rpc.clone(), call_data.clone());
+		let transaction_data = TransactionData::new(chain_rpc.clone(), call_data.clone(), None);
 
 		assert_eq!(transaction_data.chain_rpc, chain_rpc);
 		assert_eq!(transaction_data.call_data, call_data);
 	}
 
+	#[test]
+	fn transaction_data_serializes_metadata_hash() -> anyhow::Result<()> {
+		let metadata_hash = format!("0x{}", "ab".repeat(32));
+		let transaction_data = TransactionData::new(
+			"localhost:9944".to_string(),
+			vec![1, 2, 3],
+			Some(metadata_hash.clone()),
+		);
+		let json = serde_json::to_value(&transaction_data)?;
+		assert_eq!(json["metadata_hash"], metadata_hash);
+		// The hash is omitted for chains without the `CheckMetadataHash` extension.
+		let json = serde_json::to_value(default_payload())?;
+		assert!(json.get("metadata_hash").is_none());
+		Ok(())
+	}
+
 	#[tokio::test]
 	async fn take_error_works() {
 		let frontend = FrontendFromString::new(TEST_HTML.to_string());
@@ -387,8 +413,11 @@ mod tests {
 	async fn payload_handler_works() {
 		// offset port per test to avoid conflicts
 		let frontend = FrontendFromString::new(TEST_HTML.to_string());
-		let expected_payload =
-			TransactionData { chain_rpc: "localhost:9944".to_string(), call_data: vec![1, 2, 3] };
+		let expected_payload = TransactionData {
+			chain_rpc: "localhost:9944".to_string(),
+			call_data: vec![1, 2, 3],
+			metadata_hash: None,
+		};
 		let mut wim = WalletIntegrationManager::new(frontend, expected_payload.clone(), None);
 		wait().await;
 
@@ -557,6 +586,7 @@ mod tests {
 		let expected_payload = TransactionData {
 			chain_rpc: "localhost:9944".to_string(),
 			call_data: call_data_5mb.clone(),
+			metadata_hash: None,
 		};
 		let mut wim = WalletIntegrationManager::new(frontend, expected_payload.clone(), None);
 		wait().await;
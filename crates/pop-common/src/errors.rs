@@ -24,6 +24,9 @@ pub enum Error {
 	/// An error occurred while attempting to create a keypair from the provided URI.
 	#[error("Failed to create keypair from URI: {0}")]
 	KeyPairCreation(String),
+	/// An error occurred while accessing the keyring.
+	#[error("Keyring error: {0}")]
+	Keyring(String),
 	/// A manifest error occurred.
 	#[error("Manifest error: {0}")]
 	ManifestError(#[from] cargo_toml::Error),
@@ -0,0 +1,232 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use crate::{
+	errors::Error,
+	signer::{KeyType, Signer},
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use crypto_secretbox::{
+	aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng},
+	Key, Nonce, XSalsa20Poly1305,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+	collections::BTreeMap,
+	fs,
+	path::{Path, PathBuf},
+};
+
+const KEYRING_FILE: &str = "keyring.json";
+const SALT_LEN: usize = 32;
+const NONCE_LEN: usize = 24;
+// The scrypt parameters used to derive the encryption key, as used by polkadot-js.
+const SCRYPT_LOG_N: u8 = 15;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+
+/// A key stored in the keyring.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct KeyringEntry {
+	/// The name of the key.
+	pub name: String,
+	/// The cryptographic scheme of the key.
+	pub key_type: KeyType,
+	/// The address of the account.
+	pub address: String,
+	// The secret URI of the key, encrypted with its password.
+	encrypted: String,
+}
+
+/// A local keyring, storing secret URIs encrypted with a password.
+pub struct Keyring {
+	path: PathBuf,
+}
+
+impl Keyring {
+	/// Creates a keyring backed by the given file.
+	///
+	/// # Arguments
+	/// * `path` - The path of the keyring file.
+	pub fn new(path: PathBuf) -> Self {
+		Self { path }
+	}
+
+	/// The path of the keyring within the Pop config directory.
+	pub fn default_path() -> Result<PathBuf, Error> {
+		Ok(dirs::config_dir()
+			.ok_or_else(|| Error::Config("the config directory could not be determined".into()))?
+			.join("pop")
+			.join(KEYRING_FILE))
+	}
+
+	/// The path of the keyring file.
+	pub fn path(&self) -> &Path {
+		&self.path
+	}
+
+	/// The keys stored in the keyring, sorted by name.
+	pub fn entries(&self) -> Result<Vec<KeyringEntry>, Error> {
+		Ok(self.load()?.into_values().collect())
+	}
+
+	/// Encrypts a secret URI with a password and stores it in the keyring.
+	///
+	/// # Arguments
+	/// * `name` - The name of the key.
+	/// * `suri` - The secret URI of the key.
+	/// * `key_type` - The cryptographic scheme of the key.
+	/// * `password` - The password used to encrypt the key.
+	pub fn add(
+		&self,
+		name: &str,
+		suri: &str,
+		key_type: KeyType,
+		password: &str,
+	) -> Result<KeyringEntry, Error> {
+		let mut entries = self.load()?;
+		if entries.contains_key(name) {
+			return Err(Error::Keyring(format!("a key named `{name}` already exists")));
+		}
+		let signer = Signer::from_uri(suri, key_type)?;
+		let entry = KeyringEntry {
+			name: name.to_string(),
+			key_type,
+			address: signer.account_id().to_string(),
+			encrypted: encrypt(suri.as_bytes(), password)?,
+		};
+		entries.insert(name.to_string(), entry.clone());
+		self.save(&entries)?;
+		Ok(entry)
+	}
+
+	/// Removes a key from the keyring.
+	///
+	/// # Arguments
+	/// * `name` - The name of the key.
+	pub fn remove(&self, name: &str) -> Result<KeyringEntry, Error> {
+		let mut entries = self.load()?;
+		let entry = entries
+			.remove(name)
+			.ok_or_else(|| Error::Keyring(format!("no key named `{name}` exists")))?;
+		self.save(&entries)?;
+		Ok(entry)
+	}
+
+	/// Decrypts a key stored in the keyring.
+	///
+	/// # Arguments
+	/// * `name` - The name of the key.
+	/// * `password` - The password used to encrypt the key.
+	pub fn unlock(&self, name: &str, password: &str) -> Result<Signer, Error> {
+		let entries = self.load()?;
+		let entry = entries
+			.get(name)
+			.ok_or_else(|| Error::Keyring(format!("no key named `{name}` exists")))?;
+		let suri = String::from_utf8(decrypt(&entry.encrypted, password)?)
+			.map_err(|_| Error::Keyring("the key is corrupted".into()))?;
+		Signer::from_uri(&suri, entry.key_type)
+	}
+
+	// Loads the keys stored in the keyring, if any.
+	fn load(&self) -> Result<BTreeMap<String, KeyringEntry>, Error> {
+		if !self.path.exists() {
+			return Ok(BTreeMap::new());
+		}
+		serde_json::from_str(&fs::read_to_string(&self.path)?)
+			.map_err(|e| Error::Keyring(e.to_string()))
+	}
+
+	// Saves the keys to the keyring, readable by the current user only.
+	fn save(&self, entries: &BTreeMap<String, KeyringEntry>) -> Result<(), Error> {
+		if let Some(parent) = self.path.parent() {
+			fs::create_dir_all(parent)?;
+		}
+		let contents =
+			serde_json::to_string_pretty(entries).map_err(|e| Error::Keyring(e.to_string()))?;
+		fs::write(&self.path, contents)?;
+		#[cfg(unix)]
+		{
+			use std::os::unix::fs::PermissionsExt;
+			fs::set_permissions(&self.path, fs::Permissions::from_mode(0o600))?;
+		}
+		Ok(())
+	}
+}
+
+// Derives the encryption key from a password, using the scrypt parameters of polkadot-js.
+pub(crate) fn derive_key(password: &str, salt: &[u8]) -> Result<Key, Error> {
+	let params = scrypt::Params::new(SCRYPT_LOG_N, SCRYPT_R, SCRYPT_P, 32)
+		.map_err(|e| Error::Keyring(e.to_string()))?;
+	let mut key = Key::default();
+	scrypt::scrypt(password.as_bytes(), salt, &params, &mut key)
+		.map_err(|e| Error::Keyring(e.to_string()))?;
+	Ok(key)
+}
+
+// Encrypts a secret with a password, encoding the salt, nonce and ciphertext as base64.
+fn encrypt(secret: &[u8], password: &str) -> Result<String, Error> {
+	let mut salt = [0u8; SALT_LEN];
+	OsRng.fill_bytes(&mut salt);
+	let cipher = XSalsa20Poly1305::new(&derive_key(password, &salt)?);
+	let nonce = XSalsa20Poly1305::generate_nonce(&mut OsRng);
+	let ciphertext = cipher
+		.encrypt(&nonce, secret)
+		.map_err(|_| Error::Keyring("failed to encrypt the key".into()))?;
+	Ok(STANDARD.encode([salt.as_slice(), nonce.as_slice(), &ciphertext].concat()))
+}
+
+// Decrypts a secret encrypted with a password.
+fn decrypt(encrypted: &str, password: &str) -> Result<Vec<u8>, Error> {
+	let encrypted = STANDARD.decode(encrypted).map_err(|e| Error::Keyring(e.to_string()))?;
+	if encrypted.len() < SALT_LEN + NONCE_LEN {
+		return Err(Error::Keyring("the key is corrupted".into()));
+	}
+	let (salt, encrypted) = encrypted.split_at(SALT_LEN);
+	let (nonce, ciphertext) = encrypted.split_at(NONCE_LEN);
+	let cipher = XSalsa20Poly1305::new(&derive_key(password, salt)?);
+	cipher
+		.decrypt(Nonce::from_slice(nonce), ciphertext)
+		.map_err(|_| Error::Keyring("invalid password".into()))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::Result;
+
+	#[test]
+	fn keyring_works() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let keyring = Keyring::new(temp_dir.path().join(KEYRING_FILE));
+		assert!(keyring.entries()?.is_empty());
+
+		let entry = keyring.add("alice", "//Alice", KeyType::Sr25519, "password")?;
+		assert_eq!(entry.address, "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY");
+		assert!(matches!(
+			keyring.add("alice", "//Alice", KeyType::Sr25519, "password"),
+			Err(Error::Keyring(..))
+		));
+		// The secret URI is not stored in plain text.
+		assert!(!fs::read_to_string(keyring.path())?.contains("//Alice"));
+		assert_eq!(keyring.entries()?, vec![entry.clone()]);
+
+		let signer = keyring.unlock("alice", "password")?;
+		assert_eq!(signer.account_id().to_string(), entry.address);
+		assert!(matches!(keyring.unlock("alice", "wrong"), Err(Error::Keyring(..))));
+		assert!(matches!(keyring.unlock("bob", "password"), Err(Error::Keyring(..))));
+
+		assert_eq!(keyring.remove("alice")?, entry);
+		assert!(keyring.entries()?.is_empty());
+		assert!(matches!(keyring.remove("alice"), Err(Error::Keyring(..))));
+		Ok(())
+	}
+
+	#[test]
+	fn add_fails_with_invalid_uri() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let keyring = Keyring::new(temp_dir.path().join(KEYRING_FILE));
+		assert!(keyring.add("alice", "11111", KeyType::Ed25519, "password").is_err());
+		assert!(keyring.entries()?.is_empty());
+		Ok(())
+	}
+}
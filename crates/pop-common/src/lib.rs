@@ -10,9 +10,10 @@ pub use helpers::{
 	get_project_name_from_path, get_relative_or_absolute_path, prefix_with_current_dir_if_needed,
 	replace_in_file,
 };
+pub use keyring::{Keyring, KeyringEntry};
 pub use manifest::{add_crate_to_workspace, find_workspace_toml};
 pub use metadata::format_type;
-pub use signer::create_signer;
+pub use signer::{create_signer, is_encrypted, unlock_signer, KeyType, Signer, SignerScope};
 pub use sourcing::set_executable_permission;
 use std::{cmp::Ordering, net::TcpListener, ops::Deref};
 pub use subxt::{Config, PolkadotConfig as DefaultConfig};
@@ -32,13 +33,15 @@ pub mod errors;
 pub mod git;
 /// Provides general purpose file and path helpers.
 pub mod helpers;
+/// Provides a local keyring, storing keys encrypted with a password.
+pub mod keyring;
 /// Provides functionality for resolving and managing Cargo manifests.
 pub mod manifest;
 /// Provides functionality for formatting and resolving metadata types.
 pub mod metadata;
 /// Provides parsers for determining Polkadot SDK versions.
 pub mod polkadot_sdk;
-/// Provides functionality for creating a signer from a secret URI.
+/// Provides functionality for creating a signer from a secret URI or an encrypted key.
 pub mod signer;
 /// Provides functionality for sourcing binaries from a variety of different sources.
 pub mod sourcing;
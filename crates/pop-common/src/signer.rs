@@ -1,37 +1,460 @@
 // SPDX-License-Identifier: GPL-3.0
 
-use crate::errors::Error;
-use subxt_signer::{sr25519::Keypair, SecretUri};
+use crate::{
+	errors::Error,
+	keyring::{derive_key, Keyring},
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use crypto_secretbox::{
+	aead::{Aead, KeyInit},
+	Nonce, XSalsa20Poly1305,
+};
+use serde::{Deserialize, Serialize};
+use sp_core::{crypto::Pair as _, ecdsa, ed25519, hashing::blake2_256, sr25519};
+use std::{collections::BTreeMap, fs, str::FromStr, sync::Mutex};
+use strum_macros::{AsRefStr, EnumString};
+use subxt::{
+	utils::{AccountId32, MultiSignature},
+	Config,
+};
 
-/// Create a keypair from a secret URI.
+/// The environment variable from which the password of an encrypted signer is read, when it has
+/// not been unlocked.
+pub const SIGNER_PASSWORD_ENV: &str = "POP_SIGNER_PASSWORD";
+/// The prefix of a signer stored in the local keyring, e.g. `keyring:alice`.
+pub const KEYRING_PREFIX: &str = "keyring:";
+/// The prefix of a signer stored in an encrypted JSON keystore exported from polkadot-js, e.g.
+/// `json:./alice.json`.
+pub const JSON_PREFIX: &str = "json:";
+
+// The framing of the keys within a decrypted polkadot-js keystore.
+const PKCS8_HEADER: [u8; 16] = [48, 83, 2, 1, 1, 48, 5, 6, 3, 43, 101, 112, 4, 34, 4, 32];
+const PKCS8_DIVIDER: [u8; 5] = [161, 35, 3, 33, 0];
+// The scrypt parameters accepted within a polkadot-js keystore: N, p and r.
+const JSON_SCRYPT_PARAMS: [u32; 3] = [1 << 15, 1, 8];
+// The length of the salt, scrypt parameters and nonce preceding the encrypted keys.
+const JSON_SALT_LEN: usize = 32;
+const JSON_PARAMS_LEN: usize = 44;
+const JSON_NONCE_LEN: usize = 24;
+
+// Encrypted signers unlocked within the active signer scopes, keyed by their source.
+static UNLOCKED: Mutex<Unlocked> = Mutex::new(Unlocked { scopes: 0, signers: BTreeMap::new() });
+
+struct Unlocked {
+	scopes: usize,
+	signers: BTreeMap<String, Signer>,
+}
+
+/// The cryptographic scheme of a signing key.
+#[derive(
+	AsRefStr, Clone, Copy, Debug, Default, Deserialize, EnumString, Eq, PartialEq, Serialize,
+)]
+#[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum KeyType {
+	/// Schnorr signatures over Ristretto, the default for Polkadot SDK based chains.
+	#[default]
+	Sr25519,
+	/// Edwards-curve signatures.
+	Ed25519,
+	/// ECDSA signatures over secp256k1.
+	Ecdsa,
+}
+
+/// A key used to sign extrinsics.
+#[derive(Clone)]
+pub enum Signer {
+	/// An sr25519 key.
+	Sr25519(sr25519::Pair),
+	/// An ed25519 key.
+	Ed25519(Box<ed25519::Pair>),
+	/// An ECDSA key.
+	Ecdsa(ecdsa::Pair),
+}
+
+impl Signer {
+	/// Creates a signer from a secret URI.
+	///
+	/// # Arguments
+	/// * `suri` - The secret URI, e.g. `//Alice` or a mnemonic phrase.
+	/// * `key_type` - The cryptographic scheme of the key.
+	pub fn from_uri(suri: &str, key_type: KeyType) -> Result<Self, Error> {
+		let error = |e| Error::KeyPairCreation(format!("{e:?}"));
+		Ok(match key_type {
+			KeyType::Sr25519 =>
+				Signer::Sr25519(sr25519::Pair::from_string(suri, None).map_err(error)?),
+			KeyType::Ed25519 =>
+				Signer::Ed25519(Box::new(ed25519::Pair::from_string(suri, None).map_err(error)?)),
+			KeyType::Ecdsa => Signer::Ecdsa(ecdsa::Pair::from_string(suri, None).map_err(error)?),
+		})
+	}
+
+	/// Creates a signer from an encrypted JSON keystore, as exported from polkadot-js.
+	///
+	/// # Arguments
+	/// * `json` - The contents of the keystore.
+	/// * `password` - The password used to encrypt the keystore.
+	pub fn from_json(json: &str, password: &str) -> Result<Self, Error> {
+		let error = |e: &str| Error::KeyPairCreation(format!("invalid keystore: {e}"));
+		let keystore: Keystore = serde_json::from_str(json).map_err(|e| error(&e.to_string()))?;
+		let encoding = &keystore.encoding;
+		if encoding.version != "3" ||
+			!encoding.content.iter().any(|c| c == "pkcs8") ||
+			!encoding.r#type.iter().any(|t| t == "scrypt") ||
+			!encoding.r#type.iter().any(|t| t == "xsalsa20-poly1305")
+		{
+			return Err(error("unsupported encoding"));
+		}
+		let key_type = encoding
+			.content
+			.iter()
+			.find_map(|c| KeyType::from_str(c).ok())
+			.ok_or_else(|| error("unsupported key type"))?;
+
+		// Decrypt the keys, using the scrypt parameters which precede them.
+		let encoded = STANDARD.decode(&keystore.encoded).map_err(|e| error(&e.to_string()))?;
+		if encoded.len() < JSON_PARAMS_LEN + JSON_NONCE_LEN {
+			return Err(error("the keys are missing"));
+		}
+		let (params, encrypted) = encoded.split_at(JSON_PARAMS_LEN);
+		let (salt, params) = params.split_at(JSON_SALT_LEN);
+		let params: Vec<_> = params
+			.chunks_exact(4)
+			.map(|p| u32::from_le_bytes(p.try_into().expect("chunks of four bytes; qed")))
+			.collect();
+		// Only the default parameters are accepted, as costly ones could be crafted.
+		if params != JSON_SCRYPT_PARAMS {
+			return Err(error("unsupported scrypt parameters"));
+		}
+		let (nonce, ciphertext) = encrypted.split_at(JSON_NONCE_LEN);
+		let keys = XSalsa20Poly1305::new(&derive_key(password, salt)?)
+			.decrypt(Nonce::from_slice(nonce), ciphertext)
+			.map_err(|_| Error::KeyPairCreation("invalid password".into()))?;
+
+		let (secret_len, public_len) = match key_type {
+			KeyType::Sr25519 | KeyType::Ed25519 => (64, 32),
+			KeyType::Ecdsa => (32, 33),
+		};
+		let secret_end = PKCS8_HEADER.len() + secret_len;
+		if keys.len() != secret_end + PKCS8_DIVIDER.len() + public_len ||
+			keys[..PKCS8_HEADER.len()] != PKCS8_HEADER ||
+			keys[secret_end..secret_end + PKCS8_DIVIDER.len()] != PKCS8_DIVIDER
+		{
+			return Err(error("the keys are invalid"));
+		}
+		let secret = &keys[PKCS8_HEADER.len()..secret_end];
+		let public = &keys[secret_end + PKCS8_DIVIDER.len()..];
+		let signer = match key_type {
+			// polkadot-js stores sr25519 secret keys in their ed25519-compatible form.
+			KeyType::Sr25519 => schnorrkel::SecretKey::from_ed25519_bytes(secret)
+				.map(|secret| Signer::Sr25519(secret.into()))
+				.map_err(|e| error(&e.to_string()))?,
+			// The secret key of an ed25519 pair is its seed followed by its public key.
+			KeyType::Ed25519 => ed25519::Pair::from_seed_slice(&secret[..32])
+				.map(|pair| Signer::Ed25519(Box::new(pair)))
+				.map_err(|e| error(&format!("{e:?}")))?,
+			KeyType::Ecdsa => ecdsa::Pair::from_seed_slice(secret)
+				.map(Signer::Ecdsa)
+				.map_err(|e| error(&format!("{e:?}")))?,
+		};
+		let address =
+			AccountId32::from_str(&keystore.address).map_err(|e| error(&e.to_string()))?;
+		if signer.public() != public || signer.account_id() != address {
+			return Err(error("the keys do not match the address"));
+		}
+		Ok(signer)
+	}
+
+	/// The cryptographic scheme of the key.
+	pub fn key_type(&self) -> KeyType {
+		match self {
+			Signer::Sr25519(_) => KeyType::Sr25519,
+			Signer::Ed25519(_) => KeyType::Ed25519,
+			Signer::Ecdsa(_) => KeyType::Ecdsa,
+		}
+	}
+
+	/// The account identifier of the signer.
+	pub fn account_id(&self) -> AccountId32 {
+		match self {
+			Signer::Sr25519(pair) => AccountId32(pair.public().0),
+			Signer::Ed25519(pair) => AccountId32(pair.public().0),
+			// ECDSA public keys are hashed to derive the account identifier.
+			Signer::Ecdsa(pair) => AccountId32(blake2_256(&pair.public().0)),
+		}
+	}
+
+	/// Signs a message.
+	///
+	/// # Arguments
+	/// * `message` - The message to be signed.
+	pub fn sign(&self, message: &[u8]) -> MultiSignature {
+		match self {
+			Signer::Sr25519(pair) => MultiSignature::Sr25519(pair.sign(message).0),
+			Signer::Ed25519(pair) => MultiSignature::Ed25519(pair.sign(message).0),
+			Signer::Ecdsa(pair) => MultiSignature::Ecdsa(pair.sign(message).0),
+		}
+	}
+
+	// The public key of the signer.
+	fn public(&self) -> Vec<u8> {
+		match self {
+			Signer::Sr25519(pair) => pair.public().0.to_vec(),
+			Signer::Ed25519(pair) => pair.public().0.to_vec(),
+			Signer::Ecdsa(pair) => pair.public().0.to_vec(),
+		}
+	}
+}
+
+impl<T: Config> subxt::tx::Signer<T> for Signer
+where
+	T::AccountId: From<AccountId32>,
+	T::Address: From<AccountId32>,
+	T::Signature: From<MultiSignature>,
+{
+	fn account_id(&self) -> T::AccountId {
+		Signer::account_id(self).into()
+	}
+
+	fn address(&self) -> T::Address {
+		Signer::account_id(self).into()
+	}
+
+	fn sign(&self, signer_payload: &[u8]) -> T::Signature {
+		Signer::sign(self, signer_payload).into()
+	}
+}
+
+// An encrypted JSON keystore, as exported from polkadot-js.
+#[derive(Deserialize)]
+struct Keystore {
+	encoded: String,
+	encoding: KeystoreEncoding,
+	address: String,
+}
+
+#[derive(Deserialize)]
+struct KeystoreEncoding {
+	content: Vec<String>,
+	r#type: Vec<String>,
+	version: String,
+}
+
+/// A scope within which encrypted signers, once unlocked using [`unlock_signer`], remain
+/// available to [`create_signer`]. The decrypted keys are dropped once every scope has ended.
+#[must_use = "signers are locked again once the scope is dropped"]
+pub struct SignerScope(());
+
+impl SignerScope {
+	/// Starts a scope for unlocked signers, which lasts until the returned value is dropped.
+	pub fn new() -> Self {
+		UNLOCKED.lock().unwrap_or_else(|e| e.into_inner()).scopes += 1;
+		Self(())
+	}
+}
+
+impl Default for SignerScope {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl Drop for SignerScope {
+	fn drop(&mut self) {
+		let mut unlocked = UNLOCKED.lock().unwrap_or_else(|e| e.into_inner());
+		unlocked.scopes = unlocked.scopes.saturating_sub(1);
+		if unlocked.scopes == 0 {
+			unlocked.signers.clear();
+		}
+	}
+}
+
+/// Create a signer from a secret URI or a reference to an encrypted key.
+///
+/// The following formats are supported:
+/// - a secret URI for an sr25519 key, e.g. `//Alice`.
+/// - a secret URI prefixed with its key type, e.g. `ed25519://Alice` or `ecdsa://Alice`.
+/// - a key stored in the local keyring, e.g. `keyring:alice`.
+/// - an encrypted JSON keystore exported from polkadot-js, e.g. `json:./alice.json`.
+///
+/// Encrypted keys must have been unlocked using [`unlock_signer`] within an active
+/// [`SignerScope`], otherwise the password is read from the `POP_SIGNER_PASSWORD` environment
+/// variable.
+///
+/// # Arguments
+/// `suri` - Secret URI string, or a reference to an encrypted key, used to generate the `Signer`.
+pub fn create_signer(suri: &str) -> Result<Signer, Error> {
+	if is_encrypted(suri) {
+		let unlocked =
+			UNLOCKED.lock().unwrap_or_else(|e| e.into_inner()).signers.get(suri).cloned();
+		if let Some(signer) = unlocked {
+			return Ok(signer);
+		}
+		let password = std::env::var(SIGNER_PASSWORD_ENV).map_err(|_| {
+			Error::KeyPairCreation(format!(
+				"{suri} is encrypted, provide its password using {SIGNER_PASSWORD_ENV}"
+			))
+		})?;
+		return unlock_signer(suri, &password);
+	}
+	match suri
+		.split_once(':')
+		.and_then(|(prefix, suri)| KeyType::from_str(prefix).ok().map(|key_type| (key_type, suri)))
+	{
+		Some((key_type, suri)) => Signer::from_uri(suri, key_type),
+		None => Signer::from_uri(suri, KeyType::Sr25519),
+	}
+}
+
+/// Whether the signer refers to an encrypted key, requiring a password to be unlocked.
+///
+/// # Arguments
+/// `suri` - Secret URI string, or a reference to an encrypted key.
+pub fn is_encrypted(suri: &str) -> bool {
+	suri.starts_with(KEYRING_PREFIX) || suri.starts_with(JSON_PREFIX)
+}
+
+/// Decrypts an encrypted key, keeping it unlocked for any subsequent calls to [`create_signer`]
+/// until the active [`SignerScope`] ends. The key is not kept when no scope is active.
 ///
 /// # Arguments
-/// `suri` - Secret URI string used to generate the `Keypair`.
-pub fn create_signer(suri: &str) -> Result<Keypair, Error> {
-	let uri = <SecretUri as std::str::FromStr>::from_str(suri)
-		.map_err(|e| Error::ParseSecretURI(format!("{}", e)))?;
-	let keypair = Keypair::from_uri(&uri).map_err(|e| Error::KeyPairCreation(format!("{}", e)))?;
-	Ok(keypair)
+/// * `suri` - A reference to an encrypted key, e.g. `keyring:alice` or `json:./alice.json`.
+/// * `password` - The password used to encrypt the key.
+pub fn unlock_signer(suri: &str, password: &str) -> Result<Signer, Error> {
+	let signer = if let Some(name) = suri.strip_prefix(KEYRING_PREFIX) {
+		Keyring::new(Keyring::default_path()?).unlock(name, password)?
+	} else if let Some(path) = suri.strip_prefix(JSON_PREFIX) {
+		Signer::from_json(&fs::read_to_string(path)?, password)?
+	} else {
+		return Err(Error::KeyPairCreation(format!("{suri} is not an encrypted key")));
+	};
+	let mut unlocked = UNLOCKED.lock().unwrap_or_else(|e| e.into_inner());
+	if unlocked.scopes > 0 {
+		unlocked.signers.insert(suri.to_string(), signer.clone());
+	}
+	Ok(signer)
 }
 
 #[cfg(test)]
 mod tests {
 	use super::*;
 	use anyhow::Result;
+	use crypto_secretbox::aead::{rand_core::RngCore, OsRng};
+
+	// Alice's account for each key type.
+	const ALICE_SR25519: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+	const ALICE_ED25519: &str = "5FA9nQDVg267DEd8m1ZypXLBnvN7SFxYwV7ndqSYGiN9TTpu";
+	const ALICE_ECDSA: &str = "5C7C2Z5sWbytvHpuLTvzKunnnRwQxft1jiqrLD5rhucQ5S9X";
+	// Exported from polkadot-js with the password `whoisalice`.
+	const ALICE_JSON: &str = r#"{"encoded":"DumgApKCTqoCty1OZW/8WS+sgo6RdpHhCwAkA2IoDBMAgAAAAQAAAAgAAAB6IG/q24EeVf0JqWqcBd5m2tKq5BlyY84IQ8oamLn9DZe9Ouhgunr7i36J1XxUnTI801axqL/ym1gil0U8440Qvj0lFVKwGuxq38zuifgoj0B3Yru0CI6QKEvQPU5xxj4MpyxdSxP+2PnTzYao0HDH0fulaGvlAYXfqtU89xrx2/z9z7IjSwS3oDFPXRQ9kAdDebtyCVreZ9Otw9v3","encoding":{"content":["pkcs8","sr25519"],"type":["scrypt","xsalsa20-poly1305"],"version":"3"},"address":"5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY","meta":{"genesisHash":"","name":"Alice","whenCreated":1718265838755}}"#;
+
+	// Encrypts a key into a keystore, as exported by polkadot-js.
+	fn keystore(key_type: KeyType, secret: &[u8], public: &[u8], address: &str) -> Result<String> {
+		let mut salt = [0u8; JSON_SALT_LEN];
+		OsRng.fill_bytes(&mut salt);
+		let mut nonce = [0u8; JSON_NONCE_LEN];
+		OsRng.fill_bytes(&mut nonce);
+		let keys = [&PKCS8_HEADER, secret, &PKCS8_DIVIDER, public].concat();
+		let ciphertext = XSalsa20Poly1305::new(&derive_key("password", &salt)?)
+			.encrypt(Nonce::from_slice(&nonce), keys.as_slice())
+			.map_err(|e| anyhow::anyhow!("{e}"))?;
+		let params: Vec<u8> = JSON_SCRYPT_PARAMS.iter().flat_map(|p| p.to_le_bytes()).collect();
+		let encoded = STANDARD.encode([&salt, params.as_slice(), &nonce, &ciphertext].concat());
+		Ok(serde_json::json!({
+			"encoded": encoded,
+			"encoding": {
+				"content": ["pkcs8", key_type.as_ref()],
+				"type": ["scrypt", "xsalsa20-poly1305"],
+				"version": "3"
+			},
+			"address": address,
+		})
+		.to_string())
+	}
 
 	#[test]
 	fn create_signer_works() -> Result<(), Error> {
 		let keypair = create_signer("//Alice")?;
 		assert_eq!(
-			keypair.public_key().to_account_id().to_string(),
+			keypair.account_id().to_string(),
 			"5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY" //Alice account
 		);
 		Ok(())
 	}
 
+	#[test]
+	fn create_signer_with_key_type_works() -> Result<(), Error> {
+		for (suri, key_type, address) in [
+			("sr25519://Alice", KeyType::Sr25519, ALICE_SR25519),
+			("ed25519://Alice", KeyType::Ed25519, ALICE_ED25519),
+			("ecdsa://Alice", KeyType::Ecdsa, ALICE_ECDSA),
+		] {
+			let signer = create_signer(suri)?;
+			assert_eq!(signer.key_type(), key_type);
+			assert_eq!(signer.account_id().to_string(), address);
+		}
+		Ok(())
+	}
+
 	#[test]
 	fn create_signer_fails_wrong_key() -> Result<(), Error> {
 		assert!(matches!(create_signer("11111"), Err(Error::KeyPairCreation(..))));
 		Ok(())
 	}
+
+	#[test]
+	fn create_signer_fails_when_locked() -> Result<(), Error> {
+		assert!(is_encrypted("keyring:alice"));
+		assert!(is_encrypted("json:./alice.json"));
+		assert!(!is_encrypted("//Alice"));
+		if std::env::var(SIGNER_PASSWORD_ENV).is_err() {
+			assert!(matches!(create_signer("json:./alice.json"), Err(Error::KeyPairCreation(..))));
+		}
+		Ok(())
+	}
+
+	#[test]
+	fn from_json_works() -> Result<()> {
+		let signer = Signer::from_json(ALICE_JSON, "whoisalice")?;
+		assert_eq!(signer.key_type(), KeyType::Sr25519);
+		assert_eq!(signer.account_id().to_string(), ALICE_SR25519);
+		assert!(Signer::from_json(ALICE_JSON, "wrong").is_err());
+
+		let ed25519 = ed25519::Pair::from_string("//Alice", None)?;
+		let secret = [ed25519.seed().as_slice(), &ed25519.public().0].concat();
+		let json = keystore(KeyType::Ed25519, &secret, &ed25519.public().0, ALICE_ED25519)?;
+		let signer = Signer::from_json(&json, "password")?;
+		assert_eq!(signer.key_type(), KeyType::Ed25519);
+		assert_eq!(signer.account_id().to_string(), ALICE_ED25519);
+
+		let ecdsa = ecdsa::Pair::from_string("//Alice", None)?;
+		let json = keystore(KeyType::Ecdsa, &ecdsa.seed(), &ecdsa.public().0, ALICE_ECDSA)?;
+		let signer = Signer::from_json(&json, "password")?;
+		assert_eq!(signer.key_type(), KeyType::Ecdsa);
+		assert_eq!(signer.account_id().to_string(), ALICE_ECDSA);
+
+		// The keys must match the address.
+		let json = keystore(KeyType::Ecdsa, &ecdsa.seed(), &ecdsa.public().0, ALICE_SR25519)?;
+		assert!(Signer::from_json(&json, "password").is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn unlock_signer_from_json_works() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let path = temp_dir.path().join("alice.json");
+		fs::write(&path, ALICE_JSON)?;
+		let suri = format!("{JSON_PREFIX}{}", path.display());
+		let scope = SignerScope::new();
+		assert!(unlock_signer(&suri, "wrong").is_err());
+		let signer = unlock_signer(&suri, "whoisalice")?;
+		assert_eq!(signer.account_id().to_string(), ALICE_SR25519);
+		// The signer remains unlocked until the scope ends.
+		assert_eq!(create_signer(&suri)?.account_id().to_string(), ALICE_SR25519);
+		drop(scope);
+		if std::env::var(SIGNER_PASSWORD_ENV).is_err() && UNLOCKED.lock().unwrap().scopes == 0 {
+			assert!(create_signer(&suri).is_err());
+		}
+		Ok(())
+	}
 }
@@ -1,11 +1,28 @@
 // SPDX-License-Identifier: GPL-3.0
 
-use crate::{errors::Error, utils::get_manifest_path};
+use crate::{
+	errors::Error,
+	utils::{get_manifest_path, metadata::get_contract_artifacts},
+};
 pub use contract_build::Verbosity;
 use contract_build::{execute, BuildMode, BuildResult, ExecuteArgs};
 #[cfg(feature = "v6")]
 use contract_build_inkv6 as contract_build;
-use std::path::Path;
+use duct::cmd;
+pub use srtool_lib::ContainerEngine;
+use std::{
+	fs,
+	path::{Path, PathBuf},
+};
+
+/// The image used for verifiable builds, which provides the toolchain used to build contracts.
+const VERIFIABLE_IMAGE: &str = "docker.io/useink/contracts-verifiable";
+/// The tag of the image, pinned to the version of the contract build tooling used by pop. As tags
+/// are mutable, the image is resolved to its digest once pulled.
+#[cfg(feature = "v5")]
+const VERIFIABLE_TAG: &str = "5.0.3";
+#[cfg(feature = "v6")]
+const VERIFIABLE_TAG: &str = "6.0.0-alpha";
 
 /// Build the smart contract located at the specified `path` in `build_release` mode.
 ///
@@ -33,6 +50,137 @@ pub fn build_smart_contract(
 	execute(args)
 }
 
+/// Builds a smart contract within a pinned container image, so that the resulting code can be
+/// reproduced and verified by others.
+pub struct VerifiableBuilder {
+	/// The container engine used to run the build process.
+	engine: ContainerEngine,
+	/// The image used for building, pinned by its digest.
+	image: String,
+	/// The path to the contract project.
+	path: PathBuf,
+}
+
+impl VerifiableBuilder {
+	/// Creates a new instance of `VerifiableBuilder`, pulling the image used for building and
+	/// pinning it by its digest.
+	///
+	/// # Arguments
+	/// * `engine` - The container engine to use.
+	/// * `path` - The optional path to the contract project, defaulting to the current directory if
+	///   not specified.
+	pub fn new(engine: ContainerEngine, path: Option<&Path>) -> Result<Self, Error> {
+		let path = fs::canonicalize(path.unwrap_or_else(|| Path::new("./")))?;
+		let image = format!("{VERIFIABLE_IMAGE}:{VERIFIABLE_TAG}");
+		cmd(engine.to_string(), ["pull", &image]).stdout_null().stderr_null().run()?;
+		let image = image_digest(engine, &image).ok_or_else(|| {
+			Error::VerifiableBuildError(format!("the digest of {image} could not be resolved"))
+		})?;
+		Ok(Self { engine, image, path })
+	}
+
+	/// Creates a new instance of `VerifiableBuilder` using the specified image, such as that
+	/// recorded within the metadata of a contract, pulling it.
+	///
+	/// # Arguments
+	/// * `engine` - The container engine to use.
+	/// * `path` - The optional path to the contract project, defaulting to the current directory if
+	///   not specified.
+	/// * `image` - The image used for building, which must be pinned by its digest.
+	pub fn with_image(
+		engine: ContainerEngine,
+		path: Option<&Path>,
+		image: &str,
+	) -> Result<Self, Error> {
+		if !is_pinned(image) {
+			return Err(Error::VerifiableBuildError(format!(
+				"the image {image} is not pinned by its digest"
+			)));
+		}
+		let path = fs::canonicalize(path.unwrap_or_else(|| Path::new("./")))?;
+		cmd(engine.to_string(), ["pull", image]).stdout_null().stderr_null().run()?;
+		Ok(Self { engine, image: image.to_string(), path })
+	}
+
+	/// The image used for building.
+	pub fn image(&self) -> &str {
+		&self.image
+	}
+
+	/// Executes the build process, embedding the image within the metadata of the contract, and
+	/// returns the path of the contract artifact.
+	pub fn build(&self) -> Result<PathBuf, Error> {
+		cmd(self.engine.to_string(), self.build_args(&user()?))
+			.stdout_null()
+			.stderr_null()
+			.run()?;
+		let artifact = get_contract_artifacts(&self.path)?.artifact_path().to_path_buf();
+		for path in [artifact.with_extension("contract"), artifact.with_extension("json")] {
+			if path.exists() {
+				embed_image(&path, &self.image)?;
+			}
+		}
+		Ok(artifact)
+	}
+
+	// Builds the arguments of the container command, running the build as the specified user so
+	// that the output is not owned by root.
+	fn build_args(&self, user: &str) -> Vec<String> {
+		[
+			"run",
+			"--rm",
+			"--user",
+			user,
+			"-v",
+			&format!("{}:/contract", self.path.display()),
+			"-w",
+			"/contract",
+			"--entrypoint",
+			"cargo",
+			&self.image,
+			"contract",
+			"build",
+			"--release",
+		]
+		.map(String::from)
+		.to_vec()
+	}
+}
+
+// Whether an image reference is pinned by its digest.
+fn is_pinned(image: &str) -> bool {
+	image.contains("@sha256:")
+}
+
+// The user and group identifiers of the current user, formatted as expected by `--user`.
+fn user() -> Result<String, Error> {
+	let id = |flag| cmd("id", [flag]).read().map(|id| id.trim().to_string());
+	Ok(format!("{}:{}", id("-u")?, id("-g")?))
+}
+
+// Resolves the digest of a local image, returning a reference to the image which is pinned by the
+// digest.
+fn image_digest(engine: ContainerEngine, image: &str) -> Option<String> {
+	cmd(engine.to_string(), ["image", "inspect", "--format", "{{index .RepoDigests 0}}", image])
+		.stderr_null()
+		.read()
+		.ok()
+		.map(|digest| digest.trim().to_string())
+		.filter(|digest| is_pinned(digest))
+}
+
+// Records the image used to build a contract within the `image` field of its metadata.
+fn embed_image(path: &Path, image: &str) -> Result<(), Error> {
+	let mut metadata: serde_json::Value =
+		serde_json::from_slice(&fs::read(path)?).map_err(|e| Error::AnyhowError(e.into()))?;
+	metadata["image"] = image.into();
+	fs::write(
+		path,
+		serde_json::to_string_pretty(&metadata).map_err(|e| Error::AnyhowError(e.into()))?,
+	)?;
+	Ok(())
+}
+
 /// Determines whether the manifest at the supplied path is a supported smart contract project.
 ///
 /// # Arguments
@@ -48,6 +196,67 @@ mod tests {
 	use contract_build::new_contract_project;
 	use duct::cmd;
 
+	#[test]
+	fn build_args_works() {
+		let builder = VerifiableBuilder {
+			engine: ContainerEngine::Podman,
+			image: format!("{VERIFIABLE_IMAGE}@sha256:1234"),
+			path: PathBuf::from("/contracts/my flipper"),
+		};
+		assert_eq!(
+			builder.build_args("1000:1000"),
+			[
+				"run",
+				"--rm",
+				"--user",
+				"1000:1000",
+				"-v",
+				"/contracts/my flipper:/contract",
+				"-w",
+				"/contract",
+				"--entrypoint",
+				"cargo",
+				&format!("{VERIFIABLE_IMAGE}@sha256:1234"),
+				"contract",
+				"build",
+				"--release",
+			]
+		);
+		assert_eq!(builder.image(), format!("{VERIFIABLE_IMAGE}@sha256:1234"));
+	}
+
+	#[test]
+	fn with_image_fails_without_digest() {
+		assert!(matches!(
+			VerifiableBuilder::with_image(
+				ContainerEngine::Docker,
+				None,
+				&format!("{VERIFIABLE_IMAGE}:{VERIFIABLE_TAG}")
+			),
+			Err(Error::VerifiableBuildError(message)) if message.contains("not pinned")
+		));
+	}
+
+	#[test]
+	fn user_works() -> anyhow::Result<()> {
+		let user = user()?;
+		let (uid, gid) = user.split_once(':').expect("user and group are separated by a colon");
+		assert!(uid.parse::<u32>().is_ok() && gid.parse::<u32>().is_ok());
+		Ok(())
+	}
+
+	#[test]
+	fn embed_image_works() -> anyhow::Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let path = temp_dir.path().join("testing.contract");
+		fs::copy(std::env::current_dir()?.join("tests/files/testing.contract"), &path)?;
+		embed_image(&path, "image@sha256:1234")?;
+		let metadata = get_contract_artifacts(&path)?.metadata()?;
+		assert_eq!(metadata.image, Some("image@sha256:1234".to_string()));
+		assert_eq!(metadata.contract.name, "testing");
+		Ok(())
+	}
+
 	#[test]
 	fn is_supported_works() -> anyhow::Result<()> {
 		let temp_dir = tempfile::tempdir()?;
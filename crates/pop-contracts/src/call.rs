@@ -2,6 +2,7 @@
 
 use crate::{
 	errors::Error,
+	events::{decode_contract_events, display_contract_events},
 	submit_signed_payload,
 	utils::{
 		get_manifest_path,
@@ -11,7 +12,7 @@ use crate::{
 	CallExec, DefaultEnvironment, Environment, Verbosity,
 };
 use anyhow::Context;
-use pop_common::{create_signer, DefaultConfig, Keypair};
+use pop_common::{create_signer, DefaultConfig, Signer};
 use sp_weights::Weight;
 use std::path::PathBuf;
 use subxt::{tx::Payload, SubstrateConfig};
@@ -65,7 +66,7 @@ pub struct CallOpts {
 /// * `call_opts` - options for the `call` command.
 pub async fn set_up_call(
 	call_opts: CallOpts,
-) -> Result<CallExec<DefaultConfig, DefaultEnvironment, Keypair>, Error> {
+) -> Result<CallExec<DefaultConfig, DefaultEnvironment, Signer>, Error> {
 	let token_metadata = TokenMetadata::query::<DefaultConfig>(&call_opts.url).await?;
 	let signer = create_signer(&call_opts.suri)?;
 
@@ -102,7 +103,7 @@ pub async fn set_up_call(
 	)?;
 	let args = process_function_args(&function, call_opts.args)?;
 
-	let call_exec: CallExec<DefaultConfig, DefaultEnvironment, Keypair> =
+	let call_exec: CallExec<DefaultConfig, DefaultEnvironment, Signer> =
 		CallCommandBuilder::new(contract.clone(), &call_opts.message, extrinsic_opts)
 			.args(args)
 			.value(value.denominate_balance(&token_metadata)?)
@@ -119,7 +120,7 @@ pub async fn set_up_call(
 ///
 /// * `call_exec` - struct with the call to be executed.
 pub async fn dry_run_call(
-	call_exec: &CallExec<DefaultConfig, DefaultEnvironment, Keypair>,
+	call_exec: &CallExec<DefaultConfig, DefaultEnvironment, Signer>,
 ) -> Result<String, Error> {
 	let call_result = call_exec.call_dry_run().await?;
 	match call_result.result {
@@ -144,7 +145,7 @@ pub async fn dry_run_call(
 ///
 /// * `call_exec` - the preprocessed data to call a contract.
 pub async fn dry_run_gas_estimate_call(
-	call_exec: &CallExec<DefaultConfig, DefaultEnvironment, Keypair>,
+	call_exec: &CallExec<DefaultConfig, DefaultEnvironment, Signer>,
 ) -> Result<Weight, Error> {
 	let call_result = call_exec.call_dry_run().await?;
 	match call_result.result {
@@ -172,7 +173,7 @@ pub async fn dry_run_gas_estimate_call(
 /// * `gas_limit` - maximum amount of gas to be used for this call.
 /// * `url` - endpoint of the node which to send the call to.
 pub async fn call_smart_contract(
-	call_exec: CallExec<DefaultConfig, DefaultEnvironment, Keypair>,
+	call_exec: CallExec<DefaultConfig, DefaultEnvironment, Signer>,
 	gas_limit: Weight,
 	url: &Url,
 ) -> anyhow::Result<String, Error> {
@@ -190,10 +191,13 @@ pub async fn call_smart_contract(
 		.map_err(|error_variant| Error::CallContractError(format!("{:?}", error_variant)))?;
 	let display_events =
 		DisplayEvents::from_events::<DefaultConfig, DefaultEnvironment>(&events, None, &metadata)?;
+	// The call has already been executed, so events which cannot be decoded are not an error.
+	let contract_events =
+		decode_contract_events(call_exec.transcoder(), events.iter(), None).unwrap_or_default();
 
 	let output =
 		display_events.display_events::<DefaultEnvironment>(Verbosity::Default, &token_metadata)?;
-	Ok(output)
+	Ok(output + &display_contract_events(&contract_events))
 }
 
 /// Executes a smart contract call using a signed payload.
@@ -204,7 +208,7 @@ pub async fn call_smart_contract(
 /// * `payload` - The signed payload string to be submitted for executing the call.
 /// * `url` - The endpoint of the node where the call is executed.
 pub async fn call_smart_contract_from_signed_payload(
-	call_exec: CallExec<DefaultConfig, DefaultEnvironment, Keypair>,
+	call_exec: CallExec<DefaultConfig, DefaultEnvironment, Signer>,
 	payload: String,
 	url: &Url,
 ) -> anyhow::Result<String, Error> {
@@ -214,10 +218,13 @@ pub async fn call_smart_contract_from_signed_payload(
 	let display_events = DisplayEvents::from_events::<SubstrateConfig, DefaultEnvironment>(
 		&events, None, &metadata,
 	)?;
+	// The call has already been executed, so events which cannot be decoded are not an error.
+	let contract_events =
+		decode_contract_events(call_exec.transcoder(), events.iter(), None).unwrap_or_default();
 
 	let output =
 		display_events.display_events::<DefaultEnvironment>(Verbosity::Default, &token_metadata)?;
-	Ok(output)
+	Ok(output + &display_contract_events(&contract_events))
 }
 
 /// Generates the payload for executing a smart contract call.
@@ -227,7 +234,7 @@ pub async fn call_smart_contract_from_signed_payload(
 /// * `gas_limit` - The maximum amount of gas allocated for executing the contract call.
 #[cfg(feature = "v5")]
 pub fn get_call_payload(
-	call_exec: &CallExec<DefaultConfig, DefaultEnvironment, Keypair>,
+	call_exec: &CallExec<DefaultConfig, DefaultEnvironment, Signer>,
 	gas_limit: Weight,
 ) -> anyhow::Result<Vec<u8>> {
 	let storage_deposit_limit: Option<u128> = call_exec.opts().storage_deposit_limit();
@@ -251,7 +258,7 @@ pub fn get_call_payload(
 /// * `gas_limit` - The maximum amount of gas allocated for executing the contract call.
 #[cfg(feature = "v6")]
 pub fn get_call_payload(
-	call_exec: &CallExec<DefaultConfig, DefaultEnvironment, Keypair>,
+	call_exec: &CallExec<DefaultConfig, DefaultEnvironment, Signer>,
 	gas_limit: Weight,
 	storage_deposit_limit: u128,
 ) -> anyhow::Result<Vec<u8>> {
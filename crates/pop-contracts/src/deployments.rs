@@ -0,0 +1,331 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use crate::{errors::Error, utils::metadata::get_contract_artifacts};
+use pop_common::DefaultConfig;
+use serde::{Deserialize, Serialize};
+use std::{
+	collections::BTreeMap,
+	fs,
+	path::{Path, PathBuf},
+};
+use subxt::{
+	backend::rpc::{rpc_params, RpcClient},
+	utils::H256,
+	OnlineClient,
+};
+use url::Url;
+
+/// The name of the file recording the deployments of the contracts of a project.
+pub const DEPLOYMENTS_FILE: &str = "deployments.toml";
+/// The name of the directory recording the metadata of deployed contracts, keyed by code hash.
+pub const DEPLOYED_METADATA_DIR: &str = "deployments";
+
+/// A deployment of a contract.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Deployment {
+	/// The address of the contract.
+	pub address: String,
+	/// The hash of the code of the contract.
+	pub code_hash: String,
+	/// The name of the constructor used to instantiate the contract.
+	pub constructor: String,
+	/// The arguments of the constructor.
+	#[serde(default)]
+	pub args: Vec<String>,
+	/// The salt used in the derivation of the address of the contract, if any.
+	pub salt: Option<String>,
+	/// The account which deployed the contract, if known.
+	pub deployer: Option<String>,
+	/// The hash of the block including the instantiation, if known.
+	pub block: Option<String>,
+}
+
+/// The deployments of the contracts of a project, keyed by network and contract name.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Deployments {
+	#[serde(default)]
+	networks: BTreeMap<String, BTreeMap<String, Deployment>>,
+	// The deployments replaced by a later deployment of the same contract, oldest first.
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	history: BTreeMap<String, BTreeMap<String, Vec<Deployment>>>,
+}
+
+impl Deployments {
+	/// Loads the deployments recorded within a project, if any.
+	///
+	/// # Arguments
+	/// * `project` - The path of the project.
+	pub fn load(project: &Path) -> Result<Self, Error> {
+		let path = project.join(DEPLOYMENTS_FILE);
+		if !path.exists() {
+			return Ok(Self::default());
+		}
+		toml_edit::de::from_str(&fs::read_to_string(&path)?)
+			.map_err(|e| Error::DeploymentsError(format!("{}: {e}", path.display())))
+	}
+
+	/// Saves the deployments within a project.
+	///
+	/// # Arguments
+	/// * `project` - The path of the project.
+	pub fn save(&self, project: &Path) -> Result<(), Error> {
+		let contents = toml_edit::ser::to_string_pretty(self)
+			.map_err(|e| Error::DeploymentsError(e.to_string()))?;
+		fs::write(project.join(DEPLOYMENTS_FILE), contents)?;
+		Ok(())
+	}
+
+	/// Whether no deployments have been recorded.
+	pub fn is_empty(&self) -> bool {
+		self.networks.values().all(BTreeMap::is_empty)
+	}
+
+	/// Returns the deployment of a contract on a network.
+	///
+	/// # Arguments
+	/// * `network` - The key identifying the network.
+	/// * `name` - The name of the contract.
+	pub fn get(&self, network: &str, name: &str) -> Option<&Deployment> {
+		self.networks.get(network)?.get(name)
+	}
+
+	/// Returns the previous deployments of a contract on a network, oldest first.
+	///
+	/// # Arguments
+	/// * `network` - The key identifying the network.
+	/// * `name` - The name of the contract.
+	pub fn history(&self, network: &str, name: &str) -> &[Deployment] {
+		self.history
+			.get(network)
+			.and_then(|history| history.get(name))
+			.map(Vec::as_slice)
+			.unwrap_or_default()
+	}
+
+	/// Records the deployment of a contract on a network. Any previous deployment of the contract
+	/// is kept within its history and returned.
+	///
+	/// # Arguments
+	/// * `network` - The key identifying the network.
+	/// * `name` - The name of the contract.
+	/// * `deployment` - The deployment of the contract.
+	pub fn record(
+		&mut self,
+		network: &str,
+		name: &str,
+		deployment: Deployment,
+	) -> Option<Deployment> {
+		let previous = self
+			.networks
+			.entry(network.to_string())
+			.or_default()
+			.insert(name.to_string(), deployment)?;
+		self.history
+			.entry(network.to_string())
+			.or_default()
+			.entry(name.to_string())
+			.or_default()
+			.push(previous.clone());
+		Some(previous)
+	}
+
+	/// Finds a deployment on a network using identical code and salt, returning the name of the
+	/// contract along with its deployment.
+	///
+	/// # Arguments
+	/// * `network` - The key identifying the network.
+	/// * `code_hash` - The hash of the code of the contract.
+	/// * `salt` - The salt used in the derivation of the address of the contract.
+	pub fn find(
+		&self,
+		network: &str,
+		code_hash: &str,
+		salt: Option<&str>,
+	) -> Option<(&str, &Deployment)> {
+		self.all(network).find(|(_, deployment)| {
+			deployment.code_hash.eq_ignore_ascii_case(code_hash) &&
+				deployment.salt.as_deref() == salt
+		})
+	}
+
+	/// Finds the deployment of a contract at an address on a network, returning the name of the
+	/// contract along with its deployment.
+	///
+	/// # Arguments
+	/// * `network` - The key identifying the network.
+	/// * `address` - The address of the contract.
+	pub fn find_by_address(&self, network: &str, address: &str) -> Option<(&str, &Deployment)> {
+		self.all(network)
+			.find(|(_, deployment)| deployment.address.eq_ignore_ascii_case(address))
+	}
+
+	// The current deployments of the contracts on a network, followed by their previous
+	// deployments from the most recent.
+	fn all<'a>(&'a self, network: &str) -> impl Iterator<Item = (&'a str, &'a Deployment)> {
+		let current = self.networks.get(network).into_iter().flatten();
+		let previous =
+			self.history.get(network).into_iter().flatten().flat_map(|(name, history)| {
+				history.iter().rev().map(move |deployment| (name, deployment))
+			});
+		current.chain(previous).map(|(name, deployment)| (name.as_str(), deployment))
+	}
+
+	/// The path of the recorded metadata of deployed code.
+	///
+	/// # Arguments
+	/// * `project` - The path of the project.
+	/// * `code_hash` - The hash of the deployed code.
+	pub fn metadata_path(project: &Path, code_hash: &str) -> PathBuf {
+		project
+			.join(DEPLOYED_METADATA_DIR)
+			.join(format!("{}.json", code_hash.to_ascii_lowercase()))
+	}
+
+	/// Records the metadata of the built contract of a project as that of deployed code, so that
+	/// the storage layout of the deployed code can be compared when upgrading.
+	///
+	/// # Arguments
+	/// * `project` - The path of the project.
+	/// * `code_hash` - The hash of the deployed code.
+	pub fn record_metadata(project: &Path, code_hash: &str) -> Result<PathBuf, Error> {
+		let metadata = get_contract_artifacts(project)?.metadata()?;
+		let path = Self::metadata_path(project, code_hash);
+		if let Some(dir) = path.parent() {
+			fs::create_dir_all(dir)?;
+		}
+		let contents = serde_json::to_string_pretty(&metadata)
+			.map_err(|e| Error::DeploymentsError(e.to_string()))?;
+		fs::write(&path, contents)?;
+		Ok(path)
+	}
+}
+
+/// Finds the hash of the finalized block including an extrinsic, searching back from the latest
+/// finalized block.
+///
+/// # Arguments
+/// * `url` - Endpoint of the node.
+/// * `extrinsic_hash` - The hash of the extrinsic.
+pub async fn find_block(url: &Url, extrinsic_hash: H256) -> Option<String> {
+	// The extrinsic is expected to have been finalized recently, so only search the latest blocks.
+	const MAX_BLOCKS: usize = 16;
+	let rpc_client = RpcClient::from_url(url.as_str()).await.ok()?;
+	let client = OnlineClient::<DefaultConfig>::from_rpc_client(rpc_client).await.ok()?;
+	let mut block = client.blocks().at_latest().await.ok()?;
+	for _ in 0..MAX_BLOCKS {
+		let extrinsics = block.extrinsics().await.ok()?;
+		if extrinsics.iter().any(|extrinsic| extrinsic.hash() == extrinsic_hash) {
+			return Some(format!("{:?}", block.hash()));
+		}
+		block = client.blocks().at(block.header().parent_hash).await.ok()?;
+	}
+	None
+}
+
+/// Resolves the key identifying a network within the deployments file, which is the genesis hash
+/// of the chain, or its URL if the genesis hash cannot be retrieved.
+///
+/// # Arguments
+/// * `url` - Endpoint of the node.
+pub async fn network_key(url: &Url) -> String {
+	async fn genesis_hash(url: &Url) -> Option<H256> {
+		let client = RpcClient::from_url(url.as_str()).await.ok()?;
+		client.request("chain_getBlockHash", rpc_params![0]).await.ok()
+	}
+	match genesis_hash(url).await {
+		Some(hash) => format!("{hash:?}"),
+		None => url.to_string(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{mock_build_process, new_environment};
+	use anyhow::Result;
+	use std::env;
+	use tempfile::tempdir;
+
+	const NETWORK: &str = "0x01";
+
+	fn flipper() -> Deployment {
+		Deployment {
+			address: "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY".into(),
+			code_hash: "0xAB".into(),
+			constructor: "new".into(),
+			args: vec!["true".into()],
+			salt: Some("0x02".into()),
+			deployer: Some("5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty".into()),
+			block: Some("0x03".into()),
+		}
+	}
+
+	#[test]
+	fn record_and_find_deployments_works() {
+		let mut deployments = Deployments::default();
+		assert!(deployments.is_empty());
+		assert_eq!(deployments.get(NETWORK, "flipper"), None);
+		deployments.record(NETWORK, "flipper", flipper());
+		assert!(!deployments.is_empty());
+		assert_eq!(deployments.get(NETWORK, "flipper"), Some(&flipper()));
+		assert_eq!(deployments.get("0x02", "flipper"), None);
+		assert_eq!(deployments.find(NETWORK, "0xab", Some("0x02")), Some(("flipper", &flipper())));
+		assert_eq!(deployments.find(NETWORK, "0xab", None), None);
+		assert_eq!(deployments.find(NETWORK, "0xcd", Some("0x02")), None);
+		assert_eq!(
+			deployments.find_by_address(NETWORK, &flipper().address.to_lowercase()),
+			Some(("flipper", &flipper()))
+		);
+		assert_eq!(deployments.find_by_address(NETWORK, "ADDRESS"), None);
+		// A new deployment of a contract replaces the previous one, which is kept in its history.
+		let redeployed = Deployment { address: "ADDRESS".into(), salt: None, ..flipper() };
+		assert_eq!(deployments.record(NETWORK, "flipper", redeployed.clone()), Some(flipper()));
+		assert_eq!(deployments.get(NETWORK, "flipper"), Some(&redeployed));
+		assert_eq!(deployments.history(NETWORK, "flipper"), [flipper()]);
+		assert!(deployments.history("0x02", "flipper").is_empty());
+		// Previous deployments can still be found.
+		assert_eq!(deployments.find(NETWORK, "0xab", Some("0x02")), Some(("flipper", &flipper())));
+		assert_eq!(
+			deployments.find_by_address(NETWORK, &flipper().address),
+			Some(("flipper", &flipper()))
+		);
+		assert_eq!(deployments.find_by_address(NETWORK, "address"), Some(("flipper", &redeployed)));
+	}
+
+	#[test]
+	fn load_and_save_deployments_works() -> Result<()> {
+		let temp_dir = tempdir()?;
+		assert_eq!(Deployments::load(temp_dir.path())?, Deployments::default());
+		let mut deployments = Deployments::default();
+		deployments.record(NETWORK, "flipper", flipper());
+		deployments.record(NETWORK, "flipper", Deployment { salt: None, ..flipper() });
+		deployments.save(temp_dir.path())?;
+		assert_eq!(Deployments::load(temp_dir.path())?, deployments);
+
+		fs::write(temp_dir.path().join(DEPLOYMENTS_FILE), "[networks.\"0x01\".flipper]\nname = 1")?;
+		assert!(matches!(
+			Deployments::load(temp_dir.path()),
+			Err(Error::DeploymentsError(message)) if message.contains(DEPLOYMENTS_FILE)
+		));
+		Ok(())
+	}
+
+	#[test]
+	fn record_metadata_works() -> Result<()> {
+		let temp_dir = new_environment("testing")?;
+		let project = temp_dir.path().join("testing");
+		let current_dir = env::current_dir().expect("Failed to get current directory");
+		mock_build_process(
+			project.clone(),
+			current_dir.join("./tests/files/testing.contract"),
+			current_dir.join("./tests/files/testing.json"),
+		)?;
+		let path = Deployments::record_metadata(&project, "0xAB")?;
+		assert_eq!(path, project.join(DEPLOYED_METADATA_DIR).join("0xab.json"));
+		assert_eq!(Deployments::metadata_path(&project, "0xab"), path);
+		assert_eq!(get_contract_artifacts(&path)?.metadata()?.contract.name, "testing");
+		Ok(())
+	}
+}
@@ -19,12 +19,18 @@ pub enum Error {
 	/// A common error originating from `pop_common`.
 	#[error("{0}")]
 	CommonError(#[from] pop_common::Error),
+	/// Failed to load or save the deployments of a project.
+	#[error("Failed to access the deployments: {0}")]
+	DeploymentsError(String),
 	/// Dry-run contract upload failed.
 	#[error("Pre-submission dry-run failed: {0}")]
 	DryRunUploadContractError(String),
 	/// Dry-run contract call failed.
 	#[error("Pre-submission dry-run failed: {0}")]
 	DryRunCallContractError(String),
+	/// Failed to decode the events emitted by a contract.
+	#[error("Failed to decode contract events: {0}")]
+	EventError(String),
 	/// Failed to parse hex-encoded bytes.
 	#[error("Failed to parse hex encoded bytes: {0}")]
 	HexParsing(String),
@@ -78,6 +84,9 @@ pub enum Error {
 	/// An error occurred sourcing a binary.
 	#[error("Sourcing error {0}")]
 	SourcingError(SourcingError),
+	/// Failed to read the storage of a contract.
+	#[error("Failed to read the contract storage: {0}")]
+	StorageError(String),
 	/// An error occurred while executing a test command.
 	#[error("Failed to execute test command: {0}")]
 	TestCommand(String),
@@ -87,7 +96,16 @@ pub enum Error {
 		/// The operating system in use.
 		os: &'static str,
 	},
+	/// Failed to upgrade a contract.
+	#[error("Failed to upgrade the contract: {0}")]
+	UpgradeError(String),
 	/// An error occurred while uploading the contract.
 	#[error("{0}")]
 	UploadContractError(String),
+	/// Failed to build a contract within the pinned image used for verifiable builds.
+	#[error("Failed to build the contract verifiably: {0}")]
+	VerifiableBuildError(String),
+	/// Failed to verify a deployed contract.
+	#[error("Failed to verify the contract: {0}")]
+	VerificationError(String),
 }
@@ -0,0 +1,431 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use crate::{errors::Error, utils::metadata::get_contract_transcoder};
+use pop_common::DefaultConfig;
+use std::{
+	fmt::{self, Display, Formatter},
+	iter::once,
+	path::Path,
+};
+use subxt::{
+	backend::StreamOfResults,
+	blocks::Block,
+	events::EventDetails,
+	ext::codec::Decode,
+	utils::{to_hex, H256},
+	Config, OnlineClient,
+};
+use url::Url;
+#[cfg(feature = "v5")]
+use {contract_transcode::ContractMessageTranscoder, pop_common::parse_account};
+#[cfg(feature = "v6")]
+use {contract_transcode_inkv6::ContractMessageTranscoder, pop_common::parse_h160_account};
+
+// The pallet emitting the events of contracts.
+#[cfg(feature = "v5")]
+const CONTRACTS_PALLET: &str = "Contracts";
+#[cfg(feature = "v6")]
+const CONTRACTS_PALLET: &str = "Revive";
+// The event emitted by the pallet on behalf of a contract.
+const CONTRACT_EMITTED: &str = "ContractEmitted";
+
+/// An event emitted by a contract, decoded against the event specs of its metadata.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContractEvent {
+	/// The number of the block containing the event, if known.
+	pub block_number: Option<u32>,
+	/// The address of the contract which emitted the event.
+	pub contract: String,
+	/// The name of the event, if it could be decoded.
+	pub name: Option<String>,
+	/// The decoded fields of the event, or the raw data when the event could not be decoded.
+	pub fields: Vec<(String, String)>,
+	/// The topics of the event, the first of which is the signature topic unless the event is
+	/// anonymous.
+	pub topics: Vec<String>,
+}
+
+impl Display for ContractEvent {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		if let Some(number) = self.block_number {
+			write!(f, "#{number} ")?;
+		}
+		write!(f, "{} emitted by {}", self.name.as_deref().unwrap_or("<unknown>"), self.contract)?;
+		for (name, value) in &self.fields {
+			write!(f, "\n  {name}: {value}")?;
+		}
+		if !self.topics.is_empty() {
+			write!(f, "\n  topics: [{}]", self.topics.join(", "))?;
+		}
+		Ok(())
+	}
+}
+
+/// A subscription to the events emitted by contracts, decoding the events of each new finalized
+/// block.
+pub struct ContractEventSubscription {
+	blocks: StreamOfResults<Block<DefaultConfig, OnlineClient<DefaultConfig>>>,
+	transcoder: ContractMessageTranscoder,
+	contract: Option<Vec<u8>>,
+}
+
+impl ContractEventSubscription {
+	/// Subscribes to the events emitted by contracts in new finalized blocks.
+	///
+	/// # Arguments
+	/// * `path` - Location path of the project or contract artifact.
+	/// * `url` - Endpoint of the node.
+	/// * `contract` - The address of the contract whose events are streamed, otherwise the events
+	///   of every contract matching the event specs of the metadata are streamed.
+	pub async fn new(path: &Path, url: &Url, contract: Option<&str>) -> Result<Self, Error> {
+		let transcoder = get_contract_transcoder(path)?;
+		let contract = contract.map(parse_address).transpose()?;
+		let client = OnlineClient::<DefaultConfig>::from_url(url.as_str())
+			.await
+			.map_err(|e| Error::EventError(e.to_string()))?;
+		let blocks = client
+			.blocks()
+			.subscribe_finalized()
+			.await
+			.map_err(|e| Error::EventError(e.to_string()))?;
+		Ok(Self { blocks, transcoder, contract })
+	}
+
+	/// Waits for the next block, returning the decoded events emitted by contracts. Returns `None`
+	/// once the subscription has ended.
+	pub async fn next(&mut self) -> Option<Result<Vec<ContractEvent>, Error>> {
+		let block = match self.blocks.next().await? {
+			Ok(block) => block,
+			Err(e) => return Some(Err(Error::EventError(e.to_string()))),
+		};
+		let events = match block.events().await {
+			Ok(events) => events,
+			Err(e) => return Some(Err(Error::EventError(e.to_string()))),
+		};
+		Some(decode_contract_events(&self.transcoder, events.iter(), self.contract.as_deref()).map(
+			|events| {
+				events
+					.into_iter()
+					.map(|event| ContractEvent { block_number: Some(block.number()), ..event })
+					.collect()
+			},
+		))
+	}
+}
+
+/// Decodes the events emitted by contracts.
+///
+/// When a contract address is provided, only the events of that contract are returned, including
+/// any which cannot be decoded. Otherwise, only the events matching the signature topic of an
+/// event spec of the metadata are returned. Events whose data does not match their event spec are
+/// returned undecoded along with the error, rather than failing the other events, while
+/// `ContractEmitted` events which cannot be attributed to a contract are skipped.
+///
+/// # Arguments
+/// * `transcoder` - The transcoder of the contract metadata.
+/// * `events` - The events to decode.
+/// * `contract` - The address of the contract whose events are returned.
+pub(crate) fn decode_contract_events<C: Config<Hash = H256>, E: Display>(
+	transcoder: &ContractMessageTranscoder,
+	events: impl Iterator<Item = Result<EventDetails<C>, E>>,
+	contract: Option<&[u8]>,
+) -> Result<Vec<ContractEvent>, Error> {
+	let mut decoded = Vec::new();
+	for event in events {
+		let event = event.map_err(|e| Error::EventError(e.to_string()))?;
+		if event.pallet_name() != CONTRACTS_PALLET || event.variant_name() != CONTRACT_EMITTED {
+			continue;
+		}
+		let Ok(emitted) = contract_emitted(&event) else {
+			continue;
+		};
+		if contract.is_some_and(|c| c != emitted.address) {
+			continue;
+		}
+		decoded.extend(decode_emitted(transcoder, &emitted, contract.is_some()));
+	}
+	Ok(decoded)
+}
+
+/// Formats decoded contract events, to be displayed after the events of an extrinsic.
+///
+/// # Arguments
+/// * `events` - The decoded contract events.
+pub(crate) fn display_contract_events(events: &[ContractEvent]) -> String {
+	events.iter().fold(String::new(), |mut output, event| {
+		output.push_str(&format!("\nContract Event {event}"));
+		output
+	})
+}
+
+// Decodes the data of an event emitted by a contract, using the event spec matching its
+// signature topic. Anonymous events are only decoded when requested, provided the metadata
+// contains a single anonymous event.
+fn decode_contract_event(
+	transcoder: &ContractMessageTranscoder,
+	address: &[u8],
+	mut data: &[u8],
+	topics: &[H256],
+	anonymous: bool,
+) -> Result<Option<ContractEvent>, Error> {
+	let events = transcoder.metadata().spec().events();
+	let spec = match topics.first().and_then(|topic| {
+		events
+			.iter()
+			.find(|e| e.signature_topic().is_some_and(|s| s.as_bytes() == topic.as_bytes()))
+	}) {
+		Some(spec) => spec,
+		None => {
+			let mut anonymous_events = events.iter().filter(|e| e.signature_topic().is_none());
+			match (anonymous, anonymous_events.next(), anonymous_events.next()) {
+				(true, Some(spec), None) => spec,
+				_ => return Ok(None),
+			}
+		},
+	};
+	let mut fields = Vec::new();
+	for arg in spec.args() {
+		let value = transcoder.decode(arg.ty().ty().id, &mut data).map_err(|e| {
+			Error::EventError(format!("failed to decode {} of {}: {e}", arg.label(), spec.label()))
+		})?;
+		fields.push((arg.label().to_string(), value.to_string()));
+	}
+	if !data.is_empty() {
+		return Err(Error::EventError(format!(
+			"{} bytes left over after decoding {}",
+			data.len(),
+			spec.label()
+		)));
+	}
+	Ok(Some(ContractEvent {
+		block_number: None,
+		contract: format_address(address),
+		name: Some(spec.label().to_string()),
+		fields,
+		topics: topics.iter().map(|t| format!("{t:?}")).collect(),
+	}))
+}
+
+// Decodes a `ContractEmitted` event, falling back to its raw data when it is emitted by a specific
+// contract or when its data does not match the event spec of its signature topic.
+fn decode_emitted(
+	transcoder: &ContractMessageTranscoder,
+	emitted: &ContractEmitted,
+	specific: bool,
+) -> Option<ContractEvent> {
+	let ContractEmitted { address, data, topics } = emitted;
+	let raw = |error: Option<String>| ContractEvent {
+		block_number: None,
+		contract: format_address(address),
+		name: None,
+		fields: once(("data".into(), to_hex(data)))
+			.chain(error.map(|e| ("error".into(), e)))
+			.collect(),
+		topics: topics.iter().map(|t| format!("{t:?}")).collect(),
+	};
+	match decode_contract_event(transcoder, address, data, topics, specific) {
+		Ok(Some(event)) => Some(event),
+		Ok(None) if specific => Some(raw(None)),
+		Ok(None) => None,
+		Err(Error::EventError(message)) => Some(raw(Some(message))),
+		Err(e) => Some(raw(Some(e.to_string()))),
+	}
+}
+
+// A `ContractEmitted` event.
+struct ContractEmitted {
+	// The address of the contract.
+	address: Vec<u8>,
+	// The encoded event.
+	data: Vec<u8>,
+	// The topics of the event.
+	topics: Vec<H256>,
+}
+
+// Extracts the address of the contract, the data and the topics of a `ContractEmitted` event.
+fn contract_emitted<C: Config<Hash = H256>>(
+	event: &EventDetails<C>,
+) -> Result<ContractEmitted, Error> {
+	let input = &mut event.field_bytes();
+	let invalid = |e: subxt::ext::codec::Error| {
+		Error::EventError(format!("invalid {CONTRACT_EMITTED} event: {e}"))
+	};
+	#[cfg(feature = "v5")]
+	let (address, topics) = (<[u8; 32]>::decode(input).map_err(invalid)?, event.topics().to_vec());
+	#[cfg(feature = "v6")]
+	let address = <[u8; 20]>::decode(input).map_err(invalid)?;
+	let data = Vec::<u8>::decode(input).map_err(invalid)?;
+	// The topics of events emitted by Revive contracts are part of the event itself.
+	#[cfg(feature = "v6")]
+	let topics = Vec::<H256>::decode(input).map_err(invalid)?;
+	Ok(ContractEmitted { address: address.to_vec(), data, topics })
+}
+
+// Parses the address of a contract into its raw bytes.
+fn parse_address(address: &str) -> Result<Vec<u8>, Error> {
+	#[cfg(feature = "v5")]
+	return Ok(parse_account(address)?.0.to_vec());
+	#[cfg(feature = "v6")]
+	Ok(parse_h160_account(address)?.as_bytes().to_vec())
+}
+
+// Formats the raw bytes of the address of a contract.
+fn format_address(address: &[u8]) -> String {
+	#[cfg(feature = "v5")]
+	return match <[u8; 32]>::try_from(address) {
+		Ok(account) => subxt::utils::AccountId32(account).to_string(),
+		Err(_) => to_hex(address),
+	};
+	#[cfg(feature = "v6")]
+	to_hex(address)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::Result;
+	use std::env;
+	use subxt::ext::codec::Encode;
+
+	const ALICE: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+	const SIGNATURE_TOPIC: [u8; 32] = [7; 32];
+
+	// The transcoder of the testing contract, extended with a `Flipped` event and an anonymous
+	// `Reset` event.
+	fn transcoder(anonymous: bool) -> Result<ContractMessageTranscoder> {
+		let current_dir = env::current_dir().expect("Failed to get current directory");
+		let mut metadata: serde_json::Value =
+			serde_json::from_slice(&std::fs::read(current_dir.join("tests/files/testing.json"))?)?;
+		let mut events = vec![serde_json::json!({
+			"label": "Flipped",
+			"module_path": "testing",
+			"signature_topic": to_hex(SIGNATURE_TOPIC),
+			"args": [
+				{"label": "by", "indexed": true, "type": {"type": 8, "displayName": ["AccountId"]}, "docs": []},
+				{"label": "value", "indexed": false, "type": {"type": 0, "displayName": ["bool"]}, "docs": []},
+			],
+			"docs": [],
+		})];
+		if anonymous {
+			events.push(serde_json::json!({
+				"label": "Reset",
+				"module_path": "testing",
+				"signature_topic": null,
+				"args": [{"label": "number", "indexed": false, "type": {"type": 1, "displayName": ["u32"]}, "docs": []}],
+				"docs": [],
+			}));
+		}
+		metadata["spec"]["events"] = serde_json::Value::Array(events);
+		Ok(ContractMessageTranscoder::new(serde_json::from_value(metadata)?))
+	}
+
+	fn alice() -> Vec<u8> {
+		parse_address(ALICE).expect("valid address")
+	}
+
+	#[test]
+	fn decode_contract_event_works() -> Result<()> {
+		let transcoder = transcoder(false)?;
+		let topics = [H256(SIGNATURE_TOPIC), H256::from_slice(&alice())];
+		let data = [alice(), true.encode()].concat();
+		assert_eq!(
+			decode_contract_event(&transcoder, &alice(), &data, &topics, false)?,
+			Some(ContractEvent {
+				block_number: None,
+				contract: ALICE.into(),
+				name: Some("Flipped".into()),
+				fields: vec![("by".into(), ALICE.into()), ("value".into(), "true".into())],
+				topics: vec![to_hex(SIGNATURE_TOPIC), to_hex(alice())],
+			})
+		);
+		// Events with an unknown signature topic are not decoded.
+		assert_eq!(
+			decode_contract_event(&transcoder, &alice(), &data, &[H256::zero()], true)?,
+			None
+		);
+		// Left over data is an error.
+		assert!(matches!(
+			decode_contract_event(&transcoder, &alice(), &[data, vec![0]].concat(), &topics, false),
+			Err(Error::EventError(message)) if message == "1 bytes left over after decoding Flipped"
+		));
+		Ok(())
+	}
+
+	#[test]
+	fn decode_emitted_works() -> Result<()> {
+		let transcoder = transcoder(false)?;
+		let topics = vec![H256(SIGNATURE_TOPIC), H256::from_slice(&alice())];
+		let data = [alice(), true.encode()].concat();
+		let emitted = ContractEmitted { address: alice(), data: data.clone(), topics };
+		assert_eq!(
+			decode_emitted(&transcoder, &emitted, false).unwrap().name,
+			Some("Flipped".into())
+		);
+		// Events with an unknown signature topic are only returned for a specific contract.
+		let emitted = ContractEmitted { topics: vec![H256::zero()], ..emitted };
+		assert_eq!(decode_emitted(&transcoder, &emitted, false), None);
+		assert_eq!(
+			decode_emitted(&transcoder, &emitted, true),
+			Some(ContractEvent {
+				block_number: None,
+				contract: ALICE.into(),
+				name: None,
+				fields: vec![("data".into(), to_hex(&data))],
+				topics: vec![to_hex([0u8; 32])],
+			})
+		);
+		// Malformed events are returned undecoded along with the error.
+		let emitted = ContractEmitted {
+			data: [data.clone(), vec![0]].concat(),
+			topics: vec![H256(SIGNATURE_TOPIC)],
+			..emitted
+		};
+		assert_eq!(
+			decode_emitted(&transcoder, &emitted, false).unwrap().fields,
+			vec![
+				("data".into(), to_hex([data, vec![0]].concat())),
+				("error".into(), "1 bytes left over after decoding Flipped".into()),
+			]
+		);
+		Ok(())
+	}
+
+	#[test]
+	fn decode_anonymous_contract_event_works() -> Result<()> {
+		let transcoder = transcoder(true)?;
+		let data = 42u32.encode();
+		// Anonymous events are only decoded for a specific contract.
+		assert_eq!(decode_contract_event(&transcoder, &alice(), &data, &[], false)?, None);
+		assert_eq!(
+			decode_contract_event(&transcoder, &alice(), &data, &[], true)?,
+			Some(ContractEvent {
+				block_number: None,
+				contract: ALICE.into(),
+				name: Some("Reset".into()),
+				fields: vec![("number".into(), "42".into())],
+				topics: vec![],
+			})
+		);
+		Ok(())
+	}
+
+	#[test]
+	fn display_contract_event_works() {
+		let mut event = ContractEvent {
+			block_number: Some(10),
+			contract: ALICE.into(),
+			name: Some("Flipped".into()),
+			fields: vec![("value".into(), "true".into())],
+			topics: vec!["0x01".into(), "0x02".into()],
+		};
+		assert_eq!(
+			event.to_string(),
+			format!("#10 Flipped emitted by {ALICE}\n  value: true\n  topics: [0x01, 0x02]")
+		);
+		event.block_number = None;
+		event.name = None;
+		event.fields = vec![("data".into(), "0x00".into())];
+		event.topics = vec![];
+		assert_eq!(event.to_string(), format!("<unknown> emitted by {ALICE}\n  data: 0x00"));
+	}
+}
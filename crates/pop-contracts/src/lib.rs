@@ -8,23 +8,35 @@ compile_error!("only feature \"v5\" OR \"v6\" must be enabled");
 
 mod build;
 mod call;
+mod deployments;
 mod errors;
+mod events;
 mod new;
 mod node;
+mod storage;
 mod templates;
 mod test;
 mod testing;
 mod up;
+mod upgrade;
 mod utils;
+mod verify;
 
-pub use build::{build_smart_contract, is_supported, Verbosity};
+pub use build::{
+	build_smart_contract, is_supported, ContainerEngine, Verbosity, VerifiableBuilder,
+};
 pub use call::{
 	call_smart_contract, call_smart_contract_from_signed_payload, dry_run_call,
 	dry_run_gas_estimate_call, get_call_payload, set_up_call, CallOpts,
 };
+pub use deployments::{
+	find_block, network_key, Deployment, Deployments, DEPLOYED_METADATA_DIR, DEPLOYMENTS_FILE,
+};
 pub use errors::Error;
+pub use events::{ContractEvent, ContractEventSubscription};
 pub use new::{create_smart_contract, is_valid_contract_name};
 pub use node::{contracts_node_generator, is_chain_alive, run_contracts_node};
+pub use storage::{BlockId, ContractStorage, StorageNode};
 pub use templates::{Contract, ContractType};
 pub use test::test_e2e_smart_contract;
 pub use testing::{mock_build_process, new_environment};
@@ -33,12 +45,15 @@ pub use up::{
 	instantiate_contract_signed, instantiate_smart_contract, set_up_deployment, set_up_upload,
 	submit_signed_payload, upload_contract_signed, upload_smart_contract, ContractInfo, UpOpts,
 };
+pub use upgrade::{compare_storage_layouts, set_code, LayoutChange};
 pub use utils::{
 	metadata::{
-		extract_function, get_message, get_messages, ContractFunction, FunctionType, Param,
+		extract_function, get_contract_name, get_message, get_messages, ContractFunction,
+		FunctionType, Param,
 	},
 	parse_hex_bytes,
 };
+pub use verify::{recorded_image, verify_contract, Verification};
 // External exports
 pub use sp_weights::Weight;
 #[cfg(feature = "v5")]
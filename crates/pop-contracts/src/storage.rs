@@ -0,0 +1,607 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use crate::{errors::Error, utils::metadata::get_contract_transcoder, Bytes};
+use pop_common::format_type;
+use scale_info::form::PortableForm;
+use scale_value::stringify::custom_parsers;
+use std::{
+	collections::HashMap,
+	fmt::{self, Display, Formatter},
+	path::Path,
+	str::FromStr,
+};
+use subxt::{
+	backend::rpc::{rpc_params, RpcClient},
+	dynamic::Value,
+	ext::codec::{Decode, Encode},
+	utils::H256,
+	OnlineClient, SubstrateConfig,
+};
+use url::Url;
+#[cfg(feature = "v5")]
+use {
+	contract_transcode::{
+		ink_metadata::layout::{Discriminant, Layout, RootLayout, StructLayout},
+		ContractMessageTranscoder,
+	},
+	pop_common::parse_account,
+	sp_core::{bytes::from_hex, hashing::blake2_128},
+};
+#[cfg(feature = "v6")]
+use {
+	contract_transcode_inkv6::{
+		ink_metadata::layout::{Discriminant, Layout, RootLayout, StructLayout},
+		ContractMessageTranscoder,
+	},
+	pop_common::parse_h160_account,
+	sp_core_inkv6::{bytes::from_hex, hashing::blake2_128},
+};
+
+// The prefix of the key of a child trie, to which the identifier of the trie is appended.
+const CHILD_STORAGE_PREFIX: &[u8] = b":child_storage:default:";
+// The pallet holding the information of deployed contracts.
+#[cfg(feature = "v5")]
+const CONTRACTS_PALLET: &str = "Contracts";
+#[cfg(feature = "v6")]
+const CONTRACTS_PALLET: &str = "Revive";
+
+/// A block at which the storage of a contract is read.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BlockId {
+	/// The number of the block.
+	Number(u32),
+	/// The hash of the block.
+	Hash(H256),
+}
+
+impl FromStr for BlockId {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		if let Ok(number) = s.parse() {
+			return Ok(Self::Number(number));
+		}
+		match from_hex(s) {
+			Ok(hash) if hash.len() == 32 => Ok(Self::Hash(H256::from_slice(&hash))),
+			_ => Err(Error::StorageError(format!(
+				"invalid block {s}, expected a block number or hash"
+			))),
+		}
+	}
+}
+
+/// A node of the decoded storage tree of a contract.
+#[derive(Clone, Debug, PartialEq)]
+pub enum StorageNode {
+	/// A decoded value.
+	Value(String),
+	/// A struct, or the variant of an enum, with its fields.
+	Struct {
+		/// The name of the struct.
+		name: String,
+		/// The names of the fields, along with their nodes.
+		fields: Vec<(String, StorageNode)>,
+	},
+	/// A fixed-size array.
+	Array(Vec<StorageNode>),
+	/// A value stored within its own cell, which is empty when the cell has not been set.
+	Lazy(Option<Box<StorageNode>>),
+	/// A mapping, whose entries can only be read by looking up their keys.
+	Mapping {
+		/// The type of the keys.
+		key: String,
+		/// The type of the values.
+		value: String,
+	},
+}
+
+impl StorageNode {
+	// Writes the node, indenting any nested nodes beyond the specified depth.
+	fn write(&self, f: &mut Formatter<'_>, depth: usize) -> fmt::Result {
+		let indent = "  ".repeat(depth + 1);
+		match self {
+			Self::Value(value) => write!(f, "{value}"),
+			Self::Struct { name, fields } => {
+				write!(f, "{name}")?;
+				for (field, node) in fields {
+					write!(f, "\n{indent}{field}: ")?;
+					node.write(f, depth + 1)?;
+				}
+				Ok(())
+			},
+			Self::Array(nodes) => {
+				write!(f, "[{}]", nodes.len())?;
+				for (index, node) in nodes.iter().enumerate() {
+					write!(f, "\n{indent}{index}: ")?;
+					node.write(f, depth + 1)?;
+				}
+				Ok(())
+			},
+			Self::Lazy(Some(node)) => node.write(f, depth),
+			Self::Lazy(None) => write!(f, "<empty>"),
+			Self::Mapping { key, value } => write!(f, "Mapping<{key}, {value}>"),
+		}
+	}
+}
+
+impl Display for StorageNode {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		self.write(f, 0)
+	}
+}
+
+/// The storage of a deployed contract, decoded using the storage layout from its metadata.
+pub struct ContractStorage {
+	layout: StorageLayout,
+	client: RpcClient,
+	// The prefixed key of the child trie holding the storage of the contract.
+	child: Vec<u8>,
+	// The block at which the storage is read.
+	at: H256,
+}
+
+impl ContractStorage {
+	/// Locates the storage of a deployed contract.
+	///
+	/// # Arguments
+	/// * `path` - Location path of the project or contract artifact.
+	/// * `contract` - The address of the contract.
+	/// * `url` - Endpoint of the node.
+	/// * `at` - The block at which the storage is read, otherwise the best block.
+	pub async fn new(
+		path: &Path,
+		contract: &str,
+		url: &Url,
+		at: Option<BlockId>,
+	) -> Result<Self, Error> {
+		let layout = StorageLayout { transcoder: get_contract_transcoder(path)? };
+		let client = RpcClient::from_url(url.as_str())
+			.await
+			.map_err(|e| Error::StorageError(e.to_string()))?;
+		let at = match at {
+			Some(BlockId::Hash(hash)) => hash,
+			Some(BlockId::Number(number)) => client
+				.request::<Option<H256>>("chain_getBlockHash", rpc_params![number])
+				.await
+				.map_err(|e| Error::StorageError(e.to_string()))?
+				.ok_or_else(|| Error::StorageError(format!("block {number} not found")))?,
+			None => client
+				.request("chain_getBlockHash", rpc_params![])
+				.await
+				.map_err(|e| Error::StorageError(e.to_string()))?,
+		};
+		let child = [CHILD_STORAGE_PREFIX, &trie_id(&client, contract, at).await?].concat();
+		Ok(Self { layout, client, child, at })
+	}
+
+	/// The hash of the block at which the storage is read.
+	pub fn at(&self) -> H256 {
+		self.at
+	}
+
+	/// Reads the storage of the contract, returning its decoded storage tree.
+	pub async fn read(&self) -> Result<StorageNode, Error> {
+		let mut cells = HashMap::new();
+		for key in self.layout.cells() {
+			if let Some(value) = self.get(&key.encode()).await? {
+				cells.insert(key, value);
+			}
+		}
+		self.layout.decode(&cells)
+	}
+
+	/// Looks up an entry of a `Mapping` within the storage of the contract.
+	///
+	/// # Arguments
+	/// * `field` - The path to the mapping within the storage layout, with nested fields separated
+	///   by `.`.
+	/// * `key` - The key of the entry.
+	pub async fn lookup(&self, field: &str, key: &str) -> Result<Option<StorageNode>, Error> {
+		let (key, layout) = self.layout.entry(field, key)?;
+		match self.get(&key).await? {
+			Some(value) => self.layout.decode_entry(layout, &value).map(Some),
+			None => Ok(None),
+		}
+	}
+
+	// Reads a cell of the child trie of the contract, whose keys are hashed using
+	// `Blake2_128Concat`.
+	async fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+		let key = [blake2_128(key).as_slice(), key].concat();
+		let value: Option<Bytes> = self
+			.client
+			.request(
+				"childstate_getStorage",
+				rpc_params![Bytes(self.child.clone()), Bytes(key), self.at],
+			)
+			.await
+			.map_err(|e| Error::StorageError(e.to_string()))?;
+		Ok(value.map(|v| v.0))
+	}
+}
+
+// Retrieves the identifier of the child trie holding the storage of a contract.
+async fn trie_id(client: &RpcClient, contract: &str, at: H256) -> Result<Vec<u8>, Error> {
+	#[cfg(feature = "v5")]
+	let address = Value::from_bytes(parse_account(contract)?.0);
+	#[cfg(feature = "v6")]
+	let address = Value::from_bytes(parse_h160_account(contract)?.as_bytes());
+	let api = OnlineClient::<SubstrateConfig>::from_rpc_client(client.clone())
+		.await
+		.map_err(|e| Error::StorageError(e.to_string()))?;
+	let info = api
+		.storage()
+		.at(at)
+		.fetch(&subxt::dynamic::storage(CONTRACTS_PALLET, "ContractInfoOf", vec![address]))
+		.await
+		.map_err(|e| Error::StorageError(e.to_string()))?
+		.ok_or_else(|| Error::StorageError(format!("no contract found at {contract}")))?;
+	// The identifier of the trie is the first field of the contract info.
+	Vec::<u8>::decode(&mut info.encoded())
+		.map_err(|e| Error::StorageError(format!("invalid contract info: {e}")))
+}
+
+// The storage layout of a contract, along with the types required to decode its cells.
+struct StorageLayout {
+	transcoder: ContractMessageTranscoder,
+}
+
+impl StorageLayout {
+	// The keys of the cells holding the root and any lazy values of the contract.
+	fn cells(&self) -> Vec<u32> {
+		let mut keys = Vec::new();
+		self.collect_cells(self.transcoder.metadata().layout(), &mut keys);
+		keys
+	}
+
+	fn collect_cells(&self, layout: &Layout<PortableForm>, keys: &mut Vec<u32>) {
+		match layout {
+			// The entries of a mapping can only be read by key.
+			Layout::Root(root) if self.mapping(root).is_some() => {},
+			Layout::Root(root) => {
+				keys.push(*root.root_key().key());
+				self.collect_cells(root.layout(), keys);
+			},
+			Layout::Struct(layout) =>
+				layout.fields().iter().for_each(|f| self.collect_cells(f.layout(), keys)),
+			Layout::Enum(layout) => layout
+				.variants()
+				.values()
+				.flat_map(|v| v.fields())
+				.for_each(|f| self.collect_cells(f.layout(), keys)),
+			Layout::Array(layout) => self.collect_cells(layout.layout(), keys),
+			Layout::Leaf(_) | Layout::Hash(_) => {},
+		}
+	}
+
+	// Decodes the storage tree of the contract from the values of its cells.
+	fn decode(&self, cells: &HashMap<u32, Vec<u8>>) -> Result<StorageNode, Error> {
+		match self.decode_layout(self.transcoder.metadata().layout(), cells, &mut &[][..])? {
+			StorageNode::Lazy(Some(node)) => Ok(*node),
+			StorageNode::Lazy(None) =>
+				Err(Error::StorageError("the contract has no storage".into())),
+			node => Ok(node),
+		}
+	}
+
+	// Decodes the value of an entry of a mapping.
+	fn decode_entry(
+		&self,
+		layout: &Layout<PortableForm>,
+		mut value: &[u8],
+	) -> Result<StorageNode, Error> {
+		self.decode_layout(layout, &HashMap::new(), &mut value)
+	}
+
+	fn decode_layout(
+		&self,
+		layout: &Layout<PortableForm>,
+		cells: &HashMap<u32, Vec<u8>>,
+		input: &mut &[u8],
+	) -> Result<StorageNode, Error> {
+		Ok(match layout {
+			Layout::Leaf(leaf) =>
+				StorageNode::Value(self.transcoder.decode(leaf.ty().id, input)?.to_string()),
+			Layout::Root(root) => match self.mapping(root) {
+				Some((key, value)) =>
+					StorageNode::Mapping { key: self.type_name(key), value: self.type_name(value) },
+				// Lazy values are stored within their own cell, rather than packed with their
+				// parent.
+				None => StorageNode::Lazy(match cells.get(root.root_key().key()) {
+					Some(cell) => Some(Box::new(self.decode_layout(
+						root.layout(),
+						cells,
+						&mut cell.as_slice(),
+					)?)),
+					None => None,
+				}),
+			},
+			Layout::Struct(layout) => StorageNode::Struct {
+				name: layout.name().to_string(),
+				fields: self.decode_fields(layout, cells, input)?,
+			},
+			Layout::Enum(layout) => {
+				let discriminant = u8::decode(input)
+					.map_err(|e| Error::StorageError(format!("invalid discriminant: {e}")))?;
+				let variant = layout
+					.variants()
+					.get(&Discriminant::from(discriminant as usize))
+					.ok_or_else(|| {
+						Error::StorageError(format!(
+							"no variant of {} with discriminant {discriminant}",
+							layout.name()
+						))
+					})?;
+				StorageNode::Struct {
+					name: format!("{}::{}", layout.name(), variant.name()),
+					fields: self.decode_fields(variant, cells, input)?,
+				}
+			},
+			Layout::Array(layout) => StorageNode::Array(
+				(0..layout.len())
+					.map(|_| self.decode_layout(layout.layout(), cells, input))
+					.collect::<Result<_, _>>()?,
+			),
+			Layout::Hash(_) =>
+				return Err(Error::StorageError("hashed storage layouts are not supported".into())),
+		})
+	}
+
+	fn decode_fields(
+		&self,
+		layout: &StructLayout<PortableForm>,
+		cells: &HashMap<u32, Vec<u8>>,
+		input: &mut &[u8],
+	) -> Result<Vec<(String, StorageNode)>, Error> {
+		layout
+			.fields()
+			.iter()
+			.map(|f| Ok((f.name().to_string(), self.decode_layout(f.layout(), cells, input)?)))
+			.collect()
+	}
+
+	// Locates a mapping using its path within the storage layout, returning the storage key of
+	// the entry with the specified key along with the layout of its value.
+	fn entry(&self, field: &str, key: &str) -> Result<(Vec<u8>, &Layout<PortableForm>), Error> {
+		let metadata = self.transcoder.metadata();
+		let not_found = || Error::StorageError(format!("no field `{field}` in the storage layout"));
+		let mut layout = metadata.layout();
+		for name in field.split('.') {
+			// Lazy values are traversed transparently.
+			while let Layout::Root(root) = layout {
+				if self.mapping(root).is_some() {
+					return Err(not_found());
+				}
+				layout = root.layout();
+			}
+			layout = match layout {
+				Layout::Struct(layout) =>
+					layout.fields().iter().find(|f| f.name() == name).map(|f| f.layout()),
+				_ => None,
+			}
+			.ok_or_else(not_found)?;
+		}
+		let (root, key_type) = match layout {
+			Layout::Root(root) => self.mapping(root).map(|(key, _)| (root, key)),
+			_ => None,
+		}
+		.ok_or_else(|| Error::StorageError(format!("`{field}` is not a mapping")))?;
+
+		let value = scale_value::stringify::from_str_custom()
+			.add_custom_parser(custom_parsers::parse_hex)
+			.add_custom_parser(custom_parsers::parse_ss58)
+			.parse(key)
+			.0
+			.map_err(|e| Error::StorageError(format!("invalid key {key}: {e}")))?;
+		// The entries of a mapping are stored under the root key of the mapping, followed by the
+		// encoded key of the entry.
+		let mut storage_key = root.root_key().key().encode();
+		scale_value::scale::encode_as_type(&value, key_type, metadata.registry(), &mut storage_key)
+			.map_err(|e| Error::StorageError(format!("invalid key {key}: {e}")))?;
+		Ok((storage_key, root.layout()))
+	}
+
+	// The types of the keys and values of a mapping, if the root layout is that of a mapping.
+	fn mapping(&self, root: &RootLayout<PortableForm>) -> Option<(u32, u32)> {
+		let ty = self.transcoder.metadata().registry().resolve(root.ty().id)?;
+		if ty.path.segments.first().map(String::as_str) != Some("ink_storage") ||
+			ty.path.segments.last().map(String::as_str) != Some("Mapping")
+		{
+			return None;
+		}
+		let param = |name: &str| {
+			ty.type_params.iter().find(|p| p.name == name).and_then(|p| p.ty).map(|t| t.id)
+		};
+		Some((param("K")?, param("V")?))
+	}
+
+	// A concise name of a type, used to describe the entries of a mapping.
+	fn type_name(&self, id: u32) -> String {
+		let registry = self.transcoder.metadata().registry();
+		let Some(ty) = registry.resolve(id) else {
+			return id.to_string();
+		};
+		match ty.path.segments.last() {
+			Some(name) if ty.type_params.is_empty() => name.clone(),
+			Some(name) => {
+				let params: Vec<_> = ty
+					.type_params
+					.iter()
+					.filter_map(|p| p.ty.map(|t| self.type_name(t.id)))
+					.collect();
+				format!("{name}<{}>", params.join(", "))
+			},
+			None => format_type(ty, registry),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::Result;
+	use std::env;
+
+	const ALICE: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+	const ALICE_HEX: &str = "0xd43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d";
+
+	// The storage layout of the testing contract.
+	fn testing() -> Result<StorageLayout> {
+		let current_dir = env::current_dir().expect("Failed to get current directory");
+		Ok(StorageLayout {
+			transcoder: get_contract_transcoder(&current_dir.join("tests/files/testing.contract"))?,
+		})
+	}
+
+	// The storage layout of the testing contract, extended with a lazy value and a mapping.
+	fn extended() -> Result<StorageLayout> {
+		let current_dir = env::current_dir().expect("Failed to get current directory");
+		let mut metadata: serde_json::Value =
+			serde_json::from_slice(&std::fs::read(current_dir.join("tests/files/testing.json"))?)?;
+		let types = metadata["types"].as_array_mut().expect("types are an array");
+		types.extend([
+			serde_json::json!({"id": 15, "type": {"path": ["ink_storage", "lazy", "Lazy"], "params": [{"name": "V", "type": 8}, {"name": "KeyType", "type": 16}], "def": {"composite": {}}}}),
+			serde_json::json!({"id": 16, "type": {"path": ["ink_storage_traits", "impls", "ManualKey"], "def": {"composite": {}}}}),
+			serde_json::json!({"id": 17, "type": {"path": ["ink_storage", "lazy", "mapping", "Mapping"], "params": [{"name": "K", "type": 8}, {"name": "V", "type": 11}, {"name": "KeyType", "type": 16}], "def": {"composite": {}}}}),
+		]);
+		let fields = metadata["storage"]["root"]["layout"]["struct"]["fields"]
+			.as_array_mut()
+			.expect("fields are an array");
+		fields.extend([
+			serde_json::json!({"name": "owner", "layout": {"root": {"root_key": "0x01000000", "layout": {"leaf": {"key": "0x01000000", "ty": 8}}, "ty": 15}}}),
+			serde_json::json!({"name": "balances", "layout": {"root": {"root_key": "0x02000000", "layout": {"leaf": {"key": "0x02000000", "ty": 11}}, "ty": 17}}}),
+		]);
+		Ok(StorageLayout {
+			transcoder: ContractMessageTranscoder::new(serde_json::from_value(metadata)?),
+		})
+	}
+
+	fn alice() -> Vec<u8> {
+		from_hex(ALICE_HEX).expect("valid hex")
+	}
+
+	#[test]
+	fn block_id_from_str_works() -> Result<()> {
+		assert_eq!("42".parse::<BlockId>()?, BlockId::Number(42));
+		assert_eq!(ALICE_HEX.parse::<BlockId>()?, BlockId::Hash(H256::from_slice(&alice())));
+		for block in ["latest", "0x1234"] {
+			assert!(matches!(
+				block.parse::<BlockId>(),
+				Err(Error::StorageError(message)) if message == format!("invalid block {block}, expected a block number or hash")
+			));
+		}
+		Ok(())
+	}
+
+	#[test]
+	fn display_storage_node_works() {
+		let node = StorageNode::Struct {
+			name: "Token".into(),
+			fields: vec![
+				("supply".into(), StorageNode::Value("100".into())),
+				(
+					"owner".into(),
+					StorageNode::Lazy(Some(Box::new(StorageNode::Struct {
+						name: "Owner".into(),
+						fields: vec![("account".into(), StorageNode::Value(ALICE.into()))],
+					}))),
+				),
+				("admin".into(), StorageNode::Lazy(None)),
+				(
+					"limits".into(),
+					StorageNode::Array(vec![
+						StorageNode::Value("1".into()),
+						StorageNode::Value("2".into()),
+					]),
+				),
+				(
+					"balances".into(),
+					StorageNode::Mapping { key: "AccountId".into(), value: "u128".into() },
+				),
+			],
+		};
+		assert_eq!(
+			node.to_string(),
+			format!(
+				"Token\n  supply: 100\n  owner: Owner\n    account: {ALICE}\n  admin: <empty>\n  limits: [2]\n    0: 1\n    1: 2\n  balances: Mapping<AccountId, u128>"
+			)
+		);
+	}
+
+	#[test]
+	fn decode_works() -> Result<()> {
+		let layout = testing()?;
+		assert_eq!(layout.cells(), vec![0]);
+		let cells = HashMap::from([(0, vec![1, 2, 0, 0, 0])]);
+		assert_eq!(
+			layout.decode(&cells)?,
+			StorageNode::Struct {
+				name: "Testing".into(),
+				fields: vec![
+					("value".into(), StorageNode::Value("true".into())),
+					("number".into(), StorageNode::Value("2".into())),
+				],
+			}
+		);
+		assert!(matches!(
+			layout.decode(&HashMap::new()),
+			Err(Error::StorageError(message)) if message == "the contract has no storage"
+		));
+		Ok(())
+	}
+
+	#[test]
+	fn decode_lazy_and_mapping_works() -> Result<()> {
+		let layout = extended()?;
+		assert_eq!(layout.cells(), vec![0, 1]);
+		let mut cells = HashMap::from([(0, vec![0, 2, 0, 0, 0])]);
+		let fields = |owner: StorageNode| StorageNode::Struct {
+			name: "Testing".into(),
+			fields: vec![
+				("value".into(), StorageNode::Value("false".into())),
+				("number".into(), StorageNode::Value("2".into())),
+				("owner".into(), owner),
+				(
+					"balances".into(),
+					StorageNode::Mapping { key: "AccountId".into(), value: "u128".into() },
+				),
+			],
+		};
+		assert_eq!(layout.decode(&cells)?, fields(StorageNode::Lazy(None)));
+		cells.insert(1, alice());
+		assert_eq!(
+			layout.decode(&cells)?,
+			fields(StorageNode::Lazy(Some(Box::new(StorageNode::Value(ALICE.into())))))
+		);
+		Ok(())
+	}
+
+	#[test]
+	fn entry_works() -> Result<()> {
+		let layout = extended()?;
+		let expected = [vec![2, 0, 0, 0], alice()].concat();
+		for key in [ALICE, ALICE_HEX] {
+			let (storage_key, value) = layout.entry("balances", key)?;
+			assert_eq!(storage_key, expected);
+			assert_eq!(
+				layout.decode_entry(value, &100u128.encode())?,
+				StorageNode::Value("100".into())
+			);
+		}
+		assert!(matches!(
+			layout.entry("value", ALICE),
+			Err(Error::StorageError(message)) if message == "`value` is not a mapping"
+		));
+		for field in ["missing", "balances.inner"] {
+			assert!(matches!(
+				layout.entry(field, ALICE),
+				Err(Error::StorageError(message)) if message == format!("no field `{field}` in the storage layout")
+			));
+		}
+		assert!(matches!(
+			layout.entry("balances", "{"),
+			Err(Error::StorageError(message)) if message.starts_with("invalid key {")
+		));
+		Ok(())
+	}
+}
@@ -2,6 +2,7 @@
 
 use crate::{
 	errors::Error,
+	events::{decode_contract_events, ContractEvent},
 	utils::{
 		get_manifest_path,
 		metadata::{extract_function, process_function_args, FunctionType},
@@ -11,11 +12,12 @@ use crate::{
 };
 #[cfg(feature = "v6")]
 use pop_common::account_id::parse_h160_account;
-use pop_common::{create_signer, DefaultConfig, Keypair};
+use pop_common::{create_signer, DefaultConfig, Signer};
 use std::path::{Path, PathBuf};
 use subxt::{
 	blocks::ExtrinsicEvents,
 	tx::{Payload, SubmittableExtrinsic},
+	utils::H256,
 	SubstrateConfig,
 };
 #[cfg(feature = "v5")]
@@ -74,7 +76,7 @@ pub struct UpOpts {
 /// * `up_opts` - options for the `up` command.
 pub async fn set_up_deployment(
 	up_opts: UpOpts,
-) -> anyhow::Result<InstantiateExec<DefaultConfig, DefaultEnvironment, Keypair>> {
+) -> anyhow::Result<InstantiateExec<DefaultConfig, DefaultEnvironment, Signer>> {
 	let manifest_path = get_manifest_path(up_opts.path.as_deref())?;
 
 	let token_metadata = TokenMetadata::query::<DefaultConfig>(&up_opts.url).await?;
@@ -95,7 +97,7 @@ pub async fn set_up_deployment(
 		FunctionType::Constructor,
 	)?;
 	let args = process_function_args(&function, up_opts.args)?;
-	let instantiate_exec: InstantiateExec<DefaultConfig, DefaultEnvironment, Keypair> =
+	let instantiate_exec: InstantiateExec<DefaultConfig, DefaultEnvironment, Signer> =
 		InstantiateCommandBuilder::new(extrinsic_opts)
 			.constructor(up_opts.constructor.clone())
 			.args(args)
@@ -115,7 +117,7 @@ pub async fn set_up_deployment(
 /// * `up_opts` - options for the `up` command.
 pub async fn set_up_upload(
 	up_opts: UpOpts,
-) -> anyhow::Result<UploadExec<DefaultConfig, DefaultEnvironment, Keypair>> {
+) -> anyhow::Result<UploadExec<DefaultConfig, DefaultEnvironment, Signer>> {
 	let manifest_path = get_manifest_path(up_opts.path.as_deref())?;
 
 	let signer = create_signer(&up_opts.suri)?;
@@ -125,7 +127,7 @@ pub async fn set_up_upload(
 		.done();
 
 	#[allow(unused_mut)]
-	let mut upload_exec: UploadExec<DefaultConfig, DefaultEnvironment, Keypair> =
+	let mut upload_exec: UploadExec<DefaultConfig, DefaultEnvironment, Signer> =
 		UploadCommandBuilder::new(extrinsic_opts).done().await?;
 
 	#[cfg(feature = "v6")]
@@ -172,7 +174,7 @@ pub async fn get_upload_payload(code: ContractBinary, url: &str) -> anyhow::Resu
 /// * `url` - the rpc of the chain node.
 #[cfg(feature = "v6")]
 pub async fn get_upload_payload(
-	upload_exec: UploadExec<DefaultConfig, DefaultEnvironment, Keypair>,
+	upload_exec: UploadExec<DefaultConfig, DefaultEnvironment, Signer>,
 	code: ContractBinary,
 	url: &str,
 ) -> anyhow::Result<Vec<u8>> {
@@ -203,7 +205,7 @@ pub async fn get_upload_payload(
 /// * `gas_limit` - max amount of gas to be used for instantiation.
 #[cfg(feature = "v5")]
 pub fn get_instantiate_payload(
-	instantiate_exec: InstantiateExec<DefaultConfig, DefaultEnvironment, Keypair>,
+	instantiate_exec: InstantiateExec<DefaultConfig, DefaultEnvironment, Signer>,
 	gas_limit: Weight,
 ) -> anyhow::Result<Vec<u8>> {
 	let storage_deposit_limit: Option<u128> = None;
@@ -241,7 +243,7 @@ pub fn get_instantiate_payload(
 /// * `gas_limit` - max amount of gas to be used for instantiation.
 #[cfg(feature = "v6")]
 pub async fn get_instantiate_payload(
-	instantiate_exec: InstantiateExec<DefaultConfig, DefaultEnvironment, Keypair>,
+	instantiate_exec: InstantiateExec<DefaultConfig, DefaultEnvironment, Signer>,
 	gas_limit: Weight,
 ) -> anyhow::Result<Vec<u8>> {
 	let storage_deposit_limit = instantiate_exec.estimate_limits().await?.1;
@@ -283,7 +285,7 @@ pub fn get_contract_code(path: Option<&PathBuf>) -> anyhow::Result<ContractBinar
 	// signer does not matter for this
 	let signer = create_signer("//Alice")?;
 	let extrinsic_opts =
-		ExtrinsicOptsBuilder::<DefaultConfig, DefaultEnvironment, Keypair>::new(signer)
+		ExtrinsicOptsBuilder::<DefaultConfig, DefaultEnvironment, Signer>::new(signer)
 			.manifest_path(Some(manifest_path))
 			.done();
 	let artifacts = extrinsic_opts.contract_artifacts()?;
@@ -329,7 +331,7 @@ pub async fn instantiate_contract_signed(
 	#[cfg(feature = "v6")] instantiate_exec: InstantiateExec<
 		DefaultConfig,
 		DefaultEnvironment,
-		Keypair,
+		Signer,
 	>,
 	#[cfg(feature = "v6")] maybe_contract_address: Option<String>,
 	url: &str,
@@ -428,7 +430,7 @@ pub async fn submit_signed_payload(
 /// # Arguments
 /// * `instantiate_exec` - the preprocessed data to instantiate a contract.
 pub async fn dry_run_gas_estimate_instantiate(
-	instantiate_exec: &InstantiateExec<DefaultConfig, DefaultEnvironment, Keypair>,
+	instantiate_exec: &InstantiateExec<DefaultConfig, DefaultEnvironment, Signer>,
 ) -> Result<Weight, Error> {
 	let instantiate_result = instantiate_exec.instantiate_dry_run().await?;
 	match instantiate_result.result {
@@ -465,7 +467,7 @@ pub struct UploadDryRunResult {
 /// # Arguments
 /// * `upload_exec` - the preprocessed data to upload a contract.
 pub async fn dry_run_upload(
-	upload_exec: &UploadExec<DefaultConfig, DefaultEnvironment, Keypair>,
+	upload_exec: &UploadExec<DefaultConfig, DefaultEnvironment, Signer>,
 ) -> Result<UploadDryRunResult, Error> {
 	match upload_exec.upload_code_rpc().await? {
 		Ok(result) => {
@@ -489,6 +491,11 @@ pub struct ContractInfo {
 	pub address: String,
 	/// The hash of the contract's code
 	pub code_hash: Option<String>,
+	/// The hash of the extrinsic which instantiated the contract.
+	pub extrinsic_hash: H256,
+	/// The events emitted by contracts during the instantiation, decoded using the contract
+	/// metadata.
+	pub events: Vec<ContractEvent>,
 }
 
 /// Instantiate a contract.
@@ -497,7 +504,7 @@ pub struct ContractInfo {
 /// * `instantiate_exec` - the preprocessed data to instantiate a contract.
 /// * `gas_limit` - maximum amount of gas to be used for this call.
 pub async fn instantiate_smart_contract(
-	instantiate_exec: InstantiateExec<DefaultConfig, DefaultEnvironment, Keypair>,
+	instantiate_exec: InstantiateExec<DefaultConfig, DefaultEnvironment, Signer>,
 	gas_limit: Weight,
 ) -> anyhow::Result<ContractInfo, Error> {
 	let instantiate_result = instantiate_exec
@@ -516,7 +523,18 @@ pub async fn instantiate_smart_contract(
 	#[cfg(feature = "v6")]
 	let address = format!("{:?}", instantiate_result.contract_address);
 
-	Ok(ContractInfo { address, code_hash: hash })
+	// The contract has already been instantiated, so events which cannot be decoded are not an
+	// error.
+	let events = decode_contract_events(
+		instantiate_exec.transcoder(),
+		instantiate_result.events.iter(),
+		None,
+	)
+	.unwrap_or_default();
+
+	let extrinsic_hash = instantiate_result.events.extrinsic_hash();
+
+	Ok(ContractInfo { address, code_hash: hash, extrinsic_hash, events })
 }
 
 /// Upload a contract.
@@ -524,7 +542,7 @@ pub async fn instantiate_smart_contract(
 /// # Arguments
 /// * `upload_exec` - the preprocessed data to upload a contract.
 pub async fn upload_smart_contract(
-	upload_exec: &UploadExec<DefaultConfig, DefaultEnvironment, Keypair>,
+	upload_exec: &UploadExec<DefaultConfig, DefaultEnvironment, Signer>,
 ) -> anyhow::Result<String, Error> {
 	#[allow(unused_variables)]
 	let upload_result = upload_exec
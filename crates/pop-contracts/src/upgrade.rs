@@ -0,0 +1,500 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use crate::{errors::Error, utils::metadata::get_contract_transcoder};
+use pop_common::{create_signer, format_type, DefaultConfig};
+use scale_info::{form::PortableForm, PortableRegistry, TypeDef};
+use scale_value::{ValueDef, Variant};
+use std::{
+	collections::HashSet,
+	fmt::{self, Display, Formatter},
+	path::Path,
+};
+use subxt::{
+	backend::rpc::RpcClient,
+	dynamic::{self, Value},
+	OnlineClient,
+};
+use url::Url;
+#[cfg(feature = "v5")]
+use {
+	contract_transcode::ink_metadata::layout::{Layout, StructLayout},
+	pop_common::parse_account,
+	sp_core::bytes::from_hex,
+};
+#[cfg(feature = "v6")]
+use {
+	contract_transcode_inkv6::ink_metadata::layout::{Layout, StructLayout},
+	pop_common::parse_h160_account,
+	sp_core_inkv6::bytes::from_hex,
+};
+
+// The pallet holding the code of deployed contracts.
+#[cfg(feature = "v5")]
+const CONTRACTS_PALLET: &str = "Contracts";
+#[cfg(feature = "v6")]
+const CONTRACTS_PALLET: &str = "Revive";
+
+/// A change to the storage layout of a contract which is incompatible with the existing storage of
+/// the contract.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LayoutChange {
+	/// A field was added within the storage cell of its parent, which can then no longer be
+	/// decoded.
+	Added {
+		/// The path of the field.
+		field: String,
+	},
+	/// A field, or a variant of an enum, was removed.
+	Removed {
+		/// The path of the field.
+		field: String,
+	},
+	/// A field was moved to another position within its parent.
+	Reordered {
+		/// The path of the field.
+		field: String,
+		/// The previous position of the field.
+		from: usize,
+		/// The new position of the field.
+		to: usize,
+	},
+	/// A field was changed to a type with a different encoding, or to a different storage key.
+	Retyped {
+		/// The path of the field.
+		field: String,
+		/// The previous type of the field.
+		from: String,
+		/// The new type of the field.
+		to: String,
+	},
+}
+
+impl Display for LayoutChange {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		match self {
+			LayoutChange::Added { field } =>
+				write!(f, "`{field}` was added without a storage key of its own"),
+			LayoutChange::Removed { field } => write!(f, "`{field}` was removed"),
+			LayoutChange::Reordered { field, from, to } =>
+				write!(f, "`{field}` was moved from position {from} to {to}"),
+			LayoutChange::Retyped { field, from, to } =>
+				write!(f, "`{field}` was changed from {from} to {to}"),
+		}
+	}
+}
+
+/// Compares the storage layouts of a deployed contract and the code it is being upgraded to,
+/// returning the changes which are incompatible with the existing storage of the contract.
+///
+/// Types may be renamed, provided the encoding of existing fields is unchanged. Fields may only be
+/// added when stored under a new storage key of their own, such as `Lazy` values and `Mapping`s:
+/// fields packed within the storage cell of their parent would prevent the existing contents of the
+/// cell from being decoded.
+///
+/// # Arguments
+/// * `deployed` - Location path of the project or contract artifact of the deployed contract.
+/// * `upgraded` - Location path of the project or contract artifact of the new code.
+pub fn compare_storage_layouts(
+	deployed: &Path,
+	upgraded: &Path,
+) -> Result<Vec<LayoutChange>, Error> {
+	let deployed = get_contract_transcoder(deployed)?;
+	let upgraded = get_contract_transcoder(upgraded)?;
+	let mut root_keys = HashSet::new();
+	collect_root_keys(deployed.metadata().layout(), &mut root_keys);
+	let mut comparison = LayoutComparison {
+		deployed: deployed.metadata().registry(),
+		upgraded: upgraded.metadata().registry(),
+		root_keys,
+		changes: Vec::new(),
+	};
+	comparison.compare("", deployed.metadata().layout(), upgraded.metadata().layout());
+	Ok(comparison.changes)
+}
+
+/// Sets the code of a contract using the `set_code` call of the contracts pallet, dispatched by
+/// the sudo account.
+///
+/// # Arguments
+/// * `url` - Endpoint of the node.
+/// * `suri` - Secret key URI of the sudo account.
+/// * `contract` - The address of the contract.
+/// * `code_hash` - The hash of the uploaded code, encoded as hex.
+pub async fn set_code(url: &Url, suri: &str, contract: &str, code_hash: &str) -> Result<(), Error> {
+	let code_hash = from_hex(code_hash)
+		.ok()
+		.filter(|hash| hash.len() == 32)
+		.ok_or_else(|| Error::UpgradeError(format!("invalid code hash {code_hash}")))?;
+	#[cfg(feature = "v5")]
+	let dest = Value::unnamed_variant("Id", [Value::from_bytes(parse_account(contract)?)]);
+	#[cfg(feature = "v6")]
+	let dest = Value::from_bytes(parse_h160_account(contract)?);
+	let set_code =
+		dynamic::tx(CONTRACTS_PALLET, "set_code", vec![dest, Value::from_bytes(code_hash)]);
+	let sudo = dynamic::tx("Sudo", "sudo", vec![set_code.into_value()]);
+
+	let signer = create_signer(suri)?;
+	let submit = async {
+		let rpc_client = RpcClient::from_url(url.as_str()).await?;
+		let client = OnlineClient::<DefaultConfig>::from_rpc_client(rpc_client).await?;
+		client
+			.tx()
+			.sign_and_submit_then_watch_default(&sudo, &signer)
+			.await?
+			.wait_for_finalized_success()
+			.await
+	};
+	let events = submit.await.map_err(|e| Error::UpgradeError(e.to_string()))?;
+	// `Sudo::sudo` succeeds regardless of the result of the dispatched call, which is only
+	// reported by the `Sudid` event.
+	for event in events.iter() {
+		let event = event.map_err(|e| Error::UpgradeError(e.to_string()))?;
+		if event.pallet_name() != "Sudo" || event.variant_name() != "Sudid" {
+			continue;
+		}
+		let fields = event.field_values().map_err(|e| Error::UpgradeError(e.to_string()))?;
+		let error = fields
+			.values()
+			.find(
+				|value| matches!(&value.value, ValueDef::Variant(Variant { name, .. }) if name == "Err"),
+			)
+			.map(|result| result.to_string());
+		if let Some(error) = error {
+			return Err(Error::UpgradeError(format!("`set_code` failed: {error}")));
+		}
+	}
+	Ok(())
+}
+
+// Compares the storage layouts of two versions of a contract, resolving types using the registry
+// of each version.
+struct LayoutComparison<'a> {
+	deployed: &'a PortableRegistry,
+	upgraded: &'a PortableRegistry,
+	// The keys of the storage cells used by the deployed contract.
+	root_keys: HashSet<u32>,
+	changes: Vec<LayoutChange>,
+}
+
+impl LayoutComparison<'_> {
+	fn compare(
+		&mut self,
+		field: &str,
+		deployed: &Layout<PortableForm>,
+		upgraded: &Layout<PortableForm>,
+	) {
+		match (deployed, upgraded) {
+			// Lazy values and mappings are stored within their own cells, so must retain their
+			// keys along with the types of any keys and values.
+			(Layout::Root(d), Layout::Root(u))
+				if d.root_key() == u.root_key() && self.same_params(d.ty().id, u.ty().id) =>
+				self.compare(field, d.layout(), u.layout()),
+			(Layout::Struct(d), Layout::Struct(u)) => self.compare_fields(field, d, u),
+			(Layout::Enum(d), Layout::Enum(u)) =>
+				for (discriminant, variant) in d.variants() {
+					let path = format!("{field}::{}", variant.name());
+					match u.variants().get(discriminant) {
+						Some(u) if u.name() == variant.name() =>
+							self.compare_fields(&path, variant, u),
+						_ => self.changes.push(LayoutChange::Removed { field: path }),
+					}
+				},
+			(Layout::Array(d), Layout::Array(u)) if d.len() == u.len() =>
+				self.compare(field, d.layout(), u.layout()),
+			(Layout::Leaf(d), Layout::Leaf(u))
+				if self.same_type(d.ty().id, u.ty().id, &mut HashSet::new()) => {},
+			(Layout::Hash(_), Layout::Hash(_)) => {},
+			_ => self.changes.push(LayoutChange::Retyped {
+				field: field.to_string(),
+				from: describe(deployed, self.deployed),
+				to: describe(upgraded, self.upgraded),
+			}),
+		}
+	}
+
+	fn compare_fields(
+		&mut self,
+		parent: &str,
+		deployed: &StructLayout<PortableForm>,
+		upgraded: &StructLayout<PortableForm>,
+	) {
+		// Fields are reordered when their order relative to the fields retained by both versions
+		// changes, so that removing a field does not also report each subsequent field as moved.
+		let retained: Vec<_> = upgraded
+			.fields()
+			.iter()
+			.filter(|u| deployed.fields().iter().any(|d| d.name() == u.name()))
+			.map(|u| u.name())
+			.collect();
+		let mut rank = 0;
+		for (position, d) in deployed.fields().iter().enumerate() {
+			let field = match parent {
+				"" => d.name().to_string(),
+				parent => format!("{parent}.{}", d.name()),
+			};
+			let Some(to) = upgraded.fields().iter().position(|u| u.name() == d.name()) else {
+				self.changes.push(LayoutChange::Removed { field });
+				continue;
+			};
+			if retained.get(rank) == Some(&d.name()) {
+				self.compare(&field, d.layout(), upgraded.fields()[to].layout());
+			} else {
+				self.changes.push(LayoutChange::Reordered { field, from: position, to });
+			}
+			rank += 1;
+		}
+		for u in upgraded.fields() {
+			if deployed.fields().iter().any(|d| d.name() == u.name()) ||
+				self.has_own_storage(u.layout())
+			{
+				continue;
+			}
+			let field = match parent {
+				"" => u.name().to_string(),
+				parent => format!("{parent}.{}", u.name()),
+			};
+			self.changes.push(LayoutChange::Added { field });
+		}
+	}
+
+	// Whether an added field is stored entirely within storage cells unused by the deployed
+	// contract, rather than within the cell of its parent.
+	fn has_own_storage(&self, layout: &Layout<PortableForm>) -> bool {
+		match layout {
+			Layout::Root(root) => !self.root_keys.contains(root.root_key().key()),
+			Layout::Struct(layout) =>
+				layout.fields().iter().all(|field| self.has_own_storage(field.layout())),
+			_ => false,
+		}
+	}
+
+	// Whether the type parameters of two types are encoded identically, such as the keys and
+	// values of a mapping.
+	fn same_params(&self, deployed: u32, upgraded: u32) -> bool {
+		let (Some(d), Some(u)) = (self.deployed.resolve(deployed), self.upgraded.resolve(upgraded))
+		else {
+			return false;
+		};
+		d.type_params.len() == u.type_params.len() &&
+			d.type_params.iter().zip(&u.type_params).all(|(d, u)| match (d.ty, u.ty) {
+				(Some(d), Some(u)) => self.same_type(d.id, u.id, &mut HashSet::new()),
+				(d, u) => d.is_none() && u.is_none(),
+			})
+	}
+
+	// Whether two types are encoded identically, irrespective of their names.
+	fn same_type(&self, deployed: u32, upgraded: u32, visited: &mut HashSet<(u32, u32)>) -> bool {
+		// Recursive types are assumed to be identical once already being compared.
+		if !visited.insert((deployed, upgraded)) {
+			return true;
+		}
+		let (Some(d), Some(u)) = (self.deployed.resolve(deployed), self.upgraded.resolve(upgraded))
+		else {
+			return false;
+		};
+		let mut same_fields = |d: &[scale_info::Field<PortableForm>],
+		                       u: &[scale_info::Field<PortableForm>]| {
+			d.len() == u.len() &&
+				d.iter().zip(u).all(|(d, u)| self.same_type(d.ty.id, u.ty.id, visited))
+		};
+		match (&d.type_def, &u.type_def) {
+			(TypeDef::Composite(d), TypeDef::Composite(u)) => same_fields(&d.fields, &u.fields),
+			// Variants may be added, but existing variants must be encoded identically.
+			(TypeDef::Variant(d), TypeDef::Variant(u)) => d.variants.iter().all(|d| {
+				u.variants
+					.iter()
+					.find(|u| u.index == d.index)
+					.is_some_and(|u| same_fields(&d.fields, &u.fields))
+			}),
+			(TypeDef::Sequence(d), TypeDef::Sequence(u)) =>
+				self.same_type(d.type_param.id, u.type_param.id, visited),
+			(TypeDef::Array(d), TypeDef::Array(u)) =>
+				d.len == u.len && self.same_type(d.type_param.id, u.type_param.id, visited),
+			(TypeDef::Tuple(d), TypeDef::Tuple(u)) =>
+				d.fields.len() == u.fields.len() &&
+					d.fields
+						.iter()
+						.zip(&u.fields)
+						.all(|(d, u)| self.same_type(d.id, u.id, visited)),
+			(TypeDef::Primitive(d), TypeDef::Primitive(u)) => d == u,
+			(TypeDef::Compact(d), TypeDef::Compact(u)) =>
+				self.same_type(d.type_param.id, u.type_param.id, visited),
+			(TypeDef::BitSequence(d), TypeDef::BitSequence(u)) =>
+				self.same_type(d.bit_store_type.id, u.bit_store_type.id, visited) &&
+					self.same_type(d.bit_order_type.id, u.bit_order_type.id, visited),
+			_ => false,
+		}
+	}
+}
+
+// Collects the keys of the storage cells of a layout.
+fn collect_root_keys(layout: &Layout<PortableForm>, keys: &mut HashSet<u32>) {
+	match layout {
+		Layout::Root(root) => {
+			keys.insert(*root.root_key().key());
+			collect_root_keys(root.layout(), keys);
+		},
+		Layout::Struct(layout) =>
+			layout.fields().iter().for_each(|f| collect_root_keys(f.layout(), keys)),
+		Layout::Enum(layout) => layout
+			.variants()
+			.values()
+			.flat_map(|v| v.fields())
+			.for_each(|f| collect_root_keys(f.layout(), keys)),
+		Layout::Array(layout) => collect_root_keys(layout.layout(), keys),
+		Layout::Leaf(_) | Layout::Hash(_) => {},
+	}
+}
+
+// A concise description of a storage layout, used to report incompatible changes.
+fn describe(layout: &Layout<PortableForm>, registry: &PortableRegistry) -> String {
+	let type_name = |id: u32| {
+		registry
+			.resolve(id)
+			.map(|ty| format_type(ty, registry))
+			.unwrap_or_else(|| id.to_string())
+	};
+	match layout {
+		Layout::Leaf(leaf) => type_name(leaf.ty().id),
+		Layout::Root(root) =>
+			format!("{} at key 0x{:08x}", type_name(root.ty().id), root.root_key().key()),
+		Layout::Struct(layout) => layout.name().to_string(),
+		Layout::Enum(layout) => layout.name().to_string(),
+		Layout::Array(layout) =>
+			format!("[{}; {}]", describe(layout.layout(), registry), layout.len()),
+		Layout::Hash(_) => "a hashed layout".to_string(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::Result;
+	use std::{env, fs};
+	use tempfile::TempDir;
+
+	// Writes the metadata of the testing contract, modified by the specified function, returning
+	// its path.
+	fn testing(
+		temp_dir: &TempDir,
+		name: &str,
+		modify: impl FnOnce(&mut serde_json::Value),
+	) -> Result<std::path::PathBuf> {
+		let current_dir = env::current_dir().expect("Failed to get current directory");
+		let mut metadata: serde_json::Value =
+			serde_json::from_slice(&fs::read(current_dir.join("tests/files/testing.json"))?)?;
+		modify(&mut metadata);
+		let path = temp_dir.path().join(format!("{name}.json"));
+		fs::write(&path, serde_json::to_vec(&metadata)?)?;
+		Ok(path)
+	}
+
+	fn fields(metadata: &mut serde_json::Value) -> &mut Vec<serde_json::Value> {
+		metadata["storage"]["root"]["layout"]["struct"]["fields"]
+			.as_array_mut()
+			.expect("fields are an array")
+	}
+
+	#[test]
+	fn compare_identical_storage_layouts_works() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let deployed = testing(&temp_dir, "deployed", |_| {})?;
+		assert!(compare_storage_layouts(&deployed, &deployed)?.is_empty());
+		Ok(())
+	}
+
+	#[test]
+	fn compare_compatible_storage_layouts_works() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let deployed = testing(&temp_dir, "deployed", |_| {})?;
+		// Appending a lazy field under a new key and renaming the storage struct are compatible.
+		let upgraded = testing(&temp_dir, "upgraded", |metadata| {
+			fields(metadata).push(
+				serde_json::json!({"name": "total", "layout": {"root": {"root_key": "0x01000000", "layout": {"leaf": {"key": "0x01000000", "ty": 1}}, "ty": 1}}}),
+			);
+			metadata["storage"]["root"]["layout"]["struct"]["name"] = "TestingV2".into();
+		})?;
+		assert!(compare_storage_layouts(&deployed, &upgraded)?.is_empty());
+		Ok(())
+	}
+
+	#[test]
+	fn compare_incompatible_storage_layouts_works() -> Result<()> {
+		let temp_dir = tempfile::tempdir()?;
+		let deployed = testing(&temp_dir, "deployed", |_| {})?;
+
+		let removed = testing(&temp_dir, "removed", |metadata| {
+			fields(metadata).remove(1);
+		})?;
+		assert_eq!(
+			compare_storage_layouts(&deployed, &removed)?,
+			vec![LayoutChange::Removed { field: "number".into() }]
+		);
+
+		// Fields packed within the root cell, or stored under an existing key, cannot be added.
+		let added = testing(&temp_dir, "added", |metadata| {
+			fields(metadata).push(
+				serde_json::json!({"name": "total", "layout": {"leaf": {"key": "0x00000000", "ty": 1}}}),
+			);
+			fields(metadata).push(
+				serde_json::json!({"name": "shared", "layout": {"root": {"root_key": "0x00000000", "layout": {"leaf": {"key": "0x00000000", "ty": 1}}, "ty": 1}}}),
+			);
+		})?;
+		let changes = compare_storage_layouts(&deployed, &added)?;
+		assert_eq!(
+			changes,
+			vec![
+				LayoutChange::Added { field: "total".into() },
+				LayoutChange::Added { field: "shared".into() },
+			]
+		);
+		assert_eq!(changes[0].to_string(), "`total` was added without a storage key of its own");
+
+		// Fields following a removed field are not reported as moved.
+		let removed = testing(&temp_dir, "removed-first", |metadata| {
+			fields(metadata).remove(0);
+		})?;
+		assert_eq!(
+			compare_storage_layouts(&deployed, &removed)?,
+			vec![LayoutChange::Removed { field: "value".into() }]
+		);
+
+		let reordered = testing(&temp_dir, "reordered", |metadata| {
+			fields(metadata).swap(0, 1);
+		})?;
+		assert_eq!(
+			compare_storage_layouts(&deployed, &reordered)?,
+			vec![
+				LayoutChange::Reordered { field: "value".into(), from: 0, to: 1 },
+				LayoutChange::Reordered { field: "number".into(), from: 1, to: 0 },
+			]
+		);
+
+		let retyped = testing(&temp_dir, "retyped", |metadata| {
+			fields(metadata)[1]["layout"]["leaf"]["ty"] = 11.into();
+		})?;
+		let changes = compare_storage_layouts(&deployed, &retyped)?;
+		assert_eq!(
+			changes,
+			vec![LayoutChange::Retyped {
+				field: "number".into(),
+				from: "u32".into(),
+				to: "u128".into()
+			}]
+		);
+		assert_eq!(changes[0].to_string(), "`number` was changed from u32 to u128");
+		Ok(())
+	}
+
+	#[test]
+	fn display_layout_change_works() {
+		assert_eq!(
+			LayoutChange::Removed { field: "owner.account".into() }.to_string(),
+			"`owner.account` was removed"
+		);
+		assert_eq!(
+			LayoutChange::Reordered { field: "value".into(), from: 0, to: 1 }.to_string(),
+			"`value` was moved from position 0 to 1"
+		);
+	}
+}
@@ -5,11 +5,14 @@ use pop_common::format_type;
 use scale_info::{form::PortableForm, PortableRegistry};
 use std::path::Path;
 #[cfg(feature = "v5")]
-use {contract_extrinsics::ContractArtifacts, contract_transcode::ink_metadata::MessageParamSpec};
+use {
+	contract_extrinsics::ContractArtifacts,
+	contract_transcode::{ink_metadata::MessageParamSpec, ContractMessageTranscoder},
+};
 #[cfg(feature = "v6")]
 use {
 	contract_extrinsics_inkv6::ContractArtifacts,
-	contract_transcode_inkv6::ink_metadata::MessageParamSpec,
+	contract_transcode_inkv6::{ink_metadata::MessageParamSpec, ContractMessageTranscoder},
 };
 
 /// Describes a parameter.
@@ -79,14 +82,7 @@ fn get_contract_functions(
 	path: &Path,
 	function_type: FunctionType,
 ) -> Result<Vec<ContractFunction>, Error> {
-	let contract_artifacts = if path.is_dir() || path.ends_with("Cargo.toml") {
-		let cargo_toml_path =
-			if path.ends_with("Cargo.toml") { path.to_path_buf() } else { path.join("Cargo.toml") };
-		ContractArtifacts::from_manifest_or_file(Some(&cargo_toml_path), None)?
-	} else {
-		ContractArtifacts::from_manifest_or_file(None, Some(&path.to_path_buf()))?
-	};
-	let transcoder = contract_artifacts.contract_transcoder()?;
+	let transcoder = get_contract_transcoder(path)?;
 	let metadata = transcoder.metadata();
 
 	Ok(match function_type {
@@ -128,6 +124,33 @@ fn get_contract_functions(
 	})
 }
 
+/// Loads the transcoder of a smart contract, parsing the contract artifact.
+///
+/// # Arguments
+/// * `path` - Location path of the project or contract artifact.
+pub(crate) fn get_contract_transcoder(path: &Path) -> Result<ContractMessageTranscoder, Error> {
+	Ok(get_contract_artifacts(path)?.contract_transcoder()?)
+}
+
+/// Extracts the name of a smart contract parsing the contract artifact.
+///
+/// # Arguments
+/// * `path` - Location path of the project or contract artifact.
+pub fn get_contract_name(path: &Path) -> Result<String, Error> {
+	Ok(get_contract_artifacts(path)?.metadata()?.contract.name)
+}
+
+// Loads the artifacts of a smart contract, from either the project or a contract artifact.
+pub(crate) fn get_contract_artifacts(path: &Path) -> Result<ContractArtifacts, Error> {
+	Ok(if path.is_dir() || path.ends_with("Cargo.toml") {
+		let cargo_toml_path =
+			if path.ends_with("Cargo.toml") { path.to_path_buf() } else { path.join("Cargo.toml") };
+		ContractArtifacts::from_manifest_or_file(Some(&cargo_toml_path), None)?
+	} else {
+		ContractArtifacts::from_manifest_or_file(None, Some(&path.to_path_buf()))?
+	})
+}
+
 /// Extracts the information of a smart contract message parsing the contract artifact.
 ///
 /// # Arguments
@@ -424,4 +447,14 @@ mod tests {
 		);
 		Ok(())
 	}
+
+	#[test]
+	fn get_contract_name_works() -> Result<()> {
+		let current_dir = env::current_dir().expect("Failed to get current directory");
+		assert_eq!(
+			get_contract_name(&current_dir.join("./tests/files/testing.contract"))?,
+			"testing"
+		);
+		Ok(())
+	}
 }
@@ -0,0 +1,136 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use crate::{errors::Error, up::get_contract_code, utils::metadata::get_contract_artifacts};
+#[cfg(feature = "v5")]
+use pop_common::parse_account;
+#[cfg(feature = "v6")]
+use pop_common::parse_h160_account;
+use scale_value::{At, Primitive, ValueDef};
+use std::path::Path;
+use subxt::{backend::rpc::RpcClient, dynamic::Value, utils::H256, OnlineClient, SubstrateConfig};
+use url::Url;
+
+// The pallet holding the information of deployed contracts.
+#[cfg(feature = "v5")]
+const CONTRACTS_PALLET: &str = "Contracts";
+#[cfg(feature = "v6")]
+const CONTRACTS_PALLET: &str = "Revive";
+
+/// The result of verifying a deployed contract against a build of a project.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Verification {
+	/// The hash of the code of the deployed contract.
+	pub deployed: H256,
+	/// The hash of the code built from the project.
+	pub built: H256,
+	/// The image used to build the code, as recorded in the metadata of the contract.
+	pub image: Option<String>,
+}
+
+impl Verification {
+	/// Whether the code of the deployed contract is identical to the code built from the project.
+	pub fn is_verified(&self) -> bool {
+		self.deployed == self.built
+	}
+}
+
+/// Verifies a deployed contract against the build of a project, by comparing the hash of the
+/// code of the contract on chain with that of the built code.
+///
+/// # Arguments
+/// * `path` - Location path of the project or contract artifact.
+/// * `contract` - The address of the contract.
+/// * `url` - Endpoint of the node.
+pub async fn verify_contract(
+	path: &Path,
+	contract: &str,
+	url: &Url,
+) -> Result<Verification, Error> {
+	let built = H256(get_contract_code(Some(&path.to_path_buf()))?.code_hash());
+	let image = get_contract_artifacts(path)?.metadata()?.image;
+	let deployed = deployed_code_hash(url, contract).await?;
+	Ok(Verification { deployed, built, image })
+}
+
+/// Returns the image used to build a contract, as recorded within its metadata, if any.
+///
+/// # Arguments
+/// * `path` - Location path of the project or contract artifact.
+pub fn recorded_image(path: &Path) -> Result<Option<String>, Error> {
+	Ok(get_contract_artifacts(path)?.metadata()?.image)
+}
+
+// Retrieves the hash of the code of a deployed contract.
+async fn deployed_code_hash(url: &Url, contract: &str) -> Result<H256, Error> {
+	#[cfg(feature = "v5")]
+	let address = Value::from_bytes(parse_account(contract)?.0);
+	#[cfg(feature = "v6")]
+	let address = Value::from_bytes(parse_h160_account(contract)?.as_bytes());
+	let client = RpcClient::from_url(url.as_str())
+		.await
+		.map_err(|e| Error::VerificationError(e.to_string()))?;
+	let api = OnlineClient::<SubstrateConfig>::from_rpc_client(client)
+		.await
+		.map_err(|e| Error::VerificationError(e.to_string()))?;
+	let info = api
+		.storage()
+		.at_latest()
+		.await
+		.map_err(|e| Error::VerificationError(e.to_string()))?
+		.fetch(&subxt::dynamic::storage(CONTRACTS_PALLET, "ContractInfoOf", vec![address]))
+		.await
+		.map_err(|e| Error::VerificationError(e.to_string()))?
+		.ok_or_else(|| Error::VerificationError(format!("no contract found at {contract}")))?
+		.to_value()
+		.map_err(|e| Error::VerificationError(format!("invalid contract info: {e}")))?;
+	info.at("code_hash")
+		.and_then(|hash| {
+			let mut bytes = Vec::new();
+			collect_bytes(hash, &mut bytes).then_some(bytes)
+		})
+		.filter(|bytes| bytes.len() == 32)
+		.map(|bytes| H256::from_slice(&bytes))
+		.ok_or_else(|| Error::VerificationError("invalid contract info: no code hash".into()))
+}
+
+// Collects the bytes of a value, such as a hash, which is composed of a sequence of bytes.
+fn collect_bytes<T>(value: &Value<T>, bytes: &mut Vec<u8>) -> bool {
+	match &value.value {
+		ValueDef::Composite(composite) =>
+			composite.values().all(|value| collect_bytes(value, bytes)),
+		ValueDef::Primitive(Primitive::U128(byte)) => match u8::try_from(*byte) {
+			Ok(byte) => {
+				bytes.push(byte);
+				true
+			},
+			Err(_) => false,
+		},
+		_ => false,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn is_verified_works() {
+		let verification = Verification {
+			deployed: H256::repeat_byte(1),
+			built: H256::repeat_byte(1),
+			image: None,
+		};
+		assert!(verification.is_verified());
+		assert!(!Verification { built: H256::repeat_byte(2), ..verification }.is_verified());
+	}
+
+	#[test]
+	fn collect_bytes_works() {
+		let hash = Value::unnamed_composite([Value::from_bytes([1u8; 32])]);
+		let mut bytes = Vec::new();
+		assert!(collect_bytes(&hash, &mut bytes));
+		assert_eq!(bytes, vec![1u8; 32]);
+		assert!(!collect_bytes(&Value::u128(256), &mut Vec::new()));
+		assert!(!collect_bytes(&Value::string("hash"), &mut Vec::new()));
+	}
+}
@@ -24,11 +24,9 @@ use url::Url;
 #[cfg(feature = "v6")]
 use contract_extrinsics_inkv6::{ExtrinsicOpts, ExtrinsicOptsBuilder};
 #[cfg(feature = "v6")]
-use pop_common::{parse_h160_account, DefaultConfig, Keypair};
+use pop_common::{create_signer, parse_h160_account, DefaultConfig, Signer};
 #[cfg(feature = "v6")]
 use pop_contracts::{AccountMapper, DefaultEnvironment};
-#[cfg(feature = "v6")]
-use subxt_signer::sr25519::dev;
 
 #[cfg(feature = "v5")]
 const CONTRACT_FILE: &str = "./tests/files/testing_wasm.contract";
@@ -112,8 +110,8 @@ async fn run_contracts_node_works() -> Result<()> {
 async fn map_account_works(localhost_url: &str) -> Result<()> {
 	let current_dir = env::current_dir().expect("Failed to get current directory");
 	// Alice is mapped when running the contracts-node.
-	let signer = dev::bob();
-	let extrinsic_opts: ExtrinsicOpts<DefaultConfig, DefaultEnvironment, Keypair> =
+	let signer = create_signer("//Bob")?;
+	let extrinsic_opts: ExtrinsicOpts<DefaultConfig, DefaultEnvironment, Signer> =
 		ExtrinsicOptsBuilder::new(signer)
 			.file(Some(current_dir.join(CONTRACT_FILE)))
 			.url(Url::parse(&localhost_url)?)